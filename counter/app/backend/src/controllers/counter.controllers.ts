@@ -103,6 +103,7 @@ export const buildInitialize = async (req: Request, res: Response) => {
       .accounts({
         counter: counterPda,
         user: userKey,
+        payer: userKey,
         systemProgram: web3.SystemProgram.programId,
       })
       .transaction();
@@ -0,0 +1,66 @@
+//! Command-line helpers for the counter program.
+//!
+//! ```text
+//! counter address counter <creator> [label]    PDA of a counter created by a wallet
+//! counter address sequencer <authority>        PDA of a wallet's sequencer
+//! counter address config                       PDA of the program config
+//! counter address treasury                     PDA of the fee treasury
+//! counter decode-counter <base64 data>         Decode raw counter account data
+//! ```
+
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use counter_client::anchor_lang::prelude::Pubkey;
+use counter_client::interface::pda;
+use counter_client::{decode_counter, PROGRAM_ID};
+
+const USAGE: &str = "usage: counter address <counter|sequencer|config|treasury> [wallet] [label]\n       counter decode-counter <base64 data>";
+
+fn run(args: &[String]) -> Result<String, String> {
+    let wallet = |i: usize| -> Result<Pubkey, String> {
+        let arg = args.get(i).ok_or("missing wallet")?;
+        Pubkey::from_str(arg).map_err(|e| format!("invalid wallet {arg}: {e}"))
+    };
+
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["address", "counter", ..] => {
+            let label = args.get(3).map(String::as_str).unwrap_or("");
+            Ok(pda::counter(&wallet(2)?, label, &PROGRAM_ID).0.to_string())
+        }
+        ["address", "sequencer", ..] => Ok(pda::sequencer(&wallet(2)?, &PROGRAM_ID).0.to_string()),
+        ["address", "config"] => Ok(pda::config(&PROGRAM_ID).0.to_string()),
+        ["address", "treasury"] => Ok(pda::treasury(&PROGRAM_ID).0.to_string()),
+        ["decode-counter", data] => {
+            let bytes = STANDARD.decode(data).map_err(|e| format!("invalid base64: {e}"))?;
+            let counter = decode_counter(&bytes).map_err(|e| e.to_string())?;
+            Ok(format!(
+                "authority: {}\ncreator: {}\nlabel: {:?}\ncount: {}\nstate: {:?}\ntotal_ops: {}\nlast_updated: {}\nversion: {}",
+                counter.authority,
+                counter.creator,
+                counter.label,
+                counter.count,
+                counter.state,
+                counter.total_ops,
+                counter.last_updated,
+                counter.version
+            ))
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(output) => {
+            println!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
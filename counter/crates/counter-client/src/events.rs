@@ -0,0 +1,64 @@
+//! Decoding of `emit!`ted events from transaction logs.
+//!
+//! Anchor writes each event as a `Program data: <base64>` log line whose payload starts with
+//! the event's 8-byte discriminator, followed by its Borsh-encoded fields. The first field of
+//! every event is its `schema_version` (see `counter::schema`).
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use counter::{
+    ActivityProven, CounterBatchApplied, CounterClosed, CounterErrorDetail, CounterHeartbeat, CounterInitialized,
+    CounterStateChanged, CounterUpdated, MilestoneReached,
+};
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+/// The events off-chain consumers currently care about. Everything else decodes to `None`.
+pub enum CounterEvent {
+    Initialized(CounterInitialized),
+    Updated(CounterUpdated),
+    BatchApplied(CounterBatchApplied),
+    Closed(CounterClosed),
+    StateChanged(CounterStateChanged),
+    Heartbeat(CounterHeartbeat),
+    ActivityProven(ActivityProven),
+    Milestone(MilestoneReached),
+    /// Found in the logs of a transaction that failed on a limit, not of one that landed.
+    ErrorDetail(CounterErrorDetail),
+}
+
+fn parse<T: AnchorDeserialize + Discriminator>(data: &[u8]) -> Option<T> {
+    let body = data.strip_prefix(T::DISCRIMINATOR)?;
+    T::deserialize(&mut &body[..]).ok()
+}
+
+/// Decodes an event payload (discriminator included).
+pub fn decode(data: &[u8]) -> Option<CounterEvent> {
+    None.or_else(|| parse(data).map(CounterEvent::Initialized))
+        .or_else(|| parse(data).map(CounterEvent::Updated))
+        .or_else(|| parse(data).map(CounterEvent::BatchApplied))
+        .or_else(|| parse(data).map(CounterEvent::Closed))
+        .or_else(|| parse(data).map(CounterEvent::StateChanged))
+        .or_else(|| parse(data).map(CounterEvent::Heartbeat))
+        .or_else(|| parse(data).map(CounterEvent::ActivityProven))
+        .or_else(|| parse(data).map(CounterEvent::Milestone))
+        .or_else(|| parse(data).map(CounterEvent::ErrorDetail))
+}
+
+/// The schema version of an event payload (discriminator included), read without decoding the
+/// rest, for indexers that handle several versions of one event.
+pub fn schema_version(data: &[u8]) -> Option<u8> {
+    data.get(8).copied()
+}
+
+/// Decodes the data of an `emit_cpi!` self-invocation, found among a transaction's inner
+/// instructions when the program was built with the `event-cpi` feature.
+pub fn decode_cpi(ix_data: &[u8]) -> Option<CounterEvent> {
+    decode(ix_data.strip_prefix(anchor_lang::event::EVENT_IX_TAG_LE)?)
+}
+
+/// Decodes one log line, returning `None` for lines that aren't counter events.
+pub fn decode_log(line: &str) -> Option<CounterEvent> {
+    let payload = line.trim().split_once(PROGRAM_DATA_PREFIX)?.1;
+    decode(&STANDARD.decode(payload).ok()?)
+}
@@ -0,0 +1,2202 @@
+//! Off-chain helpers for talking to the counter program.
+//!
+//! - instruction builders that fill in every PDA, so callers only supply wallets and arguments
+//! - account decoders that check the Anchor discriminator before deserializing
+//! - [`events`]: decoding of the events the program writes to transaction logs
+//! - `rpc` (behind the `rpc` feature): fetching and decoding accounts with a `RpcClient`
+//!
+//! Apart from `rpc`, nothing here talks to an RPC node; pair the instructions with whichever
+//! Solana client the application already uses.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::{system_program, InstructionData};
+use anchor_spl::associated_token::get_associated_token_address;
+use counter::{
+    accounts, instruction, ActivityReceipt, AlertDirection, BatchOp, BigCounter, Commitment, Contributor, Counter, CounterArray, CounterHistory, CounterOperation, CounterSnapshot, CounterView, CounterZC, DefaultParams, Delegation, ExportedCounter,
+    CounterGroup, CounterTemplate, GlobalStats, Leaderboard, MultisigAuthority, MultisigOp, MultisigProposal, OpValidation, OverflowMode, ProgramConfig, Sequencer, Session,
+    Role, SignedCounter, TemplateParams, TwaView, UserDefaults, UserRegistry, Wager,
+};
+use counter_interface::{pda, PARTNER_ID_LEN};
+
+pub mod events;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+
+pub use anchor_lang;
+pub use counter::ID as PROGRAM_ID;
+pub use counter_interface as interface;
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Identifies a counter by the seeds it was created with: the creating wallet and the label.
+///
+/// The creator stays the same when ownership moves, so a counter received via
+/// `accept_authority` is still addressed through its original creator.
+///
+/// `history` says whether the counter keeps a `CounterHistory` (see [`initialize_history`]);
+/// updates to such a counter have to pass it in. Likewise `vault` for counters that take a
+/// deposit per increment (see [`set_vault_deposit`]), and `token_fee_mint` for counters that
+/// charge a token fee (see [`set_token_fee`]); updates then pay from the user's associated token
+/// account. `registry` is on unless the counter was created before `UserRegistry` existed, in
+/// which case closing it must leave the registry out.
+#[derive(Clone, Copy, Debug)]
+pub struct CounterRef<'a> {
+    pub creator: Pubkey,
+    pub label: &'a str,
+    pub history: bool,
+    pub vault: bool,
+    pub token_fee_mint: Option<Pubkey>,
+    pub registry: bool,
+    pub hook: Option<Pubkey>,
+    pub group: Option<Pubkey>,
+    pub namespace: Option<[u8; PARTNER_ID_LEN]>,
+}
+
+impl<'a> CounterRef<'a> {
+    pub fn new(creator: Pubkey, label: &'a str) -> Self {
+        Self { creator, label, history: false, vault: false, token_fee_mint: None, registry: true, hook: None, group: None, namespace: None }
+    }
+
+    pub fn without_registry(self) -> Self {
+        Self { registry: false, ..self }
+    }
+
+    pub fn with_history(self) -> Self {
+        Self { history: true, ..self }
+    }
+
+    pub fn with_vault(self) -> Self {
+        Self { vault: true, ..self }
+    }
+
+    pub fn with_token_fee(self, mint: Pubkey) -> Self {
+        Self { token_fee_mint: Some(mint), ..self }
+    }
+
+    pub fn with_hook(self, program: Pubkey) -> Self {
+        Self { hook: Some(program), ..self }
+    }
+
+    pub fn with_group(self, group: Pubkey) -> Self {
+        Self { group: Some(group), ..self }
+    }
+
+    /// A counter created with [`initialize_namespaced`].
+    pub fn with_namespace(self, partner_id: [u8; PARTNER_ID_LEN]) -> Self {
+        Self { namespace: Some(partner_id), ..self }
+    }
+
+    pub fn address(&self) -> Pubkey {
+        match &self.namespace {
+            Some(partner_id) => pda::namespaced_counter(partner_id, &self.creator, self.label, &PROGRAM_ID).0,
+            None => pda::counter(&self.creator, self.label, &PROGRAM_ID).0,
+        }
+    }
+
+    fn id(&self) -> String {
+        self.label.to_string()
+    }
+
+    fn registry(&self) -> Option<Pubkey> {
+        self.registry.then(|| pda::registry(&self.creator, &PROGRAM_ID).0)
+    }
+}
+
+// The sequencer is only passed in when an ordering guarantee was asked for
+fn sequencer_for(user: &Pubkey, expected_seq: Option<u64>) -> Option<Pubkey> {
+    expected_seq.map(|_| pda::sequencer(user, &PROGRAM_ID).0)
+}
+
+fn update_accounts(user: &Pubkey, counter: &CounterRef, expected_seq: Option<u64>) -> accounts::Update {
+    let address = counter.address();
+    let counter_treasury = pda::counter_treasury(&address, &PROGRAM_ID).0;
+    accounts::Update {
+        counter: address,
+        user: *user,
+        config: pda::config(&PROGRAM_ID).0,
+        stats: pda::global_stats(&PROGRAM_ID).0,
+        treasury: pda::treasury(&PROGRAM_ID).0,
+        counter_treasury,
+        fee_payer_token_account: None,
+        treasury_token_account: None,
+        token_program: counter.token_fee_mint.map(|_| anchor_spl::token::ID),
+        sequencer: sequencer_for(user, expected_seq),
+        delegation: None,
+        session: None,
+        contributor: None,
+        role: None,
+        gate_token_account: None,
+        weight_token_account: None,
+        nft_token_account: None,
+        nft_metadata: None,
+        reward_config: None,
+        reward_mint: None,
+        reward_token_account: None,
+        history: counter.history.then(|| pda::history(&address, &PROGRAM_ID).0),
+        vault: counter.vault.then(|| pda::vault(&address, &PROGRAM_ID).0),
+        fee_token_account: counter.token_fee_mint.map(|mint| get_associated_token_address(user, &mint)),
+        fee_vault: counter
+            .token_fee_mint
+            .map(|mint| get_associated_token_address(&counter_treasury, &mint)),
+        referrer_counter: None,
+        hook_program: counter.hook,
+        group: counter.group,
+        system_program: system_program::ID,
+        #[cfg(feature = "event-cpi")]
+        event_authority: pda::event_authority(&PROGRAM_ID).0,
+        #[cfg(feature = "event-cpi")]
+        program: PROGRAM_ID,
+    }
+}
+
+/// Creates `user`'s counter named `label` (`""` for the wallet's default counter), optionally
+/// limited to `min..=max`. Anyone may increment a counter created with `is_public`;
+/// `cooldown_seconds` spaces increments out, `overflow_mode` picks what happens at the bounds
+/// and `expires_at` is when the counter stops accepting changes. With `nft_gate` set, only
+/// holders of an NFT from that verified Metaplex collection (and the owner) may increment.
+/// `step` is how much [`increment`] and [`decrement`] move it by (1 when `None`).
+/// `recovery_authority` may take the counter over after `inactivity_threshold` seconds without a
+/// change to the count (see [`claim_authority`]).
+#[allow(clippy::too_many_arguments)]
+pub fn initialize(
+    user: &Pubkey,
+    label: &str,
+    min: Option<u64>,
+    max: Option<u64>,
+    is_public: bool,
+    cooldown_seconds: Option<i64>,
+    overflow_mode: Option<OverflowMode>,
+    expires_at: Option<i64>,
+    nft_gate: Option<Pubkey>,
+    step: Option<u64>,
+    window_start: Option<i64>,
+    window_end: Option<i64>,
+    recovery_authority: Option<Pubkey>,
+    inactivity_threshold: Option<i64>,
+) -> Instruction {
+    build(
+        accounts::Initialize {
+            counter: CounterRef::new(*user, label).address(),
+            user: *user,
+            payer: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            treasury: pda::treasury(&PROGRAM_ID).0,
+            registry: pda::registry(user, &PROGRAM_ID).0,
+            defaults: None,
+            system_program: system_program::ID,
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(&PROGRAM_ID).0,
+            #[cfg(feature = "event-cpi")]
+            program: PROGRAM_ID,
+        },
+        instruction::Initialize {
+            id: label.to_string(),
+            min,
+            max,
+            is_public,
+            cooldown_seconds,
+            overflow_mode,
+            expires_at,
+            nft_gate,
+            step,
+            window_start,
+            window_end,
+            recovery_authority,
+            inactivity_threshold,
+        },
+    )
+}
+
+/// Like [`initialize`], but with the bounds, cooldown, overflow mode and step all taken from the
+/// `user`'s saved defaults (see [`set_defaults`]); `user` must have set them first.
+pub fn initialize_from_defaults(user: &Pubkey, label: &str, is_public: bool) -> Instruction {
+    build(
+        accounts::Initialize {
+            counter: CounterRef::new(*user, label).address(),
+            user: *user,
+            payer: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            treasury: pda::treasury(&PROGRAM_ID).0,
+            registry: pda::registry(user, &PROGRAM_ID).0,
+            defaults: Some(pda::defaults(user, &PROGRAM_ID).0),
+            system_program: system_program::ID,
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(&PROGRAM_ID).0,
+            #[cfg(feature = "event-cpi")]
+            program: PROGRAM_ID,
+        },
+        instruction::Initialize {
+            id: label.to_string(),
+            min: None,
+            max: None,
+            is_public,
+            cooldown_seconds: None,
+            overflow_mode: None,
+            expires_at: None,
+            nft_gate: None,
+            step: None,
+            window_start: None,
+            window_end: None,
+            recovery_authority: None,
+            inactivity_threshold: None,
+        },
+    )
+}
+
+/// Like [`initialize`] with no options, but with `payer` paying the rent and the creation fee
+/// while `user` becomes the owner; both sign. `payer` gets the rent back when the counter closes.
+pub fn initialize_sponsored(payer: &Pubkey, user: &Pubkey, label: &str, is_public: bool) -> Instruction {
+    build(
+        accounts::Initialize {
+            counter: CounterRef::new(*user, label).address(),
+            user: *user,
+            payer: *payer,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            treasury: pda::treasury(&PROGRAM_ID).0,
+            registry: pda::registry(user, &PROGRAM_ID).0,
+            defaults: None,
+            system_program: system_program::ID,
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(&PROGRAM_ID).0,
+            #[cfg(feature = "event-cpi")]
+            program: PROGRAM_ID,
+        },
+        instruction::Initialize {
+            id: label.to_string(),
+            min: None,
+            max: None,
+            is_public,
+            cooldown_seconds: None,
+            overflow_mode: None,
+            expires_at: None,
+            nft_gate: None,
+            step: None,
+            window_start: None,
+            window_end: None,
+            recovery_authority: None,
+            inactivity_threshold: None,
+        },
+    )
+}
+
+/// Saves the settings `user`'s new counters start with (see [`initialize_from_defaults`]).
+pub fn set_defaults(user: &Pubkey, params: DefaultParams) -> Instruction {
+    build(
+        accounts::SetDefaults {
+            defaults: pda::defaults(user, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::SetDefaults { params },
+    )
+}
+
+/// Like [`initialize`], but in an approved partner's namespace; address it afterwards with
+/// [`CounterRef::with_namespace`].
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_namespaced(
+    user: &Pubkey,
+    partner_id: [u8; PARTNER_ID_LEN],
+    label: &str,
+    min: Option<u64>,
+    max: Option<u64>,
+    is_public: bool,
+    cooldown_seconds: Option<i64>,
+    overflow_mode: Option<OverflowMode>,
+    expires_at: Option<i64>,
+    nft_gate: Option<Pubkey>,
+    step: Option<u64>,
+    window_start: Option<i64>,
+    window_end: Option<i64>,
+    recovery_authority: Option<Pubkey>,
+    inactivity_threshold: Option<i64>,
+) -> Instruction {
+    build(
+        accounts::InitializeNamespaced {
+            counter: CounterRef::new(*user, label).with_namespace(partner_id).address(),
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            treasury: pda::treasury(&PROGRAM_ID).0,
+            registry: pda::registry(user, &PROGRAM_ID).0,
+            system_program: system_program::ID,
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(&PROGRAM_ID).0,
+            #[cfg(feature = "event-cpi")]
+            program: PROGRAM_ID,
+        },
+        instruction::InitializeNamespaced {
+            partner_id,
+            id: label.to_string(),
+            min,
+            max,
+            is_public,
+            cooldown_seconds,
+            overflow_mode,
+            expires_at,
+            nft_gate,
+            step,
+            window_start,
+            window_end,
+            recovery_authority,
+            inactivity_threshold,
+        },
+    )
+}
+
+/// Like [`initialize`], but does nothing when the counter already exists.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_if_needed(
+    user: &Pubkey,
+    label: &str,
+    min: Option<u64>,
+    max: Option<u64>,
+    is_public: bool,
+    cooldown_seconds: Option<i64>,
+    overflow_mode: Option<OverflowMode>,
+    expires_at: Option<i64>,
+    nft_gate: Option<Pubkey>,
+    step: Option<u64>,
+    window_start: Option<i64>,
+    window_end: Option<i64>,
+    recovery_authority: Option<Pubkey>,
+    inactivity_threshold: Option<i64>,
+) -> Instruction {
+    build(
+        accounts::InitializeIfNeeded {
+            counter: CounterRef::new(*user, label).address(),
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            treasury: pda::treasury(&PROGRAM_ID).0,
+            registry: pda::registry(user, &PROGRAM_ID).0,
+            system_program: system_program::ID,
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(&PROGRAM_ID).0,
+            #[cfg(feature = "event-cpi")]
+            program: PROGRAM_ID,
+        },
+        instruction::InitializeIfNeeded {
+            id: label.to_string(),
+            min,
+            max,
+            is_public,
+            cooldown_seconds,
+            overflow_mode,
+            expires_at,
+            nft_gate,
+            step,
+            window_start,
+            window_end,
+            recovery_authority,
+            inactivity_threshold,
+        },
+    )
+}
+
+/// Changes `counter` by `delta`, signed by its authority `user`. Fees, if enabled, are paid in SOL.
+pub fn apply_delta(
+    user: &Pubkey,
+    counter: &CounterRef,
+    delta: i64,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    build(
+        update_accounts(user, counter, expected_seq),
+        instruction::ApplyDelta {
+            id: counter.id(),
+            delta,
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Adds one (or the counter's `step`) to `counter`.
+pub fn increment(user: &Pubkey, counter: &CounterRef, dry_run: bool, expected_seq: Option<u64>) -> Instruction {
+    build(
+        update_accounts(user, counter, expected_seq),
+        instruction::Increment {
+            id: counter.id(),
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Adds one to `counter` only if it still holds `expected`.
+pub fn increment_if(
+    user: &Pubkey,
+    counter: &CounterRef,
+    expected: u64,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    build(
+        update_accounts(user, counter, expected_seq),
+        instruction::IncrementIf {
+            id: counter.id(),
+            expected,
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Adds one to `counter` on its owner's behalf, spending one unit of `operator`'s allowance
+/// (see [`approve_operator`]).
+pub fn increment_as_operator(
+    operator: &Pubkey,
+    counter: &CounterRef,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    let mut accounts = update_accounts(operator, counter, expected_seq);
+    accounts.delegation = Some(pda::delegation(&accounts.counter, operator, &PROGRAM_ID).0);
+    build(
+        accounts,
+        instruction::Increment {
+            id: counter.id(),
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Increments `counter` with the owner's session key `session_key` (see [`create_session`]).
+pub fn increment_with_session(
+    session_key: &Pubkey,
+    counter: &CounterRef,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    let mut accounts = update_accounts(session_key, counter, expected_seq);
+    accounts.session = Some(pda::session(&accounts.counter, session_key, &PROGRAM_ID).0);
+    build(
+        accounts,
+        instruction::Increment {
+            id: counter.id(),
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Decrements `counter` with the owner's session key `session_key` (see [`create_session`]).
+pub fn decrement_with_session(
+    session_key: &Pubkey,
+    counter: &CounterRef,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    let mut accounts = update_accounts(session_key, counter, expected_seq);
+    accounts.session = Some(pda::session(&accounts.counter, session_key, &PROGRAM_ID).0);
+    build(
+        accounts,
+        instruction::Decrement {
+            id: counter.id(),
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Changes `counter` by `delta` as `member`, using the roles its authority granted them (see
+/// [`grant_role`]).
+pub fn apply_delta_with_role(
+    member: &Pubkey,
+    counter: &CounterRef,
+    delta: i64,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    let mut accounts = update_accounts(member, counter, expected_seq);
+    accounts.role = Some(pda::role(&accounts.counter, member, &PROGRAM_ID).0);
+    build(
+        accounts,
+        instruction::ApplyDelta {
+            id: counter.id(),
+            delta,
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Adds `amount` to a shared `counter` as the registered contributor `member`, crediting their
+/// tally (see [`add_contributor`]).
+pub fn increment_as_contributor(
+    member: &Pubkey,
+    counter: &CounterRef,
+    amount: u64,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    let mut accounts = update_accounts(member, counter, expected_seq);
+    accounts.contributor = Some(pda::contributor(&accounts.counter, member, &PROGRAM_ID).0);
+    build(
+        accounts,
+        instruction::IncrementBy {
+            id: counter.id(),
+            amount,
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Adds one to a token-gated `counter`, passing `user`'s associated token account for `gate_mint`
+/// (see [`set_token_gate`]).
+pub fn increment_gated(
+    user: &Pubkey,
+    counter: &CounterRef,
+    gate_mint: &Pubkey,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    let mut accounts = update_accounts(user, counter, expected_seq);
+    accounts.gate_token_account = Some(get_associated_token_address(user, gate_mint));
+    build(
+        accounts,
+        instruction::Increment {
+            id: counter.id(),
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Adds one to a collection-gated `counter`, showing the NFT `nft_mint` in `user`'s associated
+/// token account (see `Counter.nft_gate`).
+pub fn increment_with_nft(
+    user: &Pubkey,
+    counter: &CounterRef,
+    nft_mint: &Pubkey,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    let mut accounts = update_accounts(user, counter, expected_seq);
+    accounts.nft_token_account = Some(get_associated_token_address(user, nft_mint));
+    accounts.nft_metadata = Some(pda::nft_metadata(nft_mint).0);
+    build(
+        accounts,
+        instruction::Increment {
+            id: counter.id(),
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Adds one to `counter` and mints the increment reward to `reward_token_account` (see
+/// `initialize_rewards`).
+pub fn increment_with_reward(
+    user: &Pubkey,
+    counter: &CounterRef,
+    reward_token_account: &Pubkey,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    let mut accounts = update_accounts(user, counter, expected_seq);
+    accounts.reward_config = Some(pda::reward_config(&PROGRAM_ID).0);
+    accounts.reward_mint = Some(pda::reward_mint(&PROGRAM_ID).0);
+    accounts.reward_token_account = Some(*reward_token_account);
+    accounts.token_program = Some(anchor_spl::token::ID);
+    build(
+        accounts,
+        instruction::Increment {
+            id: counter.id(),
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Adds one to `counter` and credits `referrer`, who owns `referrer_counter`.
+pub fn increment_with_referrer(
+    user: &Pubkey,
+    counter: &CounterRef,
+    referrer: &Pubkey,
+    referrer_counter: &CounterRef,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    let mut accounts = update_accounts(user, counter, expected_seq);
+    accounts.referrer_counter = Some(referrer_counter.address());
+    build(
+        accounts,
+        instruction::IncrementWithReferrer {
+            id: counter.id(),
+            referrer: *referrer,
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Adds one to `counter` if `nonce` is higher than the last nonce it accepted (replay protection
+/// for relayed intents).
+pub fn increment_with_nonce(user: &Pubkey, counter: &CounterRef, nonce: u64, dry_run: bool, expected_seq: Option<u64>) -> Instruction {
+    build(
+        update_accounts(user, counter, expected_seq),
+        instruction::IncrementWithNonce {
+            id: counter.id(),
+            nonce,
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Adds one to `counter`, recording `memo` (at most `MAX_MEMO_LEN` bytes) in its `CounterUpdated`.
+pub fn increment_with_memo(
+    user: &Pubkey,
+    counter: &CounterRef,
+    memo: &str,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    build(
+        update_accounts(user, counter, expected_seq),
+        instruction::IncrementWithMemo {
+            id: counter.id(),
+            memo: memo.to_string(),
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Adds `user`'s balance in `weight_token_account` (their token account for the counter's
+/// weight mint) divided by the counter's weight divisor to `counter`.
+pub fn increment_weighted(
+    user: &Pubkey,
+    counter: &CounterRef,
+    weight_token_account: &Pubkey,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    let mut accounts = update_accounts(user, counter, expected_seq);
+    accounts.weight_token_account = Some(*weight_token_account);
+    build(
+        accounts,
+        instruction::IncrementWeighted {
+            id: counter.id(),
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Subtracts one from `counter`, recording `memo` (at most `MAX_MEMO_LEN` bytes) in its `CounterUpdated`.
+pub fn decrement_with_memo(
+    user: &Pubkey,
+    counter: &CounterRef,
+    memo: &str,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    build(
+        update_accounts(user, counter, expected_seq),
+        instruction::DecrementWithMemo {
+            id: counter.id(),
+            memo: memo.to_string(),
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Subtracts one (or the counter's `step`) from `counter`.
+pub fn decrement(user: &Pubkey, counter: &CounterRef, dry_run: bool, expected_seq: Option<u64>) -> Instruction {
+    build(
+        update_accounts(user, counter, expected_seq),
+        instruction::Decrement {
+            id: counter.id(),
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Adds `amount` to `counter`.
+pub fn increment_by(
+    user: &Pubkey,
+    counter: &CounterRef,
+    amount: u64,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    build(
+        update_accounts(user, counter, expected_seq),
+        instruction::IncrementBy {
+            id: counter.id(),
+            amount,
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Subtracts `amount` from `counter`.
+pub fn decrement_by(
+    user: &Pubkey,
+    counter: &CounterRef,
+    amount: u64,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    build(
+        update_accounts(user, counter, expected_seq),
+        instruction::DecrementBy {
+            id: counter.id(),
+            amount,
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Applies `ops` to `counter` in order, all or nothing.
+pub fn apply_batch(
+    user: &Pubkey,
+    counter: &CounterRef,
+    ops: Vec<BatchOp>,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    build(
+        update_accounts(user, counter, expected_seq),
+        instruction::ApplyBatch { id: counter.id(), ops, dry_run, expected_seq },
+    )
+}
+
+/// Overwrites `counter` with `new_value`.
+pub fn set_count(
+    user: &Pubkey,
+    counter: &CounterRef,
+    new_value: u64,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    build(
+        update_accounts(user, counter, expected_seq),
+        instruction::SetCount { id: counter.id(), new_value, dry_run, expected_seq },
+    )
+}
+
+/// Puts `counter` back to zero.
+pub fn reset(user: &Pubkey, counter: &CounterRef, dry_run: bool, expected_seq: Option<u64>) -> Instruction {
+    build(
+        update_accounts(user, counter, expected_seq),
+        instruction::Reset { id: counter.id(), dry_run, expected_seq },
+    )
+}
+
+/// Closes `counter` and refunds its rent to its authority `user`.
+pub fn close(user: &Pubkey, counter: &CounterRef, dry_run: bool, expected_seq: Option<u64>) -> Instruction {
+    build(
+        accounts::Close {
+            counter: counter.address(),
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            sequencer: sequencer_for(user, expected_seq),
+            rent_recipient: None,
+            registry: counter.registry(),
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(&PROGRAM_ID).0,
+            #[cfg(feature = "event-cpi")]
+            program: PROGRAM_ID,
+        },
+        instruction::Close {
+            id: counter.id(),
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+/// Closes `counter` together with `children` - its history, operator approvals, sessions,
+/// contributor registrations, roles and `user`'s own snapshots - refunding all their rent to its
+/// authority `user`.
+pub fn close_all(user: &Pubkey, counter: &CounterRef, children: &[Pubkey], expected_seq: Option<u64>) -> Instruction {
+    let mut ix = build(
+        accounts::Close {
+            counter: counter.address(),
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            sequencer: sequencer_for(user, expected_seq),
+            rent_recipient: None,
+            registry: counter.registry(),
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(&PROGRAM_ID).0,
+            #[cfg(feature = "event-cpi")]
+            program: PROGRAM_ID,
+        },
+        instruction::CloseAll { id: counter.id(), expected_seq },
+    );
+    ix.accounts.extend(children.iter().map(|child| AccountMeta::new(*child, false)));
+    ix
+}
+
+/// Starts the close timelock on `counter`, signed by its authority `user`.
+pub fn request_close(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(manage_accounts(user, counter), instruction::RequestClose {})
+}
+
+/// Calls off a pending close of `counter`, signed by its authority `user`.
+pub fn cancel_close(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(manage_accounts(user, counter), instruction::CancelClose {})
+}
+
+/// Soft-closes `counter`, signed by its authority `user`; it can be reopened during the grace period.
+pub fn soft_close(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(manage_accounts(user, counter), instruction::SoftClose {})
+}
+
+/// Reopens the soft-closed `counter`, signed by its authority `user`.
+pub fn reopen(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(manage_accounts(user, counter), instruction::Reopen {})
+}
+
+/// Hands `counter` over to another program version, signed by its authority `user`; the
+/// snapshot comes back as return data (see [`decode_export`]).
+pub fn export(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(manage_accounts(user, counter), instruction::Export {})
+}
+
+/// Recreates the counter in `snapshot` (exported by another program version), signed and paid
+/// for by the config's `migration_authority`.
+pub fn import(migration_authority: &Pubkey, snapshot: ExportedCounter) -> Instruction {
+    let counter = if snapshot.namespace == [0; PARTNER_ID_LEN] {
+        pda::counter(&snapshot.creator, &snapshot.label, &PROGRAM_ID).0
+    } else {
+        pda::namespaced_counter(&snapshot.namespace, &snapshot.creator, &snapshot.label, &PROGRAM_ID).0
+    };
+    build(
+        accounts::ImportCounter {
+            counter,
+            migration_authority: *migration_authority,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(&PROGRAM_ID).0,
+            #[cfg(feature = "event-cpi")]
+            program: PROGRAM_ID,
+        },
+        instruction::Import { snapshot },
+    )
+}
+
+/// Closes `counter`, signed by its authority `user`, refunding its rent to `rent_recipient`.
+pub fn close_to(
+    user: &Pubkey,
+    counter: &CounterRef,
+    rent_recipient: &Pubkey,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+) -> Instruction {
+    build(
+        accounts::Close {
+            counter: counter.address(),
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            sequencer: sequencer_for(user, expected_seq),
+            rent_recipient: Some(*rent_recipient),
+            registry: counter.registry(),
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(&PROGRAM_ID).0,
+            #[cfg(feature = "event-cpi")]
+            program: PROGRAM_ID,
+        },
+        instruction::CloseTo {
+            id: counter.id(),
+            dry_run,
+            expected_seq,
+        },
+    )
+}
+
+fn manage_accounts(user: &Pubkey, counter: &CounterRef) -> accounts::ManageCounter {
+    accounts::ManageCounter {
+        counter: counter.address(),
+        user: *user,
+        config: pda::config(&PROGRAM_ID).0,
+        sequencer: None,
+    }
+}
+
+/// Offers `counter` to `new_authority`, who then has to call [`accept_authority`].
+pub fn transfer_authority(user: &Pubkey, counter: &CounterRef, new_authority: &Pubkey) -> Instruction {
+    build(
+        manage_accounts(user, counter),
+        instruction::TransferAuthority {
+            new_authority: *new_authority,
+        },
+    )
+}
+
+/// Adds the count of `source` to `destination` and closes `source`, signed by `user`, who owns both.
+pub fn merge(user: &Pubkey, source: &CounterRef, destination: &CounterRef) -> Instruction {
+    let destination_address = destination.address();
+    build(
+        accounts::Merge {
+            source: source.address(),
+            destination: destination_address,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            history: destination.history.then(|| pda::history(&destination_address, &PROGRAM_ID).0),
+            registry: source.registry(),
+        },
+        instruction::Merge {},
+    )
+}
+
+/// Moves `amount` from `source` to `destination` (any owner), signed by `source`'s authority `user`.
+pub fn transfer_count(user: &Pubkey, source: &CounterRef, destination: &CounterRef, amount: u64) -> Instruction {
+    let (source_address, destination_address) = (source.address(), destination.address());
+    build(
+        accounts::TransferCount {
+            source: source_address,
+            destination: destination_address,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            source_history: source.history.then(|| pda::history(&source_address, &PROGRAM_ID).0),
+            destination_history: destination.history.then(|| pda::history(&destination_address, &PROGRAM_ID).0),
+        },
+        instruction::TransferCount { amount },
+    )
+}
+
+/// Adds `source`'s count (any owner, left unchanged) to `destination`, signed by its authority `user`.
+pub fn add_from(user: &Pubkey, source: &CounterRef, destination: &CounterRef) -> Instruction {
+    let destination_address = destination.address();
+    build(
+        accounts::AddFrom {
+            source: source.address(),
+            destination: destination_address,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            destination_history: destination.history.then(|| pda::history(&destination_address, &PROGRAM_ID).0),
+        },
+        instruction::AddFrom {},
+    )
+}
+
+/// Moves `amount` out of `source` into a new counter of `user` (its authority) named `new_label`.
+pub fn split(user: &Pubkey, source: &CounterRef, amount: u64, new_label: &str) -> Instruction {
+    let source_address = source.address();
+    build(
+        accounts::Split {
+            source: source_address,
+            new_counter: pda::counter(user, new_label, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            treasury: pda::treasury(&PROGRAM_ID).0,
+            history: source.history.then(|| pda::history(&source_address, &PROGRAM_ID).0),
+            registry: pda::registry(user, &PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::Split {
+            amount,
+            new_label: new_label.to_string(),
+        },
+    )
+}
+
+/// Creates `user`'s counter `new_label` with the same settings as their counter `source`,
+/// starting at `source`'s count if `copy_value` is set and at its minimum otherwise.
+pub fn clone_counter(user: &Pubkey, source: &CounterRef, new_label: &str, copy_value: bool) -> Instruction {
+    build(
+        accounts::CloneCounter {
+            source: source.address(),
+            new_counter: pda::counter(user, new_label, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            treasury: pda::treasury(&PROGRAM_ID).0,
+            registry: pda::registry(user, &PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::CloneCounter {
+            new_label: new_label.to_string(),
+            copy_value,
+        },
+    )
+}
+
+/// Saves `params` as the template `name` of `user`, who pays for it.
+pub fn create_template(user: &Pubkey, name: &str, params: TemplateParams) -> Instruction {
+    build(
+        accounts::CreateTemplate {
+            template: pda::template(user, name, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::CreateTemplate {
+            name: name.to_string(),
+            params,
+        },
+    )
+}
+
+/// Creates the counter group `name` of `user`, who pays for it, with room for `capacity` counters.
+pub fn create_group(user: &Pubkey, name: &str, capacity: u32) -> Instruction {
+    build(
+        accounts::CreateGroup {
+            group: pda::group(user, name, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::CreateGroup { name: name.to_string(), capacity },
+    )
+}
+
+/// Adds `counter` to `group`, signed by the counter's authority `user` and the group's
+/// `authority`. Later updates need [`CounterRef::with_group`].
+pub fn join_group(user: &Pubkey, counter: &CounterRef, group: &Pubkey, authority: &Pubkey) -> Instruction {
+    build(
+        accounts::JoinGroup {
+            counter: counter.address(),
+            user: *user,
+            group: *group,
+            authority: *authority,
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::JoinGroup {},
+    )
+}
+
+/// Takes `counter` out of `group`, signed by its authority `user`.
+pub fn leave_group(user: &Pubkey, counter: &CounterRef, group: &Pubkey) -> Instruction {
+    build(
+        accounts::LeaveGroup { counter: counter.address(), user: *user, group: *group, config: pda::config(&PROGRAM_ID).0 },
+        instruction::LeaveGroup {},
+    )
+}
+
+/// Bets `opponent` that `counter` (owned by `user`) reaches `target` before `opponent_counter`
+/// does; both wallets sign and stake `stake_lamports`.
+pub fn create_wager(
+    user: &Pubkey,
+    counter: &CounterRef,
+    opponent: &Pubkey,
+    opponent_counter: &CounterRef,
+    target: u64,
+    stake_lamports: u64,
+    deadline: i64,
+) -> Instruction {
+    let (creator_counter, opponent_counter) = (counter.address(), opponent_counter.address());
+    let wager = pda::wager(&creator_counter, &opponent_counter, &PROGRAM_ID).0;
+    build(
+        accounts::CreateWager {
+            wager,
+            vault: pda::wager_vault(&wager, &PROGRAM_ID).0,
+            creator_counter,
+            opponent_counter,
+            user: *user,
+            opponent_user: *opponent,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::CreateWager { opponent: *opponent, target, stake_lamports, deadline },
+    )
+}
+
+/// Pays out `wager` to whichever side reached its target; `state` is the account decoded with [`decode_wager`].
+pub fn settle_wager(wager: &Pubkey, state: &Wager) -> Instruction {
+    build(
+        accounts::SettleWager {
+            wager: *wager,
+            vault: pda::wager_vault(wager, &PROGRAM_ID).0,
+            creator_counter: state.creator_counter,
+            opponent_counter: state.opponent_counter,
+            creator: state.creator,
+            opponent: state.opponent,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::SettleWager {},
+    )
+}
+
+/// Gives both stakes of `wager` back once its deadline has passed.
+pub fn refund_wager(wager: &Pubkey, state: &Wager) -> Instruction {
+    build(
+        accounts::RefundWager {
+            wager: *wager,
+            vault: pda::wager_vault(wager, &PROGRAM_ID).0,
+            creator: state.creator,
+            opponent: state.opponent,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::RefundWager {},
+    )
+}
+
+/// Creates `user`'s counter `label` with the settings of `template` (any wallet's).
+pub fn initialize_from_template(user: &Pubkey, template: &Pubkey, label: &str) -> Instruction {
+    build(
+        accounts::InitializeFromTemplate {
+            counter: pda::counter(user, label, &PROGRAM_ID).0,
+            template: *template,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            treasury: pda::treasury(&PROGRAM_ID).0,
+            registry: pda::registry(user, &PROGRAM_ID).0,
+            system_program: system_program::ID,
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(&PROGRAM_ID).0,
+            #[cfg(feature = "event-cpi")]
+            program: PROGRAM_ID,
+        },
+        instruction::InitializeFromTemplate { id: label.to_string() },
+    )
+}
+
+/// Accepts ownership of `counter`, signed by the pending authority.
+pub fn accept_authority(new_authority: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(
+        accounts::AcceptAuthority {
+            counter: counter.address(),
+            new_authority: *new_authority,
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::AcceptAuthority {},
+    )
+}
+
+/// Claims `counter` after its owner has gone quiet, signed by its `recovery_authority`; the owner
+/// has [`RECOVERY_CHALLENGE_SECONDS`](counter::RECOVERY_CHALLENGE_SECONDS) to [`veto_claim`].
+pub fn claim_authority(recovery_authority: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(
+        accounts::ClaimAuthority {
+            counter: counter.address(),
+            recovery_authority: *recovery_authority,
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::ClaimAuthority {},
+    )
+}
+
+/// Takes over `counter` once the challenge window of a [`claim_authority`] has passed, signed by
+/// its `recovery_authority`.
+pub fn complete_authority_claim(recovery_authority: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(
+        accounts::ClaimAuthority {
+            counter: counter.address(),
+            recovery_authority: *recovery_authority,
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::CompleteAuthorityClaim {},
+    )
+}
+
+/// Turns down a pending claim on `counter`, signed by its authority `user`.
+pub fn veto_claim(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(manage_accounts(user, counter), instruction::VetoClaim {})
+}
+
+/// Lets `operator` add up to `allowance` to `counter`, signed by its authority `user`.
+pub fn approve_operator(user: &Pubkey, counter: &CounterRef, operator: &Pubkey, allowance: u64) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::ApproveOperator {
+            counter,
+            delegation: pda::delegation(&counter, operator, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::ApproveOperator {
+            operator: *operator,
+            allowance,
+        },
+    )
+}
+
+/// Removes `operator`'s approval for `counter`, refunding its rent to the authority `user`.
+pub fn revoke_operator(user: &Pubkey, counter: &CounterRef, operator: &Pubkey) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::RevokeOperator {
+            counter,
+            delegation: pda::delegation(&counter, operator, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::RevokeOperator {},
+    )
+}
+
+/// Caps `counter` at `limit` increments per UTC day (0 removes the cap).
+pub fn set_daily_limit(user: &Pubkey, counter: &CounterRef, limit: u64) -> Instruction {
+    build(manage_accounts(user, counter), instruction::SetDailyLimit { limit })
+}
+
+/// Makes every increment of `counter` pay `lamports` into its vault (0 turns it off).
+/// Address the counter through [`CounterRef::with_vault`] from then on.
+pub fn set_vault_deposit(user: &Pubkey, counter: &CounterRef, lamports: u64) -> Instruction {
+    build(manage_accounts(user, counter), instruction::SetVaultDeposit { lamports })
+}
+
+/// Emits `ThresholdCrossed` whenever `counter`'s count crosses `threshold` in `direction`,
+/// signed by its authority `user`.
+pub fn set_alert_threshold(user: &Pubkey, counter: &CounterRef, threshold: u64, direction: AlertDirection) -> Instruction {
+    build(manage_accounts(user, counter), instruction::SetAlertThreshold { threshold, direction })
+}
+
+/// Makes every change to `counter` call `hook_program`'s `on_counter_updated` (the default key
+/// removes the hook), signed by its authority `user`. Later updates need [`CounterRef::with_hook`].
+pub fn set_hook(user: &Pubkey, counter: &CounterRef, hook_program: &Pubkey) -> Instruction {
+    build(manage_accounts(user, counter), instruction::SetHook { hook_program: *hook_program })
+}
+
+/// Turns `counter`'s threshold alert off, signed by its authority `user`.
+pub fn clear_alert_threshold(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(manage_accounts(user, counter), instruction::ClearAlertThreshold {})
+}
+
+/// Pays keepers up to `lamports` from `counter`'s vault for each [`touch`] with something to
+/// do (0 stops the tips), signed by its authority `user`.
+pub fn set_keeper_tip(user: &Pubkey, counter: &CounterRef, lamports: u64) -> Instruction {
+    build(manage_accounts(user, counter), instruction::SetKeeperTip { lamports })
+}
+
+/// Sets `counter`'s display label and URI, signed by its authority `user`, who pays for (or is
+/// refunded) the change in account size.
+pub fn set_metadata(user: &Pubkey, counter: &CounterRef, label: &str, uri: &str) -> Instruction {
+    build(
+        accounts::SetMetadata {
+            counter: counter.address(),
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::SetMetadata { label: label.to_string(), uri: uri.to_string() },
+    )
+}
+
+/// Removes `counter`'s display metadata and refunds the freed rent to its authority `user`.
+pub fn clear_metadata(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(
+        accounts::ClearMetadata {
+            counter: counter.address(),
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::ClearMetadata {},
+    )
+}
+
+/// Makes `counter` reset itself every `interval_seconds` (0 = never), signed by its authority `user`.
+pub fn set_reset_interval(user: &Pubkey, counter: &CounterRef, interval_seconds: i64) -> Instruction {
+    build(manage_accounts(user, counter), instruction::SetResetInterval { interval_seconds })
+}
+
+/// Makes `counter` lose `rate_per_day` for every full day from now on (0 = no decay), signed
+/// by its authority `user`.
+pub fn set_decay_rate(user: &Pubkey, counter: &CounterRef, rate_per_day: u64) -> Instruction {
+    build(manage_accounts(user, counter), instruction::SetDecayRate { rate_per_day })
+}
+
+/// Makes one [`increment`] / [`decrement`] of `counter` move it by `step`, signed by its authority `user`.
+pub fn set_step(user: &Pubkey, counter: &CounterRef, step: u64) -> Instruction {
+    build(manage_accounts(user, counter), instruction::SetStep { step })
+}
+
+/// Settles the decay `counter` owes. Anyone can send it; it needs no signer but the fee payer.
+pub fn apply_decay(counter: &CounterRef) -> Instruction {
+    build(
+        accounts::ApplyDecay {
+            counter: counter.address(),
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::ApplyDecay {},
+    )
+}
+
+/// Runs `counter`'s clock-based upkeep (decay, period rollover, expiry) as `keeper`, who is
+/// paid the counter's keeper tip from its vault. Anyone may call it.
+pub fn touch(keeper: &Pubkey, counter: &CounterRef) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::Touch {
+            counter: address,
+            vault: pda::vault(&address, &PROGRAM_ID).0,
+            keeper: *keeper,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::Touch {},
+    )
+}
+
+/// Locks `counter` until [`unfreeze`], signed by its authority `user`.
+pub fn freeze(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(manage_accounts(user, counter), instruction::Freeze {})
+}
+
+/// Archives `counter` for good once its counting window has closed; anyone may sign as `user`.
+pub fn finalize(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(
+        accounts::Finalize { counter: counter.address(), user: *user, config: pda::config(&PROGRAM_ID).0 },
+        instruction::Finalize {},
+    )
+}
+
+/// Unlocks a frozen `counter`, signed by its authority `user`.
+pub fn unfreeze(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(manage_accounts(user, counter), instruction::Unfreeze {})
+}
+
+/// Lets `member` increment `counter`, signed by its authority `user`.
+pub fn add_contributor(user: &Pubkey, counter: &CounterRef, member: &Pubkey) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::AddContributor {
+            counter,
+            contributor: pda::contributor(&counter, member, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::AddContributor { member: *member },
+    )
+}
+
+/// Removes `member` from `counter`, refunding the registration's rent to the authority `user`.
+pub fn remove_contributor(user: &Pubkey, counter: &CounterRef, member: &Pubkey) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::RemoveContributor {
+            counter,
+            contributor: pda::contributor(&counter, member, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::RemoveContributor {},
+    )
+}
+
+/// Grants `member` the `roles` bits (see `counter::role`) on `counter`, signed by its authority `user`.
+pub fn grant_role(user: &Pubkey, counter: &CounterRef, member: &Pubkey, roles: u8) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::GrantRole {
+            counter,
+            role: pda::role(&counter, member, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::GrantRole { member: *member, roles },
+    )
+}
+
+/// Takes the `roles` bits away from `member`; once none are left the account's rent goes back
+/// to the authority `user`.
+pub fn revoke_role(user: &Pubkey, counter: &CounterRef, member: &Pubkey, roles: u8) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::RevokeRole {
+            counter,
+            role: pda::role(&counter, member, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::RevokeRole { roles },
+    )
+}
+
+/// The commitment [`commit`] takes for `value`: sha256(value as 8 little-endian bytes || salt).
+pub fn commitment_hash(value: u64, salt: &[u8; 32]) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hashv(&[&value.to_le_bytes(), salt]).to_bytes()
+}
+
+/// Seals `commitment` (see [`commitment_hash`]) for `counter`, paid for by `user`.
+pub fn commit(user: &Pubkey, counter: &CounterRef, commitment: [u8; 32]) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::Commit {
+            counter,
+            commitment: pda::commitment(&counter, user, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::Commit { commitment },
+    )
+}
+
+/// Reveals the `value` and `salt` `user` committed to, adding `value` to `counter`.
+pub fn reveal(user: &Pubkey, counter: &CounterRef, value: u64, salt: [u8; 32]) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::Reveal {
+            counter: address,
+            commitment: pda::commitment(&address, user, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            history: counter.history.then(|| pda::history(&address, &PROGRAM_ID).0),
+        },
+        instruction::Reveal { value, salt },
+    )
+}
+
+/// Lets `session_key` increment and decrement `counter` for its authority `user` until
+/// `expires_at` or `max_ops` operations, whichever comes first.
+pub fn create_session(user: &Pubkey, counter: &CounterRef, session_key: &Pubkey, expires_at: i64, max_ops: u32) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::CreateSession {
+            counter,
+            session: pda::session(&counter, session_key, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::CreateSession { session_key: *session_key, expires_at, max_ops },
+    )
+}
+
+/// Ends `session_key`'s session on `counter` early, signed by its authority `user`.
+pub fn revoke_session(user: &Pubkey, counter: &CounterRef, session_key: &Pubkey) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::RevokeSession {
+            counter,
+            session: pda::session(&counter, session_key, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::RevokeSession {},
+    )
+}
+
+/// Puts `counter` on the program-wide leaderboard (or refreshes its place there), signed by its
+/// authority `user`, who pays for the leaderboard if it doesn't exist yet.
+pub fn submit_score(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(
+        accounts::SubmitScore {
+            counter: counter.address(),
+            leaderboard: pda::leaderboard(&PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::SubmitScore {},
+    )
+}
+
+/// Hands `counter` to a multisig of `owners`, `threshold` of which must approve every change,
+/// signed by its current authority `user`.
+pub fn create_multisig(user: &Pubkey, counter: &CounterRef, owners: &[Pubkey], threshold: u8) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::CreateMultisig {
+            counter,
+            multisig: pda::multisig(&counter, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::CreateMultisig { owners: owners.to_vec(), threshold },
+    )
+}
+
+/// Proposes `op` to `counter`'s multisig, signed by one of its owners `user`. `index` must be
+/// the multisig's current `proposal_count`.
+pub fn propose_op(user: &Pubkey, counter: &CounterRef, index: u64, op: MultisigOp) -> Instruction {
+    let multisig = pda::multisig(&counter.address(), &PROGRAM_ID).0;
+    build(
+        accounts::ProposeOp {
+            multisig,
+            proposal: pda::proposal(&multisig, index, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::ProposeOp { op },
+    )
+}
+
+/// Approves proposal `index` of `counter`'s multisig, signed by one of its owners `user`.
+pub fn approve_op(user: &Pubkey, counter: &CounterRef, index: u64) -> Instruction {
+    let multisig = pda::multisig(&counter.address(), &PROGRAM_ID).0;
+    build(
+        accounts::ApproveOp {
+            multisig,
+            proposal: pda::proposal(&multisig, index, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::ApproveOp {},
+    )
+}
+
+/// Executes proposal `index` of `counter`'s multisig once it has enough approvals. Anyone may
+/// send it; the proposal's rent goes back to `proposer`.
+pub fn execute_op(user: &Pubkey, counter: &CounterRef, index: u64, proposer: &Pubkey) -> Instruction {
+    let address = counter.address();
+    let multisig = pda::multisig(&address, &PROGRAM_ID).0;
+    build(
+        accounts::ExecuteOp {
+            counter: address,
+            multisig,
+            proposal: pda::proposal(&multisig, index, &PROGRAM_ID).0,
+            proposer: *proposer,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            history: counter.history.then(|| pda::history(&address, &PROGRAM_ID).0),
+        },
+        instruction::ExecuteOp {},
+    )
+}
+
+/// Sets what others pay the authority `user` per unit they add to `counter`.
+pub fn set_counter_fee(user: &Pubkey, counter: &CounterRef, fee_lamports: u64) -> Instruction {
+    build(manage_accounts(user, counter), instruction::SetCounterFee { fee_lamports })
+}
+
+/// Makes increments of `counter` announce every multiple of `interval` they reach (0 turns it off).
+pub fn set_milestone_interval(user: &Pubkey, counter: &CounterRef, interval: u64) -> Instruction {
+    build(manage_accounts(user, counter), instruction::SetMilestoneInterval { interval })
+}
+
+/// Limits changes to `counter` by anyone but its authority `user` to holders of at least
+/// `min_balance` of `gate_mint` (the default key removes the gate).
+pub fn set_token_gate(user: &Pubkey, counter: &CounterRef, gate_mint: &Pubkey, min_balance: u64) -> Instruction {
+    build(
+        manage_accounts(user, counter),
+        instruction::SetTokenGate {
+            gate_mint: *gate_mint,
+            min_balance,
+        },
+    )
+}
+
+/// Lets [`increment_weighted`] add one to `counter` for every `divisor` base units of
+/// `weight_mint` the caller holds, signed by its authority `user` (the default key turns it off).
+pub fn set_weight_config(user: &Pubkey, counter: &CounterRef, weight_mint: &Pubkey, divisor: u64) -> Instruction {
+    build(
+        manage_accounts(user, counter),
+        instruction::SetWeightConfig {
+            weight_mint: *weight_mint,
+            divisor,
+        },
+    )
+}
+
+/// Lets `feed_authority` set `counter` with [`push_value`], signed by its authority `user`
+/// (None stops the feed).
+pub fn set_feed_authority(user: &Pubkey, counter: &CounterRef, feed_authority: Option<Pubkey>) -> Instruction {
+    build(manage_accounts(user, counter), instruction::SetFeedAuthority { feed_authority })
+}
+
+/// Sets `counter` to `value`, observed at `observed_at` (unix timestamp), signed by its feed
+/// authority.
+pub fn push_value(feed_authority: &Pubkey, counter: &CounterRef, value: u64, observed_at: i64) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::PushValue {
+            counter: address,
+            feed_authority: *feed_authority,
+            config: pda::config(&PROGRAM_ID).0,
+            history: counter.history.then(|| pda::history(&address, &PROGRAM_ID).0),
+        },
+        instruction::PushValue { value, observed_at },
+    )
+}
+
+/// Withdraws `amount` lamports of collected increment fees from `counter`'s treasury to its authority `user`.
+pub fn withdraw_fees(user: &Pubkey, counter: &CounterRef, amount: u64) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::WithdrawFees {
+            counter,
+            counter_treasury: pda::counter_treasury(&counter, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::WithdrawFees { amount },
+    )
+}
+
+/// Makes every increment or decrement of `counter` by anyone but its authority `user` cost
+/// `fee_amount` of `fee_mint` (0 turns the fee off). Creates the counter's fee vault if needed.
+pub fn set_token_fee(user: &Pubkey, counter: &CounterRef, fee_mint: &Pubkey, fee_amount: u64) -> Instruction {
+    let counter = counter.address();
+    let counter_treasury = pda::counter_treasury(&counter, &PROGRAM_ID).0;
+    build(
+        accounts::SetTokenFee {
+            counter,
+            fee_mint: *fee_mint,
+            counter_treasury,
+            fee_vault: get_associated_token_address(&counter_treasury, fee_mint),
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::SetTokenFee { fee_amount },
+    )
+}
+
+/// Moves `amount` of collected token fees from `counter`'s fee vault to `destination`, signed by
+/// its authority `user`. `fee_mint` is the counter's current fee mint.
+pub fn collect_token_fees(
+    user: &Pubkey,
+    counter: &CounterRef,
+    fee_mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let counter = counter.address();
+    let counter_treasury = pda::counter_treasury(&counter, &PROGRAM_ID).0;
+    build(
+        accounts::CollectTokenFees {
+            counter,
+            counter_treasury,
+            fee_vault: get_associated_token_address(&counter_treasury, fee_mint),
+            destination_token_account: *destination,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            token_program: anchor_spl::token::ID,
+        },
+        instruction::CollectTokenFees { amount },
+    )
+}
+
+/// Withdraws `amount` lamports of vault deposits from `counter` to its authority `user`.
+pub fn withdraw_vault(user: &Pubkey, counter: &CounterRef, amount: u64) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::WithdrawVault {
+            counter,
+            vault: pda::vault(&counter, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::WithdrawVault { amount },
+    )
+}
+
+/// Sets `counter` to zero in one call, signed by its authority `user`. With `counter.vault`
+/// set, the deposits the drained count paid for are refunded to `user` from the vault.
+pub fn drain(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::Drain {
+            counter: address,
+            vault: counter.vault.then(|| pda::vault(&address, &PROGRAM_ID).0),
+            history: counter.history.then(|| pda::history(&address, &PROGRAM_ID).0),
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::Drain {},
+    )
+}
+
+/// Moves a counter written by an older program version to the current layout, signed and
+/// paid for by its authority `user`. Every other instruction rejects the counter until then.
+pub fn migrate(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(
+        accounts::Migrate {
+            counter: counter.address(),
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::Migrate { creator: counter.creator, id: counter.id() },
+    )
+}
+
+/// Starts logging changes to `counter` in its `CounterHistory`, signed by its authority `user`.
+/// Address the counter through [`CounterRef::with_history`] from then on.
+pub fn initialize_history(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::InitializeHistory {
+            counter,
+            history: pda::history(&counter, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeHistory {},
+    )
+}
+
+/// Creates `user`'s counter array named `label`.
+pub fn initialize_array(user: &Pubkey, label: &str) -> Instruction {
+    build(
+        accounts::InitializeArray {
+            array: pda::counter_array(user, label, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeArray { id: label.to_string() },
+    )
+}
+
+fn array_accounts(user: &Pubkey, label: &str) -> accounts::UpdateArray {
+    accounts::UpdateArray {
+        array: pda::counter_array(user, label, &PROGRAM_ID).0,
+        user: *user,
+        config: pda::config(&PROGRAM_ID).0,
+    }
+}
+
+/// Adds one to slot `index` of `user`'s counter array named `label`.
+pub fn increment_slot(user: &Pubkey, label: &str, index: u16) -> Instruction {
+    build(array_accounts(user, label), instruction::IncrementSlot { id: label.to_string(), index })
+}
+
+/// Subtracts one from slot `index` of `user`'s counter array named `label`.
+pub fn decrement_slot(user: &Pubkey, label: &str, index: u16) -> Instruction {
+    build(array_accounts(user, label), instruction::DecrementSlot { id: label.to_string(), index })
+}
+
+/// Replaces the `slot_flag` bits of slot `index` of `user`'s counter array named `label`.
+pub fn set_slot_flags(user: &Pubkey, label: &str, index: u16, flags: u8) -> Instruction {
+    build(array_accounts(user, label), instruction::SetSlotFlags { id: label.to_string(), index, flags })
+}
+
+/// Creates `user`'s signed counter named `label`.
+pub fn initialize_signed(user: &Pubkey, label: &str) -> Instruction {
+    build(
+        accounts::InitializeSigned {
+            counter: pda::signed_counter(user, label, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeSigned { id: label.to_string() },
+    )
+}
+
+fn signed_accounts(user: &Pubkey, label: &str) -> accounts::UpdateSigned {
+    accounts::UpdateSigned {
+        counter: pda::signed_counter(user, label, &PROGRAM_ID).0,
+        user: *user,
+        config: pda::config(&PROGRAM_ID).0,
+    }
+}
+
+/// Adds `amount` to `user`'s signed counter named `label`.
+pub fn increment_signed(user: &Pubkey, label: &str, amount: u64) -> Instruction {
+    build(signed_accounts(user, label), instruction::IncrementSigned { id: label.to_string(), amount })
+}
+
+/// Subtracts `amount` from `user`'s signed counter named `label` (it may go negative).
+pub fn decrement_signed(user: &Pubkey, label: &str, amount: u64) -> Instruction {
+    build(signed_accounts(user, label), instruction::DecrementSigned { id: label.to_string(), amount })
+}
+
+/// Creates `user`'s big (u128) counter named `label`.
+pub fn initialize_big(user: &Pubkey, label: &str) -> Instruction {
+    build(
+        accounts::InitializeBig {
+            counter: pda::big_counter(user, label, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeBig { id: label.to_string() },
+    )
+}
+
+fn big_accounts(user: &Pubkey, label: &str) -> accounts::UpdateBig {
+    accounts::UpdateBig {
+        counter: pda::big_counter(user, label, &PROGRAM_ID).0,
+        user: *user,
+        config: pda::config(&PROGRAM_ID).0,
+    }
+}
+
+/// Adds `amount` to `user`'s big counter named `label`.
+pub fn increment_big(user: &Pubkey, label: &str, amount: u64) -> Instruction {
+    build(big_accounts(user, label), instruction::IncrementBig { id: label.to_string(), amount })
+}
+
+/// Subtracts `amount` from `user`'s big counter named `label`.
+pub fn decrement_big(user: &Pubkey, label: &str, amount: u64) -> Instruction {
+    build(big_accounts(user, label), instruction::DecrementBig { id: label.to_string(), amount })
+}
+
+/// Moves `counter` into a new big counter of `user`, its authority, under the same label, and
+/// closes it.
+pub fn upgrade_to_big(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(
+        accounts::UpgradeToBig {
+            counter: counter.address(),
+            big_counter: pda::big_counter(user, counter.label, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            registry: counter.registry(),
+            system_program: system_program::ID,
+        },
+        instruction::UpgradeToBig {},
+    )
+}
+
+/// Creates `user`'s zero-copy counter named `label`.
+pub fn initialize_zc(user: &Pubkey, label: &str) -> Instruction {
+    build(
+        accounts::InitializeZC {
+            counter: pda::counter_zc(user, label, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeZc { id: label.to_string() },
+    )
+}
+
+fn zc_accounts(user: &Pubkey, label: &str) -> accounts::UpdateZC {
+    accounts::UpdateZC {
+        counter: pda::counter_zc(user, label, &PROGRAM_ID).0,
+        user: *user,
+        config: pda::config(&PROGRAM_ID).0,
+    }
+}
+
+/// Adds one to `user`'s zero-copy counter named `label`.
+pub fn increment_zc(user: &Pubkey, label: &str) -> Instruction {
+    build(zc_accounts(user, label), instruction::IncrementZc { id: label.to_string() })
+}
+
+/// Subtracts one from `user`'s zero-copy counter named `label`.
+pub fn decrement_zc(user: &Pubkey, label: &str) -> Instruction {
+    build(zc_accounts(user, label), instruction::DecrementZc { id: label.to_string() })
+}
+
+/// Moves `counter` into a new zero-copy counter of `user`, its authority, under the same label,
+/// and closes it.
+pub fn convert_to_zc(user: &Pubkey, counter: &CounterRef) -> Instruction {
+    build(
+        accounts::ConvertToZC {
+            counter: counter.address(),
+            counter_zc: pda::counter_zc(user, counter.label, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            registry: counter.registry(),
+            system_program: system_program::ID,
+        },
+        instruction::ConvertToZc {},
+    )
+}
+
+/// Closes the expired `counter` owned by `authority`; `cranker` gets the cranker share of the rent.
+pub fn collect_expired(counter: &CounterRef, authority: &Pubkey, cranker: &Pubkey) -> Instruction {
+    build(
+        accounts::CollectExpired {
+            counter: counter.address(),
+            authority: *authority,
+            cranker: *cranker,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            registry: counter.registry(),
+        },
+        instruction::CollectExpired {},
+    )
+}
+
+/// Closes the soft-closed `counter` owned by `authority` once its grace period is over; `cranker`
+/// gets the cranker share of the rent.
+pub fn reclaim_closed(counter: &CounterRef, authority: &Pubkey, cranker: &Pubkey) -> Instruction {
+    build(
+        accounts::ReclaimClosed {
+            counter: counter.address(),
+            authority: *authority,
+            cranker: *cranker,
+            config: pda::config(&PROGRAM_ID).0,
+            stats: pda::global_stats(&PROGRAM_ID).0,
+            registry: counter.registry(),
+        },
+        instruction::ReclaimClosed {},
+    )
+}
+
+/// Drops the `counters` that no longer exist from the registry of their creator `user`, e.g.
+/// after a cleanup run without it; counters that still exist are left listed.
+pub fn prune_registry(user: &Pubkey, counters: &[Pubkey]) -> Instruction {
+    let mut ix = build(
+        accounts::PruneRegistry {
+            registry: pda::registry(user, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::PruneRegistry {},
+    );
+    ix.accounts.extend(counters.iter().map(|counter| AccountMeta::new_readonly(*counter, false)));
+    ix
+}
+
+/// Proves the activity of `counter`; `prover` pays for the receipt.
+pub fn prove_activity(counter: &CounterRef, prover: &Pubkey, min_ops: u32, window_days: u8) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::ProveActivity {
+            counter,
+            receipt: pda::activity_receipt(&counter, min_ops, window_days, &PROGRAM_ID).0,
+            prover: *prover,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::ProveActivity { min_ops, window_days },
+    )
+}
+
+/// Reads `counter` without changing it; the answer comes back as return data (see [`decode_view`]).
+pub fn view_count(counter: &CounterRef) -> Instruction {
+    build(
+        accounts::ViewCount {
+            counter: counter.address(),
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::ViewCount {},
+    )
+}
+
+/// Reads `counter`'s time-weighted average count without changing it; the answer comes back as
+/// return data (see [`decode_twa`]).
+pub fn observe_twa(counter: &CounterRef) -> Instruction {
+    build(
+        accounts::ViewCount {
+            counter: counter.address(),
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::ObserveTwa {},
+    )
+}
+
+/// Moves `lamports` from `user` into `account`, any of the program's accounts `user` is the
+/// authority of (e.g. a counter or registry left short of rent exemption).
+pub fn top_up(user: &Pubkey, account: &Pubkey, lamports: u64) -> Instruction {
+    build(
+        accounts::TopUp {
+            account: *account,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::TopUp { lamports },
+    )
+}
+
+/// Checks whether `user` could make `op` by `amount` on `counter` right now, without making it;
+/// the answer comes back as return data (see [`decode_validation`]). No operator approval,
+/// session, contributor registration or NFT is passed in, so `user` is checked on their own;
+/// the change log, hook and group are passed as `counter` says, like for the change itself.
+pub fn validate_op(user: &Pubkey, counter: &CounterRef, op: CounterOperation, amount: u64) -> Instruction {
+    let address = counter.address();
+    build(
+        accounts::ValidateOp {
+            counter: address,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            delegation: None,
+            session: None,
+            contributor: None,
+            role: None,
+            gate_token_account: None,
+            nft_token_account: None,
+            nft_metadata: None,
+            history: counter.history.then(|| pda::history(&address, &PROGRAM_ID).0),
+            hook_program: counter.hook,
+            group: counter.group,
+        },
+        instruction::ValidateOp { op, amount },
+    )
+}
+
+/// Records the current value of `counter` under `label`, signed and paid for by its authority `user`.
+pub fn snapshot(user: &Pubkey, counter: &CounterRef, label: u64) -> Instruction {
+    let counter = counter.address();
+    build(
+        accounts::TakeSnapshot {
+            counter,
+            snapshot: pda::snapshot(&counter, label, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+            system_program: system_program::ID,
+        },
+        instruction::Snapshot { label },
+    )
+}
+
+/// Deletes the snapshot of `counter` (an address, since the counter may be gone) taken under
+/// `label`, refunding its rent to `user`, who took it.
+pub fn close_snapshot(user: &Pubkey, counter: &Pubkey, label: u64) -> Instruction {
+    build(
+        accounts::CloseSnapshot {
+            snapshot: pda::snapshot(counter, label, &PROGRAM_ID).0,
+            user: *user,
+            config: pda::config(&PROGRAM_ID).0,
+        },
+        instruction::CloseSnapshot {},
+    )
+}
+
+/// Decodes raw account data (discriminator included) into one of the program's accounts.
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_counter(data: &[u8]) -> Result<Counter> {
+    decode(data)
+}
+
+pub fn decode_config(data: &[u8]) -> Result<ProgramConfig> {
+    decode(data)
+}
+
+pub fn decode_global_stats(data: &[u8]) -> Result<GlobalStats> {
+    decode(data)
+}
+
+pub fn decode_sequencer(data: &[u8]) -> Result<Sequencer> {
+    decode(data)
+}
+
+pub fn decode_activity_receipt(data: &[u8]) -> Result<ActivityReceipt> {
+    decode(data)
+}
+
+pub fn decode_delegation(data: &[u8]) -> Result<Delegation> {
+    decode(data)
+}
+
+pub fn decode_contributor(data: &[u8]) -> Result<Contributor> {
+    decode(data)
+}
+
+pub fn decode_session(data: &[u8]) -> Result<Session> {
+    decode(data)
+}
+
+pub fn decode_leaderboard(data: &[u8]) -> Result<Leaderboard> {
+    decode(data)
+}
+
+pub fn decode_template(data: &[u8]) -> Result<CounterTemplate> {
+    decode(data)
+}
+
+pub fn decode_group(data: &[u8]) -> Result<CounterGroup> {
+    decode(data)
+}
+
+pub fn decode_wager(data: &[u8]) -> Result<Wager> {
+    decode(data)
+}
+
+pub fn decode_commitment(data: &[u8]) -> Result<Commitment> {
+    decode(data)
+}
+
+pub fn decode_role(data: &[u8]) -> Result<Role> {
+    decode(data)
+}
+
+pub fn decode_multisig(data: &[u8]) -> Result<MultisigAuthority> {
+    decode(data)
+}
+
+pub fn decode_proposal(data: &[u8]) -> Result<MultisigProposal> {
+    decode(data)
+}
+
+pub fn decode_signed_counter(data: &[u8]) -> Result<SignedCounter> {
+    decode(data)
+}
+
+pub fn decode_big_counter(data: &[u8]) -> Result<BigCounter> {
+    decode(data)
+}
+
+pub fn decode_snapshot(data: &[u8]) -> Result<CounterSnapshot> {
+    decode(data)
+}
+
+pub fn decode_registry(data: &[u8]) -> Result<UserRegistry> {
+    decode(data)
+}
+
+pub fn decode_defaults(data: &[u8]) -> Result<UserDefaults> {
+    decode(data)
+}
+
+/// Decodes the return data of a [`view_count`] call (e.g. from a simulated transaction).
+pub fn decode_view(return_data: &[u8]) -> Result<CounterView> {
+    Ok(CounterView::try_from_slice(return_data)?)
+}
+
+/// Decodes the return data of an [`export`] call, ready to pass to the new program's `import`.
+pub fn decode_export(return_data: &[u8]) -> Result<ExportedCounter> {
+    Ok(ExportedCounter::try_from_slice(return_data)?)
+}
+
+/// Decodes the return data of an [`observe_twa`] call (e.g. from a simulated transaction).
+pub fn decode_twa(return_data: &[u8]) -> Result<TwaView> {
+    Ok(TwaView::try_from_slice(return_data)?)
+}
+
+/// Decodes the return data of a [`validate_op`] call (e.g. from a simulated transaction).
+pub fn decode_validation(return_data: &[u8]) -> Result<OpValidation> {
+    Ok(OpValidation::try_from_slice(return_data)?)
+}
+
+/// Decodes a zero-copy account, which is read straight from the bytes after the discriminator.
+pub fn decode_zero_copy<T: anchor_lang::ZeroCopy>(data: &[u8]) -> Result<T> {
+    let body = data
+        .strip_prefix(T::DISCRIMINATOR)
+        .ok_or(ErrorCode::AccountDiscriminatorMismatch)?;
+    body.get(..std::mem::size_of::<T>())
+        .and_then(|bytes| bytemuck::try_pod_read_unaligned(bytes).ok())
+        .ok_or_else(|| ErrorCode::AccountDidNotDeserialize.into())
+}
+
+pub fn decode_history(data: &[u8]) -> Result<CounterHistory> {
+    decode_zero_copy(data)
+}
+
+pub fn decode_counter_array(data: &[u8]) -> Result<Box<CounterArray>> {
+    decode_zero_copy(data).map(Box::new)
+}
+
+pub fn decode_counter_zc(data: &[u8]) -> Result<CounterZC> {
+    decode_zero_copy(data)
+}
@@ -0,0 +1,153 @@
+//! Fetching the program's accounts and events over JSON-RPC (the `rpc` feature).
+//!
+//! Each account helper reads the account with a blocking `RpcClient` and decodes it like
+//! [`decode`], so an address holding some other account fails instead of being misread.
+//!
+//! The event helpers are for backfilling an indexer from history: they pull confirmed
+//! transactions (one by signature, or every one in a range of blocks) and decode the counter
+//! events in them, whether they were logged (`emit!`) or self-invoked (`emit_cpi!`, see
+//! [`events::decode_cpi`]). Failed transactions have no events.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anchor_lang::prelude::*;
+use counter::{Counter, GlobalStats, ProgramConfig};
+use counter_interface::pda;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::client_error::Error as ClientError;
+use solana_rpc_client_api::config::{RpcBlockConfig, RpcTransactionConfig};
+use solana_signature::Signature;
+use solana_transaction_status_client_types::{
+    EncodedTransactionWithStatusMeta, TransactionDetails, UiInstruction, UiTransactionEncoding,
+};
+
+use crate::events::{self, CounterEvent};
+use crate::{decode, CounterRef, PROGRAM_ID};
+
+/// Why a fetch failed: the RPC call itself, or decoding what it returned.
+#[derive(Debug)]
+pub enum FetchError {
+    Rpc(Box<ClientError>), // Boxed: the client error is much bigger than the rest
+    Decode(anchor_lang::error::Error),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Rpc(error) => write!(f, "RPC request failed: {error}"),
+            FetchError::Decode(error) => write!(f, "account could not be decoded: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+/// Fetches and decodes any of the program's accounts.
+pub fn fetch<T: AccountDeserialize>(rpc: &RpcClient, address: &Pubkey) -> std::result::Result<T, FetchError> {
+    let data = rpc.get_account_data(address).map_err(|error| FetchError::Rpc(Box::new(error)))?;
+    decode(&data).map_err(FetchError::Decode)
+}
+
+pub fn fetch_counter(rpc: &RpcClient, counter: &CounterRef) -> std::result::Result<Counter, FetchError> {
+    fetch(rpc, &counter.address())
+}
+
+/// Fetches several counters in one request. Counters that don't exist come back as `None`.
+pub fn fetch_counters(rpc: &RpcClient, counters: &[CounterRef]) -> std::result::Result<Vec<Option<Counter>>, FetchError> {
+    let addresses: Vec<Pubkey> = counters.iter().map(CounterRef::address).collect();
+    rpc.get_multiple_accounts(&addresses)
+        .map_err(|error| FetchError::Rpc(Box::new(error)))?
+        .into_iter()
+        .map(|account| account.map(|account| decode(&account.data)).transpose().map_err(FetchError::Decode))
+        .collect()
+}
+
+pub fn fetch_config(rpc: &RpcClient) -> std::result::Result<ProgramConfig, FetchError> {
+    fetch(rpc, &pda::config(&PROGRAM_ID).0)
+}
+
+pub fn fetch_global_stats(rpc: &RpcClient) -> std::result::Result<GlobalStats, FetchError> {
+    fetch(rpc, &pda::global_stats(&PROGRAM_ID).0)
+}
+
+/// The counter events of the confirmed transaction `signature`, logged events first, each
+/// kind in the order it was emitted.
+pub fn fetch_transaction_events(rpc: &RpcClient, signature: &Signature) -> std::result::Result<Vec<CounterEvent>, FetchError> {
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: None,
+        max_supported_transaction_version: Some(0),
+    };
+    let confirmed = rpc
+        .get_transaction_with_config(signature, config)
+        .map_err(|error| FetchError::Rpc(Box::new(error)))?;
+    Ok(transaction_events(&confirmed.transaction))
+}
+
+/// The counter events of every confirmed block from `start_slot` to `end_slot` (inclusive),
+/// each with the signature of the transaction that emitted it, in ledger order.
+pub fn fetch_block_events(
+    rpc: &RpcClient,
+    start_slot: u64,
+    end_slot: u64,
+) -> std::result::Result<Vec<(Signature, CounterEvent)>, FetchError> {
+    let config = RpcBlockConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        transaction_details: Some(TransactionDetails::Full),
+        rewards: Some(false),
+        commitment: None,
+        max_supported_transaction_version: Some(0),
+    };
+    let mut found = Vec::new();
+    for slot in rpc.get_blocks(start_slot, Some(end_slot)).map_err(|error| FetchError::Rpc(Box::new(error)))? {
+        let block = rpc.get_block_with_config(slot, config).map_err(|error| FetchError::Rpc(Box::new(error)))?;
+        for transaction in block.transactions.iter().flatten() {
+            let Some(signature) = transaction.transaction.decode().and_then(|tx| tx.signatures.first().copied()) else {
+                continue;
+            };
+            found.extend(transaction_events(transaction).into_iter().map(|event| (signature, event)));
+        }
+    }
+    Ok(found)
+}
+
+// The counter events of one transaction, from its logs and its inner instructions
+fn transaction_events(transaction: &EncodedTransactionWithStatusMeta) -> Vec<CounterEvent> {
+    let Some(meta) = transaction.meta.as_ref().filter(|meta| meta.err.is_none()) else {
+        return Vec::new();
+    };
+    let logs: Option<&Vec<String>> = meta.log_messages.as_ref().into();
+    let mut found: Vec<CounterEvent> = logs.into_iter().flatten().filter_map(|line| events::decode_log(line)).collect();
+
+    // `emit_cpi!` events are inner instructions to the program itself. Their program is an index
+    // into the transaction's keys: its own, then any loaded from lookup tables
+    let Some(tx) = transaction.transaction.decode() else {
+        return found;
+    };
+    let loaded: Option<&_> = meta.loaded_addresses.as_ref().into();
+    let keys: Vec<Pubkey> = tx
+        .message
+        .static_account_keys()
+        .iter()
+        .copied()
+        .chain(
+            loaded
+                .into_iter()
+                .flat_map(|loaded| loaded.writable.iter().chain(&loaded.readonly))
+                .filter_map(|key| Pubkey::from_str(key).ok()),
+        )
+        .collect();
+    let inner: Option<&Vec<_>> = meta.inner_instructions.as_ref().into();
+    for instruction in inner.into_iter().flatten().flat_map(|inner| &inner.instructions) {
+        let UiInstruction::Compiled(instruction) = instruction else {
+            continue;
+        };
+        if keys.get(instruction.program_id_index as usize) != Some(&PROGRAM_ID) {
+            continue;
+        }
+        let data = bs58::decode(&instruction.data).into_vec().unwrap_or_default();
+        found.extend(events::decode_cpi(&data));
+    }
+    found
+}
@@ -0,0 +1,237 @@
+//! Runs the compiled counter program in LiteSVM, driven through this crate's builders.
+//!
+//! The program has to be built first (`anchor build`, or `cargo build-sbf` in
+//! `programs/counter`), so every test here is `#[ignore]`d and `cargo test` keeps working on
+//! machines without the Solana toolchain. Run them with `anchor build && cargo test -- --ignored`;
+//! without `target/deploy/counter.so` they fail rather than pass without checking anything.
+
+use std::path::PathBuf;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{Instruction, InstructionError};
+use counter::{CounterError, GlobalStats, ProgramConfig, BPS_DENOMINATOR};
+use counter_client::{self as client, interface::cu_budget, interface::pda, CounterRef, PROGRAM_ID};
+use litesvm::LiteSVM;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use solana_transaction_error::TransactionError;
+
+const LAMPORTS: u64 = 10_000_000_000;
+
+// A fresh SVM with the program loaded and its config and stats in place (panics when the
+// program hasn't been built)
+fn setup() -> (LiteSVM, Keypair) {
+    let program = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../target/deploy/counter.so");
+    assert!(program.exists(), "{} not built (run `anchor build`)", program.display());
+    let mut svm = LiteSVM::new();
+    svm.add_program_from_file(PROGRAM_ID, program).unwrap();
+    let admin = funded(&mut svm);
+
+    // `initialize_config` needs an upgradeable deployment; write what it would have instead
+    let (config, bump) = pda::config(&PROGRAM_ID);
+    put(
+        &mut svm,
+        config,
+        &ProgramConfig {
+            admin: admin.pubkey(),
+            disabled_instructions: 0,
+            bump,
+            op_fee_lamports: 0,
+            fee_mint: Pubkey::default(),
+            price_authority: admin.pubkey(),
+            tokens_per_sol: 0,
+            price_updated_at: 0,
+            max_price_age_seconds: 0,
+            refund_payer_bps: 0,
+            refund_authority_bps: BPS_DENOMINATOR as u16,
+            refund_cranker_bps: 0,
+            stale_after_seconds: 0,
+            paused: false,
+            max_counters_per_user: 0,
+            disabled_instructions_hi: 0,
+            init_fee_lamports: 0,
+            fee_recipient: Pubkey::default(),
+            close_timelock_seconds: 0,
+            close_grace_seconds: 0,
+            migration_authority: Pubkey::default(),
+            partners: vec![],
+        },
+    );
+    let (stats, bump) = pda::global_stats(&PROGRAM_ID);
+    put(
+        &mut svm,
+        stats,
+        &GlobalStats { total_counters: 0, total_increments: 0, total_decrements: 0, total_closed: 0, bump },
+    );
+    (svm, admin)
+}
+
+fn put<T: AccountSerialize>(svm: &mut LiteSVM, address: Pubkey, account: &T) {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    let lamports = svm.minimum_balance_for_rent_exemption(data.len());
+    svm.set_account(
+        address,
+        solana_account::Account { lamports, data, owner: PROGRAM_ID, executable: false, rent_epoch: 0 },
+    )
+    .unwrap();
+}
+
+fn funded(svm: &mut LiteSVM) -> Keypair {
+    let wallet = Keypair::new();
+    svm.airdrop(&wallet.pubkey(), LAMPORTS).unwrap();
+    wallet
+}
+
+fn send(svm: &mut LiteSVM, signer: &Keypair, instruction: Instruction) -> std::result::Result<(), TransactionError> {
+    svm.expire_blockhash(); // Repeating an instruction must not repeat the signature
+    let tx = Transaction::new_signed_with_payer(&[instruction], Some(&signer.pubkey()), &[signer], svm.latest_blockhash());
+    svm.send_transaction(tx).map(|_| ()).map_err(|failure| failure.err)
+}
+
+// Sends `instruction`, which has to succeed, and returns the compute units it used
+fn units(svm: &mut LiteSVM, signer: &Keypair, instruction: Instruction) -> u64 {
+    svm.expire_blockhash();
+    let tx = Transaction::new_signed_with_payer(&[instruction], Some(&signer.pubkey()), &[signer], svm.latest_blockhash());
+    svm.send_transaction(tx).expect("instruction succeeds").compute_units_consumed
+}
+
+fn create(svm: &mut LiteSVM, owner: &Keypair, label: &str) {
+    let initialize = client::initialize(&owner.pubkey(), label, None, None, false, None, None, None, None, None, None, None, None, None);
+    send(svm, owner, initialize).unwrap();
+}
+
+fn count(svm: &LiteSVM, counter: &CounterRef) -> u64 {
+    let account = svm.get_account(&counter.address()).expect("counter exists");
+    client::decode_counter(&account.data).unwrap().count
+}
+
+// The custom error code the program failed with
+fn error_code(failure: TransactionError) -> u32 {
+    match failure {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => code,
+        other => panic!("expected a program error, got {other:?}"),
+    }
+}
+
+#[test]
+#[ignore = "needs target/deploy/counter.so: anchor build && cargo test -- --ignored"]
+fn initialize_and_increment() {
+    let (mut svm, _) = setup();
+    let owner = funded(&mut svm);
+    let counter = CounterRef::new(owner.pubkey(), "steps");
+    create(&mut svm, &owner, "steps");
+    assert_eq!(count(&svm, &counter), 0);
+
+    send(&mut svm, &owner, client::increment(&owner.pubkey(), &counter, false, None)).unwrap();
+    send(&mut svm, &owner, client::increment(&owner.pubkey(), &counter, false, None)).unwrap();
+    send(&mut svm, &owner, client::decrement(&owner.pubkey(), &counter, false, None)).unwrap();
+    assert_eq!(count(&svm, &counter), 1);
+
+    let account = svm.get_account(&counter.address()).unwrap();
+    let state = client::decode_counter(&account.data).unwrap();
+    assert_eq!(state.authority, owner.pubkey());
+    assert_eq!(state.label, "steps");
+}
+
+#[test]
+#[ignore = "needs target/deploy/counter.so: anchor build && cargo test -- --ignored"]
+fn increment_overflow_fails() {
+    let (mut svm, _) = setup();
+    let owner = funded(&mut svm);
+    let counter = CounterRef::new(owner.pubkey(), "");
+    create(&mut svm, &owner, "");
+    send(&mut svm, &owner, client::increment(&owner.pubkey(), &counter, false, None)).unwrap();
+
+    let overflow = client::increment_by(&owner.pubkey(), &counter, u64::MAX, false, None);
+    let failure = send(&mut svm, &owner, overflow).unwrap_err();
+    assert_eq!(error_code(failure), u32::from(CounterError::CounterOverflow));
+    assert_eq!(count(&svm, &counter), 1);
+}
+
+#[test]
+#[ignore = "needs target/deploy/counter.so: anchor build && cargo test -- --ignored"]
+fn decrement_below_zero_fails() {
+    let (mut svm, _) = setup();
+    let owner = funded(&mut svm);
+    let counter = CounterRef::new(owner.pubkey(), "");
+    create(&mut svm, &owner, "");
+
+    let failure = send(&mut svm, &owner, client::decrement(&owner.pubkey(), &counter, false, None)).unwrap_err();
+    assert_eq!(error_code(failure), u32::from(CounterError::CounterUnderflow));
+}
+
+#[test]
+#[ignore = "needs target/deploy/counter.so: anchor build && cargo test -- --ignored"]
+fn strangers_cannot_change_or_close() {
+    let (mut svm, _) = setup();
+    let owner = funded(&mut svm);
+    let stranger = funded(&mut svm);
+    let counter = CounterRef::new(owner.pubkey(), "");
+    create(&mut svm, &owner, "");
+
+    let increment = client::increment(&stranger.pubkey(), &counter, false, None);
+    let failure = send(&mut svm, &stranger, increment).unwrap_err();
+    assert_eq!(error_code(failure), u32::from(CounterError::Unauthorized));
+
+    let close = client::close(&stranger.pubkey(), &counter, false, None);
+    let failure = send(&mut svm, &stranger, close).unwrap_err();
+    assert_eq!(error_code(failure), u32::from(CounterError::Unauthorized));
+    assert_eq!(count(&svm, &counter), 0);
+}
+
+#[test]
+#[ignore = "needs target/deploy/counter.so: anchor build && cargo test -- --ignored"]
+fn counter_must_match_its_label() {
+    let (mut svm, _) = setup();
+    let owner = funded(&mut svm);
+    create(&mut svm, &owner, "a");
+    create(&mut svm, &owner, "b");
+
+    // `b`'s instruction data with `a`'s account: the seeds don't derive the address passed in
+    let mut increment = client::increment(&owner.pubkey(), &CounterRef::new(owner.pubkey(), "b"), false, None);
+    increment.accounts[0].pubkey = CounterRef::new(owner.pubkey(), "a").address();
+    let failure = send(&mut svm, &owner, increment).unwrap_err();
+    assert_eq!(error_code(failure), u32::from(anchor_lang::error::ErrorCode::ConstraintSeeds));
+}
+
+#[test]
+#[ignore = "needs target/deploy/counter.so: anchor build && cargo test -- --ignored"]
+fn counters_cannot_be_created_twice() {
+    let (mut svm, _) = setup();
+    let owner = funded(&mut svm);
+    create(&mut svm, &owner, "once");
+
+    let again = client::initialize(&owner.pubkey(), "once", None, None, false, None, None, None, None, None, None, None, None, None);
+    let failure = send(&mut svm, &owner, again).unwrap_err();
+    // The System Program refuses to allocate an address that is already in use
+    assert!(matches!(failure, TransactionError::InstructionError(0, _)));
+}
+
+#[test]
+#[ignore = "needs target/deploy/counter.so: anchor build && cargo test -- --ignored"]
+fn instructions_stay_within_their_compute_budgets() {
+    let (mut svm, _) = setup();
+    let owner = funded(&mut svm);
+    let user = owner.pubkey();
+    let counter = CounterRef::new(user, "budget");
+
+    let initialize = client::initialize(&user, "budget", None, None, false, None, None, None, None, None, None, None, None, None);
+    send(&mut svm, &owner, client::initialize_zc(&user, "budget")).unwrap();
+    let used = [
+        ("initialize", units(&mut svm, &owner, initialize), cu_budget::INITIALIZE),
+        ("increment", units(&mut svm, &owner, client::increment(&user, &counter, false, None)), cu_budget::INCREMENT),
+        (
+            "increment_with_memo",
+            units(&mut svm, &owner, client::increment_with_memo(&user, &counter, "budget check", false, None)),
+            cu_budget::INCREMENT_WITH_MEMO,
+        ),
+        ("decrement", units(&mut svm, &owner, client::decrement(&user, &counter, false, None)), cu_budget::DECREMENT),
+        ("close", units(&mut svm, &owner, client::close(&user, &counter, false, None)), cu_budget::CLOSE),
+        ("increment_zc", units(&mut svm, &owner, client::increment_zc(&user, "budget")), cu_budget::INCREMENT_ZC),
+    ];
+    for (instruction, units, budget) in used {
+        assert!(units <= u64::from(budget), "{instruction} used {units} CU, over its budget of {budget}");
+    }
+}
@@ -0,0 +1,480 @@
+//! Definitions shared by the on-chain counter program and everything off-chain.
+//!
+//! This crate is plain data and pure functions: PDA seeds, limits, instruction flag bits
+//! and address derivation. The program re-exports all of it, so
+//! `counter::instruction_flag::INCREMENT` and `counter_interface::instruction_flag::INCREMENT`
+//! are the same constant. Account, event and instruction types stay in the program crate,
+//! since Anchor has to generate them there.
+
+use anchor_lang::prelude::Pubkey;
+
+/// PDA seed prefixes. The program's `#[account(seeds = ...)]` constraints spell these out as
+/// byte literals (Anchor needs literals to put the seeds in the IDL), so keep them in sync.
+pub mod seeds {
+    pub const COUNTER: &[u8] = b"counter";
+    pub const CONFIG: &[u8] = b"config";
+    pub const TREASURY: &[u8] = b"treasury";
+    pub const SEQUENCER: &[u8] = b"sequencer";
+    pub const SHORT_ID: &[u8] = b"short_id";
+    pub const ACTIVITY: &[u8] = b"activity";
+    pub const DELEGATE: &[u8] = b"delegate";
+    pub const COUNTER_TREASURY: &[u8] = b"counter_treasury";
+    pub const REWARDS: &[u8] = b"rewards";
+    pub const STATS: &[u8] = b"stats";
+    pub const REWARD_MINT: &[u8] = b"reward_mint";
+    pub const HISTORY: &[u8] = b"history";
+    pub const COUNTER_ARRAY: &[u8] = b"counter_array";
+    pub const SIGNED_COUNTER: &[u8] = b"signed_counter";
+    pub const BIG_COUNTER: &[u8] = b"big_counter";
+    pub const COUNTER_ZC: &[u8] = b"counter_zc";
+    pub const SNAPSHOT: &[u8] = b"snapshot";
+    pub const CONTRIBUTOR: &[u8] = b"contributor";
+    pub const VAULT: &[u8] = b"vault";
+    pub const REGISTRY: &[u8] = b"registry";
+    pub const MULTISIG: &[u8] = b"multisig";
+    pub const PROPOSAL: &[u8] = b"proposal";
+    pub const SESSION: &[u8] = b"session";
+    pub const LEADERBOARD: &[u8] = b"leaderboard";
+    pub const TEMPLATE: &[u8] = b"template";
+    pub const GROUP: &[u8] = b"group";
+    pub const WAGER: &[u8] = b"wager";
+    pub const WAGER_VAULT: &[u8] = b"wager_vault";
+    pub const ROLE: &[u8] = b"role";
+    pub const COMMITMENT: &[u8] = b"commitment";
+    pub const DEFAULTS: &[u8] = b"defaults";
+    pub const EVENT_AUTHORITY: &[u8] = b"__event_authority"; // Anchor's own, used by `emit_cpi!`
+}
+
+/// Longest counter label in bytes. Labels are a PDA seed, and seeds are at most 32 bytes.
+/// The empty label derives the same address as the original one-counter-per-wallet seeds.
+pub const MAX_LABEL_LEN: usize = 32;
+
+/// Longest display label and URI `set_metadata` stores on a counter, in bytes. Together they
+/// stay well below the 10KB an account may grow by in one instruction.
+pub const MAX_METADATA_LABEL_LEN: usize = 64;
+pub const MAX_METADATA_URI_LEN: usize = 200;
+
+/// Longest reason, in bytes, `increment_with_memo` and `decrement_with_memo` attach to a change.
+pub const MAX_MEMO_LEN: usize = 64;
+
+/// Length of a partner namespace ID, the seed `initialize_namespaced` puts between the
+/// "counter" prefix and the creator.
+pub const PARTNER_ID_LEN: usize = 8;
+
+/// Most partner namespaces the admin may approve at once (see `ProgramConfig.partners`).
+pub const MAX_PARTNERS: usize = 16;
+
+/// Most owners a counter's `MultisigAuthority` may have (approvals are a 16-bit mask).
+pub const MAX_MULTISIG_OWNERS: usize = 10;
+
+/// How many counters the program-wide `Leaderboard` ranks.
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// Length of a short ID: a base32 code (A-Z and 2-7) like "ABC234" that points at a counter.
+pub const SHORT_ID_LEN: usize = 6;
+/// Lamports locked in every short ID on top of rent, returned when it is released.
+pub const SHORT_ID_DEPOSIT_LAMPORTS: u64 = 10_000_000; // 0.01 SOL
+
+/// Minimum time between two heartbeats for the same counter.
+pub const HEARTBEAT_INTERVAL_SECONDS: i64 = 60;
+
+/// Number of days of per-day activity a counter remembers.
+pub const ACTIVITY_WINDOW_DAYS: usize = 7;
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// How long an owner has to `veto_claim` a `claim_authority` before the counter's recovery
+/// authority may `complete_authority_claim` and take it over.
+pub const RECOVERY_CHALLENGE_SECONDS: i64 = 7 * SECONDS_PER_DAY;
+
+/// Number of changes a `CounterHistory` remembers before it starts overwriting the oldest.
+pub const HISTORY_LEN: usize = 32;
+
+/// Most operations one `apply_batch` call may carry.
+pub const MAX_BATCH_OPS: usize = 32;
+
+/// Number of slots in a `CounterArray`.
+pub const COUNTER_ARRAY_SLOTS: usize = 1024;
+
+/// Bits in `CounterArray.flags`, one byte per slot. Bits not listed here are free for the
+/// application (e.g. an item's rarity) and are ignored by the program.
+pub mod slot_flag {
+    pub const LOCKED: u8 = 1 << 0; // The slot can't be incremented or decremented
+}
+
+/// Bits in `Role.roles`, what a member may do to the counter without being its owner.
+pub mod role {
+    pub const INCREMENTER: u8 = 1 << 0; // Increment it
+    pub const DECREMENTER: u8 = 1 << 1; // Decrement it
+    pub const ADMIN: u8 = 1 << 2; // Any change to the count (increment, decrement, set and reset)
+    pub const ALL: u8 = INCREMENTER | DECREMENTER | ADMIN;
+}
+
+/// The Metaplex Token Metadata program, whose accounts name an NFT's collection (see `Counter.nft_gate`).
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey = Pubkey::from_str_const("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Current layout of the `Counter` account, stored in `Counter.layout_version`. Counters with an
+/// older layout are rejected until `migrate` has grown and backfilled them.
+pub const COUNTER_LAYOUT_VERSION: u8 = 22; // 2 added the display metadata, 3 the reset period, 4 the token fee, 5 decay, 6 referrals, 7 close requests, 8 step, 9 nonces, 10 keeper tips, 11 alerts, 12 hooks, 13 groups, 14 namespaces, 15 counting windows, 16 weights, 17 feeds, 18 time-weighted averages, 19 soft closes, 20 authority recovery, 21 wager locks, 22 creation times
+
+/// Lamports in one SOL; token fees are priced as "fee mint base units per 1 SOL".
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// Refund splits are in basis points and must add up to this.
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// One bit per instruction in the `ProgramConfig` mask (`disabled_instructions` holds bits
+/// 0-63, `disabled_instructions_hi` the rest).
+pub mod instruction_flag {
+    pub const INITIALIZE: u128 = 1 << 0;
+    pub const INCREMENT: u128 = 1 << 1;
+    pub const DECREMENT: u128 = 1 << 2;
+    pub const CLOSE: u128 = 1 << 3;
+    pub const CLAIM_SHORT_ID: u128 = 1 << 4;
+    pub const RELEASE_SHORT_ID: u128 = 1 << 5;
+    pub const VERIFY_INVARIANTS: u128 = 1 << 6;
+    pub const HEARTBEAT: u128 = 1 << 7;
+    pub const TRANSITION_STATE: u128 = 1 << 8;
+    pub const RECLAIM_STALE: u128 = 1 << 9;
+    pub const INITIALIZE_SEQUENCER: u128 = 1 << 10;
+    pub const APPLY_DELTA: u128 = 1 << 11;
+    pub const PROVE_ACTIVITY: u128 = 1 << 12;
+    pub const INCREMENT_BY: u128 = 1 << 13;
+    pub const DECREMENT_BY: u128 = 1 << 14;
+    pub const TRANSFER_AUTHORITY: u128 = 1 << 15;
+    pub const ACCEPT_AUTHORITY: u128 = 1 << 16;
+    pub const SET_COUNT: u128 = 1 << 17;
+    pub const RESET: u128 = 1 << 18;
+    pub const APPROVE_OPERATOR: u128 = 1 << 19;
+    pub const REVOKE_OPERATOR: u128 = 1 << 20;
+    pub const SET_COUNTER_FEE: u128 = 1 << 21;
+    pub const WITHDRAW_FEES: u128 = 1 << 22;
+    pub const SET_TOKEN_GATE: u128 = 1 << 23;
+    pub const INITIALIZE_HISTORY: u128 = 1 << 24;
+    pub const INITIALIZE_ARRAY: u128 = 1 << 25;
+    pub const INCREMENT_SLOT: u128 = 1 << 26;
+    pub const DECREMENT_SLOT: u128 = 1 << 27;
+    pub const SET_SLOT_FLAGS: u128 = 1 << 28;
+    pub const INCREMENT_IF: u128 = 1 << 29;
+    pub const INITIALIZE_SIGNED: u128 = 1 << 30;
+    pub const INCREMENT_SIGNED: u128 = 1 << 31;
+    pub const DECREMENT_SIGNED: u128 = 1 << 32;
+    pub const SET_MILESTONE_INTERVAL: u128 = 1 << 33;
+    pub const CLOSE_TO: u128 = 1 << 34;
+    pub const COLLECT_EXPIRED: u128 = 1 << 35;
+    pub const SNAPSHOT: u128 = 1 << 36;
+    pub const CLOSE_SNAPSHOT: u128 = 1 << 37;
+    pub const MERGE: u128 = 1 << 38;
+    pub const SPLIT: u128 = 1 << 39;
+    pub const APPLY_BATCH: u128 = 1 << 40;
+    pub const ADD_CONTRIBUTOR: u128 = 1 << 41;
+    pub const REMOVE_CONTRIBUTOR: u128 = 1 << 42;
+    pub const SET_DAILY_LIMIT: u128 = 1 << 43;
+    pub const VIEW_COUNT: u128 = 1 << 44;
+    pub const SET_VAULT_DEPOSIT: u128 = 1 << 45;
+    pub const WITHDRAW_VAULT: u128 = 1 << 46;
+    pub const MIGRATE: u128 = 1 << 47;
+    pub const SET_METADATA: u128 = 1 << 48;
+    pub const CLEAR_METADATA: u128 = 1 << 49;
+    pub const SET_RESET_INTERVAL: u128 = 1 << 50;
+    pub const FREEZE: u128 = 1 << 51;
+    pub const UNFREEZE: u128 = 1 << 52;
+    pub const CREATE_MULTISIG: u128 = 1 << 53;
+    pub const PROPOSE_OP: u128 = 1 << 54;
+    pub const APPROVE_OP: u128 = 1 << 55;
+    pub const EXECUTE_OP: u128 = 1 << 56;
+    pub const CREATE_SESSION: u128 = 1 << 57;
+    pub const REVOKE_SESSION: u128 = 1 << 58;
+    pub const SET_TOKEN_FEE: u128 = 1 << 59;
+    pub const COLLECT_TOKEN_FEES: u128 = 1 << 60;
+    pub const SUBMIT_SCORE: u128 = 1 << 61;
+    pub const DRAIN: u128 = 1 << 62;
+    pub const CLONE_COUNTER: u128 = 1 << 63;
+    pub const TRANSFER_COUNT: u128 = 1 << 64;
+    pub const SET_DECAY_RATE: u128 = 1 << 65;
+    pub const APPLY_DECAY: u128 = 1 << 66;
+    pub const INCREMENT_WITH_REFERRER: u128 = 1 << 67;
+    pub const INCREMENT_WITH_MEMO: u128 = 1 << 68;
+    pub const DECREMENT_WITH_MEMO: u128 = 1 << 69;
+    pub const REQUEST_CLOSE: u128 = 1 << 70;
+    pub const CANCEL_CLOSE: u128 = 1 << 71;
+    pub const CREATE_TEMPLATE: u128 = 1 << 72;
+    pub const INITIALIZE_FROM_TEMPLATE: u128 = 1 << 73;
+    pub const SET_STEP: u128 = 1 << 74;
+    pub const INITIALIZE_BIG: u128 = 1 << 75;
+    pub const INCREMENT_BIG: u128 = 1 << 76;
+    pub const DECREMENT_BIG: u128 = 1 << 77;
+    pub const UPGRADE_TO_BIG: u128 = 1 << 78;
+    pub const INCREMENT_WITH_NONCE: u128 = 1 << 79;
+    pub const TOUCH: u128 = 1 << 80;
+    pub const SET_KEEPER_TIP: u128 = 1 << 81;
+    pub const SET_ALERT_THRESHOLD: u128 = 1 << 82;
+    pub const CLEAR_ALERT_THRESHOLD: u128 = 1 << 83;
+    pub const SET_HOOK: u128 = 1 << 84;
+    pub const CREATE_GROUP: u128 = 1 << 85;
+    pub const JOIN_GROUP: u128 = 1 << 86;
+    pub const LEAVE_GROUP: u128 = 1 << 87;
+    pub const VALIDATE_OP: u128 = 1 << 88;
+    pub const CLOSE_ALL: u128 = 1 << 89;
+    pub const TOP_UP: u128 = 1 << 90;
+    pub const INITIALIZE_NAMESPACED: u128 = 1 << 91;
+    pub const ADD_FROM: u128 = 1 << 92;
+    pub const CREATE_WAGER: u128 = 1 << 93;
+    pub const SETTLE_WAGER: u128 = 1 << 94;
+    pub const REFUND_WAGER: u128 = 1 << 95;
+    pub const GRANT_ROLE: u128 = 1 << 96;
+    pub const REVOKE_ROLE: u128 = 1 << 97;
+    pub const FINALIZE: u128 = 1 << 98;
+    pub const COMMIT: u128 = 1 << 99;
+    pub const REVEAL: u128 = 1 << 100;
+    pub const SET_WEIGHT_CONFIG: u128 = 1 << 101;
+    pub const INCREMENT_WEIGHTED: u128 = 1 << 102;
+    pub const INITIALIZE_ZC: u128 = 1 << 103;
+    pub const INCREMENT_ZC: u128 = 1 << 104;
+    pub const DECREMENT_ZC: u128 = 1 << 105;
+    pub const CONVERT_TO_ZC: u128 = 1 << 106;
+    pub const SET_FEED_AUTHORITY: u128 = 1 << 107;
+    pub const PUSH_VALUE: u128 = 1 << 108;
+    pub const OBSERVE_TWA: u128 = 1 << 109;
+    pub const SOFT_CLOSE: u128 = 1 << 110;
+    pub const REOPEN: u128 = 1 << 111;
+    pub const RECLAIM_CLOSED: u128 = 1 << 112;
+    pub const SET_DEFAULTS: u128 = 1 << 113;
+    pub const EXPORT: u128 = 1 << 114;
+    pub const IMPORT: u128 = 1 << 115;
+    pub const CLAIM_AUTHORITY: u128 = 1 << 116;
+    pub const COMPLETE_AUTHORITY_CLAIM: u128 = 1 << 117;
+    pub const VETO_CLAIM: u128 = 1 << 118;
+    pub const PRUNE_REGISTRY: u128 = 1 << 119;
+}
+
+/// Bits reported in `InvariantsChecked.failures` (0 = healthy).
+pub mod invariant_failure {
+    pub const PDA_MISMATCH: u32 = 1 << 0; // The address doesn't match the stored seeds + bump
+    pub const NON_CANONICAL_BUMP: u32 = 1 << 1; // The stored bump isn't the one `find_program_address` returns
+    pub const ACCOUNT_TOO_SMALL: u32 = 1 << 2; // The account is smaller than the current layout needs
+    pub const MISSING_AUTHORITY: u32 = 1 << 3; // The authority was wiped to the default key
+    pub const OUTDATED_LAYOUT: u32 = 1 << 4; // `layout_version` isn't COUNTER_LAYOUT_VERSION (see `migrate`)
+}
+
+/// Most compute units each instruction may use in the default build (without `verbose-logs`,
+/// `diagnostics` or `event-cpi`), on a counter with no fees, gates, history or hook. The LiteSVM
+/// tests in `counter-client` hold the program to these (an ignored test: run it with
+/// `anchor build && cargo test -- --ignored`), so a client bundling several operations in one
+/// transaction can add them up for its compute unit limit.
+pub mod cu_budget {
+    pub const INITIALIZE: u32 = 60_000;
+    pub const INCREMENT: u32 = 35_000;
+    pub const DECREMENT: u32 = 35_000;
+    pub const INCREMENT_WITH_MEMO: u32 = 40_000;
+    pub const CLOSE: u32 = 30_000;
+    pub const INCREMENT_ZC: u32 = 15_000;
+}
+
+/// Whether a short ID only uses base32 characters (A-Z and 2-7).
+///
+/// Lowercase letters and the easily-confused digits 0, 1, 8 and 9 are rejected.
+pub fn is_valid_short_id(code: &[u8; SHORT_ID_LEN]) -> bool {
+    code.iter().all(|c| c.is_ascii_uppercase() || (b'2'..=b'7').contains(c))
+}
+
+/// The instruction a counter's hook program receives after every change (see `set_hook`).
+///
+/// It's laid out like an Anchor instruction `on_counter_updated(counter: Pubkey,
+/// previous_count: u64, new_count: u64)`, so an Anchor program can implement it directly. Its
+/// only account is the counter, read-only and already holding the new count.
+pub mod hook {
+    use super::Pubkey;
+
+    /// `sha256("global:on_counter_updated")[..8]`, Anchor's discriminator for the instruction.
+    pub const ON_COUNTER_UPDATED: [u8; 8] = [154, 54, 249, 219, 155, 90, 106, 171];
+
+    /// Instruction data for one change: the discriminator, then the arguments Borsh-encoded.
+    pub fn on_counter_updated_data(counter: &Pubkey, previous_count: u64, new_count: u64) -> Vec<u8> {
+        [&ON_COUNTER_UPDATED[..], counter.as_ref(), &previous_count.to_le_bytes(), &new_count.to_le_bytes()].concat()
+    }
+}
+
+/// Address derivation for every PDA the program owns. Each returns `(address, bump)`.
+pub mod pda {
+    use super::*;
+
+    /// Counters are derived from the wallet that created them, which stays the same when
+    /// ownership is transferred (see `Counter.creator`).
+    pub fn counter(creator: &Pubkey, label: &str, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::COUNTER, creator.as_ref(), label.as_bytes()], program_id)
+    }
+
+    /// Counters made with `initialize_namespaced` have the partner ID between the prefix and
+    /// the creator (see `Counter.namespace`).
+    pub fn namespaced_counter(
+        partner_id: &[u8; PARTNER_ID_LEN],
+        creator: &Pubkey,
+        label: &str,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::COUNTER, partner_id, creator.as_ref(), label.as_bytes()], program_id)
+    }
+
+    pub fn config(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::CONFIG], program_id)
+    }
+
+    pub fn treasury(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::TREASURY], program_id)
+    }
+
+    /// Collects the increment fee a counter's owner charges (see `Counter.fee_lamports`).
+    pub fn counter_treasury(counter: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::COUNTER_TREASURY, counter.as_ref()], program_id)
+    }
+
+    /// Collects the deposits increments make (see `Counter.deposit_per_increment`).
+    pub fn vault(counter: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::VAULT, counter.as_ref()], program_id)
+    }
+
+    pub fn reward_config(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::REWARDS], program_id)
+    }
+
+    pub fn reward_mint(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::REWARD_MINT], program_id)
+    }
+
+    pub fn global_stats(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::STATS], program_id)
+    }
+
+    /// The program-wide top counters (see `Leaderboard`).
+    pub fn leaderboard(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::LEADERBOARD], program_id)
+    }
+
+    /// Lets `session_key` sign increments and decrements of `counter` for a while (see `Session`).
+    pub fn session(counter: &Pubkey, session_key: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::SESSION, counter.as_ref(), session_key.as_ref()], program_id)
+    }
+
+    /// The m-of-n authority a counter can be handed to (see `MultisigAuthority`).
+    pub fn multisig(counter: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::MULTISIG, counter.as_ref()], program_id)
+    }
+
+    /// The multisig's `index`-th proposal.
+    pub fn proposal(multisig: &Pubkey, index: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::PROPOSAL, multisig.as_ref(), &index.to_le_bytes()], program_id)
+    }
+
+    /// The counter settings `authority` saved as `name` (see `CounterTemplate`).
+    pub fn template(authority: &Pubkey, name: &str, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::TEMPLATE, authority.as_ref(), name.as_bytes()], program_id)
+    }
+
+    /// The counter group `authority` created as `name` (see `CounterGroup`).
+    pub fn group(authority: &Pubkey, name: &str, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::GROUP, authority.as_ref(), name.as_bytes()], program_id)
+    }
+
+    /// The wager between two counters (see `Wager`), creator's counter first.
+    pub fn wager(creator_counter: &Pubkey, opponent_counter: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::WAGER, creator_counter.as_ref(), opponent_counter.as_ref()], program_id)
+    }
+
+    /// Holds both stakes of a wager until it's settled or refunded.
+    pub fn wager_vault(wager: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::WAGER_VAULT, wager.as_ref()], program_id)
+    }
+
+    /// Lists the counters `creator` has made (see `UserRegistry`).
+    pub fn registry(creator: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::REGISTRY, creator.as_ref()], program_id)
+    }
+
+    /// The settings `authority`'s new counters start with (see `UserDefaults`).
+    pub fn defaults(authority: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::DEFAULTS, authority.as_ref()], program_id)
+    }
+
+    pub fn sequencer(authority: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::SEQUENCER, authority.as_ref()], program_id)
+    }
+
+    pub fn short_id(code: &[u8; SHORT_ID_LEN], program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::SHORT_ID, code.as_ref()], program_id)
+    }
+
+    pub fn activity_receipt(counter: &Pubkey, min_ops: u32, window_days: u8, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                seeds::ACTIVITY,
+                counter.as_ref(),
+                &min_ops.to_le_bytes(),
+                &[window_days],
+            ],
+            program_id,
+        )
+    }
+
+    /// A snapshot of `counter` taken under `label` (see `CounterSnapshot`).
+    pub fn snapshot(counter: &Pubkey, label: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::SNAPSHOT, counter.as_ref(), &label.to_le_bytes()], program_id)
+    }
+
+    /// A counter's optional change log (see `CounterHistory`).
+    pub fn history(counter: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::HISTORY, counter.as_ref()], program_id)
+    }
+
+    /// A wallet's `CounterArray` named `label`.
+    pub fn counter_array(authority: &Pubkey, label: &str, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::COUNTER_ARRAY, authority.as_ref(), label.as_bytes()], program_id)
+    }
+
+    /// A wallet's `SignedCounter` named `label`.
+    pub fn signed_counter(authority: &Pubkey, label: &str, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::SIGNED_COUNTER, authority.as_ref(), label.as_bytes()], program_id)
+    }
+
+    /// A wallet's `BigCounter` named `label`.
+    pub fn big_counter(authority: &Pubkey, label: &str, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::BIG_COUNTER, authority.as_ref(), label.as_bytes()], program_id)
+    }
+
+    /// A wallet's zero-copy `CounterZC` named `label`.
+    pub fn counter_zc(authority: &Pubkey, label: &str, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::COUNTER_ZC, authority.as_ref(), label.as_bytes()], program_id)
+    }
+
+    pub fn delegation(counter: &Pubkey, operator: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::DELEGATE, counter.as_ref(), operator.as_ref()], program_id)
+    }
+
+    /// The signer of `emit_cpi!` self-invocations (programs built with the `event-cpi` feature).
+    pub fn event_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::EVENT_AUTHORITY], program_id)
+    }
+
+    /// The Metaplex metadata of `mint`. Owned by the Token Metadata program, not this one; passed
+    /// in by NFT holders incrementing a collection-gated counter.
+    pub fn nft_metadata(mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+            &TOKEN_METADATA_PROGRAM_ID,
+        )
+    }
+
+    /// `member`'s registration as a contributor to `counter` (see `Contributor`).
+    pub fn contributor(counter: &Pubkey, member: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::CONTRIBUTOR, counter.as_ref(), member.as_ref()], program_id)
+    }
+
+    /// `user`'s sealed value for `counter` (see `Commitment`).
+    pub fn commitment(counter: &Pubkey, user: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::COMMITMENT, counter.as_ref(), user.as_ref()], program_id)
+    }
+
+    /// The roles `member` holds on `counter` (see `Role`).
+    pub fn role(counter: &Pubkey, member: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seeds::ROLE, counter.as_ref(), member.as_ref()], program_id)
+    }
+}
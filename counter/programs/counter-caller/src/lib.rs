@@ -0,0 +1,144 @@
+// A small second program that uses the counter program through CPI
+// It exists to test (and show) what a downstream program needs: depend on `counter` with the
+// `cpi` feature, then call `counter::cpi::*` with the accounts from `counter::cpi::accounts`
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::get_return_data;
+use counter::cpi::accounts::{Update, ViewCount};
+use counter::program::Counter as CounterProgram;
+use counter::{Counter, CounterView, GlobalStats, ProgramConfig};
+
+declare_id!("CZVmpRRyvnnena9J2sQFdqSME1cqywkpEvdYGjp9DzRp");
+
+#[program]
+pub mod counter_caller {
+    use super::*;
+
+    // INCREMENT A COUNTER AS PART OF THIS PROGRAM'S OWN FLOW
+    // The user's signature carries through the CPI, so the counter program sees the same
+    // owner (or public-counter visitor) it would see in a direct call. Afterwards the new
+    // value is read back with `view_count` instead of deserializing the account by hand.
+    pub fn bump(ctx: Context<Bump>, id: String) -> Result<()> {
+        let accounts = &ctx.accounts;
+        let counter_program = accounts.counter_program.to_account_info();
+
+        counter::cpi::increment(
+            CpiContext::new(
+                counter_program.clone(),
+                Update {
+                    counter: accounts.counter.to_account_info(),
+                    user: accounts.user.to_account_info(),
+                    config: accounts.config.to_account_info(),
+                    stats: accounts.stats.to_account_info(),
+                    treasury: accounts.treasury.to_account_info(),
+                    counter_treasury: accounts.counter_treasury.to_account_info(),
+                    fee_payer_token_account: None,
+                    treasury_token_account: None,
+                    token_program: None,
+                    sequencer: None,
+                    delegation: None,
+                    session: None,
+                    contributor: None,
+                    role: None,
+                    gate_token_account: None,
+                    weight_token_account: None,
+                    nft_token_account: None,
+                    nft_metadata: None,
+                    reward_config: None,
+                    reward_mint: None,
+                    reward_token_account: None,
+                    history: None,
+                    vault: None,
+                    fee_token_account: None,
+                    fee_vault: None,
+                    referrer_counter: None,
+                    hook_program: None,
+                    group: None,
+                    system_program: accounts.system_program.to_account_info(),
+                    #[cfg(feature = "event-cpi")]
+                    event_authority: accounts.event_authority.to_account_info(),
+                    #[cfg(feature = "event-cpi")]
+                    program: counter_program.clone(),
+                },
+            ),
+            id,
+            false, // Not a dry run
+            None,  // No sequencer
+        )?;
+
+        // READ THE RESULT BACK
+        counter::cpi::view_count(CpiContext::new(
+            counter_program,
+            ViewCount {
+                counter: accounts.counter.to_account_info(),
+                config: accounts.config.to_account_info(),
+            },
+        ))?;
+        let view = match get_return_data() {
+            Some((program_id, data)) if program_id == counter::ID => CounterView::try_from_slice(&data)?,
+            _ => return err!(CallerError::MissingView),
+        };
+
+        msg!("Counter {} is now at {} (version {})", accounts.counter.key(), view.count, view.version);
+        Ok(())
+    }
+
+    // BE A COUNTER'S HOOK PROGRAM (see the counter program's `set_hook`)
+    // The counter program calls this after every change to a counter that hooks into this
+    // program, passing the counter (already holding the new count) as the only account. A real
+    // hook would react to the change; this one checks what it was told and logs it
+    pub fn on_counter_updated(ctx: Context<OnCounterUpdated>, counter: Pubkey, previous_count: u64, new_count: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.counter.key(), counter, CallerError::WrongCounter);
+        require_eq!(ctx.accounts.counter.count, new_count, CallerError::WrongCounter);
+
+        msg!("Hook: counter {} went from {} to {}", counter, previous_count, new_count);
+        Ok(())
+    }
+}
+
+// ACCOUNTS NEEDED TO BUMP A COUNTER
+// The counter program's own `Update` accounts (minus the optional ones), plus the program itself.
+// Everything is checked again by the counter program during the CPI.
+#[derive(Accounts)]
+pub struct Bump<'info> {
+    #[account(mut)]
+    pub counter: Account<'info, Counter>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(mut)]
+    pub stats: Account<'info, GlobalStats>,
+
+    #[account(mut)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub counter_treasury: SystemAccount<'info>,
+
+    /// CHECK: the counter program's event authority, verified by the counter program
+    #[cfg(feature = "event-cpi")]
+    pub event_authority: UncheckedAccount<'info>,
+
+    pub counter_program: Program<'info, CounterProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS A HOOK CALL COMES WITH
+// Just the counter, read-only - `Account` makes sure it really is one of the counter program's
+#[derive(Accounts)]
+pub struct OnCounterUpdated<'info> {
+    pub counter: Account<'info, Counter>,
+}
+
+#[error_code]
+pub enum CallerError {
+    // `view_count` didn't leave any return data behind
+    #[msg("The counter program returned no view")]
+    MissingView,
+    // A hook call whose arguments don't match the counter passed in
+    #[msg("The hook call doesn't match the counter")]
+    WrongCounter,
+}
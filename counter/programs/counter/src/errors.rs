@@ -0,0 +1,396 @@
+// ERRORS - What went wrong, for callers and explorers
+
+use crate::*;
+
+// CUSTOM ERROR MESSAGES FOR OUR PROGRAM
+// When things go wrong, these explain what happened in a user-friendly way
+#[error_code]
+pub enum CounterError {
+    // Trying to count too high (above maximum number)
+    #[msg("Counter would overflow")]
+    CounterOverflow,
+    // Trying to count below zero
+    #[msg("Counter would underflow")]
+    CounterUnderflow,
+    // Someone trying to change a counter they don't own
+    #[msg("Unauthorized access")]
+    Unauthorized,
+    // Short ID contains characters outside the base32 alphabet
+    #[msg("Short ID must be 6 characters from A-Z and 2-7")]
+    InvalidShortId,
+    // The admin has switched this instruction off
+    #[msg("This instruction is currently disabled")]
+    InstructionDisabled,
+    // A heartbeat was already sent for this counter recently
+    #[msg("Heartbeat sent too soon")]
+    HeartbeatTooSoon,
+    // Only some of the token fee accounts were passed in
+    #[msg("Pass all token fee accounts or none of them")]
+    IncompleteFeeAccounts,
+    // Tried to pay in tokens but no fee mint is configured
+    #[msg("Token fee payments are not enabled")]
+    TokenFeesDisabled,
+    // The exchange rate is missing or too old to trust
+    #[msg("Fee token price is stale")]
+    StalePrice,
+    // An exchange rate of zero makes no sense
+    #[msg("Invalid fee token price")]
+    InvalidPrice,
+    // Converting the fee into tokens produced a number too large to store
+    #[msg("Fee amount overflow")]
+    FeeOverflow,
+    // The lifecycle rules don't allow going from the current state to the requested one
+    #[msg("Invalid counter state transition")]
+    InvalidStateTransition,
+    // The counter is frozen, archived, closing or migrating
+    #[msg("Counter is not active")]
+    CounterNotActive,
+    // Frozen and migrating counters can't be closed
+    #[msg("Counter cannot be closed in its current state")]
+    CounterNotClosable,
+    // Refund basis points must add up to 100%
+    #[msg("Refund split must add up to 10000 basis points")]
+    InvalidRefundSplit,
+    // The counter was used too recently to be reclaimed
+    #[msg("Counter is not stale")]
+    CounterNotStale,
+    // `expected_seq` was given but no sequencer account was passed in
+    #[msg("Sequencer account required when expected_seq is set")]
+    SequencerRequired,
+    // Another operation got in first - the client should refresh and retry
+    #[msg("Sequence number mismatch")]
+    SequenceMismatch,
+    // Changing a counter by zero is almost certainly a client bug
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    // Activity is only remembered for `ACTIVITY_WINDOW_DAYS` days
+    #[msg("Activity window must be between 1 and 7 days")]
+    InvalidActivityWindow,
+    // The counter didn't see enough operations in the requested window
+    #[msg("Activity criteria not met")]
+    ActivityCriteriaNotMet,
+    // Labels are part of the counter's address, and address seeds are at most 32 bytes
+    #[msg("Counter label is too long")]
+    LabelTooLong,
+    // Only the address offered through `transfer_authority` can accept a counter
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    // The change would push the counter past its configured maximum
+    #[msg("Counter would go above its maximum")]
+    CounterAboveMax,
+    // The change would push the counter below its configured minimum
+    #[msg("Counter would go below its minimum")]
+    CounterBelowMin,
+    // A counter's minimum can't be larger than its maximum
+    #[msg("Counter minimum is larger than its maximum")]
+    InvalidBounds,
+    // The operator has already added as much as the owner approved
+    #[msg("Operator allowance exhausted")]
+    AllowanceExhausted,
+    // A negative cooldown makes no sense
+    #[msg("Cooldown can't be negative")]
+    InvalidCooldown,
+    // The counter was changed too recently - try again once its cooldown has passed
+    #[msg("Counter cooldown is still active")]
+    CooldownActive,
+    // The counter is token-gated and the caller doesn't hold enough of the token
+    #[msg("Insufficient token balance")]
+    InsufficientTokenBalance,
+    // Reward accounts have to be passed in all together (or not at all)
+    #[msg("Incomplete reward accounts")]
+    IncompleteRewardAccounts,
+    // The mint passed in isn't the one `RewardConfig` mints
+    #[msg("Wrong reward mint")]
+    InvalidRewardMint,
+    // The admin has pulled the emergency brake (see `set_paused`)
+    #[msg("Program is paused")]
+    ProgramPaused,
+    // The counter keeps a `CounterHistory`, so every change has to pass it in
+    #[msg("Counter history account required")]
+    HistoryRequired,
+    // Counter arrays have COUNTER_ARRAY_SLOTS slots, numbered from zero
+    #[msg("Slot index out of bounds")]
+    SlotOutOfBounds,
+    // The slot has `slot_flag::LOCKED` set
+    #[msg("Slot is locked")]
+    SlotLocked,
+    // `increment_if` found a different value than the caller expected - refresh and retry
+    #[msg("Counter does not hold the expected value")]
+    CountMismatch,
+    // `close_to` was called without saying where the rent should go
+    #[msg("Rent recipient account required")]
+    RentRecipientRequired,
+    // An expiry has to be in the future
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    // The counter's `expires_at` has passed - it can only be closed now
+    #[msg("Counter has expired")]
+    CounterExpired,
+    // `collect_expired` was called before the counter's `expires_at`
+    #[msg("Counter has not expired")]
+    CounterNotExpired,
+    // Two accounts that have to be different counters are the same one (e.g. merging a counter into itself)
+    #[msg("Counters must be different")]
+    SameCounter,
+    // `apply_batch` needs at least one and at most MAX_BATCH_OPS operations
+    #[msg("Batch must hold between 1 and MAX_BATCH_OPS operations")]
+    InvalidBatch,
+    // The counter's `daily_limit` of increments has been used up for today (UTC)
+    #[msg("Daily increment limit reached")]
+    DailyLimitReached,
+    // The counter takes a vault deposit per increment, but no vault was passed in
+    #[msg("This counter's vault must be passed in")]
+    VaultRequired,
+    // The counter has an `nft_gate`, and the caller didn't show an NFT of that verified collection
+    #[msg("Only holders of the counter's NFT collection can increment it")]
+    NotCollectionHolder,
+    // The wallet already has `max_counters_per_user` counters
+    #[msg("This wallet has reached the maximum number of counters")]
+    TooManyCounters,
+    // The counter was written by another program version: older ones need `migrate` first
+    #[msg("This counter uses an unsupported account layout; migrate it first")]
+    UnsupportedAccountVersion,
+    // `migrate` was called on a counter that already has the current layout
+    #[msg("This counter already uses the current account layout")]
+    AlreadyMigrated,
+    // The display label or URI passed to `set_metadata` is too long
+    #[msg("Metadata label or URI is too long")]
+    MetadataTooLong,
+    // A negative `set_reset_interval`
+    #[msg("Reset interval cannot be negative")]
+    InvalidResetInterval,
+    // The counter is frozen (see `freeze`), so it can't be changed or closed until `unfreeze`
+    #[msg("Counter is frozen")]
+    CounterFrozen,
+    // No owners, too many owners, a repeated owner, or a threshold outside 1..=owners
+    #[msg("Invalid multisig owners or threshold")]
+    InvalidMultisig,
+    // The signer isn't one of the multisig's owners
+    #[msg("Signer is not an owner of this multisig")]
+    NotMultisigOwner,
+    // This owner's approval is already on the proposal
+    #[msg("Proposal already approved by this owner")]
+    AlreadyApproved,
+    // Fewer than `threshold` owners have approved the proposal
+    #[msg("Proposal does not have enough approvals")]
+    NotEnoughApprovals,
+    // `create_session` needs an expiry in the future and at least one operation
+    #[msg("Session must expire in the future and allow at least one operation")]
+    InvalidSession,
+    // The session key's `expires_at` has passed
+    #[msg("Session has expired")]
+    SessionExpired,
+    // The session key has used up its `max_ops`
+    #[msg("Session has no operations left")]
+    SessionExhausted,
+    // The counter charges a token fee, but the fee token account, fee vault or token program is missing
+    #[msg("Token fee accounts are required for this counter")]
+    TokenFeeAccountsRequired,
+    // The leaderboard is full and the counter doesn't beat its lowest count
+    #[msg("Count is too low for the leaderboard")]
+    ScoreTooLow,
+    // `apply_decay` on a counter that doesn't decay, or owes less than a day's decay
+    #[msg("No decay is due yet")]
+    NoDecayDue,
+    // `increment_with_referrer` without the referrer's counter, with a counter the referrer
+    // doesn't own, or with the caller as their own referrer
+    #[msg("Invalid referrer")]
+    InvalidReferrer,
+    // A memo longer than MAX_MEMO_LEN bytes
+    #[msg("Memo is too long")]
+    MemoTooLong,
+    // A SOL withdrawal of protocol fees without the fee schedule's `fee_recipient`
+    #[msg("The fee recipient account is required")]
+    FeeRecipientRequired,
+    // `close` under a timelock without a `request_close` first, or `cancel_close` with nothing pending
+    #[msg("No close has been requested for this counter")]
+    CloseNotRequested,
+    // `close` before the timelock since `request_close` is up
+    #[msg("The close timelock hasn't passed yet")]
+    CloseTimelockActive,
+    // A second `request_close` while one is pending
+    #[msg("A close has already been requested for this counter")]
+    CloseAlreadyRequested,
+    // A negative `set_close_timelock`
+    #[msg("Close timelock cannot be negative")]
+    InvalidCloseTimelock,
+    // A step of zero at creation or in `set_step`
+    #[msg("Step must be greater than zero")]
+    InvalidStep,
+    // An `increment_with_nonce` nonce no higher than the last accepted one (a replay)
+    #[msg("Nonce already used")]
+    NonceAlreadyUsed,
+    // `set_instruction_flags` asked to both enable and disable the same instruction
+    #[msg("An instruction can't be enabled and disabled at once")]
+    ConflictingInstructionFlags,
+    // `touch` on a counter with no decay, period rollover or expiry due
+    #[msg("Nothing is due on this counter")]
+    NothingToTouch,
+    // The counter has a hook program (see `set_hook`) but it wasn't passed in as `hook_program`
+    #[msg("The counter's hook program is required")]
+    HookProgramRequired,
+    // The hook program can't be called, or its `on_counter_updated` failed
+    #[msg("The counter's hook failed")]
+    HookFailed,
+    // `set_hook` with this program itself
+    #[msg("A counter can't hook into the counter program")]
+    InvalidHookProgram,
+    // `create_group` with a capacity of zero
+    #[msg("A group has to have room for at least one counter")]
+    InvalidGroupCapacity,
+    // `join_group` on a group that already has `capacity` members
+    #[msg("The group is full")]
+    GroupFull,
+    // `join_group` on a counter that's already in a group (it has to leave it first)
+    #[msg("The counter is already in a group")]
+    AlreadyInGroup,
+    // `leave_group` with a group the counter isn't in
+    #[msg("The counter isn't in this group")]
+    NotInGroup,
+    // The counter is in a group (see `join_group`) but it wasn't passed in as `group`
+    #[msg("The counter's group is required")]
+    GroupRequired,
+    // Closing a counter that's still in a group, or changing its count any way but through
+    // `update_count` / `apply_batch`, which keep the group's total in step (see `leave_group`)
+    #[msg("The counter has to leave its group first")]
+    CounterInGroup,
+    // A `close_all` account that isn't a change log, approval, session, contributor
+    // registration, role or (the owner's own) snapshot of the counter being closed
+    #[msg("Not an account of this counter's that can be closed with it")]
+    NotACounterChild,
+    // An account left with fewer lamports than rent exemption needs for its size
+    #[msg("The account isn't rent exempt")]
+    NotRentExempt,
+    // `approve_partner` with the all-zero ID, which means "no namespace"
+    #[msg("Partner IDs can't be all zeroes")]
+    InvalidPartnerId,
+    // `approve_partner` for a partner that's already approved
+    #[msg("The partner is already approved")]
+    PartnerAlreadyApproved,
+    // `approve_partner` when `MAX_PARTNERS` are already approved
+    #[msg("Too many approved partners")]
+    TooManyPartners,
+    // A partner namespace the admin hasn't approved (or has revoked)
+    #[msg("The partner isn't approved")]
+    UnknownPartner,
+    // A wager stake too small for its vault to hold (below an empty account's rent exemption)
+    #[msg("The stake is below the minimum")]
+    InvalidStake,
+    // A wager target that one of the counters has already reached
+    #[msg("The target has already been reached")]
+    InvalidWagerTarget,
+    // `settle_wager` while neither counter has reached the target
+    #[msg("Neither counter has reached the target")]
+    WagerTargetNotReached,
+    // `settle_wager` after the wager's deadline (it can only be refunded now)
+    #[msg("The wager's deadline has passed")]
+    WagerExpired,
+    // `refund_wager` before the wager's deadline
+    #[msg("The wager's deadline hasn't passed")]
+    WagerNotExpired,
+    // `grant_role` / `revoke_role` with no roles, or bits that aren't a `role`
+    #[msg("Invalid role")]
+    InvalidRole,
+    // A counting window that ends before it starts, or has already ended
+    #[msg("Invalid counting window")]
+    InvalidWindow,
+    // Changing the count before the counter's `window_start` or after its `window_end`
+    #[msg("Outside the counter's counting window")]
+    OutsideWindow,
+    // `finalize` on a counter without a window, or before its window has closed
+    #[msg("The counting window hasn't ended")]
+    WindowNotEnded,
+    // `commit` once the counter's counting window has opened (or on a counter without one)
+    #[msg("Commitments are only taken before the counting window opens")]
+    CommitPhaseOver,
+    // `reveal` with a value and salt that don't hash to the commitment
+    #[msg("The value doesn't match the commitment")]
+    CommitmentMismatch,
+    // `set_weight_config` with a mint but a divisor of zero
+    #[msg("The weight divisor must be above zero")]
+    InvalidWeightDivisor,
+    // `increment_weighted` on a counter without a weight mint
+    #[msg("This counter doesn't take weighted increments")]
+    WeightNotConfigured,
+    // `increment_weighted` without the caller's token account for the weight mint
+    #[msg("The caller's token account for the weight mint is required")]
+    WeightAccountRequired,
+    // `increment_weighted` by a caller holding less than one divisor's worth of the weight mint
+    #[msg("The caller's balance is too small to add any weight")]
+    WeightTooLow,
+    // `push_value` signed by anyone but the counter's feed authority (or on a counter without one)
+    #[msg("Only the counter's feed authority can push values")]
+    NotFeedAuthority,
+    // `push_value` with an observation no newer than the last one pushed
+    #[msg("The observation is older than the last value pushed")]
+    StaleObservation,
+    // `push_value` with an observation time that hasn't happened yet
+    #[msg("The observation time is in the future")]
+    ObservationInFuture,
+    // Changing a counter that was soft-closed (it has to be reopened first)
+    #[msg("This counter is closed; reopen it first")]
+    CounterSoftClosed,
+    // `soft_close` on a counter that is already soft-closed
+    #[msg("This counter is already closed")]
+    AlreadySoftClosed,
+    // `reopen` or `reclaim_closed` on a counter that wasn't soft-closed
+    #[msg("This counter isn't closed")]
+    NotSoftClosed,
+    // `soft_close` while the admin hasn't set a grace period
+    #[msg("Soft closes are turned off")]
+    SoftCloseDisabled,
+    // `set_close_grace` with a negative period
+    #[msg("The close grace period can't be negative")]
+    InvalidCloseGrace,
+    // `reopen` after the grace period has run out
+    #[msg("The grace period for reopening this counter is over")]
+    CloseGraceLapsed,
+    // `reclaim_closed` before the grace period has run out
+    #[msg("The counter can still be reopened")]
+    CloseGraceActive,
+    // `import` signed by anyone but the config's `migration_authority`
+    #[msg("Only the migration authority can import counters")]
+    NotMigrationAuthority,
+    // A recovery authority without an inactivity threshold, or the other way round
+    #[msg("A recovery authority needs a positive inactivity threshold, and the other way round")]
+    InvalidRecovery,
+    // `claim_authority` or `complete_authority_claim` signed by anyone but the recovery authority
+    #[msg("Only the counter's recovery authority can claim it")]
+    NotRecoveryAuthority,
+    // `claim_authority` before the counter has been quiet for its inactivity threshold, or
+    // `complete_authority_claim` after it has changed since the claim
+    #[msg("The counter's owner has been active too recently")]
+    CounterStillActive,
+    // `claim_authority` while a claim is already pending
+    #[msg("A claim on this counter is already pending")]
+    ClaimAlreadyPending,
+    // `complete_authority_claim` or `veto_claim` without a pending claim
+    #[msg("No claim on this counter is pending")]
+    NoPendingClaim,
+    // `complete_authority_claim` before the challenge window is over
+    #[msg("The owner can still veto this claim")]
+    ClaimChallengeActive,
+    // A correction, batch or jump of more than a step on a counter with an open wager (or a
+    // change made from another counter, like `add_from`, that would raise its count), or
+    // closing it before the wager is settled
+    #[msg("This counter is in an open wager: it can only move a step at a time and can't be closed")]
+    CounterInWager,
+    // `create_wager` on a counter already in as many wagers as it can track
+    #[msg("This counter is in too many open wagers")]
+    TooManyWagers,
+    // A decaying counter joining a group, or a decay rate set on a counter in one (decay
+    // settled outside `update_count` would put the group's total out of step)
+    #[msg("Counters in a group can't decay")]
+    DecayInGroup,
+    // `reveal` on a counter that charges for increments, takes a deposit or runs a hook (a
+    // reveal has no way to pay or call it), or while the program charges a fee
+    #[msg("Values can't be revealed into a counter that charges for increments or runs a hook")]
+    RevealUnsupported,
+    // `settle_wager` with a counter that was closed and created again since the wager was made
+    #[msg("A counter in this wager has been replaced since it was made")]
+    WagerCounterReplaced,
+    // A periodically resetting counter joining a group, or a reset interval set on a counter in
+    // one (a rollover by `touch` would put the group's total out of step)
+    #[msg("Counters in a group can't reset periodically")]
+    ResetInGroup,
+}
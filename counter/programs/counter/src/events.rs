@@ -0,0 +1,1252 @@
+// EVENTS - Like announcements our program makes to the blockchain
+
+use crate::*;
+
+// EVENT SCHEMA VERSIONS
+// Every event starts with `schema_version`, the byte right after its discriminator, so an
+// indexer knows which shape it's decoding before it reads the rest. Each event has its own
+// number in this table, bumped whenever its fields change; an event keeps its name (and
+// so its discriminator) from one version to the next. Built with `legacy-events` (kept for
+// one release), events go out in their shape from before versioning, without the field
+pub mod schema {
+    use super::*;
+
+    pub trait EventSchema {
+        const SCHEMA_VERSION: u8;
+    }
+
+    macro_rules! schema_versions {
+        ($($event:ident = $version:literal,)*) => {
+            $(impl EventSchema for $event {
+                const SCHEMA_VERSION: u8 = $version;
+            })*
+        };
+    }
+
+    schema_versions! {
+        CounterInitialized = 2, // 2: `sponsor`
+        CounterUpdated = 1,
+        CounterErrorDetail = 1,
+        CounterBatchApplied = 1,
+        SignedCounterUpdated = 1,
+        BigCounterUpdated = 1,
+        BigCounterUpgraded = 1,
+        CounterZCUpdated = 1,
+        CounterConvertedToZC = 1,
+        ArraySlotUpdated = 1,
+        StreakUpdated = 1,
+        MilestoneReached = 1,
+        HookUpdated = 1,
+        CounterChildrenClosed = 1,
+        AccountToppedUp = 1,
+        GroupCreated = 1,
+        GroupTotalUpdated = 1,
+        WagerCreated = 1,
+        WagerSettled = 1,
+        ThresholdCrossed = 1,
+        GlobalStatsUpdated = 1,
+        CounterClosed = 1,
+        CountersMerged = 1,
+        CountTransferred = 1,
+        CounterAddedFrom = 1,
+        CounterSplit = 1,
+        CounterCloned = 1,
+        ShortIdClaimed = 1,
+        ShortIdReleased = 1,
+        InstructionFlagsUpdated = 1,
+        ProgramPauseChanged = 1,
+        PartnerUpdated = 1,
+        RentRefundSplit = 1,
+        CounterStateChanged = 1,
+        CounterFrozen = 1,
+        CounterUnfrozen = 1,
+        CounterFinalized = 1,
+        ValueCommitted = 1,
+        ValueRevealed = 1,
+        AuthorityTransferInitiated = 1,
+        AuthorityTransferred = 1,
+        OperatorApproved = 1,
+        OperatorRevoked = 1,
+        SessionCreated = 1,
+        SessionRevoked = 1,
+        LeaderboardUpdated = 1,
+        ContributorAdded = 1,
+        ContributorRemoved = 1,
+        RoleUpdated = 1,
+        MultisigCreated = 1,
+        OpProposed = 1,
+        OpApproved = 1,
+        OpExecuted = 1,
+        CounterHeartbeat = 1,
+        ActivityProven = 1,
+        SnapshotTaken = 1,
+        FeePaid = 1,
+        CounterFeePaid = 1,
+        ReferralRecorded = 1,
+        CounterTokenFeePaid = 1,
+        TokenFeeUpdated = 1,
+        CounterFeeUpdated = 1,
+        VaultDeposited = 1,
+        PeriodRolledOver = 1,
+        DecayApplied = 1,
+        CounterTouched = 1,
+        CounterMetadataUpdated = 1,
+        VaultDepositUpdated = 1,
+        VaultWithdrawn = 1,
+        CounterDrained = 1,
+        CounterMigrated = 1,
+        TokenGateUpdated = 1,
+        WeightConfigUpdated = 1,
+        FeedAuthorityUpdated = 1,
+        FeedValuePushed = 1,
+        RewardMinted = 1,
+        RewardsConfigured = 1,
+        FeeConfigUpdated = 1,
+        TemplateCreated = 1,
+        CounterCreatedFromTemplate = 1,
+        DefaultsUpdated = 1,
+        CloseRequested = 1,
+        CloseCancelled = 1,
+        CounterSoftClosed = 1,
+        CounterReopened = 1,
+        CounterExported = 1,
+        CounterImported = 1,
+        AuthorityClaimed = 1,
+        AuthorityClaimVetoed = 1,
+        FeeScheduleUpdated = 1,
+        FeePricePushed = 1,
+        InvariantsChecked = 1,
+    }
+}
+
+// ANNOUNCEMENT WHEN A NEW COUNTER IS CREATED
+#[event]
+pub struct CounterInitialized {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub user: Pubkey,
+    pub counter: Pubkey,
+    pub count: u64,
+    pub label: String,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub sequence: u64, // Always 0 - see `Counter.sequence`
+    pub sponsor: Pubkey, // Who paid the rent - `user` unless a dApp sponsored the counter
+}
+
+// HOW A COUNTER'S VALUE WAS CHANGED (one byte in every `CounterUpdated`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CounterOperation {
+    Increment,
+    Decrement,
+    Set,
+    Reset,
+}
+
+impl CounterOperation {
+    // The lowercase name used in logs and in the legacy string-based event
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CounterOperation::Increment => "increment",
+            CounterOperation::Decrement => "decrement",
+            CounterOperation::Set => "set",
+            CounterOperation::Reset => "reset",
+        }
+    }
+}
+
+// ANNOUNCEMENT WHEN A COUNTER VALUE CHANGES
+#[cfg(not(feature = "legacy-events"))]
+#[event]
+pub struct CounterUpdated {
+    pub schema_version: u8, // See `schema`
+    pub user: Pubkey, // Who actually made the change (not necessarily the owner)
+    pub counter: Pubkey,
+    pub previous_count: u64,
+    pub new_count: u64,
+    pub operation: CounterOperation,
+    pub version: u64, // `Counter.version` after the change, so consumers can spot missed or reordered events
+    pub slot: u64,
+    pub timestamp: i64,
+    pub sequence: u64, // `Counter.sequence` - one more than the counter's previous indexed event
+    pub memo: String,  // Why the change was made ("" unless it came with a memo, see `increment_with_memo`)
+    pub weight: u64,   // What the caller's balance added (0 unless it came from `increment_weighted`)
+}
+
+// THE NUMBERS BEHIND A CHANGE TO THE COUNT THAT RAN INTO A LIMIT (see `explain_limit_error`)
+// Logged just before the transaction fails with `error_code`, so support tools can read it from
+// the failed transaction's (or simulation's) logs: the change was over by
+// `count + amount - limit` (increments) or `limit - (count - amount)` (decrements)
+#[event]
+pub struct CounterErrorDetail {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub error_code: u32,    // What the transaction fails with (e.g. `CounterAboveMax`)
+    pub operation: CounterOperation,
+    pub amount: u64,        // How much it tried to move the count by (the new value for a set)
+    pub count: u64,         // The count it started from
+    pub limit: u64,         // The limit it ran into: the u64 range, the counter's max or min, its
+                            // daily limit or the operator's allowance
+}
+
+// THE OLD SHAPE OF `CounterUpdated` (only with `--features legacy-events`)
+// `operation` is the lowercase name as a string ("increment", ...). It keeps the same event
+// name, and so the same discriminator, so indexers that haven't moved to `CounterOperation`
+// keep working against a program built with this feature
+#[cfg(feature = "legacy-events")]
+#[deprecated(note = "build without `legacy-events` to emit `operation` as a `CounterOperation`")]
+#[event]
+pub struct CounterUpdated {
+    pub user: Pubkey,
+    pub counter: Pubkey,
+    pub previous_count: u64,
+    pub new_count: u64,
+    pub operation: String,
+}
+
+impl CounterUpdated {
+    // Builds whichever shape of the event this program was compiled with, for a counter that
+    // has just been changed from `previous_count` (the legacy shape predates `version`, `slot`,
+    // `timestamp` and `sequence` and leaves them out)
+    pub fn new(
+        user: Pubkey,
+        counter: &Account<Counter>,
+        previous_count: u64,
+        operation: CounterOperation,
+        #[cfg_attr(feature = "legacy-events", allow(unused_variables))] clock: &Clock,
+    ) -> Self {
+        versioned!(Self {
+            user,
+            counter: counter.key(),
+            previous_count,
+            new_count: counter.count,
+            #[cfg(not(feature = "legacy-events"))]
+            operation,
+            #[cfg(not(feature = "legacy-events"))]
+            version: counter.version,
+            #[cfg(not(feature = "legacy-events"))]
+            slot: clock.slot,
+            #[cfg(not(feature = "legacy-events"))]
+            timestamp: clock.unix_timestamp,
+            #[cfg(not(feature = "legacy-events"))]
+            sequence: counter.sequence,
+            #[cfg(not(feature = "legacy-events"))]
+            memo: String::new(),
+            #[cfg(not(feature = "legacy-events"))]
+            weight: 0,
+            #[cfg(feature = "legacy-events")]
+            operation: operation.as_str().to_string(),
+        })
+    }
+
+    // Attaches the caller's reason for the change (the legacy shape has nowhere to put it)
+    #[cfg_attr(feature = "legacy-events", allow(unused_variables, unused_mut))]
+    pub fn with_memo(mut self, memo: String) -> Self {
+        #[cfg(not(feature = "legacy-events"))]
+        {
+            self.memo = memo;
+        }
+        self
+    }
+
+    // Records the weight a weighted increment added (the legacy shape has nowhere to put it)
+    #[cfg_attr(feature = "legacy-events", allow(unused_variables, unused_mut))]
+    pub fn with_weight(mut self, weight: u64) -> Self {
+        #[cfg(not(feature = "legacy-events"))]
+        {
+            self.weight = weight;
+        }
+        self
+    }
+}
+
+// ANNOUNCEMENT WHEN `apply_batch` CHANGES A COUNTER (instead of one `CounterUpdated` per step)
+#[event]
+pub struct CounterBatchApplied {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub user: Pubkey,
+    pub counter: Pubkey,
+    pub start_count: u64,
+    pub end_count: u64,
+    pub ops: u8,       // How many operations the batch held
+    pub version: u64,  // The counter's `version` after the batch
+    pub slot: u64,
+    pub timestamp: i64,
+    pub sequence: u64, // `Counter.sequence`, as in `CounterUpdated`
+}
+
+// ANNOUNCEMENT WHEN A SIGNED COUNTER'S VALUE CHANGES
+#[event]
+pub struct SignedCounterUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub user: Pubkey,
+    pub counter: Pubkey,
+    pub previous_count: i64,
+    pub new_count: i64,
+    pub operation: CounterOperation,
+}
+
+// ANNOUNCEMENT WHEN A BIG COUNTER'S VALUE CHANGES
+#[event]
+pub struct BigCounterUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub user: Pubkey,
+    pub counter: Pubkey,
+    pub previous_count: u128,
+    pub new_count: u128,
+    pub operation: CounterOperation,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER IS MOVED INTO A BIG COUNTER (after the counter's `CounterClosed`)
+#[event]
+pub struct BigCounterUpgraded {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,     // The closed counter
+    pub big_counter: Pubkey,
+    pub authority: Pubkey,
+    pub count: u128,         // What the big counter starts at
+}
+
+// ANNOUNCEMENT WHEN A ZERO-COPY COUNTER CHANGES
+#[event]
+pub struct CounterZCUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub previous_count: u64,
+    pub new_count: u64,
+    pub operation: CounterOperation,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER IS MOVED INTO A ZERO-COPY COUNTER (after the counter's `CounterClosed`)
+#[event]
+pub struct CounterConvertedToZC {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,     // The closed counter
+    pub counter_zc: Pubkey,
+    pub authority: Pubkey,
+    pub count: u64,          // What the zero-copy counter starts at
+}
+
+// ANNOUNCEMENT WHEN A SLOT OF A COUNTER ARRAY CHANGES
+#[event]
+pub struct ArraySlotUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub array: Pubkey,
+    pub index: u16,
+    pub previous_count: u64,
+    pub new_count: u64,
+    pub operation: CounterOperation,
+}
+
+// ANNOUNCEMENT WHEN THE FIRST INCREMENT OF A DAY EXTENDS OR RESTARTS A COUNTER'S STREAK
+#[event]
+pub struct StreakUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub current_streak: u32,
+    pub best_streak: u32,
+    pub day: i64, // Unix time / 86400
+}
+
+// ANNOUNCEMENT WHEN AN INCREMENT REACHES A MULTIPLE OF THE COUNTER'S `milestone_interval`
+#[event]
+pub struct MilestoneReached {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub milestone: u64, // The highest multiple reached (an increment_by may jump past several)
+    pub slot: u64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER OWNER SETS OR REMOVES ITS HOOK PROGRAM (default key = removed)
+#[event]
+pub struct HookUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub hook_program: Pubkey,
+}
+
+// ANNOUNCEMENT OF THE ACCOUNTS `close_all` CLOSED ALONG WITH A COUNTER (before its `CounterClosed`)
+#[event]
+pub struct CounterChildrenClosed {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub accounts: u16,
+    pub lamports: u64, // Their rent, refunded to the owner
+}
+
+// ANNOUNCEMENT WHEN `top_up` ADDS LAMPORTS TO AN ACCOUNT
+#[event]
+pub struct AccountToppedUp {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub account: Pubkey,
+    pub payer: Pubkey,
+    pub lamports: u64,
+    pub balance: u64, // What the account holds afterwards
+}
+
+// ANNOUNCEMENT WHEN A COUNTER GROUP IS CREATED
+#[event]
+pub struct GroupCreated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub group: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+    pub capacity: u32,
+}
+
+// ANNOUNCEMENT WHEN A GROUP'S TOTAL OR MEMBERSHIP CHANGES
+// Sent for every change a member makes, and when a counter joins or leaves the group
+#[event]
+pub struct GroupTotalUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub group: Pubkey,
+    pub counter: Pubkey,       // The member that caused it
+    pub previous_total: u64,
+    pub total: u64,
+    pub members: u32,
+    pub slot: u64,
+}
+
+// ANNOUNCEMENT WHEN A CHANGE TAKES THE COUNT PAST ITS ALERT THRESHOLD (see `set_alert_threshold`)
+// Sent once per crossing: the count has to come back before it's sent again
+#[event]
+pub struct ThresholdCrossed {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub threshold: u64,
+    pub direction: AlertDirection,
+    pub count: u64,     // The count that crossed it
+    pub slot: u64,
+}
+
+// THE PROGRAM-WIDE TOTALS AFTER A CHANGE (sent whenever `GlobalStats` changes)
+#[event]
+pub struct GlobalStatsUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub total_counters: u64,
+    pub total_increments: u64,
+    pub total_decrements: u64,
+    pub total_closed: u64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER IS DELETED
+#[event]
+pub struct CounterClosed {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub user: Pubkey,
+    pub counter: Pubkey,
+    pub final_count: u64,
+    pub rent_recipient: Pubkey, // Who got the account's rent back
+    pub slot: u64,
+    pub timestamp: i64,
+    pub sequence: u64, // One more than the counter's last `Counter.sequence` - always its final event
+}
+
+impl CounterClosed {
+    // The announcement for `counter`, which `user` is about to delete
+    pub fn new(user: Pubkey, counter: &Account<Counter>, rent_recipient: Pubkey, clock: &Clock) -> Self {
+        versioned!(Self {
+            user,
+            counter: counter.key(),
+            final_count: counter.count,
+            rent_recipient,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+            sequence: counter.sequence.saturating_add(1),
+        })
+    }
+}
+
+// ANNOUNCEMENT WHEN ONE COUNTER IS MERGED INTO ANOTHER (the source is closed)
+#[event]
+pub struct CountersMerged {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,    // The source's count, added to the destination
+    pub new_count: u64, // The destination's count afterwards
+}
+
+// ANNOUNCEMENT WHEN COUNT MOVES FROM ONE OWNER'S COUNTER TO ANOTHER'S
+#[event]
+pub struct CountTransferred {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub sender: Pubkey,            // The source's owner, who signed
+    pub recipient: Pubkey,         // The destination's owner
+    pub amount: u64,
+    pub source_count: u64,         // The source's count afterwards
+    pub destination_count: u64,    // The destination's count afterwards
+}
+
+// ANNOUNCEMENT WHEN ONE COUNTER'S COUNT IS ADDED TO ANOTHER (see `add_from`)
+#[event]
+pub struct CounterAddedFrom {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub source: Pubkey,            // Left unchanged
+    pub destination: Pubkey,
+    pub authority: Pubkey,         // The destination's owner, who signed
+    pub amount: u64,               // The source's count that was added
+    pub destination_count: u64,    // The destination's count afterwards
+}
+
+// ANNOUNCEMENT WHEN PART OF A COUNTER IS MOVED INTO A NEW ONE
+#[event]
+pub struct CounterSplit {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub source: Pubkey,
+    pub new_counter: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64, // Taken from the source, and the new counter's starting count
+}
+
+// ANNOUNCEMENT WHEN A NEW COUNTER IS SET UP LIKE AN EXISTING ONE
+#[event]
+pub struct CounterCloned {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub source: Pubkey,
+    pub new_counter: Pubkey,
+    pub authority: Pubkey,
+    pub count: u64, // The new counter's starting count (the source's, or its minimum)
+}
+
+// ANNOUNCEMENT WHEN A SHORT ID IS CLAIMED
+#[event]
+pub struct ShortIdClaimed {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub code: [u8; SHORT_ID_LEN],
+    pub counter: Pubkey,
+    pub owner: Pubkey,
+    pub deposit: u64,
+}
+
+// ANNOUNCEMENT WHEN A SHORT ID IS RELEASED
+#[event]
+pub struct ShortIdReleased {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub code: [u8; SHORT_ID_LEN],
+    pub counter: Pubkey,
+    pub owner: Pubkey,
+}
+
+// ANNOUNCEMENT WHEN THE ADMIN CHANGES WHICH INSTRUCTIONS ARE DISABLED
+#[event]
+pub struct InstructionFlagsUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub admin: Pubkey,
+    pub disabled_instructions: u128,
+}
+
+// ANNOUNCEMENT WHEN THE ADMIN PAUSES OR UNPAUSES THE PROGRAM
+#[event]
+pub struct ProgramPauseChanged {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub admin: Pubkey,
+    pub paused: bool,
+}
+
+// ANNOUNCEMENT OF HOW A PERMISSIONLESS CLEANUP SPLIT THE RECLAIMED RENT
+#[event]
+pub struct RentRefundSplit {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub account: Pubkey,
+    pub payer: Pubkey,
+    pub payer_amount: u64,
+    pub authority: Pubkey,
+    pub authority_amount: u64,
+    pub cranker: Pubkey,
+    pub cranker_amount: u64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER MOVES TO A NEW LIFECYCLE STATE
+#[event]
+pub struct CounterStateChanged {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub from: CounterState,
+    pub to: CounterState,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER IS FROZEN (after its `CounterStateChanged`)
+#[event]
+pub struct CounterFrozen {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+}
+
+// ANNOUNCEMENT WHEN A FROZEN COUNTER IS ACTIVE AGAIN (after its `CounterStateChanged`)
+#[event]
+pub struct CounterUnfrozen {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+}
+
+// ANNOUNCEMENT WHEN AN OWNER OFFERS THEIR COUNTER TO SOMEONE ELSE (None = offer cancelled)
+#[event]
+pub struct AuthorityTransferInitiated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER CHANGES HANDS
+#[event]
+pub struct AuthorityTransferred {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+// ANNOUNCEMENT WHEN A RECOVERY AUTHORITY CLAIMS A QUIET COUNTER
+// The owner has until `completes_at` to `veto_claim`; after that the claim can be completed,
+// which is announced with `AuthorityTransferred`
+#[event]
+pub struct AuthorityClaimed {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub recovery_authority: Pubkey,
+    pub last_active_at: i64, // When the owner was last seen (see `Counter::last_active_at`)
+    pub claimed_at: i64,
+    pub completes_at: i64,
+}
+
+// ANNOUNCEMENT WHEN AN OWNER VETOES A RECOVERY CLAIM
+#[event]
+pub struct AuthorityClaimVetoed {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub recovery_authority: Pubkey,
+    pub claimed_at: i64, // When the claim it undid was made
+}
+
+// ANNOUNCEMENT WHEN AN OWNER LETS AN OPERATOR INCREMENT THEIR COUNTER
+#[event]
+pub struct OperatorApproved {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub operator: Pubkey,
+    pub allowance: u64,
+}
+
+// ANNOUNCEMENT WHEN AN OPERATOR'S APPROVAL IS TAKEN AWAY
+#[event]
+pub struct OperatorRevoked {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub operator: Pubkey,
+}
+
+// ANNOUNCEMENT WHEN AN OWNER STARTS (OR RENEWS) A SESSION
+#[event]
+pub struct SessionCreated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_at: i64,
+    pub max_ops: u32,
+}
+
+// ANNOUNCEMENT WHEN AN OWNER ENDS A SESSION EARLY
+#[event]
+pub struct SessionRevoked {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub session_key: Pubkey,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER TAKES (OR KEEPS) A PLACE ON THE LEADERBOARD
+#[event]
+pub struct LeaderboardUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub count: u64,
+    pub rank: u8,            // 0 = top
+}
+
+// ANNOUNCEMENT WHEN AN OWNER LETS A TEAM MEMBER INCREMENT THEIR COUNTER
+#[event]
+pub struct ContributorAdded {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub member: Pubkey,
+}
+
+// ANNOUNCEMENT WHEN A TEAM MEMBER IS TAKEN OFF A COUNTER, WITH WHAT THEY ADDED IN TOTAL
+#[event]
+pub struct ContributorRemoved {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub member: Pubkey,
+    pub contributions: u64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER IS HANDED TO A MULTISIG
+#[event]
+pub struct MultisigCreated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub multisig: Pubkey,
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+// ANNOUNCEMENT OF A NEW MULTISIG PROPOSAL
+#[event]
+pub struct OpProposed {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub multisig: Pubkey,
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub op: MultisigOp,
+}
+
+// ANNOUNCEMENT WHEN AN OWNER APPROVES A PROPOSAL
+#[event]
+pub struct OpApproved {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub proposal: Pubkey,
+    pub owner: Pubkey,
+    pub approvals: u8, // Approvals so far, this one included
+}
+
+// ANNOUNCEMENT WHEN A PROPOSAL HAS BEEN CARRIED OUT (after the change's own events)
+#[event]
+pub struct OpExecuted {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub multisig: Pubkey,
+    pub proposal: Pubkey,
+    pub counter: Pubkey,
+    pub op: MultisigOp,
+}
+
+// PERIODIC ANNOUNCEMENT OF A COUNTER'S CURRENT VALUE
+#[event]
+pub struct CounterHeartbeat {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub count: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER'S ACTIVITY HAS BEEN PROVEN (see `ActivityReceipt`)
+#[event]
+pub struct ActivityProven {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub receipt: Pubkey,
+    pub min_ops: u32,
+    pub window_days: u8,
+    pub ops_in_window: u32,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER'S VALUE IS RECORDED IN A `CounterSnapshot`
+#[event]
+pub struct SnapshotTaken {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub snapshot: Pubkey,
+    pub label: u64,
+    pub count: u64,
+    pub slot: u64,
+}
+
+// ANNOUNCEMENT WHEN A PROTOCOL FEE IS PAID (mint is the default key for SOL)
+#[event]
+pub struct FeePaid {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub payer: Pubkey,
+    pub counter: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER OWNER'S INCREMENT FEE IS PAID (always SOL)
+#[event]
+pub struct CounterFeePaid {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub payer: Pubkey,
+    pub counter: Pubkey,
+    pub amount: u64,
+}
+
+// ANNOUNCEMENT WHEN AN INCREMENT CREDITS A REFERRER
+#[event]
+pub struct ReferralRecorded {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub referrer: Pubkey,
+    pub referrer_counter: Pubkey,
+    pub counter: Pubkey,     // The counter that was incremented
+    pub user: Pubkey,        // Who incremented it
+    pub referrals: u64,      // The referrer counter's total afterwards
+}
+
+// ANNOUNCEMENT WHEN A COUNTER OWNER'S TOKEN FEE IS PAID
+#[event]
+pub struct CounterTokenFeePaid {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub payer: Pubkey,
+    pub counter: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER OWNER CHANGES THEIR TOKEN FEE (0 = off)
+#[event]
+pub struct TokenFeeUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub fee_mint: Pubkey,
+    pub fee_amount: u64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER OWNER CHANGES THEIR INCREMENT FEE
+#[event]
+pub struct CounterFeeUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub fee_lamports: u64,
+}
+
+// ANNOUNCEMENT WHEN AN INCREMENT PAYS INTO A COUNTER'S VAULT
+#[event]
+pub struct VaultDeposited {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub payer: Pubkey,
+    pub counter: Pubkey,
+    pub amount: u64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER'S RESET PERIOD HAS ENDED AND IT STARTS OVER
+// Comes just before the `CounterUpdated` of the change that noticed it
+#[event]
+pub struct PeriodRolledOver {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub last_period_count: u64, // The count the finished period ended on
+    pub period_start: i64,      // When the new period began (unix timestamp)
+    pub slot: u64,
+}
+
+// ANNOUNCEMENT WHEN A DECAYING COUNTER LOSES COUNT (see `set_decay_rate`)
+// Comes just before the `CounterUpdated` of the change that noticed it, if any
+#[event]
+pub struct DecayApplied {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub amount: u64,         // How much came off
+    pub count: u64,          // The count afterwards
+    pub slot: u64,
+}
+
+// ANNOUNCEMENT WHEN A KEEPER RUNS A COUNTER'S UPKEEP (see `touch`)
+// Comes after the `DecayApplied` / `PeriodRolledOver` / `CounterStateChanged` it caused
+#[event]
+pub struct CounterTouched {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub keeper: Pubkey,
+    pub decayed: bool,       // Decay was settled
+    pub rolled_over: bool,   // A lapsed reset period started over
+    pub archived: bool,      // The counter had expired and was archived
+    pub count: u64,          // The count afterwards
+    pub tip_lamports: u64,   // What the keeper was paid from the vault
+    pub slot: u64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER OWNER SETS OR CLEARS ITS DISPLAY METADATA (empty strings = cleared)
+#[event]
+pub struct CounterMetadataUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub label: String,
+    pub uri: String,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER OWNER CHANGES THE VAULT DEPOSIT (0 = off)
+#[event]
+pub struct VaultDepositUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub deposit_per_increment: u64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER OWNER TAKES LAMPORTS OUT OF THE VAULT
+#[event]
+pub struct VaultWithdrawn {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER OWNER EMPTIES THEIR COUNTER IN ONE GO
+#[event]
+pub struct CounterDrained {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub drained: u64,        // The count before the drain
+    pub vault_lamports: u64, // Deposits refunded from the vault (0 if it wasn't passed in)
+}
+
+// ANNOUNCEMENT WHEN A COUNTER HAS BEEN MOVED TO THE CURRENT ACCOUNT LAYOUT
+#[event]
+pub struct CounterMigrated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub from_version: u8, // `Counter.layout_version` before (0 = written before versioning)
+    pub to_version: u8,
+    pub previous_size: u32, // Account size in bytes before and after
+    pub size: u32,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER OWNER CHANGES WHO MAY TOUCH THEIR COUNTER (default mint = no gate)
+#[event]
+pub struct TokenGateUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub gate_mint: Pubkey,
+    pub min_balance: u64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER OWNER CHANGES WHAT `increment_weighted` ADDS (default mint = off)
+#[event]
+pub struct WeightConfigUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub weight_mint: Pubkey,
+    pub weight_divisor: u64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER OWNER CHANGES WHO MAY PUSH VALUES TO IT (None = no feed)
+#[event]
+pub struct FeedAuthorityUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub feed_authority: Option<Pubkey>,
+}
+
+// ANNOUNCEMENT WHEN A FEED AUTHORITY PUSHES A VALUE (after the change's `CounterUpdated`)
+#[event]
+pub struct FeedValuePushed {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub feed_authority: Pubkey,
+    pub previous_count: u64,
+    pub value: u64,        // The counter's count from now on
+    pub observed_at: i64,  // When the reporter observed it (unix timestamp)
+    pub slot: u64,
+}
+
+// ANNOUNCEMENT WHEN AN INCREMENT EARNS REWARD TOKENS
+#[event]
+pub struct RewardMinted {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub user: Pubkey,
+    pub counter: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+// ANNOUNCEMENT WHEN THE ADMIN CHANGES THE REWARD SETTINGS
+#[event]
+pub struct RewardsConfigured {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub mint: Pubkey,
+    pub tokens_per_increment: u64,
+    pub enabled: bool,
+}
+
+// ANNOUNCEMENT WHEN THE ADMIN CHANGES THE FEE SETTINGS
+#[event]
+pub struct FeeConfigUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub op_fee_lamports: u64,
+    pub fee_mint: Pubkey,
+    pub price_authority: Pubkey,
+    pub max_price_age_seconds: i64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER TEMPLATE IS SAVED
+#[event]
+pub struct TemplateCreated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub template: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+}
+
+// ANNOUNCEMENT WHEN A WALLET SAVES ITS DEFAULT COUNTER SETTINGS (see `set_defaults`)
+#[event]
+pub struct DefaultsUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub defaults: Pubkey,
+    pub authority: Pubkey,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER IS CREATED FROM A TEMPLATE (after its `CounterInitialized`)
+#[event]
+pub struct CounterCreatedFromTemplate {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub template: Pubkey,
+    pub authority: Pubkey,
+}
+
+// ANNOUNCEMENT WHEN AN OWNER STARTS THE CLOSE TIMELOCK
+#[event]
+pub struct CloseRequested {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub requested_at: i64,
+    pub closable_at: i64, // When `close` will go through under the current timelock
+}
+
+// ANNOUNCEMENT WHEN AN OWNER CALLS OFF A PENDING CLOSE
+#[event]
+pub struct CloseCancelled {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub requested_at: i64, // When the cancelled request was made
+}
+
+// ANNOUNCEMENT WHEN AN OWNER SOFT-CLOSES A COUNTER (see `soft_close`)
+#[event]
+pub struct CounterSoftClosed {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub closed_at: i64,
+    pub reclaimable_at: i64, // When anyone can `reclaim_closed` it under the current grace period
+}
+
+// ANNOUNCEMENT WHEN AN OWNER HANDS A COUNTER OVER TO ANOTHER PROGRAM VERSION (see `export`)
+// The full snapshot is in the return data
+#[event]
+pub struct CounterExported {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub count: u64,
+    pub exported_at: i64,
+}
+
+// ANNOUNCEMENT WHEN A COUNTER IS RECREATED FROM ANOTHER PROGRAM'S SNAPSHOT (after its `CounterInitialized`)
+#[event]
+pub struct CounterImported {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub count: u64,
+    pub source_program: Pubkey, // Where it was exported from
+    pub source_counter: Pubkey, // ...and its address there
+}
+
+// ANNOUNCEMENT WHEN AN OWNER REOPENS A SOFT-CLOSED COUNTER
+#[event]
+pub struct CounterReopened {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub authority: Pubkey,
+    pub closed_at: i64, // When the soft close it undid was made
+}
+
+// ANNOUNCEMENT WHEN THE ADMIN CHANGES THE FEE SCHEDULE
+#[event]
+pub struct FeeScheduleUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub init_fee_lamports: u64,
+    pub op_fee_lamports: u64,
+    pub fee_recipient: Pubkey,
+}
+
+// ANNOUNCEMENT WHEN THE ORACLE PUSHES A NEW FEE TOKEN PRICE
+#[event]
+pub struct FeePricePushed {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub fee_mint: Pubkey,
+    pub tokens_per_sol: u64,
+    pub timestamp: i64,
+}
+
+// ANNOUNCEMENT OF A COUNTER HEALTH CHECK RESULT
+#[event]
+pub struct InvariantsChecked {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub passed: bool,
+    pub failures: u32, // Bitmask of `invariant_failure` values
+}
+
+// ANNOUNCEMENT WHEN THE ADMIN APPROVES OR REVOKES A PARTNER NAMESPACE
+#[event]
+pub struct PartnerUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub partner_id: [u8; PARTNER_ID_LEN],
+    pub approved: bool, // false = revoked
+}
+
+// ANNOUNCEMENT OF A NEW WAGER (see `create_wager`)
+#[event]
+pub struct WagerCreated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub wager: Pubkey,
+    pub creator_counter: Pubkey,
+    pub opponent_counter: Pubkey,
+    pub target: u64,
+    pub stake_lamports: u64, // Each side's
+    pub deadline: i64,
+}
+
+// ANNOUNCEMENT WHEN A WAGER IS PAID OUT OR REFUNDED
+#[event]
+pub struct WagerSettled {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub wager: Pubkey,
+    pub winner: Pubkey, // Default when nobody won (a tie, or refunded after the deadline)
+    pub payout: u64,    // What the winner got (0 when the stakes went back)
+}
+
+// ANNOUNCEMENT WHEN A MEMBER'S ROLES ON A COUNTER CHANGE (see `grant_role`)
+#[event]
+pub struct RoleUpdated {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub member: Pubkey,
+    pub roles: u8, // What the member holds now (0 = none, account closed)
+}
+
+// ANNOUNCEMENT WHEN A COUNTER'S RESULT IS LOCKED IN AFTER ITS WINDOW (see `finalize`)
+#[event]
+pub struct CounterFinalized {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub count: u64,      // The final result
+    pub window_end: i64,
+}
+
+// ANNOUNCEMENT OF A SEALED VALUE (see `commit`)
+#[event]
+pub struct ValueCommitted {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub user: Pubkey,
+    pub commitment: [u8; 32],
+}
+
+// ANNOUNCEMENT WHEN A SEALED VALUE IS REVEALED AND ADDED (see `reveal`)
+#[event]
+pub struct ValueRevealed {
+    #[cfg(not(feature = "legacy-events"))]
+    pub schema_version: u8, // See `schema`
+    pub counter: Pubkey,
+    pub user: Pubkey,
+    pub value: u64,
+    pub count: u64, // The counter's count afterwards
+}
@@ -0,0 +1,364 @@
+// SHARED ACCESS
+// Operators, sessions, contributors and multisig owners
+
+use crate::*;
+
+// ACCOUNTS NEEDED TO APPROVE AN OPERATOR
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct ApproveOperator<'info> {
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE APPROVAL - one per (counter, operator)
+    #[account(
+        init_if_needed,          // Approving again just updates the allowance
+        payer = user,
+        space = 8 + Delegation::INIT_SPACE,
+        seeds = [b"delegate", counter.key().as_ref(), operator.as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    // THE OWNER (pays the approval's rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO REVOKE AN OPERATOR
+#[derive(Accounts)]
+pub struct RevokeOperator<'info> {
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE APPROVAL BEING REMOVED (deleted, rent refunded to the owner)
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"delegate", counter.key().as_ref(), delegation.operator.as_ref()],
+        bump = delegation.bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO START A SESSION
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSession<'info> {
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE SESSION - one per (counter, session key)
+    #[account(
+        init_if_needed,          // Creating it again just replaces the expiry and budget
+        payer = user,
+        space = 8 + Session::INIT_SPACE,
+        seeds = [b"session", counter.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, Session>,
+
+    // THE OWNER (pays the session's rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO REVOKE A SESSION
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE SESSION BEING ENDED (deleted, rent refunded to the owner)
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"session", counter.key().as_ref(), session.session_key.as_ref()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, Session>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO ADD A CONTRIBUTOR TO A SHARED COUNTER
+#[derive(Accounts)]
+#[instruction(member: Pubkey)]
+pub struct AddContributor<'info> {
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE REGISTRATION - one per (counter, member)
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Contributor::INIT_SPACE,
+        seeds = [b"contributor", counter.key().as_ref(), member.as_ref()],
+        bump
+    )]
+    pub contributor: Account<'info, Contributor>,
+
+    // THE OWNER (pays the registration's rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO REMOVE A CONTRIBUTOR FROM A SHARED COUNTER
+#[derive(Accounts)]
+pub struct RemoveContributor<'info> {
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE REGISTRATION BEING REMOVED (deleted, rent refunded to the owner)
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"contributor", counter.key().as_ref(), contributor.member.as_ref()],
+        bump = contributor.bump
+    )]
+    pub contributor: Account<'info, Contributor>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO GRANT A MEMBER ROLES ON A COUNTER
+#[derive(Accounts)]
+#[instruction(member: Pubkey)]
+pub struct GrantRole<'info> {
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE MEMBER'S ROLES - one account per (counter, member), created with their first role
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Role::INIT_SPACE,
+        seeds = [b"role", counter.key().as_ref(), member.as_ref()],
+        bump
+    )]
+    pub role: Account<'info, Role>,
+
+    // THE OWNER (pays the account's rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO TAKE ROLES AWAY FROM A MEMBER
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE MEMBER'S ROLES (deleted, rent refunded to the owner, once none are left)
+    #[account(mut, seeds = [b"role", counter.key().as_ref(), role.member.as_ref()], bump = role.bump)]
+    pub role: Account<'info, Role>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO PUT A COUNTER UNDER A MULTISIG
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(
+        mut,                     // The multisig becomes its authority
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE MULTISIG - one per counter
+    #[account(
+        init,
+        payer = user,
+        space = 8 + MultisigAuthority::INIT_SPACE,
+        seeds = [b"multisig", counter.key().as_ref()],
+        bump
+    )]
+    pub multisig: Account<'info, MultisigAuthority>,
+
+    // THE CURRENT OWNER (pays the multisig's rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO PROPOSE A CHANGE TO A MULTISIG COUNTER
+#[derive(Accounts)]
+pub struct ProposeOp<'info> {
+    #[account(mut, seeds = [b"multisig", multisig.counter.as_ref()], bump = multisig.bump)]
+    pub multisig: Account<'info, MultisigAuthority>,
+
+    // THE NEW PROPOSAL, NUMBERED BY THE MULTISIG'S PROPOSAL COUNT
+    #[account(
+        init,
+        payer = user,
+        space = 8 + MultisigProposal::INIT_SPACE,
+        seeds = [b"proposal", multisig.key().as_ref(), &multisig.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, MultisigProposal>,
+
+    // ONE OF THE MULTISIG'S OWNERS (pays the proposal's rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO APPROVE A PROPOSAL
+#[derive(Accounts)]
+pub struct ApproveOp<'info> {
+    #[account(seeds = [b"multisig", multisig.counter.as_ref()], bump = multisig.bump)]
+    pub multisig: Account<'info, MultisigAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), &proposal.index.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, MultisigProposal>,
+
+    // ONE OF THE MULTISIG'S OWNERS
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO EXECUTE AN APPROVED PROPOSAL
+#[derive(Accounts)]
+pub struct ExecuteOp<'info> {
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == multisig.key() @ CounterError::Unauthorized, // Still run by the multisig
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(seeds = [b"multisig", counter.key().as_ref()], bump = multisig.bump)]
+    pub multisig: Account<'info, MultisigAuthority>,
+
+    // THE PROPOSAL BEING EXECUTED (deleted, rent refunded to whoever proposed it)
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"proposal", multisig.key().as_ref(), &proposal.index.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer
+    )]
+    pub proposal: Account<'info, MultisigProposal>,
+
+    #[account(mut)]
+    pub proposer: SystemAccount<'info>,
+
+    // WHOEVER SENDS THE TRANSACTION (anyone, once there are enough approvals)
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // OPTIONAL: THE COUNTER'S CHANGE LOG (required once it has one)
+    #[account(mut, seeds = [b"history", counter.key().as_ref()], bump)]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+}
@@ -0,0 +1,167 @@
+// PROGRAM ADMINISTRATION
+// Config, global stats, protocol fees and rewards - the upgrade authority's instructions
+
+use crate::*;
+
+// ACCOUNTS NEEDED TO CREATE THE PROGRAM CONFIG
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    // THE SINGLETON CONFIG ACCOUNT
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProgramConfig::INIT_SPACE,
+        seeds = [b"config"],    // Only one config can ever exist
+        bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE DEPLOYER, WHO BECOMES THE ADMIN
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    // THIS PROGRAM AND ITS UPGRADE DATA
+    // Used to prove that `admin` is the program's upgrade authority
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Counter>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ CounterError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO CREATE THE GLOBAL STATS
+#[derive(Accounts)]
+pub struct InitializeStats<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin @ CounterError::Unauthorized)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE SINGLETON STATS ACCOUNT
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"stats"],     // Only one can ever exist
+        bump
+    )]
+    pub stats: Account<'info, GlobalStats>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED FOR ADMIN ACTIONS ON A SINGLE COUNTER
+#[derive(Accounts)]
+pub struct AdminCounter<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin @ CounterError::Unauthorized)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub admin: Signer<'info>,
+
+    // ANY COUNTER (found through its stored owner)
+    #[account(mut, seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()], bump = counter.bump, constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion)]
+    pub counter: Account<'info, Counter>,
+}
+
+// ACCOUNTS NEEDED TO PUSH A NEW FEE TOKEN PRICE
+#[derive(Accounts)]
+pub struct PushFeePrice<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = price_authority @ CounterError::Unauthorized // Only the configured oracle can push prices
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub price_authority: Signer<'info>,
+}
+
+// ACCOUNTS NEEDED TO WITHDRAW PROTOCOL FEES
+#[derive(Accounts)]
+pub struct WithdrawProtocolFees<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin @ CounterError::Unauthorized)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE ADMIN, WHO RECEIVES SOL WITHDRAWALS (unless the fee schedule names a recipient)
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: SystemAccount<'info>,
+
+    // OPTIONAL: THE FEE SCHEDULE'S `fee_recipient` (required for SOL withdrawals once set)
+    #[account(mut, address = config.fee_recipient @ CounterError::Unauthorized)]
+    pub fee_recipient: Option<SystemAccount<'info>>,
+
+    // OPTIONAL: WITHDRAW TOKENS INSTEAD OF SOL
+    #[account(mut, token::authority = treasury)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub destination_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO SET UP INCREMENT REWARDS
+#[derive(Accounts)]
+pub struct InitializeRewards<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin @ CounterError::Unauthorized)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE SINGLETON REWARD SETTINGS - also the reward mint's authority
+    #[account(
+        init_if_needed,          // Calling again just updates the settings
+        payer = admin,
+        space = 8 + RewardConfig::INIT_SPACE,
+        seeds = [b"rewards"],
+        bump
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+
+    // THE REWARD TOKEN (created on the first call)
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [b"reward_mint"],
+        bump,
+        mint::decimals = 0,      // Whole tokens only: one increment, one token
+        mint::authority = reward_config,
+    )]
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO SWITCH INCREMENT REWARDS OFF
+#[derive(Accounts)]
+pub struct DisableRewards<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin @ CounterError::Unauthorized)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(mut, seeds = [b"rewards"], bump = reward_config.bump)]
+    pub reward_config: Account<'info, RewardConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+// ACCOUNTS NEEDED FOR ADMIN-ONLY CONFIG CHANGES
+#[derive(Accounts)]
+pub struct AdminConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ CounterError::Unauthorized // Only the admin can change the config
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,534 @@
+// CLOSING AND MOVING COUNTS
+// Closing counters (by the owner or a cranker), merging, splitting and transfers
+
+use crate::*;
+
+// CLOSE A PROGRAM-OWNED ACCOUNT, SPLITTING ITS LAMPORTS BY THE CONFIGURED BASIS POINTS
+// Used by every permissionless cleanup path. The payer and cranker shares are rounded down
+// and the owner receives whatever is left, so every lamport is accounted for exactly
+pub(crate) fn close_with_refund_split<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    cranker: &AccountInfo<'info>,
+    config: &ProgramConfig,
+) -> Result<()> {
+    let total = account.lamports();
+    let (payer_amount, authority_amount, cranker_amount) = config.refund_split(total);
+
+    **account.try_borrow_mut_lamports()? = 0;
+    **payer.try_borrow_mut_lamports()? += payer_amount;
+    **authority.try_borrow_mut_lamports()? += authority_amount;
+    **cranker.try_borrow_mut_lamports()? += cranker_amount;
+
+    // Hand the emptied account back to the System Program so it is fully deleted
+    account.assign(&anchor_lang::system_program::ID);
+    account.resize(0)?;
+
+    emit_event!(RentRefundSplit {
+        account: account.key(),
+        payer: payer.key(),
+        payer_amount,
+        authority: authority.key(),
+        authority_amount,
+        cranker: cranker.key(),
+        cranker_amount,
+    });
+
+    verbose_msg!(
+        "Refunded {} lamports: payer {}, authority {}, cranker {}",
+        total, payer_amount, authority_amount, cranker_amount
+    );
+    Ok(())
+}
+
+// WHETHER A PERMISSIONLESS CLEANUP MAY DELETE A COUNTER
+// What `check_state` asks of a close by the owner: a lifecycle state that allows it, no group
+// still counting it in its total and no open wager riding on the counter
+pub(crate) fn require_reclaimable(counter: &Counter, now: i64) -> Result<()> {
+    require!(counter.state.allows_close(), CounterError::CounterNotClosable);
+    require!(counter.group == Pubkey::default(), CounterError::CounterInGroup);
+    require!(!counter.in_open_wager(now), CounterError::CounterInWager);
+    Ok(())
+}
+
+// DELETE A COUNTER
+// The shared body of `close` and `close_to`: the rent goes to `rent_recipient` when it was
+// passed in, otherwise back to the owner
+pub(crate) fn close_counter(ctx: Context<Close>, id: &str, dry_run: bool, expected_seq: Option<u64>) -> Result<()> {
+    let counter = &ctx.accounts.counter;    // The counter account we're deleting
+    let user = &ctx.accounts.user;          // The person trying to close it
+
+    // POLICY CHECKS: only the owner, and never while frozen or migrating
+    let policy = PolicyContext::new(counter, user.key(), PolicyOp::Close)?;
+    ActivePolicy::validate(&policy, counter.count)?;
+
+    // TIMELOCK: once the admin sets one, the close has to be requested and left to wait
+    let timelock = ctx.accounts.config.close_timelock_seconds;
+    if timelock > 0 {
+        require!(counter.close_requested_at != 0, CounterError::CloseNotRequested);
+        let closable_at = counter.close_requested_at.saturating_add(timelock);
+        require!(Clock::get()?.unix_timestamp >= closable_at, CounterError::CloseTimelockActive);
+    }
+
+    let final_count = counter.count;  // Remember the final value
+    let (counter_key, user_key) = (counter.key(), user.key());
+    let recipient = match &ctx.accounts.rent_recipient {
+        Some(recipient) => recipient.to_account_info(),
+        None => user.to_account_info(),
+    };
+
+    advance_sequencer(ctx.accounts.sequencer.as_mut(), expected_seq, dry_run)?; // Enforce ordering (if requested)
+    if dry_run {
+        return report_dry_run(counter_key, final_count, 0);
+    }
+
+    let stats = &mut ctx.accounts.stats;
+    stats.total_closed = stats.total_closed.saturating_add(1);
+    stats.announce();
+    unregister_counter(ctx.accounts.registry.as_mut(), counter_key);
+
+    // Tell the blockchain we're closing this counter
+    emit_indexed!(ctx, CounterClosed::new(user_key, &ctx.accounts.counter, recipient.key(), &Clock::get()?));
+
+    // Log the closure for blockchain explorers
+    verbose_msg!("Counter {:?} closed for user: {} with final count: {}", id, user_key, final_count);
+
+    // Delete the account and refund the rent
+    ctx.accounts.counter.close(recipient)?;
+    report_diagnostics!(ctx);
+    Ok(())
+}
+
+// CLOSE THE ACCOUNTS THAT BELONG TO A COUNTER (see `close_all`)
+// Every one of `children` has to be a change log, operator approval, session, contributor
+// registration or snapshot of `counter`, checked by deriving its address again from its own
+// data. Snapshots somebody else took are refused, since their rent is theirs. The rent goes
+// to `recipient`; returns how much that was
+pub(crate) fn close_children<'info>(
+    counter: Pubkey,
+    authority: Pubkey,
+    children: &'info [AccountInfo<'info>],
+    recipient: &AccountInfo<'info>,
+) -> Result<u64> {
+    let mut refunded = 0u64;
+    for child in children {
+        require!(child.is_writable && child.owner == &crate::ID, CounterError::NotACounterChild);
+        let (owner_counter, seeds) = child_seeds(child, authority)?;
+        require_keys_eq!(owner_counter, counter, CounterError::NotACounterChild);
+        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        let expected = Pubkey::create_program_address(&seeds, &crate::ID).map_err(|_| CounterError::NotACounterChild)?;
+        require_keys_eq!(child.key(), expected, CounterError::NotACounterChild);
+
+        let lamports = child.lamports();
+        **child.try_borrow_mut_lamports()? = 0;
+        **recipient.try_borrow_mut_lamports()? += lamports;
+        child.assign(&anchor_lang::system_program::ID); // Fully deleted, like `close_with_refund_split`
+        child.resize(0)?;
+        refunded = refunded.saturating_add(lamports);
+    }
+    Ok(refunded)
+}
+
+// THE COUNTER A CHILD ACCOUNT BELONGS TO, AND THE SEEDS (BUMP INCLUDED) ITS ADDRESS COMES FROM
+// Every child's seeds are its prefix, its counter, then (except for the change log) one more
+// value that tells it apart from its siblings
+fn child_seeds<'info>(child: &'info AccountInfo<'info>, authority: Pubkey) -> Result<(Pubkey, Vec<Vec<u8>>)> {
+    let data = child.try_borrow_data()?;
+    let discriminator = data.get(..8).ok_or(CounterError::NotACounterChild)?;
+    let mut data: &[u8] = &data;
+    let (prefix, counter, extra, bump): (&[u8], Pubkey, Vec<u8>, u8) = if discriminator == CounterHistory::DISCRIMINATOR {
+        let history = AccountLoader::<CounterHistory>::try_from(child)?;
+        let history = history.load()?;
+        (b"history", history.counter, vec![], history.bump)
+    } else if discriminator == Delegation::DISCRIMINATOR {
+        let delegation = Delegation::try_deserialize(&mut data)?;
+        (b"delegate", delegation.counter, delegation.operator.to_bytes().to_vec(), delegation.bump)
+    } else if discriminator == Session::DISCRIMINATOR {
+        let session = Session::try_deserialize(&mut data)?;
+        (b"session", session.counter, session.session_key.to_bytes().to_vec(), session.bump)
+    } else if discriminator == Contributor::DISCRIMINATOR {
+        let contributor = Contributor::try_deserialize(&mut data)?;
+        (b"contributor", contributor.counter, contributor.member.to_bytes().to_vec(), contributor.bump)
+    } else if discriminator == Role::DISCRIMINATOR {
+        let role = Role::try_deserialize(&mut data)?;
+        (b"role", role.counter, role.member.to_bytes().to_vec(), role.bump)
+    } else if discriminator == CounterSnapshot::DISCRIMINATOR {
+        let snapshot = CounterSnapshot::try_deserialize(&mut data)?;
+        require_keys_eq!(snapshot.authority, authority, CounterError::NotACounterChild);
+        (b"snapshot", snapshot.counter, snapshot.label.to_le_bytes().to_vec(), snapshot.bump)
+    } else {
+        return err!(CounterError::NotACounterChild);
+    };
+
+    let mut seeds = vec![prefix.to_vec(), counter.to_bytes().to_vec()];
+    if !extra.is_empty() {
+        seeds.push(extra);
+    }
+    seeds.push(vec![bump]);
+    Ok((counter, seeds))
+}
+
+// MOVE A COUNTER TO `new_count` ON BEHALF OF ANOTHER COUNTER, AND ANNOUNCE IT
+// Used when value moves between counters (`merge` / `split`) and by the other changes that
+// don't go through `update_count`: the same bookkeeping after its checks, minus fees, rewards,
+// the program-wide totals and the group roll-up. Without the roll-up a member's group total
+// would drift, so counters in a group have to leave it first (`CounterInGroup`)
+pub(crate) fn move_count(
+    counter: &mut Account<Counter>,
+    history: Option<&AccountLoader<CounterHistory>>,
+    user: Pubkey,
+    operation: CounterOperation,
+    new_count: u64,
+) -> Result<()> {
+    require!(!counter.has_history || history.is_some(), CounterError::HistoryRequired);
+    require!(counter.group == Pubkey::default(), CounterError::CounterInGroup);
+
+    let clock = Clock::get()?;
+    // Value moved in from elsewhere would let an owner jump to a wager's target (see `check_wager`)
+    require!(
+        new_count <= counter.count || !counter.in_open_wager(clock.unix_timestamp),
+        CounterError::CounterInWager
+    );
+    let previous_count = counter.count;
+    counter.accrue_twa(clock.unix_timestamp);
+    counter.count = new_count;
+    counter.last_updated = clock.unix_timestamp;
+    counter.record_activity(clock.unix_timestamp);
+    counter.next_version();
+    counter.next_sequence();
+    if let Some(history) = history {
+        history.load_mut()?.record(clock.slot, operation, previous_count, new_count);
+    }
+
+    emit!(CounterUpdated::new(user, counter, previous_count, operation, &clock));
+    if operation == CounterOperation::Increment {
+        if let Some(milestone) = counter.milestone_reached(previous_count) {
+            emit_event!(MilestoneReached { counter: counter.key(), milestone, slot: clock.slot });
+        }
+    }
+    Ok(())
+}
+
+// ACCOUNTS NEEDED TO CLOSE/DELETE A COUNTER
+// This tells Solana what accounts are required for deleting the counter
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct Close<'info> {
+    // THE COUNTER ACCOUNT WE'RE DELETING
+    #[account(
+        mut,                     // Account will be modified (then deleted by the handler, refunding rent to the user)
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), id.as_bytes()], // How to find the counter
+        bump = counter.bump,     // Use the stored bump seed
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Security check
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,  // The counter we're closing
+
+    // THE PERSON CLOSING THE COUNTER
+    #[account(mut)]             // Will receive the rent refund
+    pub user: Signer<'info>,   // Must be signed by the user
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (see `GlobalStats`)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // OPTIONAL: THE CALLER'S SEQUENCER (required when `expected_seq` is set)
+    #[account(mut, seeds = [b"sequencer", user.key().as_ref()], bump = sequencer.bump)]
+    pub sequencer: Option<Account<'info, Sequencer>>,
+
+    // OPTIONAL: WHERE THE RENT GOES (the owner when left out; required by `close_to`)
+    #[account(mut)]
+    pub rent_recipient: Option<SystemAccount<'info>>,
+
+    // OPTIONAL: THE CREATOR'S LIST OF COUNTERS (the counter is taken off it)
+    // Only left out for counters made before registries existed
+    #[account(mut, seeds = [b"registry", counter.creator.as_ref()], bump = registry.bump)]
+    pub registry: Option<Account<'info, UserRegistry>>,
+}
+
+// ACCOUNTS NEEDED TO MERGE ONE COUNTER INTO ANOTHER
+#[derive(Accounts)]
+pub struct Merge<'info> {
+    // THE COUNTER BEING MERGED AWAY (deleted, rent refunded to the owner)
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"counter", source.namespace_seed(), source.creator.as_ref(), source.label.as_bytes()],
+        bump = source.bump,
+        constraint = source.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = source.key() != destination.key() @ CounterError::SameCounter,
+        constraint = source.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub source: Account<'info, Counter>,
+
+    // THE COUNTER RECEIVING THE SOURCE'S COUNT
+    #[account(
+        mut,
+        seeds = [b"counter", destination.namespace_seed(), destination.creator.as_ref(), destination.label.as_bytes()],
+        bump = destination.bump,
+        constraint = destination.authority == user.key() @ CounterError::Unauthorized, // Same owner
+        constraint = destination.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub destination: Account<'info, Counter>,
+
+    // THE OWNER OF BOTH
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (the source counts as closed)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // OPTIONAL: THE DESTINATION'S CHANGE LOG (required once it has one)
+    #[account(mut, seeds = [b"history", destination.key().as_ref()], bump)]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+
+    // OPTIONAL: THE SOURCE CREATOR'S LIST OF COUNTERS (the source is taken off it)
+    #[account(mut, seeds = [b"registry", source.creator.as_ref()], bump = registry.bump)]
+    pub registry: Option<Account<'info, UserRegistry>>,
+}
+
+// ACCOUNTS NEEDED TO TRANSFER COUNT BETWEEN TWO COUNTERS
+#[derive(Accounts)]
+pub struct TransferCount<'info> {
+    // THE COUNTER GIVING UP `amount`
+    #[account(
+        mut,
+        seeds = [b"counter", source.namespace_seed(), source.creator.as_ref(), source.label.as_bytes()],
+        bump = source.bump,
+        constraint = source.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = source.key() != destination.key() @ CounterError::SameCounter,
+        constraint = source.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub source: Account<'info, Counter>,
+
+    // THE COUNTER RECEIVING IT (any owner)
+    #[account(
+        mut,
+        seeds = [b"counter", destination.namespace_seed(), destination.creator.as_ref(), destination.label.as_bytes()],
+        bump = destination.bump,
+        constraint = destination.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub destination: Account<'info, Counter>,
+
+    // THE SOURCE'S OWNER
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // OPTIONAL: THE COUNTERS' CHANGE LOGS (each required once its counter has one)
+    #[account(mut, seeds = [b"history", source.key().as_ref()], bump)]
+    pub source_history: Option<AccountLoader<'info, CounterHistory>>,
+    #[account(mut, seeds = [b"history", destination.key().as_ref()], bump)]
+    pub destination_history: Option<AccountLoader<'info, CounterHistory>>,
+}
+
+// ACCOUNTS NEEDED TO ADD ONE COUNTER'S COUNT TO ANOTHER
+#[derive(Accounts)]
+pub struct AddFrom<'info> {
+    // THE COUNTER WHOSE COUNT IS ADDED (any owner, left unchanged)
+    #[account(
+        seeds = [b"counter", source.namespace_seed(), source.creator.as_ref(), source.label.as_bytes()],
+        bump = source.bump,
+        constraint = source.key() != destination.key() @ CounterError::SameCounter,
+        constraint = source.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub source: Account<'info, Counter>,
+
+    // THE COUNTER IT'S ADDED TO
+    #[account(
+        mut,
+        seeds = [b"counter", destination.namespace_seed(), destination.creator.as_ref(), destination.label.as_bytes()],
+        bump = destination.bump,
+        constraint = destination.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = destination.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub destination: Account<'info, Counter>,
+
+    // THE DESTINATION'S OWNER
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // OPTIONAL: THE DESTINATION'S CHANGE LOG (required once it has one)
+    #[account(mut, seeds = [b"history", destination.key().as_ref()], bump)]
+    pub destination_history: Option<AccountLoader<'info, CounterHistory>>,
+}
+
+// ACCOUNTS NEEDED TO SPLIT A COUNTER
+#[derive(Accounts)]
+#[instruction(amount: u64, new_label: String)]
+pub struct Split<'info> {
+    // THE COUNTER GIVING UP `amount`
+    #[account(
+        mut,
+        seeds = [b"counter", source.namespace_seed(), source.creator.as_ref(), source.label.as_bytes()],
+        bump = source.bump,
+        constraint = source.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = source.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub source: Account<'info, Counter>,
+
+    // THE NEW COUNTER - created by the owner, like `initialize` would
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Counter::INIT_SPACE,
+        seeds = [b"counter", user.key().as_ref(), new_label.as_bytes()],
+        bump
+    )]
+    pub new_counter: Account<'info, Counter>,
+
+    // THE OWNER (pays the new counter's rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (see `GlobalStats`)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // THE PROTOCOL TREASURY - collects the creation fee (see `set_fee_schedule`)
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: SystemAccount<'info>,
+
+    // OPTIONAL: THE SOURCE'S CHANGE LOG (required once it has one)
+    #[account(mut, seeds = [b"history", source.key().as_ref()], bump)]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+
+    // THE OWNER'S LIST OF COUNTERS (the new counter is added to it)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserRegistry::EMPTY_SPACE,
+        seeds = [b"registry", user.key().as_ref()],
+        bump
+    )]
+    pub registry: Account<'info, UserRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO RECLAIM A STALE COUNTER
+#[derive(Accounts)]
+pub struct ReclaimStale<'info> {
+    // THE IDLE COUNTER BEING CLEANED UP
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        has_one = payer,
+        has_one = authority,
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // WHO PAID THE RENT ORIGINALLY
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+
+    // THE COUNTER'S OWNER
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+
+    // WHOEVER IS RUNNING THE CLEANUP (gets the cranker share)
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (disabled instructions, stale timeout and refund split)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (see `GlobalStats`)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // OPTIONAL: THE CREATOR'S LIST OF COUNTERS (the counter is taken off it)
+    #[account(mut, seeds = [b"registry", counter.creator.as_ref()], bump = registry.bump)]
+    pub registry: Option<Account<'info, UserRegistry>>,
+}
+
+// ACCOUNTS NEEDED TO RECLAIM A SOFT-CLOSED COUNTER ONCE ITS GRACE PERIOD IS OVER
+#[derive(Accounts)]
+pub struct ReclaimClosed<'info> {
+    // THE SOFT-CLOSED COUNTER BEING CLEANED UP
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        has_one = authority,
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE COUNTER'S OWNER (gets everything but the cranker share)
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+
+    // WHOEVER IS RUNNING THE CLEANUP (gets the cranker share)
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (disabled instructions, grace period and refund split)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (see `GlobalStats`)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // OPTIONAL: THE CREATOR'S LIST OF COUNTERS (the counter is taken off it)
+    #[account(mut, seeds = [b"registry", counter.creator.as_ref()], bump = registry.bump)]
+    pub registry: Option<Account<'info, UserRegistry>>,
+}
+
+// ACCOUNTS NEEDED TO COLLECT AN EXPIRED COUNTER
+#[derive(Accounts)]
+pub struct CollectExpired<'info> {
+    // THE EXPIRED COUNTER BEING CLEANED UP
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        has_one = authority,
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE COUNTER'S OWNER (gets everything but the cranker share)
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+
+    // WHOEVER IS RUNNING THE CLEANUP (gets the cranker share)
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (disabled instructions and refund split)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (see `GlobalStats`)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // OPTIONAL: THE CREATOR'S LIST OF COUNTERS (the counter is taken off it)
+    #[account(mut, seeds = [b"registry", counter.creator.as_ref()], bump = registry.bump)]
+    pub registry: Option<Account<'info, UserRegistry>>,
+}
@@ -0,0 +1,69 @@
+// COMMIT-REVEAL
+// Sealed increments: a hash of the value goes in before the counter's window opens, the value
+// itself during the window (see `Commitment`)
+
+use crate::*;
+
+// ACCOUNTS NEEDED TO COMMIT TO A HIDDEN VALUE
+#[derive(Accounts)]
+pub struct Commit<'info> {
+    // THE COUNTER THE VALUE WILL BE ADDED TO (not changed yet)
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE COMMITMENT - one per (counter, user)
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Commitment::INIT_SPACE,
+        seeds = [b"commitment", counter.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub commitment: Account<'info, Commitment>,
+
+    // WHO COMMITS (pays the commitment's rent, refunded on reveal)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO REVEAL A COMMITTED VALUE AND ADD IT
+#[derive(Accounts)]
+pub struct Reveal<'info> {
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE USER'S COMMITMENT (deleted, rent refunded to them)
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"commitment", counter.key().as_ref(), user.key().as_ref()],
+        bump = commitment.bump
+    )]
+    pub commitment: Account<'info, Commitment>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // OPTIONAL: THE COUNTER'S CHANGE LOG (required once it has one)
+    #[account(mut, seeds = [b"history", counter.key().as_ref()], bump)]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+}
@@ -0,0 +1,556 @@
+// CREATING COUNTERS
+// `initialize` and its variants, cloning and templates
+
+use crate::*;
+
+// THE CHOICES A COUNTER IS CREATED WITH (the optional arguments of `initialize`)
+#[derive(Default)]
+pub(crate) struct CounterSettings {
+    pub(crate) min: Option<u64>,
+    pub(crate) max: Option<u64>,
+    pub(crate) is_public: bool,
+    pub(crate) cooldown_seconds: Option<i64>,
+    pub(crate) overflow_mode: Option<OverflowMode>,
+    pub(crate) expires_at: Option<i64>,
+    pub(crate) nft_gate: Option<Pubkey>,
+    pub(crate) step: Option<u64>,
+    pub(crate) window_start: Option<i64>,
+    pub(crate) window_end: Option<i64>,
+    pub(crate) recovery_authority: Option<Pubkey>,
+    pub(crate) inactivity_threshold: Option<i64>,
+}
+
+impl CounterSettings {
+    // FILL IN WHAT WASN'T PASSED FROM THE CREATOR'S DEFAULTS (see `set_defaults`)
+    // Anything passed explicitly wins, so the combination is checked by `create_counter` as usual
+    pub(crate) fn or_defaults(self, defaults: Option<&UserDefaults>) -> Self {
+        let Some(defaults) = defaults.map(|defaults| &defaults.params) else {
+            return self;
+        };
+        Self {
+            min: self.min.or(defaults.min),
+            max: self.max.or(defaults.max),
+            cooldown_seconds: self.cooldown_seconds.or(defaults.cooldown_seconds),
+            overflow_mode: self.overflow_mode.or(defaults.overflow_mode),
+            step: self.step.or(defaults.step),
+            ..self
+        }
+    }
+}
+
+// SET UP A FRESHLY CREATED COUNTER ACCOUNT
+// The shared body of `initialize`, `initialize_if_needed`, `split` and `clone_counter`. Returns the
+// `CounterInitialized` event for the caller to emit (which way depends on its accounts)
+pub(crate) fn create_counter(
+    counter: &mut Account<Counter>,
+    bump: u8,
+    user: Pubkey,
+    stats: &mut Account<GlobalStats>,
+    id: String,
+    settings: CounterSettings,
+) -> Result<CounterInitialized> {
+    require!(id.len() <= MAX_LABEL_LEN, CounterError::LabelTooLong);
+    let (min_count, max_count) = (settings.min.unwrap_or(0), settings.max.unwrap_or(u64::MAX));
+    require!(min_count <= max_count, CounterError::InvalidBounds);
+    let cooldown_seconds = settings.cooldown_seconds.unwrap_or(0);
+    require!(cooldown_seconds >= 0, CounterError::InvalidCooldown);
+    let step = settings.step.unwrap_or(1);
+    require!(step > 0, CounterError::InvalidStep);
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let expires_at = settings.expires_at.unwrap_or(0);
+    require!(expires_at == 0 || expires_at > now, CounterError::InvalidExpiry);
+    let (window_start, window_end) = (settings.window_start.unwrap_or(0), settings.window_end.unwrap_or(0));
+    require!(window_start >= 0 && window_end >= 0, CounterError::InvalidWindow);
+    require!(window_end == 0 || (window_end > window_start && window_end > now), CounterError::InvalidWindow);
+    let inactivity_threshold = settings.inactivity_threshold.unwrap_or(0);
+    require!(
+        settings.recovery_authority.is_some() == (inactivity_threshold > 0) && inactivity_threshold >= 0,
+        CounterError::InvalidRecovery
+    );
+
+    // Set up the counter with starting values
+    counter.count = min_count;            // Start counting from the minimum (zero by default)
+    counter.bump = bump;                  // Store the "bump" seed for finding this account later
+    counter.authority = user;             // Mark this user as the owner of this counter
+    counter.last_heartbeat = 0;           // No heartbeat yet, so the first one can be sent right away
+    counter.state = CounterState::Active; // New counters can be changed right away
+    counter.payer = user;                 // Who paid the rent (refunded when the counter is cleaned up)
+    counter.last_updated = now;
+    counter.total_ops = 0;                // No operations yet
+    counter.activity_day = 0;
+    counter.daily_ops = [0; ACTIVITY_WINDOW_DAYS];
+    counter.label = id;                   // Part of the address, so it can never change
+    counter.creator = user;               // Also part of the address - stays put when ownership moves
+    counter.pending_authority = None;     // No ownership transfer in progress
+    counter.min_count = min_count;
+    counter.max_count = max_count;
+    counter.is_public = settings.is_public;
+    counter.cooldown_seconds = cooldown_seconds;
+    counter.fee_lamports = 0;             // Free to increment until the owner sets a fee
+    counter.gate_mint = Pubkey::default(); // Not token-gated until the owner sets a gate
+    counter.min_balance = 0;
+    counter.has_history = false;          // No change log until the owner asks for one
+    counter.version = 0;                  // Goes up by one with every later change
+    counter.overflow_mode = settings.overflow_mode.unwrap_or(OverflowMode::Checked);
+    counter.milestone_interval = 0;       // No milestone events until the owner picks an interval
+    counter.expires_at = expires_at;      // 0 = lives until it is closed
+    counter.current_streak = 0;           // No increments yet, so no streak
+    counter.best_streak = 0;
+    counter.last_day = 0;
+    counter.daily_limit = 0;              // No daily cap until the owner sets one
+    counter.today_count = 0;
+    counter.day_stamp = 0;
+    counter.sequence = 0;                 // `CounterInitialized` is event 0, every later one counts up
+    counter.deposit_per_increment = 0;    // No vault deposits until the owner asks for them
+    counter.nft_gate = settings.nft_gate; // Fixed for the counter's lifetime
+    counter.layout_version = COUNTER_LAYOUT_VERSION;
+    counter.metadata_label = String::new(); // No display metadata until the owner sets some
+    counter.metadata_uri = String::new();
+    counter.reset_interval = 0;           // Never resets by itself until the owner sets an interval
+    counter.period_start = 0;
+    counter.last_period_count = 0;
+    counter.fee_mint = Pubkey::default(); // No token fee until the owner sets one
+    counter.fee_amount = 0;
+    counter.decay_rate_per_day = 0;       // Scores don't decay until the owner sets a rate
+    counter.last_decay_ts = 0;
+    counter.referrals = 0;
+    counter.close_requested_at = 0;       // No close pending
+    counter.step = step;
+    counter.last_nonce = 0;               // Any nonce above zero is accepted first
+    counter.keeper_tip_lamports = 0;      // Keepers aren't paid until the owner says so
+    counter.alert_threshold = 0;          // No alert until the owner sets one
+    counter.alert_direction = None;
+    counter.last_alert_state = false;
+    counter.hook_program = Pubkey::default(); // No hook
+    counter.group = Pubkey::default();        // Not in a group
+    counter.namespace = [0; PARTNER_ID_LEN];  // No partner namespace (see `initialize_namespaced`)
+    counter.window_start = window_start;      // Fixed for the counter's lifetime
+    counter.window_end = window_end;
+    counter.weight_mint = Pubkey::default(); // No weighted increments (see `set_weight_config`)
+    counter.weight_divisor = 0;
+    counter.feed_authority = None;        // No feed (see `set_feed_authority`)
+    counter.last_observed_at = 0;
+    counter.sum_count_x_seconds = 0;      // The time-weighted average starts now
+    counter.last_update_ts = now;
+    counter.twa_started_at = now;
+    counter.closed_at = 0;                // Not soft-closed
+    counter.recovery_authority = settings.recovery_authority; // Fixed for the counter's lifetime
+    counter.inactivity_threshold = inactivity_threshold;
+    counter.recovery_claimed_at = 0;      // No claim pending
+    counter.recovery_vetoed_at = 0;
+    counter.open_wagers = 0;              // Not in a wager
+    counter.wager_deadline = 0;
+    counter.created_at = now;
+
+    // Count it in the program-wide totals
+    stats.total_counters = stats.total_counters.saturating_add(1);
+    stats.announce();
+
+    // Log a message that will appear in blockchain explorers
+    verbose_msg!("Counter initialized for user: {} with count: {}", user, counter.count);
+
+    // The announcement of this new counter (like announcing a new account)
+    Ok(versioned!(CounterInitialized {
+        user,
+        counter: counter.key(),
+        count: counter.count,
+        label: counter.label.clone(),
+        slot: clock.slot,
+        timestamp: now,
+        sequence: counter.sequence,
+        sponsor: counter.payer,
+    }))
+}
+
+// CHARGE THE PROTOCOL'S COUNTER CREATION FEE (see `set_fee_schedule`)
+// Paid in SOL by whoever pays the counter's rent into the treasury, like the per-operation fee
+pub(crate) fn collect_init_fee<'info>(
+    config: &ProgramConfig,
+    payer: &Signer<'info>,
+    treasury: &SystemAccount<'info>,
+    system_program: &Program<'info, System>,
+    counter: Pubkey,
+) -> Result<()> {
+    let amount = config.init_fee_lamports;
+    if amount == 0 {
+        return Ok(());
+    }
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: payer.to_account_info(),
+                to: treasury.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    emit_event!(FeePaid {
+        payer: payer.key(),
+        counter,
+        mint: Pubkey::default(), // SOL
+        amount,
+    });
+    Ok(())
+}
+
+// LIST A NEW COUNTER IN ITS CREATOR'S REGISTRY
+// The registry is created empty by the accounts struct on the creator's first counter and
+// grows by one entry at a time here, with `payer` (the creator, or their sponsor) paying the extra
+// rent. Enforces the config's `max_counters_per_user`
+pub(crate) fn register_counter<'info>(
+    registry: &mut Account<'info, UserRegistry>,
+    bump: u8,
+    user: Pubkey,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    config: &ProgramConfig,
+    counter: Pubkey,
+    label: &str,
+) -> Result<()> {
+    require!(
+        config.max_counters_per_user == 0 || registry.counters.len() < config.max_counters_per_user as usize,
+        CounterError::TooManyCounters
+    );
+    if registry.user == Pubkey::default() {
+        registry.user = user; // Freshly created
+        registry.bump = bump;
+    }
+    registry.counters.push(RegistryEntry { counter, label: label.to_string() });
+
+    // GROW THE ACCOUNT IF THE NEW ENTRY DOESN'T FIT (freed slots are reused, so it never shrinks)
+    let info = registry.to_account_info();
+    let needed = registry.space();
+    if needed > info.data_len() {
+        let rent = Rent::get()?.minimum_balance(needed).saturating_sub(info.lamports());
+        if rent > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer { from: payer.to_account_info(), to: info.clone() },
+                ),
+                rent,
+            )?;
+        }
+        info.resize(needed)?;
+        require_rent_exempt(&info)?;
+    }
+    Ok(())
+}
+
+// TAKE A CLOSED COUNTER OUT OF ITS CREATOR'S REGISTRY (when the registry was passed in)
+pub(crate) fn unregister_counter(registry: Option<&mut Account<UserRegistry>>, counter: Pubkey) {
+    if let Some(registry) = registry {
+        registry.counters.retain(|entry| entry.counter != counter);
+    }
+}
+
+// ACCOUNTS NEEDED TO DROP COUNTERS THAT ARE GONE FROM A REGISTRY (see `prune_registry`)
+#[derive(Accounts)]
+pub struct PruneRegistry<'info> {
+    // THE CREATOR'S LIST OF COUNTERS
+    #[account(mut, seeds = [b"registry", user.key().as_ref()], bump = registry.bump)]
+    pub registry: Account<'info, UserRegistry>,
+
+    // THE CREATOR
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// =====================================================================================
+// ACCOUNTS NEEDED TO CREATE A NEW COUNTER
+// This tells Solana what accounts are required when someone calls the "initialize" function
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct Initialize<'info> {
+    // THE NEW COUNTER ACCOUNT WE'RE CREATING
+    // This account will store the counter data (count, owner, etc.)
+    #[account(
+        init,                    // Create this account for the first time
+        payer = payer,          // The payer covers creating this account (like rent deposit)
+        space = 8 + Counter::INIT_SPACE, // How much storage space to allocate (8 bytes for Anchor + counter data)
+        seeds = [b"counter", user.key().as_ref(), id.as_bytes()], // How to find this account later (deterministic address)
+        bump                     // A number that makes the address unique
+    )]
+    pub counter: Account<'info, Counter>,  // The actual counter account
+
+    // THE PERSON CREATING THE COUNTER (becomes its owner)
+    pub user: Signer<'info>,   // Must be signed by the user (they approve this action)
+
+    // WHO PAYS THE RENT AND THE CREATION FEE
+    // Usually the user themselves; a dApp sponsoring its users' counters signs here instead, and
+    // gets the rent back when the counter is closed (see `Counter.payer`)
+    #[account(mut)]             // This account can be changed (to pay for the new account)
+    pub payer: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (see `GlobalStats`)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // THE PROTOCOL TREASURY - collects the creation fee (see `set_fee_schedule`)
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: SystemAccount<'info>,
+
+    // THE CREATOR'S LIST OF COUNTERS (created with their first counter, grown by the handler)
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserRegistry::EMPTY_SPACE,
+        seeds = [b"registry", user.key().as_ref()],
+        bump
+    )]
+    pub registry: Account<'info, UserRegistry>,
+
+    // OPTIONAL: THE CREATOR'S DEFAULT SETTINGS (fill in the arguments left out, see `set_defaults`)
+    #[account(seeds = [b"defaults", user.key().as_ref()], bump = defaults.bump)]
+    pub defaults: Option<Account<'info, UserDefaults>>,
+
+    // SOLANA'S SYSTEM PROGRAM
+    // This is like the "bank" that handles account creation and rent
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO CREATE A COUNTER THAT MAY ALREADY EXIST
+// The same as `Initialize`, except that the counter is only created when it isn't there yet
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct InitializeIfNeeded<'info> {
+    #[account(
+        init_if_needed,          // Loads the existing counter instead of failing
+        payer = user,
+        space = 8 + Counter::INIT_SPACE,
+        seeds = [b"counter", user.key().as_ref(), id.as_bytes()],
+        bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (see `GlobalStats`)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // THE PROTOCOL TREASURY - collects the creation fee (see `set_fee_schedule`)
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: SystemAccount<'info>,
+
+    // THE CREATOR'S LIST OF COUNTERS (only written when the counter is created)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserRegistry::EMPTY_SPACE,
+        seeds = [b"registry", user.key().as_ref()],
+        bump
+    )]
+    pub registry: Account<'info, UserRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO CREATE A COUNTER IN A PARTNER'S NAMESPACE
+// The same as `Initialize`, except the approved `partner_id` is part of the counter's seeds
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(partner_id: [u8; PARTNER_ID_LEN], id: String)]
+pub struct InitializeNamespaced<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Counter::INIT_SPACE,
+        seeds = [b"counter", partner_id.as_ref(), user.key().as_ref(), id.as_bytes()],
+        bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (holds the approved partner namespaces)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (see `GlobalStats`)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // THE PROTOCOL TREASURY - collects the creation fee (see `set_fee_schedule`)
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: SystemAccount<'info>,
+
+    // THE CREATOR'S LIST OF COUNTERS (created with their first counter, grown by the handler)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserRegistry::EMPTY_SPACE,
+        seeds = [b"registry", user.key().as_ref()],
+        bump
+    )]
+    pub registry: Account<'info, UserRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO CLONE A COUNTER'S SETUP
+#[derive(Accounts)]
+#[instruction(new_label: String)]
+pub struct CloneCounter<'info> {
+    // THE COUNTER WHOSE SETTINGS ARE COPIED (left unchanged)
+    #[account(
+        seeds = [b"counter", source.namespace_seed(), source.creator.as_ref(), source.label.as_bytes()],
+        bump = source.bump,
+        constraint = source.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = source.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub source: Account<'info, Counter>,
+
+    // THE NEW COUNTER - created by the owner, like `initialize` would
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Counter::INIT_SPACE,
+        seeds = [b"counter", user.key().as_ref(), new_label.as_bytes()],
+        bump
+    )]
+    pub new_counter: Account<'info, Counter>,
+
+    // THE OWNER (pays the new counter's rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (see `GlobalStats`)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // THE PROTOCOL TREASURY - collects the creation fee (see `set_fee_schedule`)
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: SystemAccount<'info>,
+
+    // THE OWNER'S LIST OF COUNTERS (the new counter is added to it)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserRegistry::EMPTY_SPACE,
+        seeds = [b"registry", user.key().as_ref()],
+        bump
+    )]
+    pub registry: Account<'info, UserRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO SAVE A COUNTER TEMPLATE
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateTemplate<'info> {
+    // THE NEW TEMPLATE (one per creator and name)
+    #[account(
+        init,
+        payer = user,
+        space = 8 + CounterTemplate::INIT_SPACE,
+        seeds = [b"template", user.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub template: Account<'info, CounterTemplate>,
+
+    // THE TEMPLATE'S CREATOR (pays its rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO CREATE A COUNTER FROM A TEMPLATE
+// The same as `Initialize`, plus the template
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct InitializeFromTemplate<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Counter::INIT_SPACE,
+        seeds = [b"counter", user.key().as_ref(), id.as_bytes()],
+        bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE TEMPLATE WHOSE SETTINGS THE COUNTER GETS (anyone's)
+    #[account(seeds = [b"template", template.authority.as_ref(), template.name.as_bytes()], bump = template.bump)]
+    pub template: Account<'info, CounterTemplate>,
+
+    // THE PERSON CREATING THE COUNTER (pays its rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (see `GlobalStats`)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // THE PROTOCOL TREASURY - collects the creation fee (see `set_fee_schedule`)
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: SystemAccount<'info>,
+
+    // THE CREATOR'S LIST OF COUNTERS (created with their first counter, grown by the handler)
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserRegistry::EMPTY_SPACE,
+        seeds = [b"registry", user.key().as_ref()],
+        bump
+    )]
+    pub registry: Account<'info, UserRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO SAVE A WALLET'S DEFAULT COUNTER SETTINGS
+#[derive(Accounts)]
+pub struct SetDefaults<'info> {
+    // THE WALLET'S DEFAULTS (one per wallet, created the first time they're set)
+    #[account(
+        init_if_needed,          // Setting them again just overwrites them
+        payer = user,
+        space = 8 + UserDefaults::INIT_SPACE,
+        seeds = [b"defaults", user.key().as_ref()],
+        bump
+    )]
+    pub defaults: Account<'info, UserDefaults>,
+
+    // THE WALLET THE DEFAULTS ARE FOR (pays their rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
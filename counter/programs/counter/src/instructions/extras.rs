@@ -0,0 +1,372 @@
+// EVERYTHING ELSE ABOUT ONE COUNTER
+// Leaderboards, sequencers, short IDs, health checks, views, activity proofs, snapshots and rent
+
+use crate::*;
+
+// MAKE SURE AN ACCOUNT STILL HOLDS ENOUGH LAMPORTS FOR ITS SIZE
+// Checked after every resize, so an account can never be left for the runtime to reject
+pub(crate) fn require_rent_exempt(info: &AccountInfo) -> Result<()> {
+    require!(Rent::get()?.is_exempt(info.lamports(), info.data_len()), CounterError::NotRentExempt);
+    Ok(())
+}
+
+// WHO AN ACCOUNT OF THIS PROGRAM BELONGS TO, READ FROM ITS OWN DATA (see `top_up`)
+// None for the shared accounts (config, stats, leaderboard...) and the zero-copy ones
+pub(crate) fn authority_of(data: &[u8]) -> Option<Pubkey> {
+    macro_rules! authority {
+        ($($account:ty => $field:ident),* $(,)?) => {
+            $(
+                if data.starts_with(<$account>::DISCRIMINATOR) {
+                    return <$account>::try_deserialize(&mut &data[..]).ok().map(|account| account.$field);
+                }
+            )*
+        };
+    }
+    authority!(
+        Counter => authority,
+        UserRegistry => user,
+        SignedCounter => authority,
+        BigCounter => authority,
+        CounterGroup => authority,
+        CounterTemplate => authority,
+        CounterSnapshot => authority,
+        Sequencer => authority,
+        Delegation => authority,
+        Session => authority,
+        Contributor => authority,
+        Role => authority,
+        ShortId => owner,
+    );
+    None
+}
+
+// ACCOUNTS NEEDED TO SUBMIT A COUNTER TO THE LEADERBOARD
+#[derive(Accounts)]
+pub struct SubmitScore<'info> {
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE LEADERBOARD - one for the whole program
+    #[account(
+        init_if_needed,          // The first submission creates it
+        payer = user,
+        space = 8 + Leaderboard::INIT_SPACE,
+        seeds = [b"leaderboard"],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    // THE OWNER (pays the leaderboard's rent if they are the first to submit)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO CREATE A SEQUENCER
+#[derive(Accounts)]
+pub struct InitializeSequencer<'info> {
+    // ONE SEQUENCER PER WALLET
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Sequencer::INIT_SPACE,
+        seeds = [b"sequencer", user.key().as_ref()],
+        bump
+    )]
+    pub sequencer: Account<'info, Sequencer>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO CLAIM A SHORT ID
+#[derive(Accounts)]
+#[instruction(code: [u8; SHORT_ID_LEN])]
+pub struct ClaimShortId<'info> {
+    // THE NEW SHORT ID ACCOUNT
+    // Its address is derived from the code itself, so each code can only ever exist once
+    #[account(
+        init,                    // Fails if someone already claimed this code
+        payer = user,           // The claimer pays rent (the deposit is added on top)
+        space = 8 + ShortId::INIT_SPACE,
+        seeds = [b"short_id", code.as_ref()], // Look up a code by deriving this address
+        bump
+    )]
+    pub short_id: Account<'info, ShortId>,
+
+    // THE COUNTER THE SHORT ID WILL POINT TO
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // You can only name your own counter
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE PERSON CLAIMING THE SHORT ID
+    #[account(mut)]             // Pays rent and the deposit
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO RELEASE A SHORT ID
+#[derive(Accounts)]
+pub struct ReleaseShortId<'info> {
+    // THE SHORT ID BEING RELEASED
+    #[account(
+        mut,
+        close = user,           // Refund rent + deposit to the owner
+        seeds = [b"short_id", short_id.code.as_ref()],
+        bump = short_id.bump,
+        constraint = short_id.owner == user.key() @ CounterError::Unauthorized // Only the owner can release it
+    )]
+    pub short_id: Account<'info, ShortId>,
+
+    // THE OWNER OF THE SHORT ID
+    #[account(mut)]             // Receives the refund
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO VERIFY A COUNTER'S INVARIANTS
+#[derive(Accounts)]
+pub struct VerifyInvariants<'info> {
+    // THE COUNTER BEING CHECKED
+    // No seeds constraint on purpose - re-deriving the address is one of the checks
+    pub counter: Account<'info, Counter>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO SEND A HEARTBEAT
+#[derive(Accounts)]
+pub struct Heartbeat<'info> {
+    // THE COUNTER WE'RE ANNOUNCING
+    // Found through its stored owner, since whoever sends the heartbeat may not be the owner
+    #[account(
+        mut,                     // Only `last_heartbeat` is written
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO SETTLE A COUNTER'S DECAY
+#[derive(Accounts)]
+pub struct ApplyDecay<'info> {
+    // THE DECAYING COUNTER
+    // Found through its stored creator, since whoever cranks it may not be the owner
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO RUN A COUNTER'S UPKEEP (see `touch`)
+#[derive(Accounts)]
+pub struct Touch<'info> {
+    // THE COUNTER BEING KEPT UP TO DATE
+    // Found through its stored creator, since the keeper usually isn't the owner
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE COUNTER'S VAULT, WHICH PAYS THE KEEPER'S TIP
+    #[account(mut, seeds = [b"vault", counter.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+
+    // WHOEVER RAN THE UPKEEP, WHO RECEIVES THE TIP
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Touch<'info> {
+    // PAY THE KEEPER THE COUNTER'S TIP OUT OF ITS VAULT
+    // Never more than the vault holds above its rent-exempt minimum, so the vault stays open
+    // (an empty or unfunded vault pays nothing). Returns what was paid
+    pub fn pay_keeper_tip(&self, vault_bump: u8) -> Result<u64> {
+        let spare = self.vault.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+        let tip = self.counter.keeper_tip_lamports.min(spare);
+        if tip == 0 {
+            return Ok(0);
+        }
+
+        let counter_key = self.counter.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[seeds::VAULT, counter_key.as_ref(), &[vault_bump]]];
+        anchor_lang::system_program::transfer(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: self.vault.to_account_info(),
+                    to: self.keeper.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            tip,
+        )?;
+        Ok(tip)
+    }
+}
+
+// ACCOUNTS NEEDED TO READ A COUNTER
+#[derive(Accounts)]
+pub struct ViewCount<'info> {
+    // THE COUNTER BEING READ (read only)
+    #[account(seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()], bump = counter.bump, constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion)]
+    pub counter: Account<'info, Counter>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO PROVE A COUNTER'S RECENT ACTIVITY
+#[derive(Accounts)]
+#[instruction(min_ops: u32, window_days: u8)]
+pub struct ProveActivity<'info> {
+    // THE COUNTER WHOSE ACTIVITY IS BEING PROVEN (read only)
+    #[account(seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()], bump = counter.bump, constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion)]
+    pub counter: Account<'info, Counter>,
+
+    // THE RECEIPT - one per (counter, criteria), so verifiers can derive its address
+    #[account(
+        init_if_needed,          // Proving again just refreshes the receipt
+        payer = prover,
+        space = 8 + ActivityReceipt::INIT_SPACE,
+        seeds = [b"activity", counter.key().as_ref(), &min_ops.to_le_bytes(), &[window_days]],
+        bump
+    )]
+    pub receipt: Account<'info, ActivityReceipt>,
+
+    // WHOEVER ASKS FOR THE PROOF (pays the receipt's rent)
+    #[account(mut)]
+    pub prover: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO TAKE A SNAPSHOT OF A COUNTER
+#[derive(Accounts)]
+#[instruction(label: u64)]
+pub struct TakeSnapshot<'info> {
+    // THE COUNTER BEING RECORDED (read only)
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE SNAPSHOT - one per (counter, label), so readers can derive its address
+    #[account(
+        init,
+        payer = user,
+        space = 8 + CounterSnapshot::INIT_SPACE,
+        seeds = [b"snapshot", counter.key().as_ref(), &label.to_le_bytes()],
+        bump
+    )]
+    pub snapshot: Account<'info, CounterSnapshot>,
+
+    // THE OWNER (pays the snapshot's rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO DELETE A SNAPSHOT
+#[derive(Accounts)]
+pub struct CloseSnapshot<'info> {
+    // THE SNAPSHOT BEING REMOVED (deleted, rent refunded to whoever took it)
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"snapshot", snapshot.counter.as_ref(), &snapshot.label.to_le_bytes()],
+        bump = snapshot.bump,
+        constraint = snapshot.authority == user.key() @ CounterError::Unauthorized
+    )]
+    pub snapshot: Account<'info, CounterSnapshot>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO ADD LAMPORTS TO AN ACCOUNT
+#[derive(Accounts)]
+pub struct TopUp<'info> {
+    // THE ACCOUNT BEING TOPPED UP (any of this program's, as long as `user` is its authority)
+    /// CHECK: owner checked here, authority by `authority_of` in the handler
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+
+    // ITS AUTHORITY (pays)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
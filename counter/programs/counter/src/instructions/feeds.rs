@@ -0,0 +1,29 @@
+// ORACLE FEEDS
+// Counters that mirror an off-chain metric, set by a reporter the owner trusts (see
+// `set_feed_authority`)
+
+use crate::*;
+
+// ACCOUNTS NEEDED TO PUSH A VALUE TO A COUNTER
+#[derive(Accounts)]
+pub struct PushValue<'info> {
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.feed_authority == Some(feed_authority.key()) @ CounterError::NotFeedAuthority,
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE COUNTER'S FEED AUTHORITY
+    pub feed_authority: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // OPTIONAL: THE COUNTER'S CHANGE LOG (required once it has one)
+    #[account(mut, seeds = [b"history", counter.key().as_ref()], bump)]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+}
@@ -0,0 +1,155 @@
+// OWNER FEES AND VAULTS
+// Collecting what a counter earned and emptying its vault
+
+use crate::*;
+
+// ACCOUNTS NEEDED TO WITHDRAW A COUNTER'S INCREMENT FEES
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(mut, seeds = [b"counter_treasury", counter.key().as_ref()], bump)]
+    pub counter_treasury: SystemAccount<'info>,
+
+    // THE OWNER, WHO RECEIVES THE WITHDRAWAL
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO SET A COUNTER'S TOKEN FEE
+#[derive(Accounts)]
+pub struct SetTokenFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE TOKEN THE FEE IS PAID IN
+    pub fee_mint: Account<'info, Mint>,
+
+    #[account(seeds = [b"counter_treasury", counter.key().as_ref()], bump)]
+    pub counter_treasury: SystemAccount<'info>,
+
+    // THE FEE VAULT - the treasury PDA's associated token account for `fee_mint`
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = fee_mint,
+        associated_token::authority = counter_treasury
+    )]
+    pub fee_vault: Account<'info, TokenAccount>,
+
+    // THE OWNER (pays the vault's rent the first time)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO COLLECT A COUNTER'S TOKEN FEES
+#[derive(Accounts)]
+pub struct CollectTokenFees<'info> {
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(seeds = [b"counter_treasury", counter.key().as_ref()], bump)]
+    pub counter_treasury: SystemAccount<'info>,
+
+    #[account(mut, associated_token::mint = counter.fee_mint, associated_token::authority = counter_treasury)]
+    pub fee_vault: Account<'info, TokenAccount>,
+
+    // WHERE THE FEES GO (any token account for the fee mint)
+    #[account(mut, token::mint = counter.fee_mint)]
+    pub destination_token_account: Account<'info, TokenAccount>,
+
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// ACCOUNTS NEEDED TO EMPTY A COUNTER'S VAULT
+#[derive(Accounts)]
+pub struct WithdrawVault<'info> {
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    #[account(mut, seeds = [b"vault", counter.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+
+    // THE OWNER, WHO RECEIVES THE WITHDRAWAL
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO DRAIN A COUNTER
+#[derive(Accounts)]
+pub struct Drain<'info> {
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // OPTIONAL: THE COUNTER'S VAULT (pass it in to get the drained count's deposits back)
+    #[account(mut, seeds = [b"vault", counter.key().as_ref()], bump)]
+    pub vault: Option<SystemAccount<'info>>,
+
+    // OPTIONAL: THE COUNTER'S CHANGE LOG (required once it has one)
+    #[account(mut, seeds = [b"history", counter.key().as_ref()], bump)]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+
+    // THE OWNER, WHO RECEIVES ANY VAULT REFUND
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
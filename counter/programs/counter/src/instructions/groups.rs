@@ -0,0 +1,86 @@
+// COUNTER GROUPS
+// Creating groups and moving counters in and out of them (see `CounterGroup`)
+
+use crate::*;
+
+// ACCOUNTS NEEDED TO CREATE A COUNTER GROUP
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateGroup<'info> {
+    // THE NEW GROUP (one per creator and name)
+    #[account(
+        init,
+        payer = user,
+        space = 8 + CounterGroup::INIT_SPACE,
+        seeds = [b"group", user.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub group: Account<'info, CounterGroup>,
+
+    // THE GROUP'S CREATOR (pays its rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO ADD A COUNTER TO A GROUP
+// Both the counter's owner and the group's authority sign (they may be the same wallet)
+#[derive(Accounts)]
+pub struct JoinGroup<'info> {
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"group", group.authority.as_ref(), group.name.as_bytes()],
+        bump = group.bump,
+        has_one = authority @ CounterError::Unauthorized,
+    )]
+    pub group: Account<'info, CounterGroup>,
+
+    pub authority: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO TAKE A COUNTER OUT OF ITS GROUP (owner only)
+#[derive(Accounts)]
+pub struct LeaveGroup<'info> {
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"group", group.authority.as_ref(), group.name.as_bytes()],
+        bump = group.bump,
+        constraint = counter.group == group.key() @ CounterError::NotInGroup,
+    )]
+    pub group: Account<'info, CounterGroup>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
@@ -0,0 +1,186 @@
+// MANAGING A COUNTER
+// Owner settings, display metadata, authority transfers and migration
+
+use crate::*;
+
+// CHANGE A COUNTER'S STATE IF THE LIFECYCLE RULES ALLOW IT, AND ANNOUNCE IT
+pub(crate) fn apply_state_transition(counter: &mut Account<Counter>, target: CounterState) -> Result<()> {
+    let from = counter.state;
+    require!(from.can_transition_to(target), CounterError::InvalidStateTransition);
+    counter.state = target;
+    counter.next_version();
+
+    emit_event!(CounterStateChanged {
+        counter: counter.key(),
+        authority: counter.authority,
+        from,
+        to: target,
+    });
+
+    // Freezing and unfreezing also get their own announcements (see `freeze` / `unfreeze`)
+    if target == CounterState::Frozen {
+        emit_event!(CounterFrozen { counter: counter.key(), authority: counter.authority });
+    } else if from == CounterState::Frozen && target == CounterState::Active {
+        emit_event!(CounterUnfrozen { counter: counter.key(), authority: counter.authority });
+    }
+
+    verbose_msg!("Counter {} moved from {:?} to {:?}", counter.key(), from, target);
+    Ok(())
+}
+
+// ACCOUNTS NEEDED FOR OWNER-ONLY COUNTER SETTINGS (e.g. lifecycle changes)
+#[derive(Accounts)]
+pub struct ManageCounter<'info> {
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // OPTIONAL: THE CALLER'S SEQUENCER (required when `expected_seq` is set)
+    #[account(mut, seeds = [b"sequencer", user.key().as_ref()], bump = sequencer.bump)]
+    pub sequencer: Option<Account<'info, Sequencer>>,
+}
+
+// ACCOUNTS NEEDED TO FINALIZE A COUNTER (anyone may, once its window has closed)
+#[derive(Accounts)]
+pub struct Finalize<'info> {
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO SET A COUNTER'S DISPLAY METADATA
+#[derive(Accounts)]
+#[instruction(label: String, uri: String)]
+pub struct SetMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+        realloc = Counter::space_for_metadata(&label, &uri), // Exactly what the new strings need
+        realloc::payer = user,
+        realloc::zero = false,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE OWNER, WHO PAYS FOR (OR IS REFUNDED) THE CHANGE IN SIZE
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO REMOVE A COUNTER'S DISPLAY METADATA
+#[derive(Accounts)]
+pub struct ClearMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+        realloc = Counter::space_for_metadata("", ""), // Back to the bare size
+        realloc::payer = user,
+        realloc::zero = false,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE OWNER, WHO GETS THE FREED RENT BACK
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO ACCEPT A COUNTER'S OWNERSHIP
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.pending_authority == Some(new_authority.key()) @ CounterError::NotPendingAuthority,
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE PROPOSED OWNER - must sign to accept
+    pub new_authority: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO CLAIM A QUIET COUNTER, OR COMPLETE THE CLAIM (recovery authority only)
+#[derive(Accounts)]
+pub struct ClaimAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.recovery_authority == Some(recovery_authority.key()) @ CounterError::NotRecoveryAuthority,
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE WALLET NAMED AT CREATION TO RECOVER THE COUNTER
+    pub recovery_authority: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO MIGRATE A COUNTER TO THE CURRENT LAYOUT
+#[derive(Accounts)]
+#[instruction(creator: Pubkey, id: String)]
+pub struct Migrate<'info> {
+    // THE COUNTER BEING MIGRATED
+    // Not an `Account<Counter>`, which couldn't load an old, shorter layout
+    /// CHECK: owned by this program; the address (from `creator`, `id` and the partner
+    /// namespace stored in the account), discriminator and authority are checked by `migrate`
+    /// once the account has grown
+    #[account(mut, owner = crate::ID)]
+    pub counter: UncheckedAccount<'info>,
+
+    // THE OWNER, WHO PAYS THE RENT FOR THE EXTRA SPACE
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
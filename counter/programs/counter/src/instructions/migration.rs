@@ -0,0 +1,40 @@
+// CROSS-PROGRAM MIGRATION
+// Handing a counter over to another version of the program (`export`) and taking one in from
+// another version (`import`), both with the same `ExportedCounter` snapshot
+
+use crate::*;
+
+// ACCOUNTS NEEDED TO RECREATE A COUNTER FROM ANOTHER PROGRAM'S SNAPSHOT
+// Only the admin-approved `migration_authority` can import, since the snapshot is taken on its word
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(snapshot: ExportedCounter)]
+pub struct ImportCounter<'info> {
+    // THE RECREATED COUNTER - at the same address it would have if it had been created here
+    #[account(
+        init,
+        payer = migration_authority,
+        space = 8 + Counter::INIT_SPACE,
+        seeds = [b"counter", snapshot.namespace_seed(), snapshot.creator.as_ref(), snapshot.label.as_bytes()],
+        bump
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // WHOEVER RUNS THE MIGRATION (pays the new counter's rent)
+    #[account(mut)]
+    pub migration_authority: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (disabled instructions and the approved migration authority)
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = migration_authority @ CounterError::NotMigrationAuthority,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (see `GlobalStats`)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,32 @@
+// INSTRUCTIONS - The accounts each instruction needs
+// Grouped by what the instructions do; the handlers themselves are in the `#[program]` module,
+// which calls into the shared bodies here (e.g. `update_count`) for the common paths
+pub mod access;
+pub mod admin;
+pub mod close;
+pub mod commitments;
+pub mod create;
+pub mod extras;
+pub mod feeds;
+pub mod fees;
+pub mod groups;
+pub mod manage;
+pub mod migration;
+pub mod update;
+pub mod variants;
+pub mod wagers;
+
+pub use access::*;
+pub use admin::*;
+pub use close::*;
+pub use commitments::*;
+pub use create::*;
+pub use extras::*;
+pub use feeds::*;
+pub use fees::*;
+pub use groups::*;
+pub use manage::*;
+pub use migration::*;
+pub use update::*;
+pub use variants::*;
+pub use wagers::*;
@@ -0,0 +1,1043 @@
+// CHANGING THE COUNT
+// The `Update` accounts every change goes through, and the shared handler bodies
+
+use crate::*;
+
+// CHECK AND BUMP THE CALLER'S SEQUENCE NUMBER
+// - `expected_seq` given: the sequencer must be passed in and hold exactly that number
+// - sequencer passed in: its number goes up by one (unless this is a dry run)
+pub(crate) fn advance_sequencer(
+    sequencer: Option<&mut Account<Sequencer>>,
+    expected_seq: Option<u64>,
+    dry_run: bool,
+) -> Result<()> {
+    let Some(sequencer) = sequencer else {
+        require!(expected_seq.is_none(), CounterError::SequencerRequired);
+        return Ok(());
+    };
+
+    if let Some(expected) = expected_seq {
+        require!(sequencer.seq == expected, CounterError::SequenceMismatch);
+    }
+    if !dry_run {
+        sequencer.seq = sequencer.seq.checked_add(1).ok_or(CounterError::CounterOverflow)?;
+    }
+    Ok(())
+}
+
+// CHANGE A COUNTER'S VALUE
+// The shared body of every instruction that changes the count. `id` is the counter's label
+// and `op` says what kind of change it is (`Increment`, `Decrement`, `Set` or `Reset`):
+// `amount` is how much to add/subtract, or the new value for `Set` (ignored for `Reset`,
+// which goes back to the counter's minimum). `memo` is passed on in `CounterUpdated`
+pub(crate) fn update_count(
+    ctx: Context<Update>,
+    id: &str,
+    op: PolicyOp,
+    amount: u64,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+    memo: String,
+) -> Result<()> {
+    update_count_weighted(ctx, id, op, amount, dry_run, expected_seq, memo, 0)
+}
+
+// `update_count`, with the `weight` an `increment_weighted` added recorded in `CounterUpdated`
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn update_count_weighted(
+    ctx: Context<Update>,
+    id: &str,
+    op: PolicyOp,
+    amount: u64,
+    dry_run: bool,
+    expected_seq: Option<u64>,
+    memo: String,
+    weight: u64,
+) -> Result<()> {
+    let counter = &ctx.accounts.counter;  // The counter account we're updating
+    let user = &ctx.accounts.user;       // The person trying to change it
+    let stored_count = counter.count; // What the counter's group total holds for it
+    let clock = Clock::get()?;
+    let mut policy = PolicyContext::at(counter, user.key(), op, clock.unix_timestamp);
+    policy.delegation = ctx.accounts.delegation.as_deref();
+    policy.session = ctx.accounts.session.as_deref();
+    policy.contributor = ctx.accounts.contributor.as_deref();
+    policy.role = ctx.accounts.role.as_deref();
+    policy.gate_balance = ctx.accounts.gate_token_account.as_ref().map(|account| account.amount);
+    policy.held_collection = ctx.accounts.held_collection();
+
+    // A LAPSED RESET PERIOD STARTS OVER BEFORE THE OPERATION (see `set_reset_interval`)
+    // (and any decay owed is taken off first, see `set_decay_rate`)
+    let new_period = counter.lapsed_period(clock.unix_timestamp);
+    let decay = counter.pending_decay(clock.unix_timestamp);
+    let previous_count = match (new_period, decay) {
+        (Some(_), _) => counter.min_count,
+        (None, Some((decayed, _))) => decayed,
+        (None, None) => counter.count,
+    }; // Remember what it was before
+
+    // WORK OUT THE NEW VALUE BUT CHECK THE LIMITS
+    // What happens at the edges depends on the counter's `OverflowMode`; by default we can't go
+    // above the biggest u64 or below zero, so we check before changing anything
+    let (count_op, operation) = match op {
+        PolicyOp::Increment => (CountOp::Increment(amount), CounterOperation::Increment),
+        PolicyOp::Decrement => (CountOp::Decrement(amount), CounterOperation::Decrement),
+        PolicyOp::Set => (CountOp::Set(amount), CounterOperation::Set),
+        PolicyOp::Reset => (CountOp::Reset, CounterOperation::Reset),
+        PolicyOp::Close | PolicyOp::TransitionState => unreachable!("not a change to the count"),
+    };
+    let explain = |error| explain_limit_error(error, counter.key(), counter, operation, amount, previous_count, None);
+    let new_count = counter.rules().apply(previous_count, count_op).map_err(explain)?;
+
+    // POLICY CHECKS: owner, lifecycle, rate and bounds (see `CounterPolicy`)
+    ActivePolicy::validate(&policy, new_count).map_err(explain)?;
+    require!(!counter.has_history || ctx.accounts.history.is_some(), CounterError::HistoryRequired);
+    ctx.accounts.require_hook()?;
+    ctx.accounts.require_group()?;
+    let fee_lamports = ActivePolicy::check_fee(&policy, &ctx.accounts.config)?;
+    let counter_fee_lamports = ActivePolicy::check_counter_fee(&policy, amount)?;
+    let deposit_lamports = if op == PolicyOp::Increment { counter.deposit_per_increment } else { 0 };
+    let changes_count = matches!(op, PolicyOp::Increment | PolicyOp::Decrement);
+    let token_fee = if changes_count && !policy.acts_for_owner() { counter.fee_amount } else { 0 };
+
+    // SESSION KEYS SPEND ONE OPERATION OF THEIR BUDGET PER CALL
+    let via_session = policy.via_session();
+
+    // OPERATORS SPEND THEIR ALLOWANCE - one unit for every unit they add
+    let acting_as_operator = op == PolicyOp::Increment && !counter.is_public && !policy.acts_for_owner();
+    let remaining_allowance = match &ctx.accounts.delegation {
+        Some(delegation) if acting_as_operator => Some(delegation.allowance.checked_sub(amount).ok_or_else(|| {
+            let error = CounterError::AllowanceExhausted.into();
+            explain_limit_error(error, counter.key(), counter, operation, amount, previous_count, Some(delegation.allowance))
+        })?),
+        _ => None,
+    };
+
+    ctx.accounts.collect_op_fee(fee_lamports, dry_run)?; // Charge the protocol fee (if any)
+    ctx.accounts.collect_counter_fee(counter_fee_lamports, dry_run)?; // And the owner's own fee (if any)
+    ctx.accounts.deposit_to_vault(deposit_lamports, dry_run)?; // And the vault deposit (if any)
+    ctx.accounts.collect_token_fee(token_fee, dry_run)?; // And the owner's token fee (if any)
+    advance_sequencer(ctx.accounts.sequencer.as_mut(), expected_seq, dry_run)?; // Enforce ordering (if requested)
+    if dry_run {
+        return report_dry_run(counter.key(), previous_count, new_count); // Stop before saving anything
+    }
+
+    if let (Some(delegation), Some(remaining)) = (ctx.accounts.delegation.as_mut(), remaining_allowance) {
+        delegation.allowance = remaining;
+    }
+    if let Some(session) = ctx.accounts.session.as_mut().filter(|_| via_session) {
+        session.ops_left = session.ops_left.checked_sub(1).ok_or(CounterError::SessionExhausted)?;
+    }
+    if op == PolicyOp::Increment {
+        ctx.accounts.mint_reward(1)?; // Reward the caller (if they asked for it), once per call
+        ctx.accounts.tally_contribution(amount); // Credit the contributor (if they passed their registration in)
+    }
+
+    let user = ctx.accounts.user.key();
+    let now = clock.unix_timestamp;
+    let counter = &mut ctx.accounts.counter;
+    if let Some(decay) = decay {
+        announce_decay(counter, decay, clock.slot);
+    }
+    if let Some(period_start) = new_period {
+        announce_rollover(counter, period_start, clock.slot);
+    }
+    counter.accrue_twa(now);
+    counter.count = new_count;
+    let alert = counter.check_alert(); // Announced after the update itself
+    counter.last_updated = now;
+    counter.record_activity(now); // Counts towards `prove_activity`
+    let streak_extended = operation == CounterOperation::Increment && counter.extend_streak(now);
+    if operation == CounterOperation::Increment {
+        counter.count_daily_increments(now, 1); // Counts towards `daily_limit`
+    }
+    counter.next_version();
+    counter.next_sequence(); // This change's `CounterUpdated`
+    if let Some(history) = &ctx.accounts.history {
+        history.load_mut()?.record(clock.slot, operation, previous_count, new_count);
+    }
+
+    let stats = &mut ctx.accounts.stats;
+    match operation {
+        CounterOperation::Increment => stats.total_increments = stats.total_increments.saturating_add(1),
+        CounterOperation::Decrement => stats.total_decrements = stats.total_decrements.saturating_add(1),
+        CounterOperation::Set | CounterOperation::Reset => {} // Corrections aren't tracked
+    }
+    stats.announce();
+    ctx.accounts.roll_up_group(stored_count, new_count, clock.slot)?;
+    let counter = &ctx.accounts.counter;
+
+    // Tell the blockchain about this change (like posting on social media)
+    emit_indexed!(ctx, CounterUpdated::new(user, counter, previous_count, operation, &clock)
+        .with_memo(memo)
+        .with_weight(weight));
+
+    if operation == CounterOperation::Increment {
+        if let Some(milestone) = counter.milestone_reached(previous_count) {
+            emit_event!(MilestoneReached { counter: counter.key(), milestone, slot: clock.slot });
+        }
+    }
+    if streak_extended {
+        emit_event!(StreakUpdated {
+            counter: counter.key(),
+            current_streak: counter.current_streak,
+            best_streak: counter.best_streak,
+            day: counter.last_day,
+        });
+    }
+    announce_alert(counter, alert, clock.slot);
+
+    // Log what happened for blockchain explorers to see
+    verbose_msg!("Counter {:?} {}: {} -> {} for user: {}", id, operation.as_str(), previous_count, new_count, user);
+    ctx.accounts.notify_hook(previous_count, new_count)?;
+    report_diagnostics!(ctx);
+    Ok(()) // Return success
+}
+
+// APPLY A LIST OF CHANGES TO A COUNTER
+// The body of `apply_batch`. Every step goes through the policy with the value it would leave
+// behind and uses checked arithmetic, whatever the counter's overflow mode. Fees are added up
+// and charged once, operators spend their allowance on the total added, and each step still
+// counts as its own operation in the history, activity and program-wide totals
+pub(crate) fn update_batch(ctx: Context<Update>, id: &str, ops: &[BatchOp], dry_run: bool, expected_seq: Option<u64>) -> Result<()> {
+    require!(!ops.is_empty() && ops.len() <= MAX_BATCH_OPS, CounterError::InvalidBatch);
+
+    let counter = &ctx.accounts.counter;
+    let stored_count = counter.count; // What the counter's group total holds for it
+    let user = ctx.accounts.user.key();
+    let gate_balance = ctx.accounts.gate_token_account.as_ref().map(|account| account.amount);
+    let held_collection = ctx.accounts.held_collection();
+    let clock = Clock::get()?; // Read once for every step
+
+    // WORK OUT EVERY STEP, CHECKING EACH ONE BEFORE ANYTHING IS CHANGED
+    // (from the starting value when the counter's reset period has lapsed, and after any decay)
+    let new_period = counter.lapsed_period(clock.unix_timestamp);
+    let decay = counter.pending_decay(clock.unix_timestamp);
+    let start_count = match (new_period, decay) {
+        (Some(_), _) => counter.min_count,
+        (None, Some((decayed, _))) => decayed,
+        (None, None) => counter.count,
+    };
+    let mut steps = Vec::with_capacity(ops.len()); // (operation, previous, new)
+    let (mut current, mut added, mut fee_lamports, mut counter_fee_lamports) = (start_count, 0u64, 0u64, 0u64);
+    let mut session_ops = 0u32; // Steps only a session key of the owner's let through
+    let (mut operator_added, mut token_fee_ops) = (0u64, 0u64); // What's charged to someone not acting for the owner
+    for op in ops {
+        let (operation, amount) = match *op {
+            BatchOp::Increment(amount) => (CounterOperation::Increment, amount),
+            BatchOp::Decrement(amount) => (CounterOperation::Decrement, amount),
+            BatchOp::Set(value) => (CounterOperation::Set, value),
+        };
+        let explain = |error| explain_limit_error(error, counter.key(), counter, operation, amount, current, None);
+        let (policy_op, next) = match *op {
+            BatchOp::Increment(amount) => {
+                require!(amount > 0, CounterError::InvalidAmount);
+                let next = current.checked_add(amount).ok_or_else(|| explain(CounterError::CounterOverflow.into()))?;
+                (PolicyOp::Increment, next)
+            }
+            BatchOp::Decrement(amount) => {
+                require!(amount > 0, CounterError::InvalidAmount);
+                let next = current.checked_sub(amount).ok_or_else(|| explain(CounterError::CounterUnderflow.into()))?;
+                (PolicyOp::Decrement, next)
+            }
+            BatchOp::Set(value) => (PolicyOp::Set, value),
+        };
+
+        let mut policy = PolicyContext::at(counter, user, policy_op, clock.unix_timestamp);
+        policy.delegation = ctx.accounts.delegation.as_deref();
+        policy.session = ctx.accounts.session.as_deref();
+        policy.contributor = ctx.accounts.contributor.as_deref();
+        policy.role = ctx.accounts.role.as_deref();
+        policy.gate_balance = gate_balance;
+        policy.held_collection = held_collection;
+        ActivePolicy::validate(&policy, next).map_err(explain)?;
+        session_ops += policy.via_session() as u32;
+        fee_lamports = fee_lamports.saturating_add(ActivePolicy::check_fee(&policy, &ctx.accounts.config)?);
+        if let BatchOp::Increment(amount) = *op {
+            counter_fee_lamports = counter_fee_lamports.saturating_add(ActivePolicy::check_counter_fee(&policy, amount)?);
+            added = added.saturating_add(amount);
+        }
+        // Like single changes, the owner, their session keys and role holders pay no token fee
+        // and spend no allowance
+        if !policy.acts_for_owner() && policy_op != PolicyOp::Set {
+            token_fee_ops += 1; // One token fee per increment/decrement
+            if let BatchOp::Increment(amount) = *op {
+                operator_added = operator_added.saturating_add(amount);
+            }
+        }
+
+        steps.push((operation, current, next));
+        current = next;
+    }
+    require!(!counter.has_history || ctx.accounts.history.is_some(), CounterError::HistoryRequired);
+    ctx.accounts.require_hook()?;
+    ctx.accounts.require_group()?;
+
+    // THE DAILY LIMIT COVERS EVERY INCREMENT IN THE BATCH, NOT JUST THE FIRST
+    let increments = ops.iter().filter(|op| matches!(op, BatchOp::Increment(_))).count() as u64;
+    require!(
+        increments <= counter.daily_increments_left(clock.unix_timestamp),
+        CounterError::DailyLimitReached
+    );
+
+    // SESSION KEYS SPEND ONE OPERATION OF THEIR BUDGET PER STEP THEY SIGNED FOR
+    let remaining_session_ops = match &ctx.accounts.session {
+        Some(session) if session_ops > 0 => {
+            Some(session.ops_left.checked_sub(session_ops).ok_or(CounterError::SessionExhausted)?)
+        }
+        _ => None,
+    };
+
+    // OPERATORS SPEND THEIR ALLOWANCE ON EVERYTHING THE BATCH ADDS
+    let acting_as_operator = operator_added > 0 && !counter.is_public;
+    let remaining_allowance = match &ctx.accounts.delegation {
+        Some(delegation) if acting_as_operator => {
+            Some(delegation.allowance.checked_sub(operator_added).ok_or(CounterError::AllowanceExhausted)?)
+        }
+        _ => None,
+    };
+
+    ctx.accounts.collect_op_fee(fee_lamports, dry_run)?;
+    ctx.accounts.collect_counter_fee(counter_fee_lamports, dry_run)?;
+    ctx.accounts.deposit_to_vault(counter.deposit_per_increment.saturating_mul(increments), dry_run)?; // One deposit per increment
+    let token_fee = counter.fee_amount.saturating_mul(token_fee_ops);
+    ctx.accounts.collect_token_fee(token_fee, dry_run)?;
+    advance_sequencer(ctx.accounts.sequencer.as_mut(), expected_seq, dry_run)?;
+    if dry_run {
+        return report_dry_run(counter.key(), start_count, current);
+    }
+
+    if let (Some(delegation), Some(remaining)) = (ctx.accounts.delegation.as_mut(), remaining_allowance) {
+        delegation.allowance = remaining;
+    }
+    if let (Some(session), Some(remaining)) = (ctx.accounts.session.as_mut(), remaining_session_ops) {
+        session.ops_left = remaining;
+    }
+    if added > 0 {
+        ctx.accounts.mint_reward(increments)?; // One reward per increment in the batch
+        ctx.accounts.tally_contribution(added);
+    }
+
+    // SAVE THE END RESULT, LOGGING EVERY STEP
+    let counter = &mut ctx.accounts.counter;
+    if let Some(decay) = decay {
+        announce_decay(counter, decay, clock.slot);
+    }
+    if let Some(period_start) = new_period {
+        announce_rollover(counter, period_start, clock.slot);
+    }
+    counter.accrue_twa(clock.unix_timestamp);
+    counter.count = current;
+    let alert = counter.check_alert(); // Only the batch's end result counts
+    counter.last_updated = clock.unix_timestamp;
+    let streak_extended = added > 0 && counter.extend_streak(clock.unix_timestamp);
+    counter.count_daily_increments(clock.unix_timestamp, increments);
+    counter.next_version();
+    counter.next_sequence(); // The batch's `CounterBatchApplied`
+    let stats = &mut ctx.accounts.stats;
+    for &(operation, previous, new) in &steps {
+        counter.record_activity(clock.unix_timestamp);
+        if let Some(history) = &ctx.accounts.history {
+            history.load_mut()?.record(clock.slot, operation, previous, new);
+        }
+        match operation {
+            CounterOperation::Increment => stats.total_increments = stats.total_increments.saturating_add(1),
+            CounterOperation::Decrement => stats.total_decrements = stats.total_decrements.saturating_add(1),
+            CounterOperation::Set | CounterOperation::Reset => {}
+        }
+    }
+    stats.announce();
+    ctx.accounts.roll_up_group(stored_count, current, clock.slot)?;
+    let counter = &ctx.accounts.counter;
+
+    emit_indexed!(ctx, CounterBatchApplied {
+        user,
+        counter: counter.key(),
+        start_count,
+        end_count: current,
+        ops: steps.len() as u8,
+        version: counter.version,
+        sequence: counter.sequence,
+        slot: clock.slot,
+        timestamp: clock.unix_timestamp,
+    });
+    if let Some(milestone) = counter.milestone_reached(start_count) {
+        emit_event!(MilestoneReached { counter: counter.key(), milestone, slot: clock.slot });
+    }
+    if streak_extended {
+        emit_event!(StreakUpdated {
+            counter: counter.key(),
+            current_streak: counter.current_streak,
+            best_streak: counter.best_streak,
+            day: counter.last_day,
+        });
+    }
+    announce_alert(counter, alert, clock.slot);
+
+    verbose_msg!("Counter {:?} batch of {}: {} -> {} for user: {}", id, steps.len(), start_count, current, user);
+    ctx.accounts.notify_hook(start_count, current)?;
+    report_diagnostics!(ctx);
+    Ok(())
+}
+
+// ANNOUNCE AN ALERT THRESHOLD THE COUNT JUST CROSSED (see `Counter::check_alert`)
+pub(crate) fn announce_alert(counter: &Account<Counter>, alert: Option<AlertDirection>, slot: u64) {
+    if let Some(direction) = alert {
+        emit_event!(ThresholdCrossed {
+            counter: counter.key(),
+            threshold: counter.alert_threshold,
+            direction,
+            count: counter.count,
+            slot,
+        });
+    }
+}
+
+// START A COUNTER'S NEW RESET PERIOD AND ANNOUNCE IT
+pub(crate) fn announce_rollover(counter: &mut Account<Counter>, period_start: i64, slot: u64) {
+    counter.roll_over_period(period_start);
+    emit_event!(PeriodRolledOver {
+        counter: counter.key(),
+        last_period_count: counter.last_period_count,
+        period_start,
+        slot,
+    });
+}
+
+// TAKE THE DECAY WORKED OUT BY `pending_decay` OFF THE COUNT, AND ANNOUNCE IT
+// Comes just before the `CounterUpdated` of the change that noticed it
+pub(crate) fn announce_decay(counter: &mut Account<Counter>, (count, decayed_at): (u64, i64), slot: u64) {
+    let amount = counter.count - count;
+    counter.accrue_twa(decayed_at); // The full count lasted until the decay fell due
+    counter.count = count;
+    counter.last_decay_ts = decayed_at;
+    emit_event!(DecayApplied {
+        counter: counter.key(),
+        amount,
+        count,
+        slot,
+    });
+}
+
+// SETTLE WHAT THE CLOCK OWES A COUNTER BEFORE AN INSTRUCTION WORKS WITH THE COUNT
+// Any decay owed, then a lapsed reset period started over, in `update_count`'s order - so a
+// value written now isn't filed away as the old period's count by the next change. For
+// instructions without a dry run; `update_count` and `update_batch` apply both themselves
+// once they know the change goes ahead
+pub(crate) fn settle_clock(counter: &mut Account<Counter>) -> Result<()> {
+    let clock = Clock::get()?;
+    if let Some(decay) = counter.pending_decay(clock.unix_timestamp) {
+        announce_decay(counter, decay, clock.slot);
+    }
+    if let Some(period_start) = counter.lapsed_period(clock.unix_timestamp) {
+        announce_rollover(counter, period_start, clock.slot);
+    }
+    Ok(())
+}
+
+// FINISH A DRY RUN
+// Everything checked out, so hand the would-be result back to the caller through return data
+// (visible in `simulateTransaction`) instead of saving it
+pub(crate) fn report_dry_run(counter: Pubkey, previous_count: u64, new_count: u64) -> Result<()> {
+    let result = WouldSucceed { counter, previous_count, new_count };
+    anchor_lang::solana_program::program::set_return_data(&result.try_to_vec()?);
+    verbose_msg!("Dry run succeeded for counter: {}", counter);
+    Ok(())
+}
+
+// ACCOUNTS NEEDED TO UPDATE A COUNTER (increment/decrement)
+// This tells Solana what accounts are required for changing the counter value
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct Update<'info> {
+    // THE EXISTING COUNTER ACCOUNT WE'RE CHANGING
+    #[account(
+        mut,                     // This account will be modified
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), id.as_bytes()], // How to find the counter account
+        bump = counter.bump,     // Use the stored bump seed
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,  // The counter we're updating
+
+    // THE PERSON MAKING THE CHANGE
+    // Usually the owner, but anyone may increment a public counter - who is allowed to do
+    // what is decided by `CounterPolicy::check_auth` in the handler
+    #[account(mut)]             // Pays the protocol fee when fees are enabled
+    pub user: Signer<'info>,   // Must be signed by the user
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions and fees)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (see `GlobalStats`)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // THE PROTOCOL TREASURY - a program address that collects SOL fees
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: SystemAccount<'info>,
+
+    // THE COUNTER'S OWN TREASURY - collects the owner's increment fee (see `set_counter_fee`)
+    #[account(mut, seeds = [b"counter_treasury", counter.key().as_ref()], bump)]
+    pub counter_treasury: SystemAccount<'info>,
+
+    // OPTIONAL: PAY THE FEE IN TOKENS INSTEAD OF SOL
+    // Pass all three to pay in `config.fee_mint`, or none of them to pay in SOL
+    #[account(mut, token::mint = config.fee_mint, token::authority = user)]
+    pub fee_payer_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut, token::mint = config.fee_mint, token::authority = treasury)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+
+    // OPTIONAL: THE CALLER'S SEQUENCER (required when `expected_seq` is set)
+    #[account(mut, seeds = [b"sequencer", user.key().as_ref()], bump = sequencer.bump)]
+    pub sequencer: Option<Account<'info, Sequencer>>,
+
+    // OPTIONAL: THE CALLER'S OPERATOR APPROVAL (needed when an operator increments for the owner)
+    #[account(mut, seeds = [b"delegate", counter.key().as_ref(), user.key().as_ref()], bump = delegation.bump)]
+    pub delegation: Option<Account<'info, Delegation>>,
+
+    // OPTIONAL: THE CALLER'S SESSION (needed when a session key signs for the owner)
+    #[account(mut, seeds = [b"session", counter.key().as_ref(), user.key().as_ref()], bump = session.bump)]
+    pub session: Option<Account<'info, Session>>,
+
+    // OPTIONAL: THE CALLER'S CONTRIBUTOR REGISTRATION (needed when a team member increments a shared counter)
+    #[account(mut, seeds = [b"contributor", counter.key().as_ref(), user.key().as_ref()], bump = contributor.bump)]
+    pub contributor: Option<Account<'info, Contributor>>,
+
+    // OPTIONAL: THE CALLER'S ROLES ON THE COUNTER (needed when a member changes it, see `grant_role`)
+    #[account(seeds = [b"role", counter.key().as_ref(), user.key().as_ref()], bump = role.bump)]
+    pub role: Option<Account<'info, Role>>,
+
+    // OPTIONAL: THE CALLER'S ASSOCIATED TOKEN ACCOUNT FOR THE COUNTER'S GATE MINT
+    // (needed by everyone but the owner when the counter is token-gated)
+    #[account(associated_token::mint = counter.gate_mint, associated_token::authority = user)]
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+
+    // OPTIONAL: THE CALLER'S TOKEN ACCOUNT FOR THE COUNTER'S WEIGHT MINT (for `increment_weighted`)
+    #[account(token::mint = counter.weight_mint, token::authority = user)]
+    pub weight_token_account: Option<Account<'info, TokenAccount>>,
+
+    // OPTIONAL: AN NFT THE CALLER HOLDS, AND ITS METAPLEX METADATA
+    // (needed by everyone but the owner to increment a counter with an `nft_gate`)
+    #[account(token::authority = user)]
+    pub nft_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: owner, mint and collection are checked by `Update::held_collection`
+    pub nft_metadata: Option<UncheckedAccount<'info>>,
+
+    // OPTIONAL: EARN THE INCREMENT REWARD (see `initialize_rewards`)
+    // Pass all three, plus the token program, to have reward tokens minted to `reward_token_account`
+    #[account(seeds = [b"rewards"], bump = reward_config.bump)]
+    pub reward_config: Option<Account<'info, RewardConfig>>,
+    #[account(mut)]
+    pub reward_mint: Option<Account<'info, Mint>>,
+    #[account(mut)]
+    pub reward_token_account: Option<Account<'info, TokenAccount>>,
+
+    // OPTIONAL: THE COUNTER'S CHANGE LOG (required once `initialize_history` has been called)
+    #[account(mut, seeds = [b"history", counter.key().as_ref()], bump)]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+
+    // OPTIONAL: THE COUNTER'S VAULT (required to increment once `set_vault_deposit` is non-zero)
+    #[account(mut, seeds = [b"vault", counter.key().as_ref()], bump)]
+    pub vault: Option<SystemAccount<'info>>,
+
+    // OPTIONAL: PAY THE COUNTER'S TOKEN FEE (required from everyone but the owner once
+    // `set_token_fee` is non-zero, together with `token_program`)
+    #[account(mut, token::mint = counter.fee_mint, token::authority = user)]
+    pub fee_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut, associated_token::mint = counter.fee_mint, associated_token::authority = counter_treasury)]
+    pub fee_vault: Option<Account<'info, TokenAccount>>,
+
+    // OPTIONAL: THE REFERRER'S COUNTER (for `increment_with_referrer`, which credits it)
+    // Boxed, since a second `Counter` would crowd the stack
+    #[account(
+        mut,
+        seeds = [b"counter", referrer_counter.namespace_seed(), referrer_counter.creator.as_ref(), referrer_counter.label.as_bytes()],
+        bump = referrer_counter.bump,
+        constraint = referrer_counter.key() != counter.key() @ CounterError::SameCounter,
+        constraint = referrer_counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub referrer_counter: Option<Box<Account<'info, Counter>>>,
+
+    // OPTIONAL: THE COUNTER'S HOOK PROGRAM (required once `set_hook` has set one)
+    /// CHECK: has to be the counter's `hook_program`, checked by `Update::require_hook`
+    pub hook_program: Option<UncheckedAccount<'info>>,
+
+    // OPTIONAL: THE COUNTER'S GROUP (required once it has joined one, see `join_group`)
+    #[account(
+        mut,
+        seeds = [b"group", group.authority.as_ref(), group.name.as_bytes()],
+        bump = group.bump,
+        constraint = group.key() == counter.group @ CounterError::NotInGroup,
+    )]
+    pub group: Option<Account<'info, CounterGroup>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Update<'info> {
+    // MAKE SURE THE COUNTER'S HOOK PROGRAM (IF IT HAS ONE) WAS PASSED IN
+    pub fn require_hook(&self) -> Result<()> {
+        let hook_program = self.counter.hook_program;
+        if hook_program != Pubkey::default() {
+            let passed = self.hook_program.as_ref().is_some_and(|program| program.key() == hook_program);
+            require!(passed, CounterError::HookProgramRequired);
+        }
+        Ok(())
+    }
+
+    // MAKE SURE THE COUNTER'S GROUP (IF IT'S IN ONE) WAS PASSED IN
+    pub fn require_group(&self) -> Result<()> {
+        require!(self.counter.group == Pubkey::default() || self.group.is_some(), CounterError::GroupRequired);
+        Ok(())
+    }
+
+    // MOVE THE COUNTER'S GROUP TOTAL (IF IT'S IN ONE) FROM `previous_count` TO `new_count`
+    // `previous_count` is what the counter held before the change, not after any decay or
+    // rollover it started with, so those count towards the total too
+    pub fn roll_up_group(&mut self, previous_count: u64, new_count: u64, slot: u64) -> Result<()> {
+        let Some(group) = self.group.as_mut() else {
+            return Ok(());
+        };
+        let previous_total = group.total;
+        group.total = if new_count >= previous_count {
+            previous_total.checked_add(new_count - previous_count).ok_or(CounterError::CounterOverflow)?
+        } else {
+            previous_total.checked_sub(previous_count - new_count).ok_or(CounterError::CounterUnderflow)?
+        };
+
+        emit_event!(GroupTotalUpdated {
+            group: group.key(),
+            counter: self.counter.key(),
+            previous_total,
+            total: group.total,
+            members: group.members,
+            slot,
+        });
+        Ok(())
+    }
+
+    // TELL THE COUNTER'S HOOK PROGRAM ABOUT A CHANGE (see `set_hook`)
+    // The last thing a change does. The counter is written out first, so the hook reads the
+    // new state, and goes in read-only; the runtime doesn't let the hook call back into this
+    // program, so it can't change the counter while it's being updated. A hook that fails
+    // fails the whole change
+    pub fn notify_hook(&mut self, previous_count: u64, new_count: u64) -> Result<()> {
+        let hook_program = self.counter.hook_program;
+        if hook_program == Pubkey::default() {
+            return Ok(());
+        }
+        self.require_hook()?;
+        let program = self.hook_program.as_ref().ok_or(CounterError::HookProgramRequired)?;
+        require!(program.executable, CounterError::HookFailed);
+
+        self.counter.exit(&crate::ID)?;
+        let counter = self.counter.key();
+        let ix = anchor_lang::solana_program::instruction::Instruction {
+            program_id: hook_program,
+            accounts: vec![anchor_lang::solana_program::instruction::AccountMeta::new_readonly(counter, false)],
+            data: hook::on_counter_updated_data(&counter, previous_count, new_count),
+        };
+        anchor_lang::solana_program::program::invoke(&ix, &[self.counter.to_account_info(), program.to_account_info()])
+            .map_err(|_| error!(CounterError::HookFailed))
+    }
+
+    // CHARGE THE PROTOCOL FEE FOR ONE OPERATION
+    // `fee_lamports` comes from the policy; it's paid in tokens when the token accounts
+    // were passed in, otherwise in SOL
+    // In a dry run the fee is still worked out (so a stale price is reported) but not taken
+    pub fn collect_op_fee(&self, fee_lamports: u64, dry_run: bool) -> Result<()> {
+        let config = &self.config;
+        if fee_lamports == 0 {
+            return Ok(()); // Nothing to pay
+        }
+
+        // The token program is also used for rewards, so it may be there on its own
+        let token_accounts = match (&self.fee_payer_token_account, &self.treasury_token_account, &self.token_program) {
+            (Some(from), Some(to), Some(token_program)) => Some((from, to, token_program)),
+            (None, None, _) => None,
+            _ => return err!(CounterError::IncompleteFeeAccounts),
+        };
+
+        // Work out what to charge
+        let (mint, amount) = match token_accounts {
+            Some(_) => {
+                require!(config.fee_mint != Pubkey::default(), CounterError::TokenFeesDisabled);
+                (config.fee_mint, config.token_fee_amount(fee_lamports, Clock::get()?.unix_timestamp)?)
+            }
+            None => (Pubkey::default(), fee_lamports),
+        };
+        if dry_run {
+            return Ok(());
+        }
+
+        // Move the money into the treasury
+        match token_accounts {
+            Some((from, to, token_program)) => token::transfer(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    token::Transfer {
+                        from: from.to_account_info(),
+                        to: to.to_account_info(),
+                        authority: self.user.to_account_info(),
+                    },
+                ),
+                amount,
+            )?,
+            None => anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: self.user.to_account_info(),
+                        to: self.treasury.to_account_info(),
+                    },
+                ),
+                amount,
+            )?,
+        }
+
+        emit_event!(FeePaid {
+            payer: self.user.key(),
+            counter: self.counter.key(),
+            mint,
+            amount,
+        });
+        Ok(())
+    }
+
+    // MINT THE INCREMENT REWARD TO THE CALLER'S TOKEN ACCOUNT
+    // Only when the reward accounts were passed in and rewards are switched on. `increments` is
+    // how many increments the call made, not how much they added: an `increment_by(1000)` earns
+    // the same single reward as an `increment`
+    pub fn mint_reward(&self, increments: u64) -> Result<()> {
+        let (rewards, mint, destination, token_program) =
+            match (&self.reward_config, &self.reward_mint, &self.reward_token_account, &self.token_program) {
+                (Some(rewards), Some(mint), Some(destination), Some(token_program)) => {
+                    (rewards, mint, destination, token_program)
+                }
+                (None, None, None, _) => return Ok(()), // Not claiming a reward
+                _ => return err!(CounterError::IncompleteRewardAccounts),
+            };
+        require_keys_eq!(mint.key(), rewards.mint, CounterError::InvalidRewardMint);
+        if !rewards.enabled {
+            return Ok(());
+        }
+
+        let amount = rewards.tokens_per_increment.saturating_mul(increments);
+        let signer_seeds: &[&[&[u8]]] = &[&[seeds::REWARDS, &[rewards.bump]]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                token::MintTo {
+                    mint: mint.to_account_info(),
+                    to: destination.to_account_info(),
+                    authority: rewards.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        emit_event!(RewardMinted {
+            user: self.user.key(),
+            counter: self.counter.key(),
+            destination: destination.key(),
+            amount,
+        });
+        Ok(())
+    }
+
+    // HOW MUCH AN `increment_weighted` BY THE CALLER ADDS
+    // Their balance of the counter's weight mint over its divisor, rounded down (at least one)
+    pub fn weight(&self) -> Result<u64> {
+        let counter = &self.counter;
+        require!(counter.weight_mint != Pubkey::default(), CounterError::WeightNotConfigured);
+        let account = self.weight_token_account.as_ref().ok_or(CounterError::WeightAccountRequired)?;
+        let weight = account.amount / counter.weight_divisor;
+        require!(weight > 0, CounterError::WeightTooLow);
+        Ok(weight)
+    }
+
+    // THE VERIFIED COLLECTION OF THE NFT THE CALLER PASSED IN (if any, see `verified_collection`)
+    pub fn held_collection(&self) -> Option<Pubkey> {
+        verified_collection(self.nft_token_account.as_ref(), self.nft_metadata.as_ref())
+    }
+
+    // CREDIT WHAT AN INCREMENT ADDED TO THE CALLER'S CONTRIBUTOR TALLY (if they passed it in)
+    pub fn tally_contribution(&mut self, units_added: u64) {
+        if let Some(contributor) = self.contributor.as_mut() {
+            contributor.contributions = contributor.contributions.saturating_add(units_added);
+        }
+    }
+
+    // PAY THE COUNTER'S VAULT DEPOSIT (see `set_vault_deposit`)
+    // Checked in a dry run too, but only moved for real
+    pub fn deposit_to_vault(&self, lamports: u64, dry_run: bool) -> Result<()> {
+        if lamports == 0 {
+            return Ok(());
+        }
+        let vault = self.vault.as_ref().ok_or(CounterError::VaultRequired)?;
+        if dry_run {
+            return Ok(());
+        }
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: self.user.to_account_info(),
+                    to: vault.to_account_info(),
+                },
+            ),
+            lamports,
+        )?;
+
+        emit_event!(VaultDeposited {
+            payer: self.user.key(),
+            counter: self.counter.key(),
+            amount: lamports,
+        });
+        Ok(())
+    }
+
+    // CHARGE THE COUNTER OWNER'S FEE FOR ONE OPERATION
+    // Always in SOL, straight into the counter's treasury; skipped in a dry run
+    pub fn collect_counter_fee(&self, fee_lamports: u64, dry_run: bool) -> Result<()> {
+        if fee_lamports == 0 || dry_run {
+            return Ok(());
+        }
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: self.user.to_account_info(),
+                    to: self.counter_treasury.to_account_info(),
+                },
+            ),
+            fee_lamports,
+        )?;
+
+        emit_event!(CounterFeePaid {
+            payer: self.user.key(),
+            counter: self.counter.key(),
+            amount: fee_lamports,
+        });
+        Ok(())
+    }
+
+    // PAY THE COUNTER'S TOKEN FEE (see `set_token_fee`) INTO ITS FEE VAULT
+    pub fn collect_token_fee(&self, amount: u64, dry_run: bool) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let (Some(from), Some(to), Some(token_program)) = (&self.fee_token_account, &self.fee_vault, &self.token_program)
+        else {
+            return err!(CounterError::TokenFeeAccountsRequired);
+        };
+        if dry_run {
+            return Ok(());
+        }
+
+        token::transfer(
+            CpiContext::new(
+                token_program.to_account_info(),
+                token::Transfer {
+                    from: from.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        emit_event!(CounterTokenFeePaid {
+            payer: self.user.key(),
+            counter: self.counter.key(),
+            mint: self.counter.fee_mint,
+            amount,
+        });
+        Ok(())
+    }
+}
+
+// THE VERIFIED COLLECTION OF AN NFT A CALLER PASSED IN
+// None unless the token account holds the NFT and the metadata really is that mint's
+// Metaplex metadata - the policy then treats the caller as holding nothing
+pub(crate) fn verified_collection(token_account: Option<&Account<TokenAccount>>, metadata: Option<&UncheckedAccount>) -> Option<Pubkey> {
+    let (token_account, metadata) = (token_account?, metadata?);
+    if token_account.amount == 0 || *metadata.owner != TOKEN_METADATA_PROGRAM_ID {
+        return None;
+    }
+    let data = metadata.try_borrow_data().ok()?;
+    let head = MetadataHead::deserialize(&mut &data[..]).ok()?;
+    if head.key != METADATA_V1_KEY || head.mint != token_account.mint {
+        return None;
+    }
+    head.collection.filter(|collection| collection.verified).map(|collection| collection.key)
+}
+
+// ACCOUNTS NEEDED TO PREFLIGHT AN OPERATION (see `validate_op`)
+// The counter and the signer's own approvals, as `Update` takes them, all read only
+#[derive(Accounts)]
+pub struct ValidateOp<'info> {
+    #[account(
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // WHO WOULD MAKE THE CHANGE
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for pauses, disabled instructions and fees)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // OPTIONAL: THE SIGNER'S OPERATOR APPROVAL, SESSION, CONTRIBUTOR REGISTRATION OR ROLES
+    #[account(seeds = [b"delegate", counter.key().as_ref(), user.key().as_ref()], bump = delegation.bump)]
+    pub delegation: Option<Account<'info, Delegation>>,
+    #[account(seeds = [b"session", counter.key().as_ref(), user.key().as_ref()], bump = session.bump)]
+    pub session: Option<Account<'info, Session>>,
+    #[account(seeds = [b"contributor", counter.key().as_ref(), user.key().as_ref()], bump = contributor.bump)]
+    pub contributor: Option<Account<'info, Contributor>>,
+    #[account(seeds = [b"role", counter.key().as_ref(), user.key().as_ref()], bump = role.bump)]
+    pub role: Option<Account<'info, Role>>,
+
+    // OPTIONAL: THE SIGNER'S ASSOCIATED TOKEN ACCOUNT FOR THE COUNTER'S GATE MINT
+    #[account(associated_token::mint = counter.gate_mint, associated_token::authority = user)]
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+
+    // OPTIONAL: AN NFT THE SIGNER HOLDS, AND ITS METAPLEX METADATA (for an `nft_gate`)
+    #[account(token::authority = user)]
+    pub nft_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: owner, mint and collection are checked by `verified_collection`
+    pub nft_metadata: Option<UncheckedAccount<'info>>,
+
+    // OPTIONAL: THE ACCOUNTS `update_count` REQUIRES ONCE THE COUNTER HAS THEM - its change
+    // log, hook program and group - so a call that would leave them out is reported as refused
+    #[account(seeds = [b"history", counter.key().as_ref()], bump)]
+    pub history: Option<AccountLoader<'info, CounterHistory>>,
+    /// CHECK: only compared with the counter's `hook_program`
+    pub hook_program: Option<UncheckedAccount<'info>>,
+    #[account(
+        seeds = [b"group", group.authority.as_ref(), group.name.as_bytes()],
+        bump = group.bump,
+        constraint = group.key() == counter.group @ CounterError::NotInGroup,
+    )]
+    pub group: Option<Account<'info, CounterGroup>>,
+}
+
+impl<'info> ValidateOp<'info> {
+    // WHETHER `op` BY `amount` WOULD GO THROUGH `update_count` RIGHT NOW
+    // A refusal is part of the answer, not an error: only failing to read the clock fails this
+    pub fn validate(&self, op: CounterOperation, amount: u64) -> Result<OpValidation> {
+        let counter = &self.counter;
+        let now = Clock::get()?.unix_timestamp;
+        let previous_count = match (counter.lapsed_period(now), counter.pending_decay(now)) {
+            (Some(_), _) => counter.min_count,
+            (None, Some((decayed, _))) => decayed,
+            (None, None) => counter.count,
+        };
+
+        let mut validation = OpValidation {
+            counter: counter.key(),
+            op,
+            allowed: false,
+            error_code: 0,
+            previous_count,
+            new_count: previous_count,
+            fee_lamports: 0,
+            counter_fee_lamports: 0,
+            token_fee: 0,
+        };
+        match self.check(op, amount, previous_count, &mut validation) {
+            Ok(()) => validation.allowed = true,
+            Err(error) => validation.error_code = error_code(&error),
+        }
+        Ok(validation)
+    }
+
+    // The checks `update_count` and the instruction in front of it make, in the same order
+    fn check(&self, op: CounterOperation, amount: u64, previous_count: u64, validation: &mut OpValidation) -> Result<()> {
+        let (flag, policy_op, count_op) = match op {
+            CounterOperation::Increment => (instruction_flag::INCREMENT_BY, PolicyOp::Increment, CountOp::Increment(amount)),
+            CounterOperation::Decrement => (instruction_flag::DECREMENT_BY, PolicyOp::Decrement, CountOp::Decrement(amount)),
+            CounterOperation::Set => (instruction_flag::SET_COUNT, PolicyOp::Set, CountOp::Set(amount)),
+            CounterOperation::Reset => (instruction_flag::RESET, PolicyOp::Reset, CountOp::Reset),
+        };
+        self.config.require_enabled(flag)?;
+        if matches!(op, CounterOperation::Increment | CounterOperation::Decrement) {
+            require!(amount > 0, CounterError::InvalidAmount);
+        }
+
+        let counter = &self.counter;
+        let user = self.user.key();
+        let mut policy = PolicyContext::new(counter, user, policy_op)?;
+        policy.delegation = self.delegation.as_deref();
+        policy.session = self.session.as_deref();
+        policy.contributor = self.contributor.as_deref();
+        policy.role = self.role.as_deref();
+        policy.gate_balance = self.gate_token_account.as_ref().map(|account| account.amount);
+        policy.held_collection = verified_collection(self.nft_token_account.as_ref(), self.nft_metadata.as_ref());
+
+        let new_count = counter.rules().apply(previous_count, count_op)?;
+        ActivePolicy::validate(&policy, new_count)?;
+        require!(!counter.has_history || self.history.is_some(), CounterError::HistoryRequired);
+        let hook_passed = self.hook_program.as_ref().is_some_and(|program| program.key() == counter.hook_program);
+        require!(counter.hook_program == Pubkey::default() || hook_passed, CounterError::HookProgramRequired);
+        require!(counter.group == Pubkey::default() || self.group.is_some(), CounterError::GroupRequired);
+        let acting_as_operator =
+            policy_op == PolicyOp::Increment && user != counter.authority && !counter.is_public && !policy.acts_for_owner();
+        if let Some(delegation) = self.delegation.as_ref().filter(|_| acting_as_operator) {
+            require!(delegation.allowance >= amount, CounterError::AllowanceExhausted);
+        }
+
+        validation.new_count = new_count;
+        validation.fee_lamports = ActivePolicy::check_fee(&policy, &self.config)?;
+        validation.counter_fee_lamports = ActivePolicy::check_counter_fee(&policy, amount)?;
+        let changes_count = matches!(policy_op, PolicyOp::Increment | PolicyOp::Decrement);
+        validation.token_fee = if changes_count && !policy.acts_for_owner() { counter.fee_amount } else { 0 };
+        Ok(())
+    }
+}
+
+// ANNOUNCE THE NUMBERS BEHIND A LIMIT ERROR, THEN HAND THE ERROR BACK (see `CounterErrorDetail`)
+// For a change of `amount` to `counter` (at `key`) from `count`; `allowance` is the operator's,
+// when one was spent. Only limit errors get a detail: anything else is returned as it is
+pub(crate) fn explain_limit_error(
+    error: Error,
+    key: Pubkey,
+    counter: &Counter,
+    operation: CounterOperation,
+    amount: u64,
+    count: u64,
+    allowance: Option<u64>,
+) -> Error {
+    let code = error_code(&error);
+    let limit = [
+        (CounterError::CounterOverflow, u64::MAX),
+        (CounterError::CounterUnderflow, 0),
+        (CounterError::CounterAboveMax, counter.max_count),
+        (CounterError::CounterBelowMin, counter.min_count),
+        (CounterError::DailyLimitReached, counter.daily_limit),
+        (CounterError::AllowanceExhausted, allowance.unwrap_or(0)),
+    ]
+    .into_iter()
+    .find(|(limit_error, _)| u32::from(*limit_error) == code);
+    if let Some((_, limit)) = limit {
+        emit_event!(CounterErrorDetail { counter: key, error_code: code, operation, amount, count, limit });
+    }
+    error
+}
+
+// THE NUMBER A CLIENT WOULD SEE FOR AN ERROR (a `CounterError` / Anchor code, or a runtime one)
+pub(crate) fn error_code(error: &Error) -> u32 {
+    match error {
+        Error::AnchorError(error) => error.error_code_number,
+        Error::ProgramError(error) => u64::from(error.program_error.clone()) as u32,
+    }
+}
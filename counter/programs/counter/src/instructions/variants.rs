@@ -0,0 +1,399 @@
+// OTHER KINDS OF COUNTER
+// History, counter arrays, signed counters, big (u128) counters and zero-copy counters
+
+use crate::*;
+
+// CHANGE ONE SLOT OF A COUNTER ARRAY BY ONE
+// The shared body of `increment_slot` and `decrement_slot` (`op` is one of the two)
+pub(crate) fn update_slot(ctx: Context<UpdateArray>, id: &str, index: u16, op: CounterOperation) -> Result<()> {
+    let mut array = ctx.accounts.array.load_mut()?;
+    let slot = index as usize;
+    require!(slot < COUNTER_ARRAY_SLOTS, CounterError::SlotOutOfBounds);
+    require!(array.flags[slot] & slot_flag::LOCKED == 0, CounterError::SlotLocked);
+
+    let previous_count = array.slots[slot];
+    let new_count = match op {
+        CounterOperation::Increment => previous_count.checked_add(1).ok_or(CounterError::CounterOverflow)?,
+        CounterOperation::Decrement => previous_count.checked_sub(1).ok_or(CounterError::CounterUnderflow)?,
+        CounterOperation::Set | CounterOperation::Reset => unreachable!("slots only move by one"),
+    };
+    array.slots[slot] = new_count;
+
+    emit_event!(ArraySlotUpdated {
+        array: ctx.accounts.array.key(),
+        index,
+        previous_count,
+        new_count,
+        operation: op,
+    });
+
+    verbose_msg!("Counter array {:?} slot {} {}: {} -> {}", id, index, op.as_str(), previous_count, new_count);
+    drop(array);
+    report_diagnostics!(ctx);
+    Ok(())
+}
+
+// CHANGE A SIGNED COUNTER BY `amount`
+// The shared body of `increment_signed` and `decrement_signed` (`op` is one of the two).
+// The result still has to fit in an i64
+pub(crate) fn update_signed(ctx: Context<UpdateSigned>, id: &str, op: CounterOperation, amount: u64) -> Result<()> {
+    require!(amount > 0, CounterError::InvalidAmount);
+
+    let counter = &mut ctx.accounts.counter;
+    let previous_count = counter.count;
+    let new_count = match op {
+        CounterOperation::Increment => counter.stepped(amount, true)?,
+        CounterOperation::Decrement => counter.stepped(amount, false)?,
+        CounterOperation::Set | CounterOperation::Reset => unreachable!("signed counters only move by an amount"),
+    };
+    counter.count = new_count;
+
+    emit_event!(SignedCounterUpdated {
+        user: ctx.accounts.user.key(),
+        counter: counter.key(),
+        previous_count,
+        new_count,
+        operation: op,
+    });
+
+    verbose_msg!("Signed counter {:?} {}: {} -> {}", id, op.as_str(), previous_count, new_count);
+    report_diagnostics!(ctx);
+    Ok(())
+}
+
+// CHANGE A BIG COUNTER BY `amount`
+// The shared body of `increment_big` and `decrement_big` (`op` is one of the two)
+pub(crate) fn update_big(ctx: Context<UpdateBig>, id: &str, op: CounterOperation, amount: u64) -> Result<()> {
+    require!(amount > 0, CounterError::InvalidAmount);
+
+    let counter = &mut ctx.accounts.counter;
+    let previous_count = counter.count();
+    let new_count = match op {
+        CounterOperation::Increment => counter.stepped(amount, true)?,
+        CounterOperation::Decrement => counter.stepped(amount, false)?,
+        CounterOperation::Set | CounterOperation::Reset => unreachable!("big counters only move by an amount"),
+    };
+    counter.set_count(new_count);
+
+    emit_event!(BigCounterUpdated {
+        user: ctx.accounts.user.key(),
+        counter: counter.key(),
+        previous_count,
+        new_count,
+        operation: op,
+    });
+
+    verbose_msg!("Big counter {:?} {}: {} -> {}", id, op.as_str(), previous_count, new_count);
+    report_diagnostics!(ctx);
+    Ok(())
+}
+
+// CHANGE A ZERO-COPY COUNTER BY ONE
+// The shared body of `increment_zc` and `decrement_zc` (`op` is one of the two). Only the
+// count's 8 bytes are read and written
+pub(crate) fn update_zc(ctx: Context<UpdateZC>, id: &str, op: CounterOperation) -> Result<()> {
+    let mut counter = ctx.accounts.counter.load_mut()?;
+    let previous_count = counter.count;
+    let new_count = match op {
+        CounterOperation::Increment => previous_count.checked_add(1).ok_or(CounterError::CounterOverflow)?,
+        CounterOperation::Decrement => previous_count.checked_sub(1).ok_or(CounterError::CounterUnderflow)?,
+        CounterOperation::Set | CounterOperation::Reset => unreachable!("zero-copy counters only move by one"),
+    };
+    counter.count = new_count;
+
+    emit_event!(CounterZCUpdated {
+        counter: ctx.accounts.counter.key(),
+        previous_count,
+        new_count,
+        operation: op,
+    });
+
+    verbose_msg!("Zero-copy counter {:?} {}: {} -> {}", id, op.as_str(), previous_count, new_count);
+    drop(counter);
+    report_diagnostics!(ctx);
+    Ok(())
+}
+
+// ACCOUNTS NEEDED TO START A COUNTER'S CHANGE LOG
+#[derive(Accounts)]
+pub struct InitializeHistory<'info> {
+    #[account(
+        mut,                     // `has_history` is switched on
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // ONE HISTORY PER COUNTER
+    #[account(
+        init,
+        payer = user,
+        space = 8 + std::mem::size_of::<CounterHistory>(),
+        seeds = [b"history", counter.key().as_ref()],
+        bump
+    )]
+    pub history: AccountLoader<'info, CounterHistory>,
+
+    // THE OWNER (pays the history's rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO CREATE A COUNTER ARRAY
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct InitializeArray<'info> {
+    // THE NEW ARRAY - one per (wallet, label), like counters
+    #[account(
+        init,
+        payer = user,
+        space = 8 + std::mem::size_of::<CounterArray>(),
+        seeds = [b"counter_array", user.key().as_ref(), id.as_bytes()],
+        bump
+    )]
+    pub array: AccountLoader<'info, CounterArray>,
+
+    // THE OWNER (pays the array's rent)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO CHANGE A COUNTER ARRAY
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct UpdateArray<'info> {
+    // Derived from the signer, so only the owner's own arrays can be found
+    #[account(
+        mut,
+        seeds = [b"counter_array", user.key().as_ref(), id.as_bytes()],
+        bump = array.load()?.bump,
+    )]
+    pub array: AccountLoader<'info, CounterArray>,
+
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO CREATE A SIGNED COUNTER
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct InitializeSigned<'info> {
+    // THE NEW SIGNED COUNTER - one per (wallet, label), like counters
+    #[account(
+        init,
+        payer = user,
+        space = 8 + SignedCounter::INIT_SPACE,
+        seeds = [b"signed_counter", user.key().as_ref(), id.as_bytes()],
+        bump
+    )]
+    pub counter: Account<'info, SignedCounter>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO CHANGE A SIGNED COUNTER
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct UpdateSigned<'info> {
+    // Derived from the signer, so only the owner's own signed counters can be found
+    #[account(mut, seeds = [b"signed_counter", user.key().as_ref(), id.as_bytes()], bump = counter.bump)]
+    pub counter: Account<'info, SignedCounter>,
+
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO CREATE A BIG COUNTER
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct InitializeBig<'info> {
+    // THE NEW BIG COUNTER - one per (wallet, label), like counters
+    #[account(
+        init,
+        payer = user,
+        space = 8 + BigCounter::INIT_SPACE,
+        seeds = [b"big_counter", user.key().as_ref(), id.as_bytes()],
+        bump
+    )]
+    pub counter: Account<'info, BigCounter>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO CHANGE A BIG COUNTER
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct UpdateBig<'info> {
+    // Derived from the signer, so only the owner's own big counters can be found
+    #[account(mut, seeds = [b"big_counter", user.key().as_ref(), id.as_bytes()], bump = counter.bump)]
+    pub counter: Account<'info, BigCounter>,
+
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO MOVE A COUNTER INTO A BIG COUNTER
+#[derive(Accounts)]
+pub struct UpgradeToBig<'info> {
+    // THE COUNTER BEING UPGRADED (deleted, rent refunded to the owner)
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE NEW BIG COUNTER - the owner's, under the counter's label
+    #[account(
+        init,
+        payer = user,
+        space = 8 + BigCounter::INIT_SPACE,
+        seeds = [b"big_counter", user.key().as_ref(), counter.label.as_bytes()],
+        bump
+    )]
+    pub big_counter: Account<'info, BigCounter>,
+
+    // THE OWNER (pays for the big counter, gets the counter's rent back)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (the counter counts as closed)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // OPTIONAL: THE CREATOR'S LIST OF COUNTERS (the counter is taken off it)
+    #[account(mut, seeds = [b"registry", counter.creator.as_ref()], bump = registry.bump)]
+    pub registry: Option<Account<'info, UserRegistry>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO CREATE A ZERO-COPY COUNTER
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct InitializeZC<'info> {
+    // THE NEW ZERO-COPY COUNTER - one per (wallet, label), like counters
+    #[account(
+        init,
+        payer = user,
+        space = 8 + std::mem::size_of::<CounterZC>(),
+        seeds = [b"counter_zc", user.key().as_ref(), id.as_bytes()],
+        bump
+    )]
+    pub counter: AccountLoader<'info, CounterZC>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO CHANGE A ZERO-COPY COUNTER
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct UpdateZC<'info> {
+    // Derived from the signer, so only the owner's own zero-copy counters can be found
+    #[account(
+        mut,
+        seeds = [b"counter_zc", user.key().as_ref(), id.as_bytes()],
+        bump = counter.load()?.bump,
+    )]
+    pub counter: AccountLoader<'info, CounterZC>,
+
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+// ACCOUNTS NEEDED TO MOVE A COUNTER INTO A ZERO-COPY COUNTER
+#[derive(Accounts)]
+pub struct ConvertToZC<'info> {
+    // THE COUNTER BEING CONVERTED (deleted, rent refunded to the owner)
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"counter", counter.namespace_seed(), counter.creator.as_ref(), counter.label.as_bytes()],
+        bump = counter.bump,
+        constraint = counter.authority == user.key() @ CounterError::Unauthorized, // Only the owner
+        constraint = counter.layout_version == COUNTER_LAYOUT_VERSION @ CounterError::UnsupportedAccountVersion,
+    )]
+    pub counter: Account<'info, Counter>,
+
+    // THE NEW ZERO-COPY COUNTER - the owner's, under the counter's label
+    #[account(
+        init,
+        payer = user,
+        space = 8 + std::mem::size_of::<CounterZC>(),
+        seeds = [b"counter_zc", user.key().as_ref(), counter.label.as_bytes()],
+        bump
+    )]
+    pub counter_zc: AccountLoader<'info, CounterZC>,
+
+    // THE OWNER (pays for the zero-copy counter, gets the counter's rent back)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    // THE PROGRAM-WIDE TOTALS (the counter counts as closed)
+    #[account(mut, seeds = [b"stats"], bump = stats.bump)]
+    pub stats: Account<'info, GlobalStats>,
+
+    // OPTIONAL: THE CREATOR'S LIST OF COUNTERS (the counter is taken off it)
+    #[account(mut, seeds = [b"registry", counter.creator.as_ref()], bump = registry.bump)]
+    pub registry: Option<Account<'info, UserRegistry>>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,152 @@
+// WAGERS
+// Two owners betting on whose counter reaches a target first (see `Wager`)
+
+use crate::*;
+
+// ACCOUNTS NEEDED TO START A WAGER
+// Both sides sign, since both stakes go into the vault straight away
+#[derive(Accounts)]
+#[instruction(opponent: Pubkey)]
+pub struct CreateWager<'info> {
+    // THE NEW WAGER (one at a time per pair of counters)
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Wager::INIT_SPACE,
+        seeds = [b"wager", creator_counter.key().as_ref(), opponent_counter.key().as_ref()],
+        bump
+    )]
+    pub wager: Account<'info, Wager>,
+
+    // WHERE BOTH STAKES ARE HELD UNTIL THE WAGER IS SETTLED
+    #[account(mut, seeds = [b"wager_vault", wager.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+
+    // THE CREATOR'S COUNTER (locked while the wager is open, see `Counter::in_open_wager`)
+    #[account(
+        mut,
+        seeds = [b"counter", creator_counter.namespace_seed(), creator_counter.creator.as_ref(), creator_counter.label.as_bytes()],
+        bump = creator_counter.bump,
+        constraint = creator_counter.authority == user.key() @ CounterError::Unauthorized,
+        constraint = creator_counter.key() != opponent_counter.key() @ CounterError::SameCounter,
+    )]
+    pub creator_counter: Account<'info, Counter>,
+
+    // THE OPPONENT'S COUNTER (locked the same way)
+    #[account(
+        mut,
+        seeds = [b"counter", opponent_counter.namespace_seed(), opponent_counter.creator.as_ref(), opponent_counter.label.as_bytes()],
+        bump = opponent_counter.bump,
+        constraint = opponent_counter.authority == opponent @ CounterError::Unauthorized,
+    )]
+    pub opponent_counter: Account<'info, Counter>,
+
+    // THE CREATOR (pays the wager's rent and their stake)
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // THE OPPONENT (pays their stake)
+    #[account(mut, address = opponent @ CounterError::Unauthorized)]
+    pub opponent_user: Signer<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO PAY OUT A WAGER (anyone can crank it)
+#[derive(Accounts)]
+pub struct SettleWager<'info> {
+    // THE WAGER (closed, its rent goes back to the creator)
+    #[account(
+        mut,
+        seeds = [b"wager", wager.creator_counter.as_ref(), wager.opponent_counter.as_ref()],
+        bump = wager.bump,
+        has_one = creator_counter,
+        has_one = opponent_counter,
+        has_one = creator,
+        has_one = opponent,
+        close = creator,
+    )]
+    pub wager: Account<'info, Wager>,
+
+    #[account(mut, seeds = [b"wager_vault", wager.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+
+    // BOTH COUNTERS (read, and unlocked once the wager is paid out) - the ones the wager was
+    // made on, not counters created at their addresses since
+    #[account(
+        mut,
+        constraint = creator_counter.created_at == wager.creator_counter_created_at @ CounterError::WagerCounterReplaced,
+    )]
+    pub creator_counter: Account<'info, Counter>,
+    #[account(
+        mut,
+        constraint = opponent_counter.created_at == wager.opponent_counter_created_at @ CounterError::WagerCounterReplaced,
+    )]
+    pub opponent_counter: Account<'info, Counter>,
+
+    // BOTH SIDES (whoever wins is paid, a tie pays each their stake back)
+    #[account(mut)]
+    pub creator: SystemAccount<'info>,
+    #[account(mut)]
+    pub opponent: SystemAccount<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNTS NEEDED TO HAND BOTH STAKES BACK AFTER THE DEADLINE (anyone can crank it)
+// The counters aren't needed, so a wager can be refunded even if one of them was closed; the
+// wager stopped locking them at its deadline anyway (see `Counter::in_open_wager`)
+#[derive(Accounts)]
+pub struct RefundWager<'info> {
+    #[account(
+        mut,
+        seeds = [b"wager", wager.creator_counter.as_ref(), wager.opponent_counter.as_ref()],
+        bump = wager.bump,
+        has_one = creator,
+        has_one = opponent,
+        close = creator,
+    )]
+    pub wager: Account<'info, Wager>,
+
+    #[account(mut, seeds = [b"wager_vault", wager.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub creator: SystemAccount<'info>,
+    #[account(mut)]
+    pub opponent: SystemAccount<'info>,
+
+    // THE PROGRAM-WIDE CONFIG (checked for disabled instructions)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// PAY `lamports` OUT OF A WAGER'S VAULT (the vault PDA signs)
+pub(crate) fn pay_from_wager_vault<'info>(
+    wager: Pubkey,
+    vault: &SystemAccount<'info>,
+    vault_bump: u8,
+    to: &SystemAccount<'info>,
+    system_program: &Program<'info, System>,
+    lamports: u64,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[seeds::WAGER_VAULT, wager.as_ref(), &[vault_bump]]];
+    anchor_lang::system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer { from: vault.to_account_info(), to: to.to_account_info() },
+            signer_seeds,
+        ),
+        lamports,
+    )
+}
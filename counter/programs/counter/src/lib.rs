@@ -2,12 +2,186 @@
 // It as a smart contract that runs on the Solana blockchain
 // It manages a simple counter that users can create, increment, decrement, and close
 
+// Downstream programs depend on this crate with the `cpi` feature and call `counter::cpi::*`
+// (accounts in `counter::cpi::accounts`); the generated `initialize` wrappers take as many
+// arguments as the instructions themselves
+#![cfg_attr(feature = "cpi", allow(clippy::too_many_arguments))]
+
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
 
 // This is the unique address of our program on the blockchain
-// Like a street address for a building - this identifies our specific program
+// Like a street address for a building - this identifies our specific program.
+// Each cluster has its own, picked by cargo feature so every deploy builds from the same
+// source: `--features devnet` or `--features mainnet`, and neither for localnet (what the
+// tests use). Keep them in sync with the `[programs.<cluster>]` sections of Anchor.toml
+#[cfg(all(feature = "devnet", feature = "mainnet"))]
+compile_error!("`devnet` and `mainnet` pick different program IDs - enable at most one");
+
+#[cfg(feature = "mainnet")]
+compile_error!("The program isn't deployed to mainnet yet: add its address here and to Anchor.toml's `[programs.mainnet]`");
+
+#[cfg(feature = "devnet")]
+declare_id!("8hQm3nryK3s2x32nm38h5U7usk6QYRBFZbi2j3oU1kG1");
+
+#[cfg(not(any(feature = "devnet", feature = "mainnet")))]
 declare_id!("8hQm3nryK3s2x32nm38h5U7usk6QYRBFZbi2j3oU1kG1");
 
+// SHARED SETTINGS
+// Seeds, limits and flag bits live in the `counter-interface` crate so off-chain code can use
+// exactly the same values. They're re-exported here, so `counter::SHORT_ID_LEN` still works:
+// - SHORT_ID_LEN / SHORT_ID_DEPOSIT_LAMPORTS: short ID length and anti-squatting deposit
+// - MAX_LABEL_LEN: longest counter label (labels let one wallet own many counters)
+// - instruction_flag: one bit per instruction in `ProgramConfig.disabled_instructions(_hi)`
+// - hook: the `on_counter_updated` instruction hook programs receive (see `set_hook`)
+// - HEARTBEAT_INTERVAL_SECONDS: minimum time between two heartbeats for one counter
+// - HISTORY_LEN: how many changes a `CounterHistory` remembers
+// - MAX_BATCH_OPS: the most operations one `apply_batch` call may carry
+// - COUNTER_ARRAY_SLOTS / slot_flag: size of a `CounterArray` and the per-slot flag bits
+// - ACTIVITY_WINDOW_DAYS / SECONDS_PER_DAY: how much per-day activity a counter remembers
+// - RECOVERY_CHALLENGE_SECONDS: how long an owner has to veto a recovery claim
+// - LAMPORTS_PER_SOL / BPS_DENOMINATOR: fee pricing and refund split units
+// - invariant_failure: the bits `verify_invariants` reports (0 = healthy)
+// - LEADERBOARD_SIZE: how many counters the `Leaderboard` ranks
+// - MAX_MEMO_LEN: longest reason `increment_with_memo` / `decrement_with_memo` accept
+// - TOKEN_METADATA_PROGRAM_ID: Metaplex, which owns the NFT metadata `nft_gate` checks
+// - COUNTER_LAYOUT_VERSION: the `Counter.layout_version` every instruction but `migrate` expects
+// - MAX_METADATA_LABEL_LEN / MAX_METADATA_URI_LEN: longest display metadata `set_metadata` takes
+// - MAX_MULTISIG_OWNERS: the most owners a `MultisigAuthority` may have
+// - role: the bits of `Role.roles` (what `grant_role` lets a member do)
+// - PARTNER_ID_LEN / MAX_PARTNERS: partner namespace ID size and how many the admin may approve
+// - cu_budget: the most compute units the common instructions may use
+pub use counter_interface::{
+    cu_budget, hook, instruction_flag, invariant_failure, is_valid_short_id, pda, role, seeds, slot_flag, ACTIVITY_WINDOW_DAYS,
+    BPS_DENOMINATOR, COUNTER_ARRAY_SLOTS, COUNTER_LAYOUT_VERSION, HEARTBEAT_INTERVAL_SECONDS, HISTORY_LEN, LAMPORTS_PER_SOL, RECOVERY_CHALLENGE_SECONDS, SECONDS_PER_DAY,
+    MAX_BATCH_OPS, MAX_LABEL_LEN, MAX_METADATA_LABEL_LEN, MAX_METADATA_URI_LEN, MAX_MULTISIG_OWNERS, MAX_PARTNERS, PARTNER_ID_LEN, LEADERBOARD_SIZE, MAX_MEMO_LEN, SHORT_ID_DEPOSIT_LAMPORTS, SHORT_ID_LEN, TOKEN_METADATA_PROGRAM_ID,
+};
+
+// DIAGNOSTICS (only with `--features diagnostics`)
+// Every instruction finishes by writing its remaining compute units and the size of each
+// account it touched into return data, so integrators can see exactly where costs come from.
+// Without the feature this expands to nothing
+macro_rules! report_diagnostics {
+    ($ctx:expr) => {
+        #[cfg(feature = "diagnostics")]
+        diagnostics::report(&$ctx.accounts.to_account_infos())?;
+    };
+}
+
+// FORMATTED LOG LINES (only with `--features verbose-logs`)
+// `msg!` with arguments formats the line on-chain, which costs compute on every call (keys
+// are turned into base58). Everything these lines say is in the events too, so by default
+// they're compiled out - the arguments are still type-checked, so nothing goes unused
+macro_rules! verbose_msg {
+    ($($arg:tt)*) => {
+        if cfg!(feature = "verbose-logs") {
+            msg!($($arg)*);
+        }
+    };
+}
+
+// EVENTS STAMPED WITH THEIR SCHEMA VERSION (see `events::schema`)
+// `versioned!(Name { .. })` is the struct literal with `schema_version` filled in from the
+// table, and `emit_event!(Name { .. })` emits it. With `legacy-events` the field doesn't
+// exist and they're the plain literal / `emit!`
+macro_rules! versioned {
+    ($name:ident { $($fields:tt)* }) => {
+        $name {
+            #[cfg(not(feature = "legacy-events"))]
+            schema_version: <$name as $crate::schema::EventSchema>::SCHEMA_VERSION,
+            $($fields)*
+        }
+    };
+}
+
+macro_rules! emit_event {
+    ($($event:tt)*) => {
+        emit!(versioned!($($event)*))
+    };
+}
+
+// INDEXED EVENTS (through a self-CPI with `--features event-cpi`)
+// The events indexers build their view from. Log lines get truncated when a transaction
+// logs a lot, so with the feature these go out as self-CPI instruction data instead (what
+// Anchor's `emit_cpi!` does), which is always kept. The instruction's accounts struct has to
+// be an `#[event_cpi]` one
+macro_rules! emit_indexed {
+    ($ctx:expr, $name:ident { $($fields:tt)* }) => {
+        emit_indexed!($ctx, versioned!($name { $($fields)* }))
+    };
+    ($ctx:expr, $event:expr) => {
+        #[cfg(feature = "event-cpi")]
+        event_cpi::emit(&$ctx.accounts.event_authority, $ctx.bumps.event_authority, &$event)?;
+        #[cfg(not(feature = "event-cpi"))]
+        emit!($event);
+    };
+}
+
+#[cfg(feature = "event-cpi")]
+pub mod event_cpi {
+    use super::*;
+    use anchor_lang::solana_program::{instruction::{AccountMeta, Instruction}, program::invoke_signed};
+
+    // The body of `emit_cpi!`, which only works when `ctx` is written out at the call site
+    pub fn emit<E: anchor_lang::Event>(event_authority: &AccountInfo, bump: u8, event: &E) -> Result<()> {
+        let data = [anchor_lang::event::EVENT_IX_TAG_LE, &event.data()].concat();
+        let ix = Instruction::new_with_bytes(crate::ID, &data, vec![AccountMeta::new_readonly(event_authority.key(), true)]);
+        invoke_signed(&ix, std::slice::from_ref(event_authority), &[&[seeds::EVENT_AUTHORITY, &[bump]]])?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics {
+    use super::*;
+
+    // THE METRICS WRITTEN TO RETURN DATA (Borsh-encoded)
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct Diagnostics {
+        pub compute_units_remaining: u64,
+        pub account_sizes: Vec<AccountSize>,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct AccountSize {
+        pub account: Pubkey,
+        pub data_len: u32,
+    }
+
+    pub fn report(accounts: &[AccountInfo]) -> Result<()> {
+        let report = Diagnostics {
+            compute_units_remaining: anchor_lang::solana_program::compute_units::sol_remaining_compute_units(),
+            account_sizes: accounts
+                .iter()
+                .map(|info| AccountSize { account: info.key(), data_len: info.data_len() as u32 })
+                .collect(),
+        };
+        anchor_lang::solana_program::program::set_return_data(&report.try_to_vec()?);
+        Ok(())
+    }
+}
+
+// THE REST OF THE PROGRAM
+// - state: the accounts we store and what instructions return, with the logic that only
+//   needs the account itself (e.g. `apply_op`, the counting arithmetic)
+// - policy: the rules every counter operation has to pass (`CounterPolicy`)
+// - instructions: the accounts each instruction needs, and the handler bodies several share
+// - events / errors: what the program announces, and how it fails
+// Everything is re-exported here, so `counter::Counter` or `counter::CounterError` still work
+pub mod errors;
+pub mod events;
+pub mod instructions;
+pub mod policy;
+pub mod state;
+
+pub use errors::*;
+pub use events::*;
+pub use instructions::*;
+pub use policy::*;
+pub use state::*;
+
+
 // This defines our program module and all its functionality
 #[program]
 pub mod counter {
@@ -15,229 +189,3473 @@ pub mod counter {
 
     // CREATE A NEW COUNTER ACCOUNT
     // This is like opening a new bank account - it creates a place to store your counter
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        // Get references to the accounts we need
-        let counter = &mut ctx.accounts.counter;  // The new counter account we're creating
-        let user = &ctx.accounts.user;           // The person creating the counter
+    // `min` / `max` optionally limit the values the counter may ever hold (e.g. a loyalty
+    // points cap); the counter starts at `min`. With `is_public` set, anyone can increment it
+    // (a community click counter) - decrementing, correcting and closing stay owner-only.
+    // `cooldown_seconds` is the shortest time allowed between two increments (against bot spam).
+    // `overflow_mode` says what happens when an increment/decrement would leave `min..=max`
+    // (see `OverflowMode`; failing is the default). After `expires_at` (a unix timestamp) the
+    // counter can't be changed any more and anyone may clean it up with `collect_expired`.
+    // `step` is how much `increment` / `decrement` move it by (1 when left out). With
+    // `window_start` / `window_end` (unix timestamps) the count can only change between them,
+    // e.g. for a vote; neither can be changed later, and `finalize` locks the result once the
+    // window has closed. With `recovery_authority` and `inactivity_threshold` (seconds) set, that
+    // wallet may take the counter over once it has gone that long without a change to the count
+    // (see `claim_authority`), in case the owner's key is lost
+    #[allow(clippy::too_many_arguments)] // Each argument is part of the instruction's wire format
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        id: String,
+        min: Option<u64>,
+        max: Option<u64>,
+        is_public: bool,
+        cooldown_seconds: Option<i64>,
+        overflow_mode: Option<OverflowMode>,
+        expires_at: Option<i64>,
+        nft_gate: Option<Pubkey>,
+        step: Option<u64>,
+        window_start: Option<i64>,
+        window_end: Option<i64>,
+        recovery_authority: Option<Pubkey>,
+        inactivity_threshold: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INITIALIZE)?;
+        let settings = CounterSettings {
+            min,
+            max,
+            is_public,
+            cooldown_seconds,
+            overflow_mode,
+            expires_at,
+            nft_gate,
+            step,
+            window_start,
+            window_end,
+            recovery_authority,
+            inactivity_threshold,
+        }
+        .or_defaults(ctx.accounts.defaults.as_deref());
+        let user = ctx.accounts.user.key();
+        register_counter(
+            &mut ctx.accounts.registry,
+            ctx.bumps.registry,
+            user,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            &ctx.accounts.config,
+            ctx.accounts.counter.key(),
+            &id,
+        )?;
+        let mut initialized =
+            create_counter(&mut ctx.accounts.counter, ctx.bumps.counter, user, &mut ctx.accounts.stats, id, settings)?;
+        ctx.accounts.counter.payer = ctx.accounts.payer.key(); // Refunded the rent when it's closed
+        initialized.sponsor = ctx.accounts.counter.payer;
+        collect_init_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.payer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+            initialized.counter,
+        )?;
+        emit_indexed!(ctx, initialized);
+        report_diagnostics!(ctx);
+        Ok(()) // Return success
+    }
+
+    // CREATE A COUNTER UNLESS IT ALREADY EXISTS
+    // Same arguments as `initialize`, but calling it for a counter that's already there is a
+    // silent no-op (nothing changes, nothing is emitted) instead of an error, so clients that
+    // may race with themselves can call it unconditionally
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_if_needed(
+        ctx: Context<InitializeIfNeeded>,
+        id: String,
+        min: Option<u64>,
+        max: Option<u64>,
+        is_public: bool,
+        cooldown_seconds: Option<i64>,
+        overflow_mode: Option<OverflowMode>,
+        expires_at: Option<i64>,
+        nft_gate: Option<Pubkey>,
+        step: Option<u64>,
+        window_start: Option<i64>,
+        window_end: Option<i64>,
+        recovery_authority: Option<Pubkey>,
+        inactivity_threshold: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INITIALIZE)?;
+        if ctx.accounts.counter.authority != Pubkey::default() {
+            return Ok(()); // Created earlier - a fresh account is all zeroes
+        }
+        let settings = CounterSettings {
+            min,
+            max,
+            is_public,
+            cooldown_seconds,
+            overflow_mode,
+            expires_at,
+            nft_gate,
+            step,
+            window_start,
+            window_end,
+            recovery_authority,
+            inactivity_threshold,
+        };
+        let user = ctx.accounts.user.key();
+        register_counter(
+            &mut ctx.accounts.registry,
+            ctx.bumps.registry,
+            ctx.accounts.user.key(),
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            &ctx.accounts.config,
+            ctx.accounts.counter.key(),
+            &id,
+        )?;
+        let initialized =
+            create_counter(&mut ctx.accounts.counter, ctx.bumps.counter, user, &mut ctx.accounts.stats, id, settings)?;
+        collect_init_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.user,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+            initialized.counter,
+        )?;
+        emit_indexed!(ctx, initialized);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CREATE A COUNTER IN A PARTNER'S NAMESPACE
+    // Same arguments as `initialize` after `partner_id`, which must be one the admin approved
+    // with `approve_partner`. The counter's address is derived from
+    // ["counter", partner_id, creator, id], so partners get their own label space; every other
+    // instruction reads the namespace back from the counter and works on it unchanged
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_namespaced(
+        ctx: Context<InitializeNamespaced>,
+        partner_id: [u8; PARTNER_ID_LEN],
+        id: String,
+        min: Option<u64>,
+        max: Option<u64>,
+        is_public: bool,
+        cooldown_seconds: Option<i64>,
+        overflow_mode: Option<OverflowMode>,
+        expires_at: Option<i64>,
+        nft_gate: Option<Pubkey>,
+        step: Option<u64>,
+        window_start: Option<i64>,
+        window_end: Option<i64>,
+        recovery_authority: Option<Pubkey>,
+        inactivity_threshold: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INITIALIZE_NAMESPACED)?;
+        require!(ctx.accounts.config.partners.contains(&partner_id), CounterError::UnknownPartner);
+        let settings = CounterSettings {
+            min,
+            max,
+            is_public,
+            cooldown_seconds,
+            overflow_mode,
+            expires_at,
+            nft_gate,
+            step,
+            window_start,
+            window_end,
+            recovery_authority,
+            inactivity_threshold,
+        };
+        let user = ctx.accounts.user.key();
+        register_counter(
+            &mut ctx.accounts.registry,
+            ctx.bumps.registry,
+            ctx.accounts.user.key(),
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            &ctx.accounts.config,
+            ctx.accounts.counter.key(),
+            &id,
+        )?;
+        let initialized =
+            create_counter(&mut ctx.accounts.counter, ctx.bumps.counter, user, &mut ctx.accounts.stats, id, settings)?;
+        ctx.accounts.counter.namespace = partner_id;
+        collect_init_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.user,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+            initialized.counter,
+        )?;
+        emit_indexed!(ctx, initialized);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CHANGE THE COUNTER BY ANY AMOUNT IN ONE GO
+    // Positive `delta` adds, negative subtracts; zero is rejected
+    // This is the consolidated update instruction - `increment`/`decrement` forward to it
+    // With `dry_run` set, every check runs but nothing is saved (see `WouldSucceed`)
+    pub fn apply_delta(ctx: Context<Update>, id: String, delta: i64, dry_run: bool, expected_seq: Option<u64>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::APPLY_DELTA)?;
+        require!(delta != 0, CounterError::InvalidAmount);
+
+        let op = if delta > 0 { PolicyOp::Increment } else { PolicyOp::Decrement };
+        update_count(ctx, &id, op, delta.unsigned_abs(), dry_run, expected_seq, String::new())
+    }
+
+    // ADD ONE TO THE COUNTER, CREDITING WHOEVER REFERRED YOU
+    // `referrer` has to own the counter passed in as `referrer_counter`, whose `referrals`
+    // goes up by one (nobody can refer themselves). The increment itself is checked and
+    // charged exactly like `increment`
+    pub fn increment_with_referrer(
+        ctx: Context<Update>,
+        id: String,
+        referrer: Pubkey,
+        dry_run: bool,
+        expected_seq: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INCREMENT_WITH_REFERRER)?;
+        let user = ctx.accounts.user.key();
+        let counter = ctx.accounts.counter.key();
+        let Some(referrer_counter) = ctx.accounts.referrer_counter.as_mut() else {
+            return err!(CounterError::InvalidReferrer);
+        };
+        require!(referrer_counter.authority == referrer && referrer != user, CounterError::InvalidReferrer);
+
+        if !dry_run {
+            referrer_counter.referrals = referrer_counter.referrals.saturating_add(1);
+            emit_event!(ReferralRecorded {
+                referrer,
+                referrer_counter: referrer_counter.key(),
+                counter,
+                user,
+                referrals: referrer_counter.referrals,
+            });
+        }
+        let step = ctx.accounts.counter.step;
+        update_count(ctx, &id, PolicyOp::Increment, step, dry_run, expected_seq, String::new())
+    }
+
+    // ADD ONE TO THE COUNTER, AT MOST ONCE PER `nonce`
+    // For relayers submitting intents users signed off-chain: `nonce` has to be higher than the
+    // last one the counter accepted (`NonceAlreadyUsed` otherwise), so a relayed intent can't be
+    // replayed. The increment itself is checked and charged exactly like `increment`
+    pub fn increment_with_nonce(
+        ctx: Context<Update>,
+        id: String,
+        nonce: u64,
+        dry_run: bool,
+        expected_seq: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INCREMENT_WITH_NONCE)?;
+        let counter = &mut ctx.accounts.counter;
+        require!(nonce > counter.last_nonce, CounterError::NonceAlreadyUsed);
+        if !dry_run {
+            counter.last_nonce = nonce;
+        }
+        let step = counter.step;
+        update_count(ctx, &id, PolicyOp::Increment, step, dry_run, expected_seq, String::new())
+    }
+
+    // ADD ONE TO THE COUNTER, SAYING WHY
+    // Like `increment`, with a short reason (at most MAX_MEMO_LEN bytes) that is recorded in
+    // the change's `CounterUpdated` event
+    pub fn increment_with_memo(
+        ctx: Context<Update>,
+        id: String,
+        memo: String,
+        dry_run: bool,
+        expected_seq: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INCREMENT_WITH_MEMO)?;
+        require!(memo.len() <= MAX_MEMO_LEN, CounterError::MemoTooLong);
+        let step = ctx.accounts.counter.step;
+        update_count(ctx, &id, PolicyOp::Increment, step, dry_run, expected_seq, memo)
+    }
+
+    // ADD THE CALLER'S TOKEN BALANCE TO THE COUNTER
+    // Adds the caller's balance of the counter's `weight_mint` divided by its `weight_divisor`
+    // (see `set_weight_config`) instead of `step`, for votes weighted by stake. The caller passes
+    // their token account for the mint, and the weight is recorded in `CounterUpdated`
+    pub fn increment_weighted(ctx: Context<Update>, id: String, dry_run: bool, expected_seq: Option<u64>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INCREMENT_WEIGHTED)?;
+        let weight = ctx.accounts.weight()?;
+        update_count_weighted(ctx, &id, PolicyOp::Increment, weight, dry_run, expected_seq, String::new(), weight)
+    }
+
+    // SUBTRACT ONE FROM THE COUNTER, SAYING WHY (see `increment_with_memo`)
+    pub fn decrement_with_memo(
+        ctx: Context<Update>,
+        id: String,
+        memo: String,
+        dry_run: bool,
+        expected_seq: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::DECREMENT_WITH_MEMO)?;
+        require!(memo.len() <= MAX_MEMO_LEN, CounterError::MemoTooLong);
+        let step = ctx.accounts.counter.step;
+        update_count(ctx, &id, PolicyOp::Decrement, step, dry_run, expected_seq, memo)
+    }
+
+    // ADD ANY AMOUNT TO THE COUNTER
+    // For clients that batch several presses off-chain and settle them in one transaction
+    pub fn increment_by(ctx: Context<Update>, id: String, amount: u64, dry_run: bool, expected_seq: Option<u64>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INCREMENT_BY)?;
+        require!(amount > 0, CounterError::InvalidAmount);
+        update_count(ctx, &id, PolicyOp::Increment, amount, dry_run, expected_seq, String::new())
+    }
+
+    // SUBTRACT ANY AMOUNT FROM THE COUNTER
+    // Fails with `CounterUnderflow` if the counter would go below zero
+    pub fn decrement_by(ctx: Context<Update>, id: String, amount: u64, dry_run: bool, expected_seq: Option<u64>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::DECREMENT_BY)?;
+        require!(amount > 0, CounterError::InvalidAmount);
+        update_count(ctx, &id, PolicyOp::Decrement, amount, dry_run, expected_seq, String::new())
+    }
+
+    // ADD ONE, BUT ONLY IF THE COUNTER STILL HOLDS `expected` (compare-and-swap)
+    // Lets clients that may double-submit say "I saw 41, make it 42": if someone else got in
+    // first the call fails with `CountMismatch` instead of counting twice
+    pub fn increment_if(
+        ctx: Context<Update>,
+        id: String,
+        expected: u64,
+        dry_run: bool,
+        expected_seq: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INCREMENT_IF)?;
+        require!(ctx.accounts.counter.count == expected, CounterError::CountMismatch);
+        let step = ctx.accounts.counter.step;
+        update_count(ctx, &id, PolicyOp::Increment, step, dry_run, expected_seq, String::new())
+    }
+
+    // OVERWRITE THE COUNTER WITH AN EXACT VALUE
+    // For correcting counters that drifted (e.g. because of a client bug). Owner only, and the
+    // new value still has to respect the counter's bounds
+    pub fn set_count(
+        ctx: Context<Update>,
+        id: String,
+        new_value: u64,
+        dry_run: bool,
+        expected_seq: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_COUNT)?;
+        update_count(ctx, &id, PolicyOp::Set, new_value, dry_run, expected_seq, String::new())
+    }
+
+    // PUT THE COUNTER BACK TO ITS STARTING VALUE (its minimum, zero by default)
+    pub fn reset(ctx: Context<Update>, id: String, dry_run: bool, expected_seq: Option<u64>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::RESET)?;
+        update_count(ctx, &id, PolicyOp::Reset, 0, dry_run, expected_seq, String::new())
+    }
+
+    // APPLY SEVERAL CHANGES IN ONE INSTRUCTION
+    // Instead of one transaction per increment: `ops` run in order, each one checked like the
+    // matching single instruction would be, and the counter only changes if all of them pass.
+    // One `CounterBatchApplied` event reports the start and end values
+    pub fn apply_batch(
+        ctx: Context<Update>,
+        id: String,
+        ops: Vec<BatchOp>,
+        dry_run: bool,
+        expected_seq: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::APPLY_BATCH)?;
+        update_batch(ctx, &id, &ops, dry_run, expected_seq)
+    }
+
+    // =====================================================================================
+    // ALIASES - Old instruction names kept for existing clients and CPIs
+    // Anchor derives each instruction's 8-byte discriminator from its name, so these must
+    // never be renamed. They keep their own kill switch bits and forward to the new logic
+    // =====================================================================================
+
+    // ADD ONE TO THE COUNTER
+    // This increases the counter by 1 (or its `step`), like pressing the "+" button
+    // Alias for `apply_delta(step, ..)`
+    pub fn increment(ctx: Context<Update>, id: String, dry_run: bool, expected_seq: Option<u64>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INCREMENT)?;
+        let step = ctx.accounts.counter.step;
+        update_count(ctx, &id, PolicyOp::Increment, step, dry_run, expected_seq, String::new())
+    }
+
+    // SUBTRACT ONE FROM THE COUNTER
+    // This decreases the counter by 1 (or its `step`), like pressing the "-" button
+    // Alias for `apply_delta(-step, ..)`
+    pub fn decrement(ctx: Context<Update>, id: String, dry_run: bool, expected_seq: Option<u64>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::DECREMENT)?;
+        let step = ctx.accounts.counter.step;
+        update_count(ctx, &id, PolicyOp::Decrement, step, dry_run, expected_seq, String::new())
+    }
+
+    // DELETE THE COUNTER ACCOUNT
+    // This permanently removes the counter and gives back the rent money to the owner
+    // (or to `rent_recipient`, if one was passed in - see `close_to`)
+    // With `dry_run` set, every check runs but the account is left in place
+    pub fn close(ctx: Context<Close>, id: String, dry_run: bool, expected_seq: Option<u64>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CLOSE)?;
+        close_counter(ctx, &id, dry_run, expected_seq)
+    }
+
+    // DELETE THE COUNTER ACCOUNT, SENDING THE RENT SOMEWHERE ELSE
+    // For custodial wallets that close counters but keep funds in cold storage: the refund
+    // goes to `rent_recipient`, which has to be passed in
+    pub fn close_to(ctx: Context<Close>, id: String, dry_run: bool, expected_seq: Option<u64>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CLOSE_TO)?;
+        require!(ctx.accounts.rent_recipient.is_some(), CounterError::RentRecipientRequired);
+        close_counter(ctx, &id, dry_run, expected_seq)
+    }
+
+    // DELETE THE COUNTER AND EVERYTHING THAT BELONGS TO IT
+    // `close`, plus the counter's change log, operator approvals, sessions, contributor
+    // registrations, roles and the owner's own snapshots, passed in as remaining accounts (writable).
+    // Each is checked to really belong to this counter, and all their rent goes to the owner
+    pub fn close_all<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Close<'info>>,
+        id: String,
+        expected_seq: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CLOSE_ALL)?;
+        let (counter, user) = (ctx.accounts.counter.key(), ctx.accounts.user.key());
+        let lamports = close_children(counter, user, ctx.remaining_accounts, &ctx.accounts.user.to_account_info())?;
+
+        emit_event!(CounterChildrenClosed {
+            counter,
+            accounts: ctx.remaining_accounts.len() as u16,
+            lamports,
+        });
+        verbose_msg!("Closed {} accounts of counter {}, refunding {} lamports", ctx.remaining_accounts.len(), counter, lamports);
+        close_counter(ctx, &id, false, expected_seq)
+    }
 
-        // Set up the counter with starting values
-        counter.count = 0;                    // Start counting from zero
-        counter.bump = ctx.bumps.counter;     // Store the "bump" seed for finding this account later
-        counter.authority = user.key();       // Mark this user as the owner of this counter
+    // START THE CLOSE TIMELOCK (owner only)
+    // Once the admin sets a `close_timelock_seconds`, `close` / `close_to` only go through that
+    // long after this was called, leaving time to `cancel_close` a close made by mistake
+    pub fn request_close(ctx: Context<ManageCounter>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::REQUEST_CLOSE)?;
+        let counter = &ctx.accounts.counter;
+        require!(counter.close_requested_at == 0, CounterError::CloseAlreadyRequested);
+        // Fail now, not when the timelock is up, if the counter couldn't be closed anyway
+        ActivePolicy::validate(&PolicyContext::new(counter, ctx.accounts.user.key(), PolicyOp::Close)?, counter.count)?;
 
-        // Tell the blockchain network about this new counter (like announcing a new account)
-        emit!(CounterInitialized {
-            user: user.key(),
+        let requested_at = Clock::get()?.unix_timestamp;
+        let counter = &mut ctx.accounts.counter;
+        counter.close_requested_at = requested_at;
+        counter.next_version();
+
+        emit_event!(CloseRequested {
             counter: counter.key(),
-            count: counter.count,
+            authority: counter.authority,
+            requested_at,
+            closable_at: requested_at.saturating_add(ctx.accounts.config.close_timelock_seconds),
         });
 
-        // Log a message that will appear in blockchain explorers
-        msg!("Counter initialized for user: {} with count: {}", user.key(), counter.count);
-        Ok(()) // Return success
+        verbose_msg!("Close of counter {} requested", counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
     }
 
-    // ADD ONE TO THE COUNTER
-    // This increases the counter by 1, like pressing the "+" button
-    pub fn increment(ctx: Context<Update>) -> Result<()> {
-        let counter = &mut ctx.accounts.counter;  // The counter account we're updating
-        let user = &ctx.accounts.user;           // The person trying to increment
+    // CALL OFF A PENDING CLOSE (owner only)
+    pub fn cancel_close(ctx: Context<ManageCounter>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CANCEL_CLOSE)?;
+        let counter = &mut ctx.accounts.counter;
+        let requested_at = counter.close_requested_at;
+        require!(requested_at != 0, CounterError::CloseNotRequested);
+        counter.close_requested_at = 0;
+        counter.next_version();
 
-        // SECURITY CHECK: Only the owner can change their counter
-        require!(counter.authority == user.key(), CounterError::Unauthorized);
+        emit_event!(CloseCancelled { counter: counter.key(), authority: counter.authority, requested_at });
+
+        verbose_msg!("Close of counter {} cancelled", counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
 
-        let previous_count = counter.count;  // Remember what it was before
+    // CLOSE A COUNTER, BUT LEAVE ROOM TO CHANGE YOUR MIND (owner only)
+    // The counter stops taking changes and stays on chain for the admin's
+    // `close_grace_seconds`: until then the owner can `reopen` it with its value intact, and
+    // afterwards anyone can `reclaim_closed` it. `close` still deletes it straight away
+    pub fn soft_close(ctx: Context<ManageCounter>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SOFT_CLOSE)?;
+        let grace = ctx.accounts.config.close_grace_seconds;
+        require!(grace > 0, CounterError::SoftCloseDisabled);
+        let counter = &ctx.accounts.counter;
+        require!(counter.closed_at == 0, CounterError::AlreadySoftClosed);
+        let closed_at = Clock::get()?.unix_timestamp;
+        ActivePolicy::validate(&PolicyContext::at(counter, ctx.accounts.user.key(), PolicyOp::Close, closed_at), counter.count)?;
 
-        // ADD 1 BUT CHECK FOR MAXIMUM LIMIT
-        // Solana numbers can't go above a certain size, so we check before adding
-        counter.count = counter.count.checked_add(1)
-            .ok_or(CounterError::CounterOverflow)?;  // If it would overflow, stop and show error
+        let counter = &mut ctx.accounts.counter;
+        counter.closed_at = closed_at;
+        counter.next_version();
 
-        // Tell the blockchain about this change (like posting on social media)
-        emit!(CounterUpdated {
-            user: user.key(),
+        emit_event!(CounterSoftClosed {
             counter: counter.key(),
-            previous_count,
-            new_count: counter.count,
-            operation: "increment".to_string(),
+            authority: counter.authority,
+            closed_at,
+            reclaimable_at: closed_at.saturating_add(grace),
         });
 
-        // Log what happened for blockchain explorers to see
-        msg!("Counter incremented from {} to {} for user: {}", previous_count, counter.count, user.key());
-        Ok(()) // Return success
+        verbose_msg!("Counter {} soft-closed", counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
     }
 
-    // SUBTRACT ONE FROM THE COUNTER
-    // This decreases the counter by 1, like pressing the "-" button
-    pub fn decrement(ctx: Context<Update>) -> Result<()> {
-        let counter = &mut ctx.accounts.counter;  // The counter account we're updating
-        let user = &ctx.accounts.user;           // The person trying to decrement
+    // UNDO A SOFT CLOSE WHILE ITS GRACE PERIOD LASTS (owner only)
+    pub fn reopen(ctx: Context<ManageCounter>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::REOPEN)?;
+        let closed_at = ctx.accounts.counter.closed_at;
+        require!(closed_at != 0, CounterError::NotSoftClosed);
+        let reclaimable_at = closed_at.saturating_add(ctx.accounts.config.close_grace_seconds);
+        require!(Clock::get()?.unix_timestamp < reclaimable_at, CounterError::CloseGraceLapsed);
+
+        let counter = &mut ctx.accounts.counter;
+        counter.closed_at = 0;
+        counter.next_version();
+
+        emit_event!(CounterReopened { counter: counter.key(), authority: counter.authority, closed_at });
+
+        verbose_msg!("Counter {} reopened", counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CLEAN UP A SOFT-CLOSED COUNTER ONCE ITS GRACE PERIOD IS OVER
+    // Anyone can call this. The rent is split like `collect_expired`'s: the owner gets
+    // everything but the caller's reward
+    pub fn reclaim_closed(ctx: Context<ReclaimClosed>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        config.require_enabled(instruction_flag::RECLAIM_CLOSED)?;
+
+        let counter = &ctx.accounts.counter;
+        require!(counter.closed_at != 0, CounterError::NotSoftClosed);
+        let reclaimable_at = counter.closed_at.saturating_add(config.close_grace_seconds);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= reclaimable_at, CounterError::CloseGraceActive);
+        require_reclaimable(counter, now)?;
+
+        let stats = &mut ctx.accounts.stats;
+        stats.total_closed = stats.total_closed.saturating_add(1);
+        stats.announce();
+        unregister_counter(ctx.accounts.registry.as_mut(), ctx.accounts.counter.key());
+
+        let counter = &ctx.accounts.counter;
+        emit!(CounterClosed::new(ctx.accounts.cranker.key(), counter, counter.authority, &Clock::get()?));
+
+        let authority = ctx.accounts.authority.to_account_info();
+        close_with_refund_split(
+            &counter.to_account_info(),
+            &authority,
+            &authority,
+            &ctx.accounts.cranker.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // MERGE ONE COUNTER INTO ANOTHER (owner of both only)
+    // For consolidating counters created under two labels by mistake: the source's count is
+    // added to the destination (always checked, whatever the destination's overflow mode), and
+    // the source is closed with its rent going back to the owner
+    pub fn merge(ctx: Context<Merge>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::MERGE)?;
+        settle_clock(&mut ctx.accounts.source)?;
+        settle_clock(&mut ctx.accounts.destination)?;
+
+        let (source, destination) = (&ctx.accounts.source, &ctx.accounts.destination);
+        let user = ctx.accounts.user.key();
+        let amount = source.count;
+        let new_count = destination.count.checked_add(amount).ok_or(CounterError::CounterOverflow)?;
+
+        // POLICY CHECKS: the source has to be closable, and the destination has to accept the
+        // new value like an owner correction would (no cooldown, but bounds and lifecycle apply)
+        ActivePolicy::validate(&PolicyContext::new(source, user, PolicyOp::Close)?, amount)?;
+        ActivePolicy::validate(&PolicyContext::new(destination, user, PolicyOp::Set)?, new_count)?;
+
+        let (source_key, destination_key) = (source.key(), destination.key());
+        move_count(
+            &mut ctx.accounts.destination,
+            ctx.accounts.history.as_ref(),
+            user,
+            CounterOperation::Increment,
+            new_count,
+        )?;
+
+        let stats = &mut ctx.accounts.stats;
+        stats.total_closed = stats.total_closed.saturating_add(1);
+        stats.announce();
+        unregister_counter(ctx.accounts.registry.as_mut(), source_key);
+
+        emit!(CounterClosed::new(user, &ctx.accounts.source, user, &Clock::get()?));
+        emit_event!(CountersMerged { source: source_key, destination: destination_key, authority: user, amount, new_count });
+
+        verbose_msg!("Counter {} merged into {}: +{} -> {}", source_key, destination_key, amount, new_count);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // SEND PART OF YOUR COUNT TO SOMEONE ELSE'S COUNTER
+    // A minimal points transfer: the source's owner signs, `amount` comes off the source (checked
+    // like their own decrement) and goes onto the destination, whoever owns it (checked like an
+    // owner correction there: bounds and lifecycle apply, but no cooldown). Both sides use
+    // checked arithmetic whatever their overflow mode
+    pub fn transfer_count(ctx: Context<TransferCount>, amount: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::TRANSFER_COUNT)?;
+        require!(amount > 0, CounterError::InvalidAmount);
+        settle_clock(&mut ctx.accounts.source)?;
+        settle_clock(&mut ctx.accounts.destination)?;
+
+        let (source, destination) = (&ctx.accounts.source, &ctx.accounts.destination);
+        let user = ctx.accounts.user.key();
+        let source_count = source.count.checked_sub(amount).ok_or(CounterError::CounterUnderflow)?;
+        let destination_count = destination.count.checked_add(amount).ok_or(CounterError::CounterOverflow)?;
+        ActivePolicy::validate(&PolicyContext::new(source, user, PolicyOp::Decrement)?, source_count)?;
+        ActivePolicy::validate(&PolicyContext::new(destination, destination.authority, PolicyOp::Set)?, destination_count)?;
+        let recipient = destination.authority;
+
+        move_count(
+            &mut ctx.accounts.source,
+            ctx.accounts.source_history.as_ref(),
+            user,
+            CounterOperation::Decrement,
+            source_count,
+        )?;
+        move_count(
+            &mut ctx.accounts.destination,
+            ctx.accounts.destination_history.as_ref(),
+            user,
+            CounterOperation::Increment,
+            destination_count,
+        )?;
+
+        let (source_key, destination_key) = (ctx.accounts.source.key(), ctx.accounts.destination.key());
+        emit_event!(CountTransferred {
+            source: source_key,
+            destination: destination_key,
+            sender: user,
+            recipient,
+            amount,
+            source_count,
+            destination_count,
+        });
+
+        verbose_msg!("Moved {} from counter {} to counter {}", amount, source_key, destination_key);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // ADD ANOTHER COUNTER'S COUNT TO ONE OF YOURS (for running grand totals)
+    // `source` can belong to anyone and isn't changed; the destination's owner signs. The sum
+    // has to fit in a u64 and in the destination's bounds whatever its `overflow_mode`. The
+    // source's count is read with any decay it's owed taken off
+    pub fn add_from(ctx: Context<AddFrom>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::ADD_FROM)?;
+        settle_clock(&mut ctx.accounts.destination)?;
+
+        let (source, destination) = (&ctx.accounts.source, &ctx.accounts.destination);
+        let user = ctx.accounts.user.key();
+        let now = Clock::get()?.unix_timestamp;
+        let amount = source.pending_decay(now).map_or(source.count, |(decayed, _)| decayed);
+        let destination_count = destination.count.checked_add(amount).ok_or(CounterError::CounterOverflow)?;
+        ActivePolicy::validate(&PolicyContext::new(destination, user, PolicyOp::Set)?, destination_count)?;
+        let source_key = source.key();
+
+        move_count(
+            &mut ctx.accounts.destination,
+            ctx.accounts.destination_history.as_ref(),
+            user,
+            CounterOperation::Increment,
+            destination_count,
+        )?;
+
+        let destination_key = ctx.accounts.destination.key();
+        emit_event!(CounterAddedFrom {
+            source: source_key,
+            destination: destination_key,
+            authority: user,
+            amount,
+            destination_count,
+        });
+
+        verbose_msg!("Added {} from counter {} to counter {}", amount, source_key, destination_key);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // MOVE PART OF A COUNTER INTO A NEW ONE (owner only)
+    // The inverse of `merge`: `amount` is subtracted from the counter and a new counter named
+    // `new_label` is created for the same owner, holding `amount`. The new counter starts with
+    // the default settings (no bounds, private, no cooldown, checked, no expiry), and pays the
+    // creation fee like any other (see `set_fee_schedule`)
+    pub fn split(ctx: Context<Split>, amount: u64, new_label: String) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SPLIT)?;
+        require!(amount > 0, CounterError::InvalidAmount);
+        settle_clock(&mut ctx.accounts.source)?;
+
+        let source = &ctx.accounts.source;
+        let user = ctx.accounts.user.key();
+        let new_count = source.count.checked_sub(amount).ok_or(CounterError::CounterUnderflow)?;
+        ActivePolicy::validate(&PolicyContext::new(source, user, PolicyOp::Decrement)?, new_count)?;
+
+        move_count(
+            &mut ctx.accounts.source,
+            ctx.accounts.history.as_ref(),
+            user,
+            CounterOperation::Decrement,
+            new_count,
+        )?;
+        register_counter(
+            &mut ctx.accounts.registry,
+            ctx.bumps.registry,
+            ctx.accounts.user.key(),
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            &ctx.accounts.config,
+            ctx.accounts.new_counter.key(),
+            &new_label,
+        )?;
+        emit!(create_counter(
+            &mut ctx.accounts.new_counter,
+            ctx.bumps.new_counter,
+            user,
+            &mut ctx.accounts.stats,
+            new_label,
+            CounterSettings::default(),
+        )?);
+        move_count(&mut ctx.accounts.new_counter, None, user, CounterOperation::Increment, amount)?;
+        collect_init_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.user,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+            ctx.accounts.new_counter.key(),
+        )?;
+
+        let (source_key, new_counter_key) = (ctx.accounts.source.key(), ctx.accounts.new_counter.key());
+        emit_event!(CounterSplit { source: source_key, new_counter: new_counter_key, authority: user, amount });
+
+        verbose_msg!("Counter {} split {} into {}", source_key, amount, new_counter_key);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // START A NEW COUNTER SET UP EXACTLY LIKE ONE YOU ALREADY OWN
+    // The new counter (seeds = ["counter", user, new_label]) takes the source's bounds,
+    // overflow mode, public flag, cooldown, expiry, token and NFT gates, daily limit and
+    // milestone interval. With `copy_value` it also starts at the source's count, otherwise at
+    // its minimum. The source itself is left alone, and the creation fee is charged like for
+    // any new counter (see `set_fee_schedule`)
+    pub fn clone_counter(ctx: Context<CloneCounter>, new_label: String, copy_value: bool) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CLONE_COUNTER)?;
+
+        let source = &ctx.accounts.source;
+        let user = ctx.accounts.user.key();
+        let settings = CounterSettings {
+            min: Some(source.min_count),
+            max: Some(source.max_count),
+            is_public: source.is_public,
+            cooldown_seconds: Some(source.cooldown_seconds),
+            overflow_mode: Some(source.overflow_mode),
+            expires_at: Some(source.expires_at),
+            nft_gate: source.nft_gate,
+            step: Some(source.step),
+            window_start: Some(source.window_start),
+            window_end: Some(source.window_end),
+            recovery_authority: None, // The copy belongs to someone else
+            inactivity_threshold: None,
+        };
+        let (gate_mint, min_balance) = (source.gate_mint, source.min_balance);
+        let (daily_limit, milestone_interval) = (source.daily_limit, source.milestone_interval);
+        let (source_key, source_count) = (source.key(), source.count);
+
+        register_counter(
+            &mut ctx.accounts.registry,
+            ctx.bumps.registry,
+            ctx.accounts.user.key(),
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            &ctx.accounts.config,
+            ctx.accounts.new_counter.key(),
+            &new_label,
+        )?;
+        emit!(create_counter(
+            &mut ctx.accounts.new_counter,
+            ctx.bumps.new_counter,
+            user,
+            &mut ctx.accounts.stats,
+            new_label,
+            settings,
+        )?);
+        let new_counter = &mut ctx.accounts.new_counter;
+        new_counter.gate_mint = gate_mint;
+        new_counter.min_balance = min_balance;
+        new_counter.daily_limit = daily_limit;
+        new_counter.milestone_interval = milestone_interval;
+        if copy_value {
+            move_count(new_counter, None, user, CounterOperation::Set, source_count)?;
+        }
+        collect_init_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.user,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+            ctx.accounts.new_counter.key(),
+        )?;
+
+        let new_counter_key = ctx.accounts.new_counter.key();
+        let count = ctx.accounts.new_counter.count;
+        emit_event!(CounterCloned { source: source_key, new_counter: new_counter_key, authority: user, count });
+
+        verbose_msg!("Counter {} cloned into {} starting at {}", source_key, new_counter_key, count);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // SAVE A SET OF COUNTER SETTINGS UNDER A NAME
+    // For organisations that create many counters with the same policy: the template holds what
+    // `initialize` takes (minus the expiry, which is per counter) plus the settings owners
+    // normally add afterwards, and `initialize_from_template` applies them all in one go.
+    // Anyone can create counters from any template; only `params` are checked here
+    pub fn create_template(ctx: Context<CreateTemplate>, name: String, params: TemplateParams) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CREATE_TEMPLATE)?;
+        require!(name.len() <= MAX_LABEL_LEN, CounterError::LabelTooLong);
+        require!(params.min.unwrap_or(0) <= params.max.unwrap_or(u64::MAX), CounterError::InvalidBounds);
+        require!(params.cooldown_seconds.unwrap_or(0) >= 0, CounterError::InvalidCooldown);
+        require!(params.step != Some(0), CounterError::InvalidStep);
+
+        let template = &mut ctx.accounts.template;
+        template.authority = ctx.accounts.user.key();
+        template.name = name;
+        template.params = params;
+        template.bump = ctx.bumps.template;
+
+        emit_event!(TemplateCreated {
+            template: template.key(),
+            authority: template.authority,
+            name: template.name.clone(),
+        });
+
+        verbose_msg!("Template {:?} created: {}", template.name, template.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CREATE A COUNTER WITH A TEMPLATE'S SETTINGS
+    // Like `initialize` (same checks, fees and events), except every setting comes from `template`
+    pub fn initialize_from_template(ctx: Context<InitializeFromTemplate>, id: String) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INITIALIZE_FROM_TEMPLATE)?;
+        let params = ctx.accounts.template.params.clone();
+        let settings = CounterSettings {
+            min: params.min,
+            max: params.max,
+            is_public: params.is_public,
+            cooldown_seconds: params.cooldown_seconds,
+            overflow_mode: params.overflow_mode,
+            expires_at: None,
+            nft_gate: params.nft_gate,
+            step: params.step,
+            window_start: None,
+            window_end: None,
+            recovery_authority: None,
+            inactivity_threshold: None,
+        };
+        let user = ctx.accounts.user.key();
+        register_counter(
+            &mut ctx.accounts.registry,
+            ctx.bumps.registry,
+            ctx.accounts.user.key(),
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            &ctx.accounts.config,
+            ctx.accounts.counter.key(),
+            &id,
+        )?;
+        let initialized =
+            create_counter(&mut ctx.accounts.counter, ctx.bumps.counter, user, &mut ctx.accounts.stats, id, settings)?;
+        let counter = &mut ctx.accounts.counter;
+        counter.fee_lamports = params.fee_lamports;
+        counter.gate_mint = params.gate_mint;
+        counter.min_balance = params.min_balance;
+        counter.daily_limit = params.daily_limit;
+        counter.milestone_interval = params.milestone_interval;
+        collect_init_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.user,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+            initialized.counter,
+        )?;
+
+        let (counter_key, template_key) = (initialized.counter, ctx.accounts.template.key());
+        emit_indexed!(ctx, initialized);
+        emit_event!(CounterCreatedFromTemplate { counter: counter_key, template: template_key, authority: user });
+
+        verbose_msg!("Counter {} created from template {}", counter_key, template_key);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // SAVE THE SETTINGS YOUR NEW COUNTERS START WITH
+    // `initialize` fills in any of `params` it isn't given when the wallet's `UserDefaults` is
+    // passed in, so the same bounds, cooldown and step don't have to be sent with every counter.
+    // Setting them again replaces them; all None turns them off
+    pub fn set_defaults(ctx: Context<SetDefaults>, params: DefaultParams) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_DEFAULTS)?;
+        require!(params.min.unwrap_or(0) <= params.max.unwrap_or(u64::MAX), CounterError::InvalidBounds);
+        require!(params.cooldown_seconds.unwrap_or(0) >= 0, CounterError::InvalidCooldown);
+        require!(params.step != Some(0), CounterError::InvalidStep);
+
+        let defaults = &mut ctx.accounts.defaults;
+        defaults.authority = ctx.accounts.user.key();
+        defaults.params = params;
+        defaults.bump = ctx.bumps.defaults;
+
+        emit_event!(DefaultsUpdated { defaults: defaults.key(), authority: defaults.authority });
+
+        verbose_msg!("Defaults of {} updated", defaults.authority);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CREATE A GROUP OF COUNTERS WITH A RUNNING TOTAL (see `CounterGroup`)
+    // Up to `capacity` counters can `join_group`; the group keeps their counts added up
+    pub fn create_group(ctx: Context<CreateGroup>, name: String, capacity: u32) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CREATE_GROUP)?;
+        require!(name.len() <= MAX_LABEL_LEN, CounterError::LabelTooLong);
+        require!(capacity > 0, CounterError::InvalidGroupCapacity);
+
+        let group = &mut ctx.accounts.group;
+        group.authority = ctx.accounts.user.key();
+        group.name = name;
+        group.total = 0;
+        group.members = 0;
+        group.capacity = capacity;
+        group.bump = ctx.bumps.group;
 
-        // SECURITY CHECK: Only the owner can change their counter
-        require!(counter.authority == user.key(), CounterError::Unauthorized);
+        emit_event!(GroupCreated {
+            group: group.key(),
+            authority: group.authority,
+            name: group.name.clone(),
+            capacity,
+        });
+
+        verbose_msg!("Group {:?} created: {}", group.name, group.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
 
-        let previous_count = counter.count;  // Remember what it was before
+    // ADD A COUNTER TO A GROUP (signed by the counter's owner and the group's authority)
+    // The counter's count is added to the group's total. From then on its changes through
+    // `update_count` / `apply_batch` move the total too, and have to pass the group in as
+    // `group`. Every other way of moving a count (transfers, merges, `add_from`, reveals, feeds,
+    // multisig ops, drains) and the permissionless cleanups refuse a member with `CounterInGroup`,
+    // and decaying or periodically resetting counters can't join at all (`DecayInGroup`,
+    // `ResetInGroup`), since `touch` settles those without the group. So the total always
+    // matches its members' counts
+    pub fn join_group(ctx: Context<JoinGroup>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::JOIN_GROUP)?;
+        let counter = &mut ctx.accounts.counter;
+        let group = &mut ctx.accounts.group;
+        require!(counter.group == Pubkey::default(), CounterError::AlreadyInGroup);
+        require!(counter.closed_at == 0, CounterError::CounterSoftClosed); // It couldn't leave again before being reclaimed
+        require!(counter.decay_rate_per_day == 0, CounterError::DecayInGroup);
+        require!(counter.reset_interval == 0, CounterError::ResetInGroup);
+        require!(group.members < group.capacity, CounterError::GroupFull);
 
-        // SUBTRACT 1 BUT CHECK FOR MINIMUM LIMIT
-        // Can't go below zero, so we check before subtracting
-        counter.count = counter.count.checked_sub(1)
-            .ok_or(CounterError::CounterUnderflow)?;  // If it would go below zero, stop and show error
+        let previous_total = group.total;
+        group.total = group.total.checked_add(counter.count).ok_or(CounterError::CounterOverflow)?;
+        group.members += 1;
+        counter.group = group.key();
+        counter.next_version();
 
-        // Tell the blockchain about this change
-        emit!(CounterUpdated {
-            user: user.key(),
+        emit_event!(GroupTotalUpdated {
+            group: group.key(),
             counter: counter.key(),
-            previous_count,
-            new_count: counter.count,
-            operation: "decrement".to_string(),
+            previous_total,
+            total: group.total,
+            members: group.members,
+            slot: Clock::get()?.slot,
         });
 
-        // Log what happened for blockchain explorers
-        msg!("Counter decremented from {} to {} for user: {}", previous_count, counter.count, user.key());
-        Ok(()) // Return success
+        verbose_msg!("Counter {} joined group {}", counter.key(), group.key());
+        report_diagnostics!(ctx);
+        Ok(())
     }
 
-    // DELETE THE COUNTER ACCOUNT
-    // This permanently removes the counter and gives back the rent money to the owner
-    pub fn close(ctx: Context<Close>) -> Result<()> {
-        let counter = &ctx.accounts.counter;    // The counter account we're deleting
-        let user = &ctx.accounts.user;          // The person trying to close it
+    // TAKE A COUNTER OUT OF ITS GROUP (owner only)
+    // Its count comes off the group's total (never below zero, in case the total missed a
+    // change), and it no longer needs the group passed in. Members have to leave to be closed
+    pub fn leave_group(ctx: Context<LeaveGroup>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::LEAVE_GROUP)?;
+        let counter = &mut ctx.accounts.counter;
+        let group = &mut ctx.accounts.group;
+
+        let previous_total = group.total;
+        group.total = group.total.saturating_sub(counter.count);
+        group.members = group.members.saturating_sub(1);
+        counter.group = Pubkey::default();
+        counter.next_version();
+
+        emit_event!(GroupTotalUpdated {
+            group: group.key(),
+            counter: counter.key(),
+            previous_total,
+            total: group.total,
+            members: group.members,
+            slot: Clock::get()?.slot,
+        });
+
+        verbose_msg!("Counter {} left group {}", counter.key(), group.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // BET ANOTHER OWNER THAT YOUR COUNTER REACHES `target` FIRST (signed by both)
+    // Each side puts `stake_lamports` into the wager's vault. Whoever's counter is at `target`
+    // or above when `settle_wager` runs (before `deadline`) gets both stakes; after the
+    // deadline `refund_wager` gives each their stake back. Until then both counters are locked
+    // to moving a step at a time (see `check_wager`): no corrections, `increment_by` jumps or
+    // value moved in from other counters, which would let either owner jump to the target and
+    // settle in one transaction. Public counters can still be pushed along by anyone, so both
+    // sides should know what they're betting on
+    pub fn create_wager(
+        ctx: Context<CreateWager>,
+        opponent: Pubkey,
+        target: u64,
+        stake_lamports: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CREATE_WAGER)?;
+        require!(stake_lamports >= Rent::get()?.minimum_balance(0), CounterError::InvalidStake);
+        require!(deadline > Clock::get()?.unix_timestamp, CounterError::InvalidExpiry);
+        let (creator_counter, opponent_counter) = (&ctx.accounts.creator_counter, &ctx.accounts.opponent_counter);
+        require!(target > creator_counter.count.max(opponent_counter.count), CounterError::InvalidWagerTarget);
+        let now = Clock::get()?.unix_timestamp;
+        for counter in [&mut ctx.accounts.creator_counter, &mut ctx.accounts.opponent_counter] {
+            if !counter.in_open_wager(now) {
+                counter.open_wagers = 0; // Whatever it was in can only be refunded by now
+            }
+            counter.open_wagers = counter.open_wagers.checked_add(1).ok_or(CounterError::TooManyWagers)?;
+            counter.wager_deadline = counter.wager_deadline.max(deadline);
+        }
+
+        for payer in [ctx.accounts.user.to_account_info(), ctx.accounts.opponent_user.to_account_info()] {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer { from: payer, to: ctx.accounts.vault.to_account_info() },
+                ),
+                stake_lamports,
+            )?;
+        }
+
+        let wager = &mut ctx.accounts.wager;
+        wager.creator = ctx.accounts.user.key();
+        wager.opponent = opponent;
+        wager.creator_counter = ctx.accounts.creator_counter.key();
+        wager.opponent_counter = ctx.accounts.opponent_counter.key();
+        wager.target = target;
+        wager.stake_lamports = stake_lamports;
+        wager.deadline = deadline;
+        wager.bump = ctx.bumps.wager;
+        wager.creator_counter_created_at = ctx.accounts.creator_counter.created_at;
+        wager.opponent_counter_created_at = ctx.accounts.opponent_counter.created_at;
+
+        emit_event!(WagerCreated {
+            wager: wager.key(),
+            creator_counter: wager.creator_counter,
+            opponent_counter: wager.opponent_counter,
+            target,
+            stake_lamports,
+            deadline,
+        });
+
+        verbose_msg!("Wager {} created: first to {} wins {} lamports", wager.key(), target, stake_lamports * 2);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // PAY OUT A WAGER ONCE A COUNTER HAS REACHED THE TARGET (anyone can crank it)
+    // The winner gets the whole vault. If both counters are at the target by the time it's
+    // settled, it's a tie and each side gets their stake back
+    pub fn settle_wager(ctx: Context<SettleWager>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SETTLE_WAGER)?;
+        let wager = &ctx.accounts.wager;
+        require!(Clock::get()?.unix_timestamp <= wager.deadline, CounterError::WagerExpired);
+        let creator_won = ctx.accounts.creator_counter.count >= wager.target;
+        let opponent_won = ctx.accounts.opponent_counter.count >= wager.target;
+        require!(creator_won || opponent_won, CounterError::WagerTargetNotReached);
+
+        for counter in [&mut ctx.accounts.creator_counter, &mut ctx.accounts.opponent_counter] {
+            counter.open_wagers = counter.open_wagers.saturating_sub(1); // Unlocked once none is left
+        }
+
+        let wager = &ctx.accounts.wager;
+        let (wager_key, vault_bump) = (wager.key(), ctx.bumps.vault);
+        let accounts = &ctx.accounts;
+        let (winner, payout) = match (creator_won, opponent_won) {
+            (true, true) => {
+                pay_from_wager_vault(wager_key, &accounts.vault, vault_bump, &accounts.creator, &accounts.system_program, wager.stake_lamports)?;
+                pay_from_wager_vault(wager_key, &accounts.vault, vault_bump, &accounts.opponent, &accounts.system_program, accounts.vault.lamports())?;
+                (Pubkey::default(), 0)
+            }
+            (true, false) => (accounts.creator.key(), accounts.vault.lamports()),
+            _ => (accounts.opponent.key(), accounts.vault.lamports()),
+        };
+        if payout > 0 {
+            let to = if winner == accounts.creator.key() { &accounts.creator } else { &accounts.opponent };
+            pay_from_wager_vault(wager_key, &accounts.vault, vault_bump, to, &accounts.system_program, payout)?;
+        }
+
+        emit_event!(WagerSettled { wager: wager_key, winner, payout });
+
+        verbose_msg!("Wager {} settled, {} lamports to {}", wager_key, payout, winner);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // GIVE BOTH STAKES BACK ONCE A WAGER'S DEADLINE HAS PASSED (anyone can crank it)
+    pub fn refund_wager(ctx: Context<RefundWager>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::REFUND_WAGER)?;
+        let wager = &ctx.accounts.wager;
+        require!(Clock::get()?.unix_timestamp > wager.deadline, CounterError::WagerNotExpired);
+
+        let (wager_key, vault_bump) = (wager.key(), ctx.bumps.vault);
+        let accounts = &ctx.accounts;
+        pay_from_wager_vault(wager_key, &accounts.vault, vault_bump, &accounts.creator, &accounts.system_program, wager.stake_lamports)?;
+        pay_from_wager_vault(wager_key, &accounts.vault, vault_bump, &accounts.opponent, &accounts.system_program, accounts.vault.lamports())?;
+
+        emit_event!(WagerSettled { wager: wager_key, winner: Pubkey::default(), payout: 0 });
+
+        verbose_msg!("Wager {} refunded", wager_key);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // MOVE A COUNTER TO A NEW LIFECYCLE STATE
+    // Every lifecycle feature goes through here, so the rules about which state can
+    // follow which live in one place (`CounterState::can_transition_to`)
+    pub fn transition_state(
+        ctx: Context<ManageCounter>,
+        target: CounterState,
+        dry_run: bool,
+        expected_seq: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::TRANSITION_STATE)?;
+
+        // Migrations are run by the program admin, never by counter owners
+        require!(target != CounterState::Migrating, CounterError::InvalidStateTransition);
+        let counter = &ctx.accounts.counter;
+        let policy = PolicyContext::new(counter, ctx.accounts.user.key(), PolicyOp::TransitionState)?;
+        ActivePolicy::validate(&policy, counter.count)?;
+        require!(counter.state.can_transition_to(target), CounterError::InvalidStateTransition);
+        let now = policy.now;
+        advance_sequencer(ctx.accounts.sequencer.as_mut(), expected_seq, dry_run)?; // Enforce ordering (if requested)
+
+        let counter = &mut ctx.accounts.counter;
+        if dry_run {
+            return report_dry_run(counter.key(), counter.count, counter.count);
+        }
+        counter.last_updated = now;
+        apply_state_transition(counter, target)?;
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // LOCK IN A COUNTER'S RESULT ONCE ITS COUNTING WINDOW HAS CLOSED (anyone can crank it)
+    // Archives the counter for good (see `CounterState`), so the count stays as it was when
+    // `window_end` passed. It can still be read, and closed by its owner
+    pub fn finalize(ctx: Context<Finalize>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::FINALIZE)?;
+        let counter = &mut ctx.accounts.counter;
+        let now = Clock::get()?.unix_timestamp;
+        require!(counter.window_end != 0 && now > counter.window_end, CounterError::WindowNotEnded);
+
+        apply_state_transition(counter, CounterState::Archived)?;
+        emit_event!(CounterFinalized { counter: counter.key(), count: counter.count, window_end: counter.window_end });
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // COMMIT TO A VALUE WITHOUT SHOWING IT (e.g. a sealed vote)
+    // `commitment` is sha256(value as 8 little-endian bytes || a random 32-byte salt). Only
+    // taken before the counter's counting window opens (see `window_start`), so every value
+    // is sealed before the first one is revealed
+    pub fn commit(ctx: Context<Commit>, commitment: [u8; 32]) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::COMMIT)?;
+        let counter = &ctx.accounts.counter;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < counter.window_start, CounterError::CommitPhaseOver);
+
+        let record = &mut ctx.accounts.commitment;
+        record.counter = counter.key();
+        record.user = ctx.accounts.user.key();
+        record.hash = commitment;
+        record.committed_at = now;
+        record.bump = ctx.bumps.commitment;
 
-        // SECURITY CHECK: Only the owner can delete their counter
-        require!(counter.authority == user.key(), CounterError::Unauthorized);
+        emit_event!(ValueCommitted { counter: counter.key(), user: record.user, commitment });
 
-        let final_count = counter.count;  // Remember the final value
+        verbose_msg!("{} committed a value for counter {}", record.user, counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // REVEAL A COMMITTED VALUE AND ADD IT TO THE COUNTER
+    // Only inside the counter's counting window, and through the policy checks of an increment
+    // by `value` from the revealer (so on a public counter, for a poll open to anyone), counting
+    // towards its daily limit. Nothing else of an increment happens: a reveal can't pay fees or
+    // deposits or call a hook, so counters (or a program) that would charge the revealer or run
+    // one are refused (`RevealUnsupported`), as are counters in a group (see `move_count`). The
+    // commitment is closed, refunding its rent
+    pub fn reveal(ctx: Context<Reveal>, value: u64, salt: [u8; 32]) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::REVEAL)?;
+        require!(ctx.accounts.commitment.matches(value, &salt), CounterError::CommitmentMismatch);
+        settle_clock(&mut ctx.accounts.counter)?;
+
+        let counter = &ctx.accounts.counter;
+        let user = ctx.accounts.user.key();
+        let clock = Clock::get()?;
+        let new_count = counter.apply(CountOp::Increment(value))?;
+        let policy = PolicyContext::at(counter, user, PolicyOp::Increment, clock.unix_timestamp);
+        ActivePolicy::validate(&policy, new_count)?;
+        let charged = ActivePolicy::check_fee(&policy, &ctx.accounts.config)? > 0
+            || ActivePolicy::check_counter_fee(&policy, value)? > 0
+            || (counter.fee_amount > 0 && !policy.acts_for_owner())
+            || counter.deposit_per_increment > 0;
+        require!(!charged && counter.hook_program == Pubkey::default(), CounterError::RevealUnsupported);
+
+        move_count(&mut ctx.accounts.counter, ctx.accounts.history.as_ref(), user, CounterOperation::Increment, new_count)?;
+        ctx.accounts.counter.count_daily_increments(clock.unix_timestamp, 1);
+        emit_event!(ValueRevealed { counter: ctx.accounts.counter.key(), user, value, count: new_count });
+
+        verbose_msg!("{} revealed {} for counter {}", user, value, ctx.accounts.counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // LOCK A COUNTER, E.G. WHILE A DISPUTE IS RESOLVED (owner only)
+    // Shorthand for `transition_state(Frozen)`: until `unfreeze`, every increment, decrement,
+    // correction and close fails with `CounterFrozen`
+    pub fn freeze(ctx: Context<ManageCounter>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::FREEZE)?;
+
+        let counter = &ctx.accounts.counter;
+        let policy = PolicyContext::new(counter, ctx.accounts.user.key(), PolicyOp::TransitionState)?;
+        ActivePolicy::validate(&policy, counter.count)?;
+        let now = policy.now;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.last_updated = now;
+        apply_state_transition(counter, CounterState::Frozen)?;
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // UNLOCK A FROZEN COUNTER (owner only)
+    // Shorthand for `transition_state(Active)` from the frozen state
+    pub fn unfreeze(ctx: Context<ManageCounter>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::UNFREEZE)?;
+        require!(ctx.accounts.counter.is_frozen(), CounterError::InvalidStateTransition);
+
+        let counter = &ctx.accounts.counter;
+        let policy = PolicyContext::new(counter, ctx.accounts.user.key(), PolicyOp::TransitionState)?;
+        ActivePolicy::validate(&policy, counter.count)?;
+        let now = policy.now;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.last_updated = now;
+        apply_state_transition(counter, CounterState::Active)?;
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // START HANDING A COUNTER OVER TO SOMEONE ELSE (step 1 of 2)
+    // Nothing changes hands yet: `new_authority` has to call `accept_authority` first, so a
+    // typo'd address can never take the counter away from its owner. Proposing a new owner
+    // replaces any earlier proposal, and proposing yourself cancels the transfer
+    pub fn transfer_authority(ctx: Context<ManageCounter>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::TRANSFER_AUTHORITY)?;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.pending_authority = (new_authority != counter.authority).then_some(new_authority);
+        counter.next_version();
 
-        // Tell the blockchain we're closing this counter
-        emit!(CounterClosed {
-            user: user.key(),
+        emit_event!(AuthorityTransferInitiated {
             counter: counter.key(),
-            final_count,
+            authority: counter.authority,
+            pending_authority: counter.pending_authority,
         });
 
-        // Log the closure for blockchain explorers
-        msg!("Counter closed for user: {} with final count: {}", user.key(), final_count);
-        Ok(()) // Return success - the account will be deleted automatically
+        verbose_msg!("Counter {} ownership offered to {:?}", counter.key(), counter.pending_authority);
+        report_diagnostics!(ctx);
+        Ok(())
     }
-}
 
-// =====================================================================================
-// ACCOUNTS NEEDED TO CREATE A NEW COUNTER
-// This tells Solana what accounts are required when someone calls the "initialize" function
-#[derive(Accounts)]
-#[instruction()]
-pub struct Initialize<'info> {
-    // THE NEW COUNTER ACCOUNT WE'RE CREATING
-    // This account will store the counter data (count, owner, etc.)
-    #[account(
-        init,                    // Create this account for the first time
-        payer = user,           // The user pays for creating this account (like rent deposit)
-        space = 8 + Counter::INIT_SPACE, // How much storage space to allocate (8 bytes for Anchor + counter data)
-        seeds = [b"counter", user.key().as_ref()], // How to find this account later (deterministic address)
-        bump                     // A number that makes the address unique
-    )]
-    pub counter: Account<'info, Counter>,  // The actual counter account
-
-    // THE PERSON CREATING THE COUNTER
-    #[account(mut)]             // This account can be changed (to pay for the new account)
-    pub user: Signer<'info>,   // Must be signed by the user (they approve this action)
-
-    // SOLANA'S SYSTEM PROGRAM
-    // This is like the "bank" that handles account creation and rent
-    pub system_program: Program<'info, System>,
-}
+    // TAKE OVER A COUNTER THAT WAS OFFERED TO YOU (step 2 of 2)
+    // Must be signed by the proposed owner, which proves the new key is real and controlled
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::ACCEPT_AUTHORITY)?;
 
-// ACCOUNTS NEEDED TO UPDATE A COUNTER (increment/decrement)
-// This tells Solana what accounts are required for changing the counter value
-#[derive(Accounts)]
-pub struct Update<'info> {
-    // THE EXISTING COUNTER ACCOUNT WE'RE CHANGING
-    #[account(
-        mut,                     // This account will be modified
-        seeds = [b"counter", user.key().as_ref()], // How to find the counter account
-        bump = counter.bump,     // Use the stored bump seed
-        constraint = counter.authority == user.key() @ CounterError::Unauthorized // Extra security check
-    )]
-    pub counter: Account<'info, Counter>,  // The counter we're updating
-
-    // THE PERSON MAKING THE CHANGE
-    pub user: Signer<'info>,   // Must be signed by the user
-}
+        let counter = &mut ctx.accounts.counter;
+        let previous_authority = counter.authority;
+        counter.authority = ctx.accounts.new_authority.key();
+        counter.pending_authority = None;
+        counter.next_version();
 
-// ACCOUNTS NEEDED TO CLOSE/DELETE A COUNTER
-// This tells Solana what accounts are required for deleting the counter
-#[derive(Accounts)]
-pub struct Close<'info> {
-    // THE COUNTER ACCOUNT WE'RE DELETING
-    #[account(
-        mut,                     // Account will be modified (then deleted)
-        close = user,           // When deleted, refund the rent to the user
-        seeds = [b"counter", user.key().as_ref()], // How to find the counter
-        bump = counter.bump,     // Use the stored bump seed
-        constraint = counter.authority == user.key() @ CounterError::Unauthorized // Security check
-    )]
-    pub counter: Account<'info, Counter>,  // The counter we're closing
-
-    // THE PERSON CLOSING THE COUNTER
-    #[account(mut)]             // Will receive the rent refund
-    pub user: Signer<'info>,   // Must be signed by the user
-}
+        emit_event!(AuthorityTransferred {
+            counter: counter.key(),
+            previous_authority,
+            new_authority: counter.authority,
+        });
 
-// CUSTOM ERROR MESSAGES FOR OUR PROGRAM
-// When things go wrong, these explain what happened in a user-friendly way
-#[error_code]
-pub enum CounterError {
-    // Trying to count too high (above maximum number)
-    #[msg("Counter would overflow")]
-    CounterOverflow,
-    // Trying to count below zero
-    #[msg("Counter would underflow")]
-    CounterUnderflow,
-    // Someone trying to change a counter they don't own
-    #[msg("Unauthorized access")]
-    Unauthorized,
-}
+        verbose_msg!("Counter {} now owned by {}", counter.key(), counter.authority);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
 
-// =====================================================================================
-// EVENTS - Like announcements our program makes to the blockchain
-// =====================================================================================
+    // CLAIM A COUNTER WHOSE OWNER HAS GONE QUIET (recovery authority only, step 1 of 2)
+    // Allowed once the count hasn't changed for the `inactivity_threshold` set at creation. Opens
+    // a `RECOVERY_CHALLENGE_SECONDS` window in which the owner can `veto_claim`, proving their
+    // key still works; once it has passed, `complete_authority_claim` hands the counter over.
+    // Any change to the count in the meantime voids the claim, and a new one can be made once
+    // the counter has gone quiet again
+    pub fn claim_authority(ctx: Context<ClaimAuthority>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CLAIM_AUTHORITY)?;
+        let counter = &mut ctx.accounts.counter;
+        require!(!counter.claim_pending(), CounterError::ClaimAlreadyPending);
+        let claimed_at = Clock::get()?.unix_timestamp;
+        let last_active_at = counter.last_active_at();
+        require!(
+            claimed_at.saturating_sub(last_active_at) > counter.inactivity_threshold,
+            CounterError::CounterStillActive
+        );
+        counter.recovery_claimed_at = claimed_at;
+        counter.next_version();
 
-// ANNOUNCEMENT WHEN A NEW COUNTER IS CREATED
-#[event]
-pub struct CounterInitialized {
-    pub user: Pubkey,
-    pub counter: Pubkey,
-    pub count: u64,
-}
+        emit_event!(AuthorityClaimed {
+            counter: counter.key(),
+            authority: counter.authority,
+            recovery_authority: ctx.accounts.recovery_authority.key(),
+            last_active_at,
+            claimed_at,
+            completes_at: claimed_at.saturating_add(RECOVERY_CHALLENGE_SECONDS),
+        });
 
-// ANNOUNCEMENT WHEN A COUNTER VALUE CHANGES
-#[event]
-pub struct CounterUpdated {
-    pub user: Pubkey,
-    pub counter: Pubkey,
-    pub previous_count: u64,
-    pub new_count: u64,
-    pub operation: String,
-}
+        verbose_msg!("Counter {} claimed by {}", counter.key(), ctx.accounts.recovery_authority.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
 
-// ANNOUNCEMENT WHEN A COUNTER IS DELETED
-#[event]
-pub struct CounterClosed {
-    pub user: Pubkey,
-    pub counter: Pubkey,
-    pub final_count: u64,
-}
+    // TAKE OVER A CLAIMED COUNTER ONCE THE CHALLENGE WINDOW HAS PASSED (step 2 of 2)
+    // Only if the counter has stayed quiet since the claim (`CounterStillActive` otherwise)
+    pub fn complete_authority_claim(ctx: Context<ClaimAuthority>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::COMPLETE_AUTHORITY_CLAIM)?;
+        let counter = &mut ctx.accounts.counter;
+        let claimed_at = counter.recovery_claimed_at;
+        require!(claimed_at != 0, CounterError::NoPendingClaim);
+        require!(counter.last_active_at() < claimed_at, CounterError::CounterStillActive);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= claimed_at.saturating_add(RECOVERY_CHALLENGE_SECONDS), CounterError::ClaimChallengeActive);
+
+        let previous_authority = counter.authority;
+        counter.authority = ctx.accounts.recovery_authority.key();
+        counter.pending_authority = None; // An offer the lost key made no longer stands
+        counter.recovery_claimed_at = 0;
+        counter.next_version();
+
+        emit_event!(AuthorityTransferred {
+            counter: counter.key(),
+            previous_authority,
+            new_authority: counter.authority,
+        });
+
+        verbose_msg!("Counter {} recovered by {}", counter.key(), counter.authority);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // TURN DOWN A RECOVERY CLAIM ON YOUR COUNTER (owner only)
+    // Also restarts the inactivity clock, so the claim can't simply be made again straight away
+    pub fn veto_claim(ctx: Context<ManageCounter>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::VETO_CLAIM)?;
+        let counter = &mut ctx.accounts.counter;
+        let claimed_at = counter.recovery_claimed_at;
+        require!(claimed_at != 0, CounterError::NoPendingClaim);
+        counter.recovery_claimed_at = 0;
+        counter.recovery_vetoed_at = Clock::get()?.unix_timestamp;
+        counter.next_version();
+
+        emit_event!(AuthorityClaimVetoed {
+            counter: counter.key(),
+            authority: counter.authority,
+            recovery_authority: counter.recovery_authority.unwrap_or_default(),
+            claimed_at,
+        });
+
+        verbose_msg!("Claim on counter {} vetoed", counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // LET ANOTHER WALLET INCREMENT YOUR COUNTER FOR YOU
+    // For backend services that press the button on a user's behalf without holding their key.
+    // `allowance` is how much the operator may add in total; approving the same operator again
+    // replaces the remaining allowance. Handing the counter to a new owner voids every approval
+    pub fn approve_operator(ctx: Context<ApproveOperator>, operator: Pubkey, allowance: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::APPROVE_OPERATOR)?;
+
+        let counter = &ctx.accounts.counter;
+        let delegation = &mut ctx.accounts.delegation;
+        delegation.counter = counter.key();
+        delegation.operator = operator;
+        delegation.authority = counter.authority; // Only valid while this wallet owns the counter
+        delegation.allowance = allowance;
+        delegation.bump = ctx.bumps.delegation;
+
+        emit_event!(OperatorApproved {
+            counter: counter.key(),
+            operator,
+            allowance,
+        });
+
+        verbose_msg!("Operator {} may add {} to counter {}", operator, allowance, counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // TAKE AN OPERATOR'S APPROVAL AWAY (the delegation's rent goes back to the owner)
+    pub fn revoke_operator(ctx: Context<RevokeOperator>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::REVOKE_OPERATOR)?;
+
+        let (counter, operator) = (ctx.accounts.counter.key(), ctx.accounts.delegation.operator);
+        emit_event!(OperatorRevoked { counter, operator });
+
+        verbose_msg!("Operator {} revoked for counter {}", operator, counter);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // LET A THROWAWAY KEY SIGN INCREMENTS AND DECREMENTS FOR YOU (owner only)
+    // For apps that can't ask the wallet to approve every tap: the app keeps `session_key` and
+    // it may increment or decrement as the owner until `expires_at` or until it has used
+    // `max_ops` operations, whichever comes first (each step of an `apply_batch` is one). Only
+    // changes the session is needed for use it up: owners and role holders passing it in don't.
+    // Writes a `Session` PDA (seeds = ["session", counter, session_key]); creating it again
+    // replaces the expiry and budget
+    pub fn create_session(ctx: Context<CreateSession>, session_key: Pubkey, expires_at: i64, max_ops: u32) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CREATE_SESSION)?;
+        require!(expires_at > Clock::get()?.unix_timestamp && max_ops > 0, CounterError::InvalidSession);
+
+        let counter = &ctx.accounts.counter;
+        let session = &mut ctx.accounts.session;
+        session.counter = counter.key();
+        session.session_key = session_key;
+        session.authority = counter.authority; // Only valid while this wallet owns the counter
+        session.expires_at = expires_at;
+        session.ops_left = max_ops;
+        session.bump = ctx.bumps.session;
+
+        emit_event!(SessionCreated {
+            counter: counter.key(),
+            session_key,
+            expires_at,
+            max_ops,
+        });
+
+        verbose_msg!("Session key {} may change counter {} {} times until {}", session_key, counter.key(), max_ops, expires_at);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // END A SESSION EARLY (the session's rent goes back to the owner)
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::REVOKE_SESSION)?;
+
+        let (counter, session_key) = (ctx.accounts.counter.key(), ctx.accounts.session.session_key);
+        emit_event!(SessionRevoked { counter, session_key });
+
+        verbose_msg!("Session key {} revoked for counter {}", session_key, counter);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
 
-// =====================================================================================
-// DATA STRUCTURES - What information we store in accounts
-// =====================================================================================
-
-// THE COUNTER ACCOUNT DATA STRUCTURE
-// This defines what information is stored in each counter account on the blockchain
-#[account]
-#[derive(InitSpace)]  // This helps calculate how much storage space we need
-pub struct Counter {
-    pub count: u64,        // The current number (0, 1, 2, 3, etc.)
-    pub bump: u8,          // A special number that helps find this account
-    pub authority: Pubkey, // The owner of this counter (who can change it)
+    // PUT YOUR COUNTER ON THE TOP-LEADERBOARD_SIZE LEADERBOARD (owner only)
+    // The leaderboard (seeds = ["leaderboard"]) is created by the first submission. A counter
+    // already on it just has its count refreshed; otherwise it takes a free place or, once the
+    // board is full, must beat the lowest count there (`ScoreTooLow`), which it pushes off
+    pub fn submit_score(ctx: Context<SubmitScore>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SUBMIT_SCORE)?;
+
+        let counter = &ctx.accounts.counter;
+        let entry = LeaderboardEntry {
+            counter: counter.key(),
+            authority: counter.authority,
+            count: counter.count,
+        };
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.bump = ctx.bumps.leaderboard;
+        let rank = leaderboard.submit(entry).ok_or(CounterError::ScoreTooLow)?;
+
+        emit_event!(LeaderboardUpdated {
+            counter: entry.counter,
+            authority: entry.authority,
+            count: entry.count,
+            rank: rank as u8,
+        });
+
+        verbose_msg!("Counter {} is number {} on the leaderboard with {}", entry.counter, rank + 1, entry.count);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CAP HOW MANY INCREMENTS THE COUNTER ACCEPTS PER UTC DAY (owner only)
+    // Limits abuse of public counters: once `limit` increments have been made on a day, further
+    // ones fail with `DailyLimitReached` until midnight UTC. Zero removes the cap
+    pub fn set_daily_limit(ctx: Context<ManageCounter>, limit: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_DAILY_LIMIT)?;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.daily_limit = limit;
+        counter.next_version();
+
+        verbose_msg!("Counter {} daily increment limit set to: {}", counter.key(), limit);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CHANGE HOW MUCH ONE `increment` / `decrement` MOVES THE COUNTER (owner only)
+    // E.g. 5 for a tally kept in units of five. Instructions that take an amount
+    // (`increment_by`, `apply_delta`, batches, ...) aren't affected
+    pub fn set_step(ctx: Context<ManageCounter>, step: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_STEP)?;
+        require!(step > 0, CounterError::InvalidStep);
+
+        let counter = &mut ctx.accounts.counter;
+        counter.step = step;
+        counter.next_version();
+
+        verbose_msg!("Counter {} now moves in steps of {}", counter.key(), step);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // RESET THE COUNTER AUTOMATICALLY EVERY `interval_seconds` (owner only)
+    // E.g. 604_800 for a weekly analytics counter. The first period starts now; the first
+    // increment or decrement after a period has ended keeps its final count in
+    // `last_period_count` and starts again from the counter's starting value. Zero turns it off.
+    // Not for counters in a group (`ResetInGroup`)
+    pub fn set_reset_interval(ctx: Context<ManageCounter>, interval_seconds: i64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_RESET_INTERVAL)?;
+        require!(interval_seconds >= 0, CounterError::InvalidResetInterval);
+
+        let counter = &mut ctx.accounts.counter;
+        require!(interval_seconds == 0 || counter.group == Pubkey::default(), CounterError::ResetInGroup);
+        counter.reset_interval = interval_seconds;
+        counter.period_start = Clock::get()?.unix_timestamp;
+        counter.next_version();
+
+        verbose_msg!("Counter {} reset interval set to: {}s", counter.key(), interval_seconds);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // MAKE THE COUNT GO DOWN BY ITSELF OVER TIME (owner only)
+    // For reputation-style scores: `rate_per_day` comes off the count for every full day from
+    // now on, never going below the counter's minimum. It is settled by the next change to the
+    // count (before that change applies) or by anyone calling `apply_decay`. Zero turns it off.
+    // Not for counters in a group (`DecayInGroup`)
+    pub fn set_decay_rate(ctx: Context<ManageCounter>, rate_per_day: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_DECAY_RATE)?;
+        settle_clock(&mut ctx.accounts.counter)?; // Days already passed decay at the old rate
+
+        let counter = &mut ctx.accounts.counter;
+        require!(rate_per_day == 0 || counter.group == Pubkey::default(), CounterError::DecayInGroup);
+        counter.decay_rate_per_day = rate_per_day;
+        counter.last_decay_ts = Clock::get()?.unix_timestamp;
+        counter.next_version();
+
+        verbose_msg!("Counter {} decays by {} per day", counter.key(), rate_per_day);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // SETTLE A COUNTER'S DECAY WITHOUT CHANGING IT OTHERWISE
+    // Anyone can call this, so a crank can keep decaying scores current for readers that
+    // don't work the pending decay out themselves. Fails with `NoDecayDue` when nothing is owed
+    pub fn apply_decay(ctx: Context<ApplyDecay>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::APPLY_DECAY)?;
+
+        let counter = &mut ctx.accounts.counter;
+        require!(!counter.is_frozen(), CounterError::CounterFrozen);
+        let clock = Clock::get()?;
+        let decay = counter.pending_decay(clock.unix_timestamp).ok_or(CounterError::NoDecayDue)?;
+        announce_decay(counter, decay, clock.slot);
+        counter.next_version();
+
+        verbose_msg!("Counter {} decayed to {}", counter.key(), counter.count);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // RUN A COUNTER'S CLOCK-BASED UPKEEP WITHOUT CHANGING IT OTHERWISE (for keepers)
+    // Anyone can call this: it settles any decay owed, starts a lapsed reset period over and
+    // archives an active counter whose `expires_at` has passed, then pays the caller the
+    // counter's keeper tip (see `set_keeper_tip`) from its vault. Fails with `NothingToTouch`
+    // when none of that is due, so keepers aren't paid for nothing
+    pub fn touch(ctx: Context<Touch>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::TOUCH)?;
+
+        let clock = Clock::get()?;
+        let counter = &mut ctx.accounts.counter;
+        require!(!counter.is_frozen(), CounterError::CounterFrozen);
+        // Read-only states (archived, migrating, ...) keep their count as it is
+        let changeable = counter.state.allows_updates();
+        let decay = counter.pending_decay(clock.unix_timestamp).filter(|_| changeable);
+        let new_period = counter.lapsed_period(clock.unix_timestamp).filter(|_| changeable);
+        let expired = counter.state == CounterState::Active && counter.is_expired(clock.unix_timestamp);
+        require!(decay.is_some() || new_period.is_some() || expired, CounterError::NothingToTouch);
+
+        // Same order as `update_count`: the decay comes off the old period's count first
+        if let Some(decay) = decay {
+            announce_decay(counter, decay, clock.slot);
+        }
+        if let Some(period_start) = new_period {
+            announce_rollover(counter, period_start, clock.slot);
+        }
+        if decay.is_some() || new_period.is_some() {
+            counter.next_version();
+        }
+        if expired {
+            apply_state_transition(counter, CounterState::Archived)?;
+        }
+
+        let tip_lamports = ctx.accounts.pay_keeper_tip(ctx.bumps.vault)?;
+        let counter = &ctx.accounts.counter;
+        emit_event!(CounterTouched {
+            counter: counter.key(),
+            keeper: ctx.accounts.keeper.key(),
+            decayed: decay.is_some(),
+            rolled_over: new_period.is_some(),
+            archived: expired,
+            count: counter.count,
+            tip_lamports,
+            slot: clock.slot,
+        });
+
+        verbose_msg!("Counter {} touched by {} (tip: {} lamports)", counter.key(), ctx.accounts.keeper.key(), tip_lamports);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // MAKE EVERY INCREMENT PAY INTO THE COUNTER'S VAULT (think crowdfunding clicks)
+    // Each increment - the owner's too - moves `lamports` from the caller into the vault PDA
+    // (seeds = ["vault", counter]), which only the owner can empty with `withdraw_vault`.
+    // Zero turns the deposit off
+    pub fn set_vault_deposit(ctx: Context<ManageCounter>, lamports: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_VAULT_DEPOSIT)?;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.deposit_per_increment = lamports;
+        counter.next_version();
+
+        emit_event!(VaultDepositUpdated {
+            counter: counter.key(),
+            deposit_per_increment: lamports,
+        });
+
+        verbose_msg!("Counter {} now takes a {} lamport deposit per increment", counter.key(), lamports);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // PAY KEEPERS FOR RUNNING `touch` ON THIS COUNTER (owner only)
+    // Each upkeep that had something to do pays up to `lamports` out of the counter's vault
+    // to the keeper. Zero stops the tips
+    pub fn set_keeper_tip(ctx: Context<ManageCounter>, lamports: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_KEEPER_TIP)?;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.keeper_tip_lamports = lamports;
+        counter.next_version();
+
+        verbose_msg!("Counter {} now tips keepers {} lamports", counter.key(), lamports);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // GIVE THE COUNTER A DISPLAY NAME AND A LINK FOR EXPLORERS (owner only)
+    // Unlike the counter's own `label`, which is part of its address, these can change at any
+    // time. The account grows or shrinks to fit exactly (the owner pays or gets back the rent),
+    // and both strings together stay far below the 10KB a single instruction may add
+    pub fn set_metadata(ctx: Context<SetMetadata>, label: String, uri: String) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_METADATA)?;
+        require!(
+            label.len() <= MAX_METADATA_LABEL_LEN && uri.len() <= MAX_METADATA_URI_LEN,
+            CounterError::MetadataTooLong
+        );
+
+        let counter = &mut ctx.accounts.counter;
+        counter.metadata_label = label;
+        counter.metadata_uri = uri;
+        counter.next_version();
+        require_rent_exempt(&counter.to_account_info())?; // After the realloc
+
+        emit_event!(CounterMetadataUpdated {
+            counter: counter.key(),
+            label: counter.metadata_label.clone(),
+            uri: counter.metadata_uri.clone(),
+        });
+
+        verbose_msg!("Counter {} metadata set to {:?} ({})", counter.key(), counter.metadata_label, counter.metadata_uri);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // REMOVE THE DISPLAY METADATA (owner only)
+    // Shrinks the account back to its bare size and refunds the freed rent to the owner
+    pub fn clear_metadata(ctx: Context<ClearMetadata>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CLEAR_METADATA)?;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.metadata_label = String::new();
+        counter.metadata_uri = String::new();
+        counter.next_version();
+        require_rent_exempt(&counter.to_account_info())?; // After the realloc
+
+        emit_event!(CounterMetadataUpdated {
+            counter: counter.key(),
+            label: String::new(),
+            uri: String::new(),
+        });
+
+        verbose_msg!("Counter {} metadata cleared", counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // LET A TEAM MEMBER INCREMENT A SHARED COUNTER (owner only)
+    // Writes a `Contributor` PDA (seeds = ["contributor", counter, member]). Unlike an operator,
+    // a contributor has no allowance: they may increment as often as they like, and everything
+    // they add is tallied in `contributions`, so a leaderboard is one account scan away
+    pub fn add_contributor(ctx: Context<AddContributor>, member: Pubkey) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::ADD_CONTRIBUTOR)?;
+
+        let counter = &ctx.accounts.counter;
+        let contributor = &mut ctx.accounts.contributor;
+        contributor.counter = counter.key();
+        contributor.member = member;
+        contributor.authority = counter.authority; // Only valid while this wallet owns the counter
+        contributor.contributions = 0;
+        contributor.bump = ctx.bumps.contributor;
+
+        emit_event!(ContributorAdded { counter: counter.key(), member });
+
+        verbose_msg!("Member {} may now increment counter {}", member, counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // TAKE A TEAM MEMBER OFF A SHARED COUNTER (owner only)
+    pub fn remove_contributor(ctx: Context<RemoveContributor>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::REMOVE_CONTRIBUTOR)?;
+
+        let contributor = &ctx.accounts.contributor;
+        let (counter, member) = (ctx.accounts.counter.key(), contributor.member);
+        emit_event!(ContributorRemoved { counter, member, contributions: contributor.contributions });
+
+        verbose_msg!("Member {} removed from counter {}", member, counter);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // LET `member` MAKE SOME CHANGES TO THE COUNT WITHOUT OWNING THE COUNTER (owner only)
+    // `roles` are `role` bits, added to any the member already holds: `INCREMENTER` may
+    // increment, `DECREMENTER` may decrement and `ADMIN` may make any change to the count.
+    // Members pass their `Role` account in as `role` when they update the counter. Closing it,
+    // and changing its settings, stay with the owner
+    pub fn grant_role(ctx: Context<GrantRole>, member: Pubkey, roles: u8) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::GRANT_ROLE)?;
+        require!(roles != 0 && roles & !role::ALL == 0, CounterError::InvalidRole);
+
+        let counter = &ctx.accounts.counter;
+        let role = &mut ctx.accounts.role;
+        if role.authority != counter.authority {
+            role.roles = 0; // New, or granted by a previous owner and no longer valid
+        }
+        role.counter = counter.key();
+        role.member = member;
+        role.authority = counter.authority; // Only valid while this wallet owns the counter
+        role.roles |= roles;
+        role.bump = ctx.bumps.role;
+
+        emit_event!(RoleUpdated { counter: counter.key(), member, roles: role.roles });
+
+        verbose_msg!("Member {} holds roles {:#b} on counter {}", member, role.roles, counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // TAKE `roles` AWAY FROM A MEMBER (owner only)
+    // The account is closed, refunding its rent to the owner, once the member has none left
+    pub fn revoke_role(ctx: Context<RevokeRole>, roles: u8) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::REVOKE_ROLE)?;
+        require!(roles != 0 && roles & !role::ALL == 0, CounterError::InvalidRole);
+
+        let counter = ctx.accounts.counter.key();
+        let role = &mut ctx.accounts.role;
+        role.roles &= !roles;
+        let (member, remaining) = (role.member, role.roles);
+        if remaining == 0 {
+            role.close(ctx.accounts.user.to_account_info())?;
+        }
+
+        emit_event!(RoleUpdated { counter, member, roles: remaining });
+
+        verbose_msg!("Member {} holds roles {:#b} on counter {}", member, remaining, counter);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // HAND A COUNTER TO A GROUP OF WALLETS, `threshold` OF WHICH MUST AGREE ON EVERY CHANGE
+    // Creates the counter's `MultisigAuthority` PDA (seeds = ["multisig", counter]) and makes it
+    // the counter's authority. Nobody can sign for that address, so from then on the counter
+    // only changes through proposals (`propose_op` -> `approve_op` -> `execute_op`), and a
+    // `TransferAuthority` proposal is the way back to a single owner
+    pub fn create_multisig(ctx: Context<CreateMultisig>, owners: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CREATE_MULTISIG)?;
+        require!(!owners.is_empty() && owners.len() <= MAX_MULTISIG_OWNERS, CounterError::InvalidMultisig);
+        require!(threshold >= 1 && threshold as usize <= owners.len(), CounterError::InvalidMultisig);
+        let mut distinct = owners.clone();
+        distinct.sort();
+        distinct.dedup();
+        require!(distinct.len() == owners.len(), CounterError::InvalidMultisig); // Each owner gets one vote
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.counter = ctx.accounts.counter.key();
+        multisig.owners = owners;
+        multisig.threshold = threshold;
+        multisig.proposal_count = 0;
+        multisig.bump = ctx.bumps.multisig;
+
+        let counter = &mut ctx.accounts.counter;
+        let previous_authority = counter.authority;
+        counter.authority = multisig.key();
+        counter.pending_authority = None; // An unfinished handover would bypass the vote
+        counter.next_version();
+
+        emit_event!(AuthorityTransferred {
+            counter: counter.key(),
+            previous_authority,
+            new_authority: counter.authority,
+        });
+        emit_event!(MultisigCreated {
+            counter: counter.key(),
+            multisig: multisig.key(),
+            owners: multisig.owners.clone(),
+            threshold,
+        });
+
+        verbose_msg!("Counter {} now needs {} of {} owners", counter.key(), threshold, multisig.owners.len());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // SUGGEST A CHANGE TO A MULTISIG COUNTER (any of its owners)
+    // Writes a `MultisigProposal` PDA (seeds = ["proposal", multisig, index]), already carrying
+    // the proposer's own approval. The proposer pays its rent and gets it back on execution
+    pub fn propose_op(ctx: Context<ProposeOp>, op: MultisigOp) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::PROPOSE_OP)?;
+        if let MultisigOp::Increment(amount) | MultisigOp::Decrement(amount) = op {
+            require!(amount > 0, CounterError::InvalidAmount);
+        }
+
+        let user = ctx.accounts.user.key();
+        let multisig = &mut ctx.accounts.multisig;
+        let slot = multisig.owner_slot(&user).ok_or(CounterError::NotMultisigOwner)?;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.multisig = multisig.key();
+        proposal.index = multisig.proposal_count;
+        proposal.proposer = user;
+        proposal.op = op;
+        proposal.approvals = 1 << slot;
+        proposal.bump = ctx.bumps.proposal;
+        multisig.proposal_count = multisig.proposal_count.saturating_add(1);
+
+        emit_event!(OpProposed {
+            multisig: multisig.key(),
+            proposal: proposal.key(),
+            index: proposal.index,
+            proposer: user,
+            op,
+        });
+
+        verbose_msg!("Proposal {} for counter {}: {:?}", proposal.index, multisig.counter, op);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // VOTE FOR A PENDING PROPOSAL (any owner who hasn't yet)
+    pub fn approve_op(ctx: Context<ApproveOp>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::APPROVE_OP)?;
+
+        let user = ctx.accounts.user.key();
+        let slot = ctx.accounts.multisig.owner_slot(&user).ok_or(CounterError::NotMultisigOwner)?;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.approvals & (1 << slot) == 0, CounterError::AlreadyApproved);
+        proposal.approvals |= 1 << slot;
+
+        emit_event!(OpApproved {
+            proposal: proposal.key(),
+            owner: user,
+            approvals: proposal.approvals.count_ones() as u8,
+        });
+
+        verbose_msg!("Proposal {} approved by {} ({} approvals)", proposal.index, user, proposal.approvals.count_ones());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CARRY OUT A PROPOSAL THAT HAS ENOUGH APPROVALS (anyone)
+    // The change goes through the same policy checks as an owner's own call, with the multisig
+    // as the owner. The proposal is deleted and its rent returned to the proposer
+    pub fn execute_op(ctx: Context<ExecuteOp>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::EXECUTE_OP)?;
+        settle_clock(&mut ctx.accounts.counter)?;
+
+        let multisig = &ctx.accounts.multisig;
+        let proposal = &ctx.accounts.proposal;
+        require!(proposal.approvals.count_ones() >= multisig.threshold as u32, CounterError::NotEnoughApprovals);
+        let (multisig_key, op) = (multisig.key(), proposal.op);
+
+        if let MultisigOp::TransferAuthority(new_authority) = op {
+            let counter = &mut ctx.accounts.counter;
+            counter.authority = new_authority;
+            counter.next_version();
+            emit_event!(AuthorityTransferred {
+                counter: counter.key(),
+                previous_authority: multisig_key,
+                new_authority,
+            });
+        } else {
+            let counter = &ctx.accounts.counter;
+            let (policy_op, operation, new_count) = match op {
+                MultisigOp::Increment(amount) => (PolicyOp::Increment, CounterOperation::Increment, counter.stepped(amount, true)?),
+                MultisigOp::Decrement(amount) => (PolicyOp::Decrement, CounterOperation::Decrement, counter.stepped(amount, false)?),
+                MultisigOp::Set(value) => (PolicyOp::Set, CounterOperation::Set, value),
+                MultisigOp::Reset => (PolicyOp::Reset, CounterOperation::Reset, counter.min_count),
+                MultisigOp::TransferAuthority(_) => unreachable!("handled above"),
+            };
+            let policy = PolicyContext::new(counter, multisig_key, policy_op)?;
+            ActivePolicy::validate(&policy, new_count)?;
+            move_count(&mut ctx.accounts.counter, ctx.accounts.history.as_ref(), multisig_key, operation, new_count)?;
+        }
+
+        emit_event!(OpExecuted {
+            multisig: multisig_key,
+            proposal: ctx.accounts.proposal.key(),
+            counter: ctx.accounts.counter.key(),
+            op,
+        });
+
+        verbose_msg!("Proposal {} executed on counter {}", ctx.accounts.proposal.index, ctx.accounts.counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CHARGE OTHER PEOPLE FOR INCREMENTING YOUR COUNTER
+    // Every increment by someone other than the owner (a public counter's visitors, or an
+    // operator) pays `fee_lamports` per unit added into the counter's own treasury, on top of
+    // any protocol fee. Zero turns the fee off
+    pub fn set_counter_fee(ctx: Context<ManageCounter>, fee_lamports: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_COUNTER_FEE)?;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.fee_lamports = fee_lamports;
+        counter.next_version();
+
+        emit_event!(CounterFeeUpdated {
+            counter: counter.key(),
+            fee_lamports,
+        });
+
+        verbose_msg!("Counter {} now charges {} lamports per increment", counter.key(), fee_lamports);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CHARGE OTHER PEOPLE IN YOUR PROJECT'S TOKEN FOR CHANGING YOUR COUNTER
+    // Every increment or decrement by someone other than the owner then moves `fee_amount` of
+    // `fee_mint` from the caller into the counter's fee vault: the associated token account of
+    // the counter's treasury PDA, created here if needed. Zero turns the fee off
+    pub fn set_token_fee(ctx: Context<SetTokenFee>, fee_amount: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_TOKEN_FEE)?;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.fee_mint = ctx.accounts.fee_mint.key();
+        counter.fee_amount = fee_amount;
+        counter.next_version();
+
+        emit_event!(TokenFeeUpdated {
+            counter: counter.key(),
+            fee_mint: counter.fee_mint,
+            fee_amount,
+        });
+
+        verbose_msg!("Counter {} now charges {} of {} per operation", counter.key(), fee_amount, counter.fee_mint);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // ANNOUNCE EVERY `interval`-TH VALUE THE COUNTER REACHES (e.g. 100, 200, ...)
+    // Increments that land on or jump past a multiple of `interval` emit `MilestoneReached`,
+    // so off-chain celebrations don't need to watch every update. Zero turns it off
+    pub fn set_milestone_interval(ctx: Context<ManageCounter>, interval: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_MILESTONE_INTERVAL)?;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.milestone_interval = interval;
+        counter.next_version();
+
+        verbose_msg!("Counter {} milestone interval set to: {}", counter.key(), interval);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // RAISE AN ALERT WHEN THE COUNT CROSSES `threshold` (owner only)
+    // E.g. `Above` 1_000 for a queue that's falling behind. Every change through `increment`,
+    // `decrement` and friends that takes the count to the threshold or past it in `direction`
+    // emits `ThresholdCrossed` - once, until the count comes back. A count that is already past
+    // the threshold doesn't alert until it has come back and crossed again
+    pub fn set_alert_threshold(ctx: Context<ManageCounter>, threshold: u64, direction: AlertDirection) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_ALERT_THRESHOLD)?;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.alert_threshold = threshold;
+        counter.alert_direction = Some(direction);
+        counter.last_alert_state = direction.is_past(counter.count, threshold);
+        counter.next_version();
+
+        verbose_msg!("Counter {} alerts when {:?} {}", counter.key(), direction, threshold);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // NOTIFY ANOTHER PROGRAM OF EVERY CHANGE TO THE COUNT (owner only)
+    // Changes through `update_count` / `apply_batch` then end with a CPI into `hook_program`'s
+    // `on_counter_updated` (see `counter_interface::hook`), and have to pass that program in
+    // as `hook_program`. The hook can't be this program itself. The default key removes it
+    pub fn set_hook(ctx: Context<ManageCounter>, hook_program: Pubkey) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_HOOK)?;
+        require_keys_neq!(hook_program, crate::ID, CounterError::InvalidHookProgram);
+
+        let counter = &mut ctx.accounts.counter;
+        counter.hook_program = hook_program;
+        counter.next_version();
+
+        emit_event!(HookUpdated {
+            counter: counter.key(),
+            hook_program,
+        });
+
+        verbose_msg!("Counter {} hook set to: {}", counter.key(), hook_program);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // STOP THE COUNTER'S THRESHOLD ALERT (owner only)
+    pub fn clear_alert_threshold(ctx: Context<ManageCounter>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CLEAR_ALERT_THRESHOLD)?;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.alert_threshold = 0;
+        counter.alert_direction = None;
+        counter.last_alert_state = false;
+        counter.next_version();
+
+        verbose_msg!("Counter {} alert cleared", counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // ONLY LET HOLDERS OF A TOKEN CHANGE YOUR COUNTER (e.g. a DAO's members)
+    // Everyone but the owner then has to pass their associated token account for `gate_mint`
+    // holding at least `min_balance`. The default key turns the gate off
+    pub fn set_token_gate(ctx: Context<ManageCounter>, gate_mint: Pubkey, min_balance: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_TOKEN_GATE)?;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.gate_mint = gate_mint;
+        counter.min_balance = min_balance;
+        counter.next_version();
+
+        emit_event!(TokenGateUpdated {
+            counter: counter.key(),
+            gate_mint,
+            min_balance,
+        });
+
+        verbose_msg!("Counter {} gated to holders of {} {}", counter.key(), min_balance, gate_mint);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // LET `increment_weighted` ADD THE CALLER'S BALANCE OF A TOKEN (owner only)
+    // Each `divisor` base units of `weight_mint` the caller holds add one to the count. The
+    // default key turns weighted increments off
+    pub fn set_weight_config(ctx: Context<ManageCounter>, weight_mint: Pubkey, divisor: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_WEIGHT_CONFIG)?;
+        let enabled = weight_mint != Pubkey::default();
+        require!(!enabled || divisor > 0, CounterError::InvalidWeightDivisor);
+
+        let counter = &mut ctx.accounts.counter;
+        counter.weight_mint = weight_mint;
+        counter.weight_divisor = if enabled { divisor } else { 0 };
+        counter.next_version();
+
+        emit_event!(WeightConfigUpdated {
+            counter: counter.key(),
+            weight_mint,
+            weight_divisor: counter.weight_divisor,
+        });
+
+        verbose_msg!("Counter {} weighted by {} per unit of {}", counter.key(), counter.weight_divisor, weight_mint);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // LET A TRUSTED REPORTER SET THE COUNT (owner only)
+    // For a counter that mirrors an off-chain metric: from now on `feed_authority` may call
+    // `push_value`. None stops the feed. The owner can still change the count themselves
+    pub fn set_feed_authority(ctx: Context<ManageCounter>, feed_authority: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_FEED_AUTHORITY)?;
+
+        let counter = &mut ctx.accounts.counter;
+        counter.feed_authority = feed_authority;
+        counter.next_version();
+
+        emit_event!(FeedAuthorityUpdated { counter: counter.key(), feed_authority });
+
+        verbose_msg!("Counter {} feed authority set to: {:?}", counter.key(), feed_authority);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // SET THE COUNT TO A VALUE THE FEED AUTHORITY OBSERVED (feed authority only)
+    // `observed_at` (unix timestamp) has to be newer than the last value pushed and not in the
+    // future, so a delayed or replayed push can never overwrite a fresher one. The count must
+    // still suit the counter (bounds, state, expiry, window), like a `set_count` by the owner
+    pub fn push_value(ctx: Context<PushValue>, value: u64, observed_at: i64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::PUSH_VALUE)?;
+        let clock = Clock::get()?;
+        let counter = &ctx.accounts.counter;
+        require!(observed_at > counter.last_observed_at, CounterError::StaleObservation);
+        require!(observed_at <= clock.unix_timestamp, CounterError::ObservationInFuture);
+        settle_clock(&mut ctx.accounts.counter)?;
+
+        // The reporter acts for the owner, so only the counter's own rules apply
+        let counter = &ctx.accounts.counter;
+        let policy = PolicyContext::at(counter, counter.authority, PolicyOp::Set, clock.unix_timestamp);
+        ActivePolicy::validate(&policy, value)?;
+
+        let feed_authority = ctx.accounts.feed_authority.key();
+        let previous_count = counter.count;
+        ctx.accounts.counter.last_observed_at = observed_at;
+        move_count(&mut ctx.accounts.counter, ctx.accounts.history.as_ref(), feed_authority, CounterOperation::Set, value)?;
+        emit_event!(FeedValuePushed {
+            counter: ctx.accounts.counter.key(),
+            feed_authority,
+            previous_count,
+            value,
+            observed_at,
+            slot: clock.slot,
+        });
+
+        verbose_msg!("Counter {} pushed to {} (observed at {})", ctx.accounts.counter.key(), value, observed_at);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // START KEEPING AN ON-CHAIN LOG OF A COUNTER'S CHANGES
+    // Creates the counter's `CounterHistory` (seeds = ["history", counter]), which remembers the
+    // last HISTORY_LEN changes so explorers can audit them without parsing logs. From then on
+    // every change to the count has to pass the history account in, so nothing is ever missed
+    pub fn initialize_history(ctx: Context<InitializeHistory>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INITIALIZE_HISTORY)?;
+
+        let counter = &mut ctx.accounts.counter;
+        let mut history = ctx.accounts.history.load_init()?;
+        history.counter = counter.key();
+        history.bump = ctx.bumps.history;
+        counter.has_history = true;
+        counter.next_version();
+
+        verbose_msg!("History enabled for counter {}", counter.key());
+        drop(history); // Zero-copy borrows have to end before diagnostics read the accounts
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CREATE A COUNTER ARRAY
+    // One account holding COUNTER_ARRAY_SLOTS counters (e.g. a game inventory), far cheaper
+    // than one account per counter. Slots start at zero and are addressed by index
+    pub fn initialize_array(ctx: Context<InitializeArray>, id: String) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INITIALIZE_ARRAY)?;
+        require!(id.len() <= MAX_LABEL_LEN, CounterError::LabelTooLong);
+
+        let mut array = ctx.accounts.array.load_init()?;
+        array.authority = ctx.accounts.user.key();
+        array.bump = ctx.bumps.array;
+
+        verbose_msg!("Counter array {:?} initialized for user: {}", id, array.authority);
+        drop(array);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // ADD ONE TO A SLOT OF A COUNTER ARRAY (owner only)
+    pub fn increment_slot(ctx: Context<UpdateArray>, id: String, index: u16) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INCREMENT_SLOT)?;
+        update_slot(ctx, &id, index, CounterOperation::Increment)
+    }
+
+    // SUBTRACT ONE FROM A SLOT OF A COUNTER ARRAY (owner only)
+    pub fn decrement_slot(ctx: Context<UpdateArray>, id: String, index: u16) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::DECREMENT_SLOT)?;
+        update_slot(ctx, &id, index, CounterOperation::Decrement)
+    }
+
+    // REPLACE A SLOT'S FLAG BYTE (owner only)
+    // `slot_flag::LOCKED` stops the slot from changing; the other bits are the application's own
+    pub fn set_slot_flags(ctx: Context<UpdateArray>, id: String, index: u16, flags: u8) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SET_SLOT_FLAGS)?;
+
+        let mut array = ctx.accounts.array.load_mut()?;
+        *array.flags.get_mut(index as usize).ok_or(CounterError::SlotOutOfBounds)? = flags;
+
+        verbose_msg!("Counter array {:?} slot {} flags set to: {:#04x}", id, index, flags);
+        drop(array);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CREATE A SIGNED COUNTER
+    // Like a counter, but holding an `i64` that may go below zero (e.g. an account balance)
+    pub fn initialize_signed(ctx: Context<InitializeSigned>, id: String) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INITIALIZE_SIGNED)?;
+        require!(id.len() <= MAX_LABEL_LEN, CounterError::LabelTooLong);
+
+        let counter = &mut ctx.accounts.counter;
+        counter.authority = ctx.accounts.user.key();
+        counter.label = id;
+        counter.count = 0;
+        counter.bump = ctx.bumps.counter;
+
+        verbose_msg!("Signed counter {:?} initialized for user: {}", counter.label, counter.authority);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // ADD `amount` TO A SIGNED COUNTER (owner only)
+    pub fn increment_signed(ctx: Context<UpdateSigned>, id: String, amount: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INCREMENT_SIGNED)?;
+        update_signed(ctx, &id, CounterOperation::Increment, amount)
+    }
+
+    // SUBTRACT `amount` FROM A SIGNED COUNTER (owner only) - going below zero is fine
+    pub fn decrement_signed(ctx: Context<UpdateSigned>, id: String, amount: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::DECREMENT_SIGNED)?;
+        update_signed(ctx, &id, CounterOperation::Decrement, amount)
+    }
+
+    // CREATE A BIG COUNTER
+    // Like a signed counter, but holding a `u128` for machine-generated counts that could
+    // outgrow a u64 (e.g. aggregated micro-events)
+    pub fn initialize_big(ctx: Context<InitializeBig>, id: String) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INITIALIZE_BIG)?;
+        require!(id.len() <= MAX_LABEL_LEN, CounterError::LabelTooLong);
+
+        let counter = &mut ctx.accounts.counter;
+        counter.authority = ctx.accounts.user.key();
+        counter.label = id;
+        counter.set_count(0);
+        counter.bump = ctx.bumps.counter;
+
+        verbose_msg!("Big counter {:?} initialized for user: {}", counter.label, counter.authority);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // ADD `amount` TO A BIG COUNTER (owner only)
+    pub fn increment_big(ctx: Context<UpdateBig>, id: String, amount: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INCREMENT_BIG)?;
+        update_big(ctx, &id, CounterOperation::Increment, amount)
+    }
+
+    // SUBTRACT `amount` FROM A BIG COUNTER (owner only)
+    pub fn decrement_big(ctx: Context<UpdateBig>, id: String, amount: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::DECREMENT_BIG)?;
+        update_big(ctx, &id, CounterOperation::Decrement, amount)
+    }
+
+    // MOVE A COUNTER THAT IS OUTGROWING A u64 INTO A BIG COUNTER (owner only)
+    // The big counter gets the counter's label and current count (any decay owed settled
+    // first), and the counter is closed like the source of a `merge`, rent back to the owner
+    pub fn upgrade_to_big(ctx: Context<UpgradeToBig>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::UPGRADE_TO_BIG)?;
+        settle_clock(&mut ctx.accounts.counter)?;
+
+        let counter = &ctx.accounts.counter;
+        let user = ctx.accounts.user.key();
+        ActivePolicy::validate(&PolicyContext::new(counter, user, PolicyOp::Close)?, counter.count)?;
+
+        let big_counter = &mut ctx.accounts.big_counter;
+        big_counter.authority = user;
+        big_counter.label = counter.label.clone();
+        big_counter.set_count(counter.count as u128);
+        big_counter.bump = ctx.bumps.big_counter;
+
+        let stats = &mut ctx.accounts.stats;
+        stats.total_closed = stats.total_closed.saturating_add(1);
+        stats.announce();
+        unregister_counter(ctx.accounts.registry.as_mut(), counter.key());
+
+        emit!(CounterClosed::new(user, counter, user, &Clock::get()?));
+        emit_event!(BigCounterUpgraded {
+            counter: counter.key(),
+            big_counter: big_counter.key(),
+            authority: user,
+            count: big_counter.count(),
+        });
+
+        verbose_msg!("Counter {} upgraded to big counter {}", counter.key(), big_counter.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CREATE A ZERO-COPY COUNTER
+    // A bare count for counters changed thousands of times a day: `increment_zc` and
+    // `decrement_zc` change it in place instead of (de)serializing the account. No bounds, fees
+    // or policies - owner-only changes by one
+    pub fn initialize_zc(ctx: Context<InitializeZC>, id: String) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INITIALIZE_ZC)?;
+        require!(id.len() <= MAX_LABEL_LEN, CounterError::LabelTooLong);
+
+        let mut counter = ctx.accounts.counter.load_init()?;
+        counter.authority = ctx.accounts.user.key();
+        counter.bump = ctx.bumps.counter;
+
+        verbose_msg!("Zero-copy counter {:?} initialized for user: {}", id, counter.authority);
+        drop(counter);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // ADD ONE TO A ZERO-COPY COUNTER (owner only)
+    pub fn increment_zc(ctx: Context<UpdateZC>, id: String) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INCREMENT_ZC)?;
+        update_zc(ctx, &id, CounterOperation::Increment)
+    }
+
+    // SUBTRACT ONE FROM A ZERO-COPY COUNTER (owner only)
+    pub fn decrement_zc(ctx: Context<UpdateZC>, id: String) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::DECREMENT_ZC)?;
+        update_zc(ctx, &id, CounterOperation::Decrement)
+    }
+
+    // MOVE A COUNTER INTO A ZERO-COPY COUNTER (owner only)
+    // For a counter that turned out to be hot: like `upgrade_to_big`, the zero-copy counter gets
+    // the counter's label and current count (any decay owed settled first), and the counter is
+    // closed, rent back to the owner. Its settings (bounds, fees, history...) don't carry over
+    pub fn convert_to_zc(ctx: Context<ConvertToZC>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CONVERT_TO_ZC)?;
+        settle_clock(&mut ctx.accounts.counter)?;
+
+        let counter = &ctx.accounts.counter;
+        let user = ctx.accounts.user.key();
+        ActivePolicy::validate(&PolicyContext::new(counter, user, PolicyOp::Close)?, counter.count)?;
+
+        let mut counter_zc = ctx.accounts.counter_zc.load_init()?;
+        counter_zc.authority = user;
+        counter_zc.count = counter.count;
+        counter_zc.bump = ctx.bumps.counter_zc;
+        drop(counter_zc);
+
+        let stats = &mut ctx.accounts.stats;
+        stats.total_closed = stats.total_closed.saturating_add(1);
+        stats.announce();
+        unregister_counter(ctx.accounts.registry.as_mut(), counter.key());
+
+        emit!(CounterClosed::new(user, counter, user, &Clock::get()?));
+        emit_event!(CounterConvertedToZC {
+            counter: counter.key(),
+            counter_zc: ctx.accounts.counter_zc.key(),
+            authority: user,
+            count: counter.count,
+        });
+
+        verbose_msg!("Counter {} converted to zero-copy counter {}", counter.key(), ctx.accounts.counter_zc.key());
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // MOVE COLLECTED INCREMENT FEES OUT OF A COUNTER'S TREASURY (owner only)
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::WITHDRAW_FEES)?;
+
+        let counter_key = ctx.accounts.counter.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[seeds::COUNTER_TREASURY, counter_key.as_ref(), &[ctx.bumps.counter_treasury]]];
+
+        // The treasury PDA signs the System Program transfer
+        anchor_lang::system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.counter_treasury.to_account_info(),
+                    to: ctx.accounts.user.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        verbose_msg!("Withdrew {} lamports from the treasury of counter {}", amount, counter_key);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // MOVE COLLECTED TOKEN FEES OUT OF A COUNTER'S FEE VAULT (owner only)
+    // The vault belongs to the counter's treasury PDA, which signs the token transfer
+    pub fn collect_token_fees(ctx: Context<CollectTokenFees>, amount: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::COLLECT_TOKEN_FEES)?;
+
+        let counter_key = ctx.accounts.counter.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[seeds::COUNTER_TREASURY, counter_key.as_ref(), &[ctx.bumps.counter_treasury]]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.fee_vault.to_account_info(),
+                    to: ctx.accounts.destination_token_account.to_account_info(),
+                    authority: ctx.accounts.counter_treasury.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        verbose_msg!("Collected {} of {} in fees from counter {}", amount, ctx.accounts.counter.fee_mint, counter_key);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // TAKE LAMPORTS OUT OF A COUNTER'S VAULT (owner only)
+    // The vault is a system account at a PDA derived from the counter, so the program signs
+    // for it with the counter's address in the seeds
+    pub fn withdraw_vault(ctx: Context<WithdrawVault>, amount: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::WITHDRAW_VAULT)?;
+
+        let counter_key = ctx.accounts.counter.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[seeds::VAULT, counter_key.as_ref(), &[ctx.bumps.vault]]];
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.user.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        emit_event!(VaultWithdrawn {
+            counter: counter_key,
+            authority: ctx.accounts.user.key(),
+            amount,
+        });
+
+        verbose_msg!("Withdrew {} lamports from the vault of counter {}", amount, counter_key);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // EMPTY THE COUNTER IN ONE CALL (owner only)
+    // Instead of decrementing it to zero one step at a time: the count goes straight to zero
+    // (checked like a `set_count(0)`, so a counter with a higher minimum refuses). When the
+    // vault is passed in, the deposits the drained count paid for come back to the owner too:
+    // `deposit_per_increment` per unit, or whatever the vault holds if that is less
+    pub fn drain(ctx: Context<Drain>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::DRAIN)?;
+        settle_clock(&mut ctx.accounts.counter)?;
+
+        let counter = &ctx.accounts.counter;
+        let (counter_key, user) = (counter.key(), ctx.accounts.user.key());
+        let policy = PolicyContext::new(counter, user, PolicyOp::Set)?;
+        ActivePolicy::validate(&policy, 0)?;
+        let drained = counter.count;
+        let refund = counter.deposit_per_increment.saturating_mul(drained);
+
+        let vault_lamports = match (&ctx.accounts.vault, ctx.bumps.vault) {
+            (Some(vault), Some(bump)) if refund > 0 => {
+                let amount = refund.min(vault.lamports());
+                let signer_seeds: &[&[&[u8]]] = &[&[seeds::VAULT, counter_key.as_ref(), &[bump]]];
+                anchor_lang::system_program::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: vault.to_account_info(),
+                            to: ctx.accounts.user.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    amount,
+                )?;
+                amount
+            }
+            _ => 0,
+        };
+
+        move_count(&mut ctx.accounts.counter, ctx.accounts.history.as_ref(), user, CounterOperation::Set, 0)?;
+
+        emit_event!(CounterDrained {
+            counter: counter_key,
+            authority: user,
+            drained,
+            vault_lamports,
+        });
+
+        verbose_msg!("Counter {} drained of {} ({} lamports back from the vault)", counter_key, drained, vault_lamports);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // BRING A COUNTER FROM AN OLDER PROGRAM VERSION UP TO THE CURRENT LAYOUT
+    // Grows the account to today's size (the owner pays the extra rent), fills in the fields
+    // the old layout didn't have and stamps it with COUNTER_LAYOUT_VERSION. Every other
+    // instruction rejects a counter until this has been run on it
+    pub fn migrate(ctx: Context<Migrate>, creator: Pubkey, id: String) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::MIGRATE)?;
+
+        let info = ctx.accounts.counter.to_account_info();
+        let user = &ctx.accounts.user;
+
+        // GROW FIRST: an old layout is too short to deserialize, the new bytes read as zeroes.
+        // The display metadata strings come before the newer fields, so how much is missing
+        // depends on them: grow by a whole layout's worth and trim to the real size below
+        let previous_size = info.data_len();
+        info.resize(previous_size + Counter::INIT_SPACE)?;
+        let mut counter = Counter::try_deserialize(&mut &info.try_borrow_data()?[..])?; // Also checks the discriminator
+        let from_version = counter.layout_version;
+        require!(from_version != COUNTER_LAYOUT_VERSION, CounterError::AlreadyMigrated);
+        require!(from_version < COUNTER_LAYOUT_VERSION, CounterError::UnsupportedAccountVersion); // From a newer program
+
+        // THE ADDRESS HAS TO BE THE COUNTER OF `creator` + `id` (in its namespace, if it was
+        // created in one - layouts from before namespaces read back as none)
+        let seeds: &[&[u8]] = &[seeds::COUNTER, counter.namespace_seed(), creator.as_ref(), id.as_bytes()];
+        let (address, bump) = Pubkey::find_program_address(seeds, ctx.program_id);
+        require_keys_eq!(address, info.key(), ErrorCode::ConstraintSeeds);
+
+        // BACKFILL: zero is the right default for almost every newer field, except these
+        counter.bump = bump;                  // The address proves the seeds and bump...
+        counter.creator = creator;
+        counter.label = id;                   // ...and the label
+        if counter.authority == Pubkey::default() {
+            counter.authority = creator;
+        }
+        if counter.payer == Pubkey::default() {
+            counter.payer = counter.authority;
+        }
+        if counter.min_count == 0 && counter.max_count == 0 {
+            counter.max_count = u64::MAX;     // Bounds didn't exist yet: no upper limit
+        }
+        if counter.step == 0 {
+            counter.step = 1;                 // Steps didn't exist yet: one at a time
+        }
+        if counter.twa_started_at == 0 {
+            let now = Clock::get()?.unix_timestamp;
+            counter.last_update_ts = now;     // The time-weighted average starts from the migration
+            counter.twa_started_at = now;
+        }
+        require_keys_eq!(counter.authority, user.key(), CounterError::Unauthorized); // Only the owner
+        counter.layout_version = COUNTER_LAYOUT_VERSION;
+        counter.next_version();
+
+        // TRIM TO THE MIGRATED SIZE, TOPPING UP THE RENT FOR IT
+        let needed = Counter::space_for_metadata(&counter.metadata_label, &counter.metadata_uri);
+        info.resize(needed)?;
+        let rent = Rent::get()?.minimum_balance(needed).saturating_sub(info.lamports());
+        if rent > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer { from: user.to_account_info(), to: info.clone() },
+                ),
+                rent,
+            )?;
+        }
+        require_rent_exempt(&info)?;
+        counter.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        emit_event!(CounterMigrated {
+            counter: info.key(),
+            authority: counter.authority,
+            from_version,
+            to_version: COUNTER_LAYOUT_VERSION,
+            previous_size: previous_size as u32,
+            size: info.data_len() as u32,
+        });
+
+        verbose_msg!("Counter {} migrated from layout {} to {}", info.key(), from_version, COUNTER_LAYOUT_VERSION);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // HAND A COUNTER OVER TO ANOTHER VERSION OF THE PROGRAM (owner only)
+    // Settles any decay, moves the counter to `Migrating` so it can't change any more, and hands
+    // back an `ExportedCounter` through return data for the new program to `import`. If the move
+    // is called off, the admin can make it active again with `set_migrating(false)`
+    pub fn export(ctx: Context<ManageCounter>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::EXPORT)?;
+        let counter = &ctx.accounts.counter;
+        let now = Clock::get()?.unix_timestamp;
+        let policy = PolicyContext::at(counter, ctx.accounts.user.key(), PolicyOp::TransitionState, now);
+        ActivePolicy::validate(&policy, counter.count)?;
+
+        let counter = &mut ctx.accounts.counter;
+        settle_clock(counter)?;
+        apply_state_transition(counter, CounterState::Migrating)?;
+        let snapshot = ExportedCounter::of(counter, counter.key(), now);
+
+        emit_event!(CounterExported {
+            counter: snapshot.counter,
+            authority: snapshot.authority,
+            count: snapshot.count,
+            exported_at: now,
+        });
+        anchor_lang::solana_program::program::set_return_data(&snapshot.try_to_vec()?);
+        Ok(()) // No `report_diagnostics!` - it would overwrite the return data
+    }
+
+    // RECREATE A COUNTER EXPORTED BY ANOTHER VERSION OF THE PROGRAM (migration authority only)
+    // The counter comes back at the address it would have had if it had been created here, with
+    // the snapshot's owner, bounds, step, cooldown, overflow mode, count and version. Everything
+    // else starts out as on a new counter, and it isn't added to the creator's `UserRegistry`
+    pub fn import(ctx: Context<ImportCounter>, snapshot: ExportedCounter) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::IMPORT)?;
+        let settings = CounterSettings {
+            min: Some(snapshot.min_count),
+            max: Some(snapshot.max_count),
+            is_public: snapshot.is_public,
+            cooldown_seconds: Some(snapshot.cooldown_seconds),
+            overflow_mode: Some(snapshot.overflow_mode),
+            step: Some(snapshot.step),
+            ..Default::default()
+        };
+        let mut initialized = create_counter(
+            &mut ctx.accounts.counter,
+            ctx.bumps.counter,
+            snapshot.creator,
+            &mut ctx.accounts.stats,
+            snapshot.label.clone(),
+            settings,
+        )?;
+        let counter = &mut ctx.accounts.counter;
+        counter.rules().check(snapshot.count)?;
+        counter.count = snapshot.count;
+        counter.authority = snapshot.authority;
+        counter.namespace = snapshot.namespace;
+        counter.payer = ctx.accounts.migration_authority.key();
+        counter.version = snapshot.version;
+        counter.total_ops = snapshot.total_ops;
+        initialized.count = snapshot.count;
+        initialized.sponsor = counter.payer;
+
+        emit_indexed!(ctx, initialized);
+        emit_event!(CounterImported {
+            counter: ctx.accounts.counter.key(),
+            authority: snapshot.authority,
+            count: snapshot.count,
+            source_program: snapshot.program,
+            source_counter: snapshot.counter,
+        });
+
+        verbose_msg!("Counter {} imported from {}", ctx.accounts.counter.key(), snapshot.program);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CLEAN UP A COUNTER THAT NOBODY HAS TOUCHED FOR A LONG TIME
+    // Anyone can call this once a counter has been idle for `config.stale_after_seconds`.
+    // The rent is split between the original payer, the owner and the caller (as a reward)
+    // using the basis points in the config
+    pub fn reclaim_stale(ctx: Context<ReclaimStale>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        config.require_enabled(instruction_flag::RECLAIM_STALE)?;
+
+        let counter = &ctx.accounts.counter;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            config.stale_after_seconds > 0
+                && now.saturating_sub(counter.last_updated) >= config.stale_after_seconds,
+            CounterError::CounterNotStale
+        );
+        require_reclaimable(counter, now)?;
+
+        let stats = &mut ctx.accounts.stats;
+        stats.total_closed = stats.total_closed.saturating_add(1);
+        stats.announce();
+        unregister_counter(ctx.accounts.registry.as_mut(), ctx.accounts.counter.key());
+
+        let counter = &ctx.accounts.counter;
+        // The owner gets the rounding remainder (see `RentRefundSplit` for the shares)
+        emit!(CounterClosed::new(ctx.accounts.cranker.key(), counter, counter.authority, &Clock::get()?));
+
+        close_with_refund_split(
+            &counter.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.cranker.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CLEAN UP A COUNTER THAT HAS EXPIRED
+    // Anyone can call this once a counter's `expires_at` has passed. The rent is split like
+    // `reclaim_stale`'s, except that the original payer's share goes to the owner as well, so
+    // only the owner and the caller (as a reward) are paid
+    pub fn collect_expired(ctx: Context<CollectExpired>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        config.require_enabled(instruction_flag::COLLECT_EXPIRED)?;
+
+        let counter = &ctx.accounts.counter;
+        let now = Clock::get()?.unix_timestamp;
+        require!(counter.is_expired(now), CounterError::CounterNotExpired);
+        require_reclaimable(counter, now)?;
+
+        let stats = &mut ctx.accounts.stats;
+        stats.total_closed = stats.total_closed.saturating_add(1);
+        stats.announce();
+        unregister_counter(ctx.accounts.registry.as_mut(), ctx.accounts.counter.key());
+
+        let counter = &ctx.accounts.counter;
+        emit!(CounterClosed::new(ctx.accounts.cranker.key(), counter, counter.authority, &Clock::get()?));
+
+        let authority = ctx.accounts.authority.to_account_info();
+        close_with_refund_split(
+            &counter.to_account_info(),
+            &authority,
+            &authority,
+            &ctx.accounts.cranker.to_account_info(),
+            &ctx.accounts.config,
+        )?;
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // DROP COUNTERS THAT NO LONGER EXIST FROM YOUR REGISTRY (creator only)
+    // The permissionless cleanups (`reclaim_stale`, `reclaim_closed`, `collect_expired`) only
+    // take a counter off the registry when whoever runs them passes it in. Pass the listed
+    // counters that are gone as remaining accounts: each one that really is (no lamports left,
+    // or no longer this program's) loses its entry, so it stops counting towards
+    // `max_counters_per_user`. Accounts still holding a counter stay listed
+    pub fn prune_registry<'info>(ctx: Context<'_, '_, 'info, 'info, PruneRegistry<'info>>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::PRUNE_REGISTRY)?;
+        let gone: Vec<Pubkey> = ctx
+            .remaining_accounts
+            .iter()
+            .filter(|info| info.lamports() == 0 || *info.owner != crate::ID)
+            .map(|info| info.key())
+            .collect();
+
+        let registry = &mut ctx.accounts.registry;
+        let listed = registry.counters.len();
+        registry.counters.retain(|entry| !gone.contains(&entry.counter));
+        let removed = listed - registry.counters.len();
+
+        verbose_msg!("Pruned {} closed counters from the registry of {}", removed, registry.user);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CREATE A SEQUENCER FOR THE CALLING WALLET
+    // A sequencer is a per-wallet operation number shared by all of that wallet's counters.
+    // Clients (e.g. several devices of the same user) pass the number they expect as
+    // `expected_seq`; the operation only goes through if nobody else got in first
+    pub fn initialize_sequencer(ctx: Context<InitializeSequencer>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::INITIALIZE_SEQUENCER)?;
+
+        let sequencer = &mut ctx.accounts.sequencer;
+        sequencer.authority = ctx.accounts.user.key();
+        sequencer.seq = 0;
+        sequencer.bump = ctx.bumps.sequencer;
+
+        verbose_msg!("Sequencer initialized for user: {}", sequencer.authority);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CLAIM A SHORT, SHAREABLE ID FOR A COUNTER
+    // Like registering a short link - "ABC234" is much easier to share than a 44-character address
+    // First come, first served: whoever claims a code first owns it until they release it
+    pub fn claim_short_id(ctx: Context<ClaimShortId>, code: [u8; SHORT_ID_LEN]) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CLAIM_SHORT_ID)?;
+
+        // Only base32 characters are allowed so codes are easy to read out loud and type
+        require!(is_valid_short_id(&code), CounterError::InvalidShortId);
+
+        // Lock the anti-squatting deposit inside the short ID account (refunded on release)
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: ctx.accounts.short_id.to_account_info(),
+                },
+            ),
+            SHORT_ID_DEPOSIT_LAMPORTS,
+        )?;
+
+        let short_id = &mut ctx.accounts.short_id;
+        short_id.code = code;
+        short_id.counter = ctx.accounts.counter.key();
+        short_id.owner = ctx.accounts.user.key();
+        short_id.deposit = SHORT_ID_DEPOSIT_LAMPORTS;
+        short_id.bump = ctx.bumps.short_id;
+
+        emit_event!(ShortIdClaimed {
+            code,
+            counter: short_id.counter,
+            owner: short_id.owner,
+            deposit: short_id.deposit,
+        });
+
+        verbose_msg!("Short ID {} claimed for counter: {}", String::from_utf8_lossy(&code), short_id.counter);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // GIVE UP A SHORT ID
+    // Deletes the short ID so someone else can claim it, refunding the rent and deposit to the owner
+    pub fn release_short_id(ctx: Context<ReleaseShortId>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::RELEASE_SHORT_ID)?;
+
+        let short_id = &ctx.accounts.short_id;
+
+        emit_event!(ShortIdReleased {
+            code: short_id.code,
+            counter: short_id.counter,
+            owner: short_id.owner,
+        });
+
+        verbose_msg!("Short ID {} released by: {}", String::from_utf8_lossy(&short_id.code), short_id.owner);
+        report_diagnostics!(ctx);
+        Ok(()) // The account is closed automatically, refunding rent + deposit
+    }
+
+    // HEALTH CHECK FOR A COUNTER
+    // Anyone (usually a monitoring bot) can call this to re-check that a counter's stored
+    // data is still consistent. It never changes anything - it just announces pass/fail
+    pub fn verify_invariants(ctx: Context<VerifyInvariants>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::VERIFY_INVARIANTS)?;
+
+        let counter = &ctx.accounts.counter;
+        let info = counter.to_account_info();
+        let failures = counter.invariant_failures(&counter.key(), info.data_len(), ctx.program_id);
+
+        emit_event!(InvariantsChecked {
+            counter: counter.key(),
+            passed: failures == 0,
+            failures,
+        });
+
+        verbose_msg!("Invariant check for counter {}: failures = {:#x}", counter.key(), failures);
+        report_diagnostics!(ctx);
+        Ok(()) // A failed check is still a successful call - the result is in the event
+    }
+
+    // ANNOUNCE THE CURRENT VALUE WITHOUT CHANGING IT
+    // Anyone can call this (at most once per HEARTBEAT_INTERVAL_SECONDS per counter) so that
+    // services which only watch logs still hear about idle counters from time to time
+    pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::HEARTBEAT)?;
+
+        let counter = &mut ctx.accounts.counter;
+        let clock = Clock::get()?;
+
+        // RATE LIMIT: one heartbeat per interval
+        require!(
+            clock.unix_timestamp >= counter.last_heartbeat.saturating_add(HEARTBEAT_INTERVAL_SECONDS),
+            CounterError::HeartbeatTooSoon
+        );
+        counter.last_heartbeat = clock.unix_timestamp; // Only the timestamp changes, never the count
+
+        emit_event!(CounterHeartbeat {
+            counter: counter.key(),
+            authority: counter.authority,
+            count: counter.count,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+        });
+
+        verbose_msg!("Heartbeat for counter {} at count: {}", counter.key(), counter.count);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // READ A COUNTER WITHOUT TOUCHING IT
+    // Hands the value back through return data (see `CounterView`), so another program can
+    // read it right after a CPI and clients can read it from `simulateTransaction` - neither
+    // has to know the account layout. Anyone can call it and nothing is written.
+    pub fn view_count(ctx: Context<ViewCount>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::VIEW_COUNT)?;
+
+        let counter = &ctx.accounts.counter;
+        let view = CounterView {
+            count: counter.count,
+            authority: counter.authority,
+            version: counter.version,
+        };
+        anchor_lang::solana_program::program::set_return_data(&view.try_to_vec()?);
+        Ok(()) // No `report_diagnostics!` - it would overwrite the return data
+    }
+
+    // READ A COUNTER'S TIME-WEIGHTED AVERAGE COUNT
+    // The average over the counter's lifetime (or since the `migrate` that started tracking
+    // it), with the current count counted up to now. Handed back through return data (see
+    // `TwaView`) like `view_count`; anyone can call it and nothing is written
+    pub fn observe_twa(ctx: Context<ViewCount>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::OBSERVE_TWA)?;
+
+        let view = ctx.accounts.counter.time_weighted_average(Clock::get()?.unix_timestamp);
+        anchor_lang::solana_program::program::set_return_data(&view.try_to_vec()?);
+        Ok(())
+    }
+
+    // ADD LAMPORTS TO ONE OF YOUR ACCOUNTS
+    // For an account that has come to hold less than its size needs, e.g. after growing: any
+    // account of this program whose authority signs (a counter's owner, a registry's creator,
+    // a group's or template's creator...) can be topped up from the signer's wallet
+    pub fn top_up(ctx: Context<TopUp>, lamports: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::TOP_UP)?;
+        require!(lamports > 0, CounterError::InvalidAmount);
+        let authority = authority_of(&ctx.accounts.account.try_borrow_data()?);
+        require!(authority == Some(ctx.accounts.user.key()), CounterError::Unauthorized);
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: ctx.accounts.account.to_account_info(),
+                },
+            ),
+            lamports,
+        )?;
+
+        let account = &ctx.accounts.account;
+        emit_event!(AccountToppedUp {
+            account: account.key(),
+            payer: ctx.accounts.user.key(),
+            lamports,
+            balance: account.lamports(),
+        });
+
+        verbose_msg!("Account {} topped up by {} lamports", account.key(), lamports);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CHECK WHETHER AN OPERATION WOULD GO THROUGH, WITHOUT MAKING IT
+    // A preflight for wallets and UIs: runs the checks `increment_by` / `decrement_by` /
+    // `set_count` / `reset` would for the signer (pause and kill switches, authorization, token
+    // and NFT gates, lifecycle, cooldown and daily limit, bounds, and the change log, hook and
+    // group accounts the counter needs passed in) and hands back an `OpValidation` through
+    // return data, with the fees it would charge, instead of failing. Unlike a `dry_run` it
+    // needs none of the fee accounts, so it doesn't check the signer could actually pay
+    pub fn validate_op(ctx: Context<ValidateOp>, op: CounterOperation, amount: u64) -> Result<()> {
+        // Only this instruction's own switch fails it; a pause is reported like any other refusal
+        require!(
+            ctx.accounts.config.disabled_mask() & instruction_flag::VALIDATE_OP == 0,
+            CounterError::InstructionDisabled
+        );
+
+        let validation = ctx.accounts.validate(op, amount)?;
+        verbose_msg!("Counter {} {} by {}: allowed = {}", validation.counter, op.as_str(), amount, validation.allowed);
+        anchor_lang::solana_program::program::set_return_data(&validation.try_to_vec()?);
+        Ok(()) // No `report_diagnostics!` - it would overwrite the return data
+    }
+
+    // PROVE RECENT ACTIVITY TO A THIRD PARTY
+    // Checks that the counter had at least `min_ops` operations in the last `window_days` days
+    // (today included) and writes the answer into a receipt PDA, so a quest platform can verify
+    // "did X at least N times this week" by reading a single account. Anyone can call this -
+    // it only records what the counter itself already says. Proving again refreshes the receipt
+    pub fn prove_activity(ctx: Context<ProveActivity>, min_ops: u32, window_days: u8) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::PROVE_ACTIVITY)?;
+        require!(min_ops > 0, CounterError::InvalidAmount);
+        require!(
+            window_days > 0 && window_days as usize <= ACTIVITY_WINDOW_DAYS,
+            CounterError::InvalidActivityWindow
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let counter = &ctx.accounts.counter;
+        let ops_in_window = counter.ops_in_window(now, window_days);
+        require!(ops_in_window >= min_ops, CounterError::ActivityCriteriaNotMet);
+
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.counter = counter.key();
+        receipt.authority = counter.authority;
+        receipt.min_ops = min_ops;
+        receipt.window_days = window_days;
+        receipt.ops_in_window = ops_in_window;
+        receipt.total_ops = counter.total_ops;
+        receipt.proven_at = now;
+        receipt.bump = ctx.bumps.receipt;
+
+        emit_event!(ActivityProven {
+            counter: counter.key(),
+            authority: counter.authority,
+            receipt: receipt.key(),
+            min_ops,
+            window_days,
+            ops_in_window,
+        });
+
+        verbose_msg!("Counter {} had {} ops in the last {} days", counter.key(), ops_in_window, window_days);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // RECORD A COUNTER'S VALUE AS IT IS RIGHT NOW (owner only)
+    // Writes a `CounterSnapshot` (seeds = ["snapshot", counter, label as u64 LE]) that never
+    // changes afterwards, for periodic reporting. `label` is the caller's own key, e.g. a date
+    // like 20250131 or a period number; taking the same label twice fails
+    pub fn snapshot(ctx: Context<TakeSnapshot>, label: u64) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::SNAPSHOT)?;
+
+        let clock = Clock::get()?;
+        let counter = &ctx.accounts.counter;
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.counter = counter.key();
+        snapshot.authority = ctx.accounts.user.key();
+        snapshot.label = label;
+        snapshot.count = counter.count;
+        snapshot.slot = clock.slot;
+        snapshot.timestamp = clock.unix_timestamp;
+        snapshot.bump = ctx.bumps.snapshot;
+
+        emit_event!(SnapshotTaken {
+            counter: counter.key(),
+            snapshot: snapshot.key(),
+            label,
+            count: counter.count,
+            slot: clock.slot,
+        });
+
+        verbose_msg!("Snapshot {} of counter {} taken at: {}", label, counter.key(), counter.count);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // DELETE A SNAPSHOT AND GET ITS RENT BACK (whoever took it)
+    // Works even after the counter itself has been closed
+    pub fn close_snapshot(ctx: Context<CloseSnapshot>) -> Result<()> {
+        ctx.accounts.config.require_enabled(instruction_flag::CLOSE_SNAPSHOT)?;
+
+        let snapshot = &ctx.accounts.snapshot;
+        verbose_msg!("Snapshot {} of counter {} closed", snapshot.label, snapshot.counter);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // =================================================================================
+    // ADMIN INSTRUCTIONS
+    // =================================================================================
+
+    // CREATE THE PROGRAM-WIDE CONFIG ACCOUNT
+    // There is exactly one config for the whole program, and only the program's
+    // upgrade authority (the deployer) can create it and become its admin
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.disabled_instructions = 0; // Everything is enabled by default
+        config.disabled_instructions_hi = 0;
+        config.init_fee_lamports = 0;     // Creating counters is free until the admin sets a fee schedule
+        config.fee_recipient = Pubkey::default();
+        config.close_timelock_seconds = 0; // Closes go through right away
+        config.close_grace_seconds = 0;   // Soft closes start switched off
+        config.migration_authority = Pubkey::default(); // Nobody can import counters yet
+        config.op_fee_lamports = 0;       // Fees start switched off
+        config.fee_mint = Pubkey::default();
+        config.price_authority = config.admin;
+        config.refund_authority_bps = BPS_DENOMINATOR as u16; // By default the owner gets all reclaimed rent
+        config.stale_after_seconds = 0;   // Stale reclaiming starts switched off
+        config.paused = false;
+        config.max_counters_per_user = 0; // No cap on counters per wallet
+        config.partners = Vec::new();     // No partner namespaces until the admin approves some
+        config.bump = ctx.bumps.config;
+
+        verbose_msg!("Program config initialized with admin: {}", config.admin);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CREATE THE PROGRAM-WIDE STATISTICS ACCOUNT
+    // Needed once, right after `initialize_config` - counters created before it existed
+    // aren't included in the totals
+    pub fn initialize_stats(ctx: Context<InitializeStats>) -> Result<()> {
+        let stats = &mut ctx.accounts.stats;
+        stats.total_counters = 0;
+        stats.total_increments = 0;
+        stats.total_decrements = 0;
+        stats.total_closed = 0;
+        stats.bump = ctx.bumps.stats;
+
+        msg!("Global stats initialized");
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // TURN INDIVIDUAL INSTRUCTIONS OFF (OR BACK ON)
+    // An emergency switch that doesn't need a program upgrade - each bit in the mask
+    // matches one of the `instruction_flag` constants
+    pub fn set_disabled_instructions(ctx: Context<AdminConfig>, disabled_instructions: u128) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.set_disabled_mask(disabled_instructions);
+
+        emit_event!(InstructionFlagsUpdated {
+            admin: config.admin,
+            disabled_instructions,
+        });
+
+        verbose_msg!("Disabled instruction mask set to: {:#x}", disabled_instructions);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // TURN SOME INSTRUCTIONS OFF (OR BACK ON), LEAVING THE OTHERS AS THEY ARE
+    // Like `set_disabled_instructions`, but only the bits in `enable` / `disable` change, so
+    // e.g. switching `close` off for a migration window can't undo a switch someone else
+    // flipped in the meantime. A bit can't be in both masks
+    pub fn set_instruction_flags(ctx: Context<AdminConfig>, enable: u128, disable: u128) -> Result<()> {
+        require!(enable & disable == 0, CounterError::ConflictingInstructionFlags);
+        let config = &mut ctx.accounts.config;
+        let disabled_instructions = config.disabled_mask() & !enable | disable;
+        config.set_disabled_mask(disabled_instructions);
+
+        emit_event!(InstructionFlagsUpdated {
+            admin: config.admin,
+            disabled_instructions,
+        });
+
+        verbose_msg!("Enabled {:#x} and disabled {:#x}, mask is now: {:#x}", enable, disable, disabled_instructions);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // THE EMERGENCY BRAKE
+    // While paused, every instruction that isn't an admin action fails with `ProgramPaused`,
+    // whatever `disabled_instructions` says. Admin actions keep working so the admin can fix
+    // whatever went wrong and unpause
+    pub fn set_paused(ctx: Context<AdminConfig>, paused: bool) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.paused = paused;
+
+        emit_event!(ProgramPauseChanged {
+            admin: config.admin,
+            paused,
+        });
+
+        verbose_msg!("Program {}", if paused { "paused" } else { "unpaused" });
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // PUT A COUNTER INTO (OR TAKE IT OUT OF) THE MIGRATING STATE
+    // Used by the admin while moving a counter to a new layout or program
+    pub fn set_migrating(ctx: Context<AdminCounter>, migrating: bool) -> Result<()> {
+        let target = if migrating { CounterState::Migrating } else { CounterState::Active };
+        apply_state_transition(&mut ctx.accounts.counter, target)?;
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CONFIGURE PERMISSIONLESS CLEANUP
+    // How long a counter must sit idle before anyone may reclaim it (0 = never), and how the
+    // reclaimed rent is split between the original payer, the owner and the caller
+    pub fn set_cleanup_config(
+        ctx: Context<AdminConfig>,
+        refund_payer_bps: u16,
+        refund_authority_bps: u16,
+        refund_cranker_bps: u16,
+        stale_after_seconds: i64,
+    ) -> Result<()> {
+        require!(
+            refund_payer_bps as u64 + refund_authority_bps as u64 + refund_cranker_bps as u64 == BPS_DENOMINATOR,
+            CounterError::InvalidRefundSplit
+        );
+        require!(stale_after_seconds >= 0, CounterError::InvalidRefundSplit);
+
+        let config = &mut ctx.accounts.config;
+        config.refund_payer_bps = refund_payer_bps;
+        config.refund_authority_bps = refund_authority_bps;
+        config.refund_cranker_bps = refund_cranker_bps;
+        config.stale_after_seconds = stale_after_seconds;
+
+        verbose_msg!(
+            "Cleanup config: payer {} bps, authority {} bps, cranker {} bps, stale after {}s",
+            refund_payer_bps, refund_authority_bps, refund_cranker_bps, stale_after_seconds
+        );
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CAP HOW MANY COUNTERS ONE WALLET MAY HAVE AT A TIME (0 = no cap)
+    // Counted from the creator's `UserRegistry`, so closing a counter frees a place. Lowering
+    // the cap doesn't touch counters that already exist
+    pub fn set_max_counters_per_user(ctx: Context<AdminConfig>, max_counters_per_user: u32) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.max_counters_per_user = max_counters_per_user;
+
+        verbose_msg!("Counters per wallet capped at: {}", max_counters_per_user);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // LET COUNTERS BE CREATED IN A PARTNER'S NAMESPACE (see `initialize_namespaced`)
+    // The all-zero ID is reserved for counters without a namespace
+    pub fn approve_partner(ctx: Context<AdminConfig>, partner_id: [u8; PARTNER_ID_LEN]) -> Result<()> {
+        require!(partner_id != [0; PARTNER_ID_LEN], CounterError::InvalidPartnerId);
+        let config = &mut ctx.accounts.config;
+        require!(!config.partners.contains(&partner_id), CounterError::PartnerAlreadyApproved);
+        require!(config.partners.len() < MAX_PARTNERS, CounterError::TooManyPartners);
+        config.partners.push(partner_id);
+
+        emit_event!(PartnerUpdated { partner_id, approved: true });
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // STOP NEW COUNTERS BEING CREATED IN A PARTNER'S NAMESPACE
+    // Counters already created in it keep their address and keep working
+    pub fn revoke_partner(ctx: Context<AdminConfig>, partner_id: [u8; PARTNER_ID_LEN]) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let position = config.partners.iter().position(|id| *id == partner_id).ok_or(CounterError::UnknownPartner)?;
+        config.partners.remove(position);
+
+        emit_event!(PartnerUpdated { partner_id, approved: false });
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // MAKE CLOSES WAIT AFTER `request_close` (0 = close right away, no request needed)
+    // Counters that already have a request pending count from when it was made
+    pub fn set_close_timelock(ctx: Context<AdminConfig>, close_timelock_seconds: i64) -> Result<()> {
+        require!(close_timelock_seconds >= 0, CounterError::InvalidCloseTimelock);
+        let config = &mut ctx.accounts.config;
+        config.close_timelock_seconds = close_timelock_seconds;
+
+        verbose_msg!("Closes wait {} seconds after being requested", close_timelock_seconds);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // HOW LONG A SOFT-CLOSED COUNTER CAN BE REOPENED (0 = `soft_close` is turned off)
+    // Counters that are already soft-closed count from when they were closed
+    pub fn set_close_grace(ctx: Context<AdminConfig>, close_grace_seconds: i64) -> Result<()> {
+        require!(close_grace_seconds >= 0, CounterError::InvalidCloseGrace);
+        let config = &mut ctx.accounts.config;
+        config.close_grace_seconds = close_grace_seconds;
+
+        verbose_msg!("Soft-closed counters can be reopened for {} seconds", close_grace_seconds);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // NAME WHO MAY `import` COUNTERS FROM ANOTHER VERSION OF THE PROGRAM (default key = nobody)
+    pub fn set_migration_authority(ctx: Context<AdminConfig>, migration_authority: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.migration_authority = migration_authority;
+
+        verbose_msg!("Migration authority set to: {}", migration_authority);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // CONFIGURE THE PROTOCOL FEE CHARGED ON EVERY INCREMENT/DECREMENT
+    // The fee is always expressed in lamports. Callers can pay it in SOL, or in `fee_mint`
+    // tokens converted at the latest rate pushed by `price_authority`.
+    // Set `op_fee_lamports` to 0 to turn fees off, or `fee_mint` to the default key to accept SOL only
+    pub fn set_fee_config(
+        ctx: Context<AdminConfig>,
+        op_fee_lamports: u64,
+        fee_mint: Pubkey,
+        price_authority: Pubkey,
+        max_price_age_seconds: i64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        // A new mint means the old rate is meaningless, so forget it
+        if config.fee_mint != fee_mint {
+            config.tokens_per_sol = 0;
+            config.price_updated_at = 0;
+        }
+        config.op_fee_lamports = op_fee_lamports;
+        config.fee_mint = fee_mint;
+        config.price_authority = price_authority;
+        config.max_price_age_seconds = max_price_age_seconds;
+
+        emit_event!(FeeConfigUpdated {
+            op_fee_lamports,
+            fee_mint,
+            price_authority,
+            max_price_age_seconds,
+        });
+
+        verbose_msg!("Fee config updated: {} lamports per operation", op_fee_lamports);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // SET WHAT THE PROTOCOL CHARGES TO SUSTAIN THE PROGRAM
+    // `init_fee_lamports` is paid by every new counter's creator and `op_fee_lamports` on
+    // every change (the same fee `set_fee_config` sets), both into the treasury and on top of
+    // any fee the counter's owner charges. `fee_recipient` is where `withdraw_protocol_fees`
+    // sends SOL (the default key = the admin)
+    pub fn set_fee_schedule(
+        ctx: Context<AdminConfig>,
+        init_fee_lamports: u64,
+        op_fee_lamports: u64,
+        fee_recipient: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.init_fee_lamports = init_fee_lamports;
+        config.op_fee_lamports = op_fee_lamports;
+        config.fee_recipient = fee_recipient;
+
+        emit_event!(FeeScheduleUpdated {
+            init_fee_lamports,
+            op_fee_lamports,
+            fee_recipient,
+        });
+
+        verbose_msg!("Fee schedule: {} lamports per counter, {} per operation", init_fee_lamports, op_fee_lamports);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // PUSH THE LATEST SOL -> FEE TOKEN EXCHANGE RATE
+    // Called regularly by the price authority (an off-chain oracle service)
+    pub fn push_fee_price(ctx: Context<PushFeePrice>, tokens_per_sol: u64) -> Result<()> {
+        require!(tokens_per_sol > 0, CounterError::InvalidPrice);
+
+        let config = &mut ctx.accounts.config;
+        let now = Clock::get()?.unix_timestamp;
+        config.tokens_per_sol = tokens_per_sol;
+        config.price_updated_at = now;
+
+        emit_event!(FeePricePushed {
+            fee_mint: config.fee_mint,
+            tokens_per_sol,
+            timestamp: now,
+        });
+
+        verbose_msg!("Fee price updated: {} tokens per SOL", tokens_per_sol);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // START (OR RESUME) MINTING REWARD TOKENS FOR INCREMENTS
+    // The first call creates the reward mint (seeds = ["reward_mint"], no decimals) with the
+    // `RewardConfig` PDA as its mint authority, so only this program can ever mint it.
+    // Every unit added by an increment then mints `tokens_per_increment` to the caller
+    pub fn initialize_rewards(ctx: Context<InitializeRewards>, tokens_per_increment: u64) -> Result<()> {
+        require!(tokens_per_increment > 0, CounterError::InvalidAmount);
+
+        let rewards = &mut ctx.accounts.reward_config;
+        rewards.mint = ctx.accounts.reward_mint.key();
+        rewards.tokens_per_increment = tokens_per_increment;
+        rewards.enabled = true;
+        rewards.bump = ctx.bumps.reward_config;
+
+        emit_event!(RewardsConfigured {
+            mint: rewards.mint,
+            tokens_per_increment,
+            enabled: true,
+        });
+
+        verbose_msg!("Rewards enabled: {} of {} per increment", tokens_per_increment, rewards.mint);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // STOP MINTING REWARD TOKENS (the mint stays, so `initialize_rewards` can turn them back on)
+    pub fn disable_rewards(ctx: Context<DisableRewards>) -> Result<()> {
+        let rewards = &mut ctx.accounts.reward_config;
+        rewards.enabled = false;
+
+        emit_event!(RewardsConfigured {
+            mint: rewards.mint,
+            tokens_per_increment: rewards.tokens_per_increment,
+            enabled: false,
+        });
+
+        msg!("Rewards disabled");
+        report_diagnostics!(ctx);
+        Ok(())
+    }
+
+    // MOVE COLLECTED PROTOCOL FEES OUT OF THE TREASURY
+    // Withdraws SOL, or fee tokens when the token accounts are passed in
+    pub fn withdraw_protocol_fees(ctx: Context<WithdrawProtocolFees>, amount: u64) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[&[seeds::TREASURY, &[ctx.bumps.treasury]]];
+        let accounts = &ctx.accounts;
+
+        match (&accounts.treasury_token_account, &accounts.destination_token_account, &accounts.token_program) {
+            // Token withdrawal: the treasury PDA signs as the token account owner
+            (Some(from), Some(to), Some(token_program)) => token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    token::Transfer {
+                        from: from.to_account_info(),
+                        to: to.to_account_info(),
+                        authority: accounts.treasury.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+            )?,
+            // SOL withdrawal: the treasury PDA signs the System Program transfer, to the fee
+            // recipient if the fee schedule names one
+            (None, None, None) => {
+                let to = match &accounts.fee_recipient {
+                    Some(recipient) => recipient.to_account_info(),
+                    None if accounts.config.fee_recipient == Pubkey::default() => accounts.admin.to_account_info(),
+                    None => return err!(CounterError::FeeRecipientRequired),
+                };
+                anchor_lang::system_program::transfer(
+                    CpiContext::new_with_signer(
+                        accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: accounts.treasury.to_account_info(),
+                            to,
+                        },
+                        signer_seeds,
+                    ),
+                    amount,
+                )?
+            }
+            _ => return err!(CounterError::IncompleteFeeAccounts),
+        }
+
+        verbose_msg!("Withdrew {} from the protocol treasury", amount);
+        report_diagnostics!(ctx);
+        Ok(())
+    }
 }
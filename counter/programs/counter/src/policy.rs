@@ -0,0 +1,249 @@
+// POLICIES - The rules every counter operation has to pass
+
+use crate::*;
+
+// WHICH OPERATION A POLICY IS BEING ASKED ABOUT
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PolicyOp {
+    Increment,
+    Decrement,
+    Set,
+    Reset,
+    Close,
+    TransitionState,
+}
+
+// EVERYTHING A POLICY GETS TO LOOK AT
+pub struct PolicyContext<'a> {
+    pub counter: &'a Counter, // The counter being changed (before the change)
+    pub signer: Pubkey,       // Who signed the operation
+    pub op: PolicyOp,         // What they're trying to do
+    pub now: i64,             // Current unix timestamp
+    pub delegation: Option<&'a Delegation>, // The signer's operator approval, if they passed one in
+    pub session: Option<&'a Session>, // The signer's session, if they passed one in
+    pub contributor: Option<&'a Contributor>, // The signer's contributor registration, if they passed one in
+    pub role: Option<&'a Role>, // The signer's roles on the counter, if they passed them in
+    pub gate_balance: Option<u64>, // The signer's balance of the counter's gate token, if they passed it in
+    pub held_collection: Option<Pubkey>, // The verified collection of an NFT the signer holds, if they passed one in
+}
+
+impl<'a> PolicyContext<'a> {
+    // WHETHER THE SIGNER IS THE OWNER, A LIVE SESSION KEY OF THE OWNER'S OR HOLDS A ROLE FOR
+    // THIS OPERATION
+    pub fn acts_for_owner(&self) -> bool {
+        self.signer == self.counter.authority || self.has_role() || self.via_session()
+    }
+
+    // WHETHER A LIVE SESSION OF THE OWNER'S IS WHAT LETS THE SIGNER MAKE THIS CHANGE
+    // Not when they'd be let in anyway, as the owner or through a role (checked first, like in
+    // `check_auth`), so only a session that was needed spends one of its operations
+    pub fn via_session(&self) -> bool {
+        self.signer != self.counter.authority
+            && !self.has_role()
+            && matches!(self.op, PolicyOp::Increment | PolicyOp::Decrement)
+            && self.session.is_some_and(|s| s.authority == self.counter.authority && s.is_live(self.now))
+    }
+
+    // WHETHER THE SIGNER WAS GRANTED A ROLE (BY THE CURRENT OWNER) THAT COVERS THIS OPERATION
+    pub fn has_role(&self) -> bool {
+        let needed = match self.op {
+            PolicyOp::Increment => role::INCREMENTER | role::ADMIN,
+            PolicyOp::Decrement => role::DECREMENTER | role::ADMIN,
+            PolicyOp::Set | PolicyOp::Reset => role::ADMIN,
+            PolicyOp::Close | PolicyOp::TransitionState => return false, // Owner only
+        };
+        self.role.is_some_and(|r| r.authority == self.counter.authority && r.roles & needed != 0)
+    }
+
+    pub fn new(counter: &'a Counter, signer: Pubkey, op: PolicyOp) -> Result<Self> {
+        Ok(Self::at(counter, signer, op, Clock::get()?.unix_timestamp))
+    }
+
+    // `new`, for a handler that has already read the clock (every read is a syscall)
+    pub fn at(counter: &'a Counter, signer: Pubkey, op: PolicyOp, now: i64) -> Self {
+        Self {
+            counter,
+            signer,
+            op,
+            now,
+            delegation: None,
+            session: None,
+            contributor: None,
+            role: None,
+            gate_balance: None,
+            held_collection: None,
+        }
+    }
+}
+
+// THE RULES, AS ONE TRAIT
+// Forks of this program can add their own rules by implementing this trait on a new type
+// and pointing `ActivePolicy` at it - no instruction handler needs to change. Each check has
+// a sensible default, so a custom policy only overrides what it cares about
+pub trait CounterPolicy {
+    // Who is allowed to make this change (default: only the owner, except that anyone may
+    // increment a public counter, registered contributors may increment a shared one,
+    // approved operators may increment while they have allowance and members may make the
+    // changes their roles allow)
+    fn check_auth(ctx: &PolicyContext) -> Result<()> {
+        if ctx.counter.authority == ctx.signer || ctx.has_role() {
+            return Ok(());
+        }
+        // Session keys sign increments and decrements for the owner until they expire or run
+        // out of operations. Like approvals, sessions started by a previous owner don't count
+        if let Some(session) = ctx.session.filter(|s| s.authority == ctx.counter.authority) {
+            if matches!(ctx.op, PolicyOp::Increment | PolicyOp::Decrement) {
+                require!(ctx.now < session.expires_at, CounterError::SessionExpired);
+                require!(session.ops_left > 0, CounterError::SessionExhausted);
+                return Ok(());
+            }
+        }
+        if ctx.op == PolicyOp::Increment {
+            if ctx.counter.is_public {
+                return Ok(());
+            }
+            // Like approvals, registrations made by a previous owner don't count
+            if ctx.contributor.is_some_and(|c| c.authority == ctx.counter.authority) {
+                return Ok(());
+            }
+            // Approvals made by a previous owner don't count
+            if let Some(delegation) = ctx.delegation.filter(|d| d.authority == ctx.counter.authority) {
+                require!(delegation.allowance > 0, CounterError::AllowanceExhausted);
+                return Ok(());
+            }
+        }
+        err!(CounterError::Unauthorized)
+    }
+
+    // Whether the signer holds enough of the counter's gate token (default: required from
+    // everyone but the owner for changes to the count, when the counter has a gate), and an
+    // NFT of its gate collection (everyone but the owner, for increments)
+    fn check_gate(ctx: &PolicyContext) -> Result<()> {
+        let counter = ctx.counter;
+        let changes_count = matches!(ctx.op, PolicyOp::Increment | PolicyOp::Decrement | PolicyOp::Set | PolicyOp::Reset);
+        if changes_count && counter.gate_mint != Pubkey::default() && !ctx.acts_for_owner() {
+            let balance = ctx.gate_balance.unwrap_or(0);
+            require!(balance > 0 && balance >= counter.min_balance, CounterError::InsufficientTokenBalance);
+        }
+        // A counter created with an `nft_gate` only takes increments from holders of that collection
+        if let Some(collection) = counter.nft_gate {
+            if ctx.op == PolicyOp::Increment && !ctx.acts_for_owner() {
+                require!(ctx.held_collection == Some(collection), CounterError::NotCollectionHolder);
+            }
+        }
+        Ok(())
+    }
+
+    // Whether the counter's lifecycle state allows this operation
+    fn check_state(ctx: &PolicyContext) -> Result<()> {
+        // Frozen gets its own error, so apps can tell "locked for now" from "gone for good"
+        if ctx.counter.is_frozen() && ctx.op != PolicyOp::TransitionState {
+            return err!(CounterError::CounterFrozen);
+        }
+        // Soft-closed counters can only be reopened or closed for good (see `soft_close`)
+        if ctx.counter.closed_at != 0 && ctx.op != PolicyOp::Close {
+            return err!(CounterError::CounterSoftClosed);
+        }
+        match ctx.op {
+            PolicyOp::Increment | PolicyOp::Decrement | PolicyOp::Set | PolicyOp::Reset => {
+                require!(ctx.counter.state.allows_updates(), CounterError::CounterNotActive)
+            }
+            PolicyOp::Close => {
+                require!(ctx.counter.state.allows_close(), CounterError::CounterNotClosable);
+                // Members leave first, so their count doesn't stay in the group's total
+                require!(ctx.counter.group == Pubkey::default(), CounterError::CounterInGroup);
+                // And a wager has to be settled first: closing a counter and creating it again
+                // with its minimum at the target would win it
+                require!(!ctx.counter.in_open_wager(ctx.now), CounterError::CounterInWager);
+            }
+            PolicyOp::TransitionState => {} // Checked against the target state by the handler
+        }
+        Ok(())
+    }
+
+    // Whether the counter has expired (default: once `expires_at` has passed, nothing but
+    // closing is allowed)
+    fn check_expiry(ctx: &PolicyContext) -> Result<()> {
+        if ctx.op != PolicyOp::Close {
+            require!(!ctx.counter.is_expired(ctx.now), CounterError::CounterExpired);
+        }
+        Ok(())
+    }
+
+    // Whether the count may change right now (default: only inside the counter's window,
+    // when it was created with one)
+    fn check_window(ctx: &PolicyContext) -> Result<()> {
+        if matches!(ctx.op, PolicyOp::Increment | PolicyOp::Decrement | PolicyOp::Set | PolicyOp::Reset) {
+            require!(ctx.counter.in_window(ctx.now), CounterError::OutsideWindow);
+        }
+        Ok(())
+    }
+
+    // Whether the operation is coming too fast (default: increments have to wait out the
+    // counter's cooldown since its last change, and stop once today's limit is used up)
+    fn check_rate(ctx: &PolicyContext) -> Result<()> {
+        let counter = ctx.counter;
+        if ctx.op == PolicyOp::Increment && counter.cooldown_seconds > 0 {
+            let ready_at = counter.last_updated.saturating_add(counter.cooldown_seconds);
+            require!(ctx.now >= ready_at, CounterError::CooldownActive);
+        }
+        if ctx.op == PolicyOp::Increment {
+            require!(counter.daily_increments_left(ctx.now) > 0, CounterError::DailyLimitReached);
+        }
+        Ok(())
+    }
+
+    // Whether the change could decide an open wager out of turn (default: while the counter is
+    // in one, the count only moves a step at a time - no `set_count`, `reset` or jumps of more
+    // than a step, so the target is reached by counting, not by whoever sends a jump first)
+    fn check_wager(ctx: &PolicyContext, new_count: u64) -> Result<()> {
+        let counter = ctx.counter;
+        if counter.in_open_wager(ctx.now) {
+            let correction = matches!(ctx.op, PolicyOp::Set | PolicyOp::Reset);
+            let jump = new_count > counter.count.saturating_add(counter.step);
+            require!(!correction && !jump, CounterError::CounterInWager);
+        }
+        Ok(())
+    }
+
+    // Whether the resulting value is acceptable (default: within the counter's own min/max)
+    fn check_bounds(ctx: &PolicyContext, new_count: u64) -> Result<()> {
+        ctx.counter.rules().check(new_count)
+    }
+
+    // How many lamports to charge for the operation (default: the protocol fee for any change
+    // to the count, so `set_count` is never a cheaper way to increment)
+    fn check_fee(ctx: &PolicyContext, config: &ProgramConfig) -> Result<u64> {
+        Ok(match ctx.op {
+            PolicyOp::Increment | PolicyOp::Decrement | PolicyOp::Set | PolicyOp::Reset => config.op_fee_lamports,
+            PolicyOp::Close | PolicyOp::TransitionState => 0,
+        })
+    }
+
+    // How many lamports the counter's owner charges for the operation (default: the counter's
+    // `fee_lamports` for every unit added by someone other than the owner)
+    fn check_counter_fee(ctx: &PolicyContext, amount: u64) -> Result<u64> {
+        let charged = ctx.op == PolicyOp::Increment && !ctx.acts_for_owner();
+        Ok(if charged { ctx.counter.fee_lamports.saturating_mul(amount) } else { 0 })
+    }
+
+    // Run every non-fee check in order
+    fn validate(ctx: &PolicyContext, new_count: u64) -> Result<()> {
+        Self::check_auth(ctx)?;
+        Self::check_gate(ctx)?;
+        Self::check_state(ctx)?;
+        Self::check_expiry(ctx)?;
+        Self::check_window(ctx)?;
+        Self::check_rate(ctx)?;
+        Self::check_wager(ctx, new_count)?;
+        Self::check_bounds(ctx, new_count)
+    }
+}
+
+// THE RULES THIS PROGRAM SHIPS WITH
+pub struct DefaultPolicy;
+
+impl CounterPolicy for DefaultPolicy {}
+
+// THE POLICY THE INSTRUCTION HANDLERS USE - swap this out to customize a fork
+pub type ActivePolicy = DefaultPolicy;
@@ -0,0 +1,1204 @@
+// STATE - What we store in accounts, and what instructions hand back
+
+use crate::*;
+
+// =====================================================================================
+// RETURN DATA - What instructions hand back to the caller
+// =====================================================================================
+
+// THE START OF A METAPLEX METADATA ACCOUNT
+// Just enough of Token Metadata's layout to reach `collection` (everything after it is
+// ignored), so the program doesn't need the Metaplex crate for the NFT gate
+pub(crate) const METADATA_V1_KEY: u8 = 4;
+
+#[derive(AnchorDeserialize)]
+pub(crate) struct MetadataHead {
+    pub(crate) key: u8,
+    pub(crate) _update_authority: Pubkey,
+    pub(crate) mint: Pubkey,
+    pub(crate) _name: Vec<u8>, // Strings, but padded with NULs - raw bytes skip the UTF-8 check
+    pub(crate) _symbol: Vec<u8>,
+    pub(crate) _uri: Vec<u8>,
+    pub(crate) _seller_fee_basis_points: u16,
+    pub(crate) _creators: Option<Vec<MetadataCreator>>,
+    pub(crate) _primary_sale_happened: bool,
+    pub(crate) _is_mutable: bool,
+    pub(crate) _edition_nonce: Option<u8>,
+    pub(crate) _token_standard: Option<u8>,
+    pub(crate) collection: Option<MetadataCollection>,
+}
+
+#[derive(AnchorDeserialize)]
+pub(crate) struct MetadataCreator {
+    pub(crate) _address: Pubkey,
+    pub(crate) _verified: bool,
+    pub(crate) _share: u8,
+}
+
+#[derive(AnchorDeserialize)]
+pub(crate) struct MetadataCollection {
+    pub(crate) verified: bool, // Signed off by the collection's authority - unverified ones can be set by anyone
+    pub(crate) key: Pubkey,
+}
+
+// RESULT OF A SUCCESSFUL DRY RUN
+// Returned (Borsh-encoded) through return data when an instruction is called with `dry_run = true`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct WouldSucceed {
+    pub counter: Pubkey,
+    pub previous_count: u64,
+    pub new_count: u64,
+}
+
+// RESULT OF A `validate_op` PREFLIGHT
+// Returned (Borsh-encoded) through return data. `error_code` is the error the operation would
+// fail with (0 when `allowed`); `new_count` and the fees are only meaningful when it's allowed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct OpValidation {
+    pub counter: Pubkey,
+    pub op: CounterOperation,
+    pub allowed: bool,
+    pub error_code: u32,
+    pub previous_count: u64,       // After any decay or period rollover the operation would start with
+    pub new_count: u64,
+    pub fee_lamports: u64,         // The protocol fee
+    pub counter_fee_lamports: u64, // The owner's own fee
+    pub token_fee: u64,            // The owner's token fee, in `Counter.fee_mint` base units
+}
+
+// A COUNTER AS SEEN BY `view_count`
+// Returned (Borsh-encoded) through return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CounterView {
+    pub count: u64,
+    pub authority: Pubkey,
+    pub version: u64,
+}
+
+// A COUNTER AS HANDED FROM ONE PROGRAM VERSION TO ANOTHER (see `export` / `import`)
+// Returned (Borsh-encoded) through return data by `export`, so the receiving program can read it
+// right after a CPI and know which program it came from. `import` takes the same struct
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExportedCounter {
+    pub program: Pubkey,        // The program that exported it
+    pub counter: Pubkey,        // Its address there
+    pub creator: Pubkey,
+    pub namespace: [u8; PARTNER_ID_LEN],
+    pub label: String,
+    pub authority: Pubkey,
+    pub count: u64,
+    pub min_count: u64,
+    pub max_count: u64,
+    pub step: u64,
+    pub is_public: bool,
+    pub cooldown_seconds: i64,
+    pub overflow_mode: OverflowMode,
+    pub version: u64,
+    pub total_ops: u64,
+    pub exported_at: i64,
+}
+
+impl ExportedCounter {
+    // THE SNAPSHOT OF `counter` (at `address`, in this program) AS OF `now`
+    pub fn of(counter: &Counter, address: Pubkey, now: i64) -> Self {
+        Self {
+            program: crate::ID,
+            counter: address,
+            creator: counter.creator,
+            namespace: counter.namespace,
+            label: counter.label.clone(),
+            authority: counter.authority,
+            count: counter.count,
+            min_count: counter.min_count,
+            max_count: counter.max_count,
+            step: counter.step,
+            is_public: counter.is_public,
+            cooldown_seconds: counter.cooldown_seconds,
+            overflow_mode: counter.overflow_mode,
+            version: counter.version,
+            total_ops: counter.total_ops,
+            exported_at: now,
+        }
+    }
+
+    // THE NAMESPACE PART OF THE COUNTER'S SEEDS (see `Counter::namespace_seed`)
+    pub fn namespace_seed(&self) -> &[u8] {
+        if self.namespace == [0; PARTNER_ID_LEN] {
+            &[]
+        } else {
+            &self.namespace
+        }
+    }
+}
+
+// A COUNTER'S TIME-WEIGHTED AVERAGE AS SEEN BY `observe_twa`
+// Returned (Borsh-encoded) through return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TwaView {
+    pub average: u64,             // `sum_count_x_seconds / seconds`, rounded down
+    pub sum_count_x_seconds: u128,
+    pub seconds: i64,             // Since `Counter.twa_started_at`
+}
+
+// =====================================================================================
+// DATA STRUCTURES - What information we store in accounts
+// =====================================================================================
+
+// THE COUNTER ACCOUNT DATA STRUCTURE
+// This defines what information is stored in each counter account on the blockchain
+#[account]
+#[derive(InitSpace)]  // This helps calculate how much storage space we need
+pub struct Counter {
+    pub count: u64,        // The current number (0, 1, 2, 3, etc.)
+    pub bump: u8,          // A special number that helps find this account
+    pub authority: Pubkey, // The owner of this counter (who can change it)
+    pub last_heartbeat: i64, // When the last heartbeat was sent (unix timestamp, 0 = never)
+    pub state: CounterState, // Where the counter is in its lifecycle
+    pub payer: Pubkey,       // Who paid the rent for this account
+    pub last_updated: i64,   // When the owner last changed the counter (unix timestamp)
+    pub total_ops: u64,      // How many times the count has been changed, ever
+    pub activity_day: i64,   // The most recent day (unix time / 86400) with recorded activity
+    pub daily_ops: [u32; ACTIVITY_WINDOW_DAYS], // Operations per day, indexed by day % 7
+    #[max_len(MAX_LABEL_LEN)]
+    pub label: String,       // The counter's name ("steps", "score", ...); "" for a wallet's default counter
+    pub creator: Pubkey,     // Who created the counter - its address is derived from this, not `authority`
+    pub pending_authority: Option<Pubkey>, // Proposed new owner who still has to accept (see `transfer_authority`)
+    pub min_count: u64,      // Lowest value the counter may hold (0 = no lower limit)
+    pub max_count: u64,      // Highest value the counter may hold (u64::MAX = no upper limit)
+    pub is_public: bool,     // Whether anyone (not just the owner) may increment it
+    pub cooldown_seconds: i64, // Shortest time between two increments, measured from `last_updated` (0 = no limit)
+    pub fee_lamports: u64,   // What others pay the owner per unit they add (see `set_counter_fee`)
+    pub gate_mint: Pubkey,   // Token others must hold to change the counter (default key = no gate)
+    pub min_balance: u64,    // How much of `gate_mint` they must hold
+    pub has_history: bool,   // Whether changes are logged to a `CounterHistory` (see `initialize_history`)
+    pub version: u64,        // Goes up by one with every change to this account (heartbeats aside)
+    pub overflow_mode: OverflowMode, // What increments/decrements do at the edges of `min_count..=max_count`
+    pub milestone_interval: u64, // Announce every multiple of this the counter reaches (0 = off)
+    pub expires_at: i64,     // When the counter stops accepting changes (unix timestamp, 0 = never)
+    pub current_streak: u32, // Consecutive UTC days, up to `last_day`, with at least one increment
+    pub best_streak: u32,    // The longest `current_streak` ever reached
+    pub last_day: i64,       // The most recent day (unix time / 86400) with an increment
+    pub daily_limit: u64,    // Most increments accepted per UTC day (0 = no limit)
+    pub today_count: u64,    // Increments made on `day_stamp`
+    pub day_stamp: i64,      // The day (unix time / 86400) `today_count` belongs to
+    pub sequence: u64,       // Number of the counter's latest `CounterInitialized` (0) / `CounterUpdated` / `CounterBatchApplied` event
+    pub deposit_per_increment: u64, // Lamports every increment pays into the vault (see `set_vault_deposit`)
+    pub nft_gate: Option<Pubkey>, // Verified Metaplex collection others must hold an NFT of to increment (set at creation)
+    pub layout_version: u8,  // Which account layout this is (COUNTER_LAYOUT_VERSION; older ones need `migrate`). Not `version`, the change count
+    // Display metadata (see `set_metadata`), sized as empty here: the account grows to fit them
+    #[max_len(0)]
+    pub metadata_label: String, // Name to show in explorers (up to MAX_METADATA_LABEL_LEN bytes, "" = none)
+    #[max_len(0)]
+    pub metadata_uri: String,   // Link to more about the counter (up to MAX_METADATA_URI_LEN bytes, "" = none)
+    pub reset_interval: i64,    // Seconds between automatic resets (0 = never, see `set_reset_interval`)
+    pub period_start: i64,      // When the current reset period began (unix timestamp)
+    pub last_period_count: u64, // The count the previous reset period ended on
+    pub fee_mint: Pubkey,       // Token others pay `fee_amount` of per increment/decrement (see `set_token_fee`)
+    pub fee_amount: u64,        // Tokens (in base units) per operation (0 = no token fee)
+    pub decay_rate_per_day: u64, // Taken off the count for every full day that passes (0 = no decay)
+    pub last_decay_ts: i64,     // When decay was last settled (unix timestamp)
+    pub referrals: u64,         // Increments made with this counter's owner as referrer
+    pub close_requested_at: i64, // When `request_close` was called (0 = no close pending)
+    pub step: u64,              // How much `increment` / `decrement` move the count (never 0, see `set_step`)
+    pub last_nonce: u64,        // Highest nonce `increment_with_nonce` accepted (0 = none yet)
+    pub keeper_tip_lamports: u64, // Paid from the vault to whoever runs `touch` (0 = no tip)
+    pub alert_threshold: u64,   // The count `ThresholdCrossed` watches for (see `set_alert_threshold`)
+    pub alert_direction: Option<AlertDirection>, // Which side of it to alert on (None = no alert)
+    pub last_alert_state: bool, // Whether the count was past the threshold after the last change
+    pub hook_program: Pubkey,   // Notified of every change (see `set_hook`, default = none)
+    pub group: Pubkey,          // The `CounterGroup` its changes add up in (see `join_group`, default = none)
+    pub namespace: [u8; PARTNER_ID_LEN], // The partner it was created under (see `initialize_namespaced`, zeroes = none)
+    pub window_start: i64,      // The count can't change before this unix timestamp (0 = no start, fixed at creation)
+    pub window_end: i64,        // ...or after this one (0 = no end, fixed at creation; see `finalize`)
+    pub weight_mint: Pubkey,    // Token whose balance `increment_weighted` adds (see `set_weight_config`, default = off)
+    pub weight_divisor: u64,    // Balance units per unit added (never 0 while `weight_mint` is set)
+    pub feed_authority: Option<Pubkey>, // Reporter allowed to `push_value` (see `set_feed_authority`, None = no feed)
+    pub last_observed_at: i64,  // When the latest pushed value was observed (0 = nothing pushed yet)
+    pub sum_count_x_seconds: u128, // Every count it has held times the seconds it held it, added up (see `observe_twa`)
+    pub last_update_ts: i64,    // When that sum was last brought up to date
+    pub twa_started_at: i64,    // When the sum started (creation, or the `migrate` that added it)
+    pub closed_at: i64,         // When `soft_close` was called (0 = open; see `reopen`)
+    pub recovery_authority: Option<Pubkey>, // May `claim_authority` once the counter goes quiet (fixed at creation, None = no recovery)
+    // How long without a change to the count before it may (fixed at creation). This measures
+    // traffic on the counter, not whether the owner is around: `last_updated` moves with every
+    // change, including public visitors', operators', role holders' and feeds', so a busy
+    // counter can't be recovered even if its owner lost their key long ago
+    pub inactivity_threshold: i64,
+    pub recovery_claimed_at: i64,  // When the pending claim was made (0 = none; see `veto_claim`)
+    pub recovery_vetoed_at: i64,   // When the owner last vetoed one, which restarts the inactivity clock
+    pub open_wagers: u8,        // Wagers on this counter not settled yet (see `in_open_wager`)
+    pub wager_deadline: i64,    // The latest deadline among them
+    pub created_at: i64,        // When this account was created, telling it apart from a counter later recreated at its address (0 = before layout 22)
+}
+
+// WHAT AN INCREMENT/DECREMENT DOES WHEN IT WOULD LEAVE THE COUNTER'S RANGE
+// The range is `min_count..=max_count` (all of u64 unless bounds were set). Corrections
+// (`set_count` / `reset`) are always checked against the bounds, whatever the mode
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum OverflowMode {
+    Checked,    // Fail with an overflow/underflow or bounds error (the original behavior)
+    Saturating, // Stop at the edge: 98 + 5 with a max of 100 gives 100
+    Wrapping,   // Go round: 98 + 5 with a range of 0..=100 gives 2 (e.g. round numbers)
+}
+
+// WHICH WAY A COUNT HAS TO CROSS `alert_threshold` TO RAISE AN ALERT
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum AlertDirection {
+    Above, // Reaching the threshold or more from below it (e.g. a queue growing too long)
+    Below, // Reaching the threshold or less from above it (e.g. stock running low)
+}
+
+impl AlertDirection {
+    // Whether `count` is on the alerting side of `threshold` (the threshold itself included)
+    pub fn is_past(self, count: u64, threshold: u64) -> bool {
+        match self {
+            AlertDirection::Above => count >= threshold,
+            AlertDirection::Below => count <= threshold,
+        }
+    }
+}
+
+// ONE CHANGE TO THE COUNT, AS PLAIN DATA (see `apply_op`)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CountOp {
+    Increment(u64), // Add this much
+    Decrement(u64), // Subtract this much
+    Set(u64),       // Overwrite the count
+    Reset,          // Go back to the minimum
+}
+
+// WHAT A COUNTER'S COUNT HAS TO STAY WITHIN, AND WHAT HAPPENS AT THE EDGES
+// A copy of the counter's `min_count`, `max_count` and `overflow_mode` (see `Counter::rules`),
+// so the arithmetic can be worked out - and tested - without an account
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CountRules {
+    pub min: u64,
+    pub max: u64,
+    pub overflow_mode: OverflowMode,
+}
+
+impl CountRules {
+    // WHERE `op` TAKES `count`, FOLLOWING THE OVERFLOW MODE
+    // Only the arithmetic: whether the result is within the bounds is `check`'s job
+    pub fn apply(&self, count: u64, op: CountOp) -> Result<u64> {
+        let (amount, up) = match op {
+            CountOp::Increment(amount) => (amount, true),
+            CountOp::Decrement(amount) => (amount, false),
+            CountOp::Set(value) => return Ok(value),
+            CountOp::Reset => return Ok(self.min),
+        };
+        let (min, max) = (self.min, self.max);
+        Ok(match self.overflow_mode {
+            OverflowMode::Checked if up => count.checked_add(amount).ok_or(CounterError::CounterOverflow)?,
+            OverflowMode::Checked => count.checked_sub(amount).ok_or(CounterError::CounterUnderflow)?,
+            OverflowMode::Saturating if up => count.saturating_add(amount).min(max),
+            OverflowMode::Saturating => count.saturating_sub(amount).max(min),
+            OverflowMode::Wrapping => {
+                // Work in u128 so a full 0..=u64::MAX range (2^64 values) still fits
+                let size = (max - min) as u128 + 1;
+                let offset = (count - min) as u128;
+                let step = amount as u128 % size;
+                let moved = if up { (offset + step) % size } else { (offset + size - step) % size };
+                min + moved as u64
+            }
+        })
+    }
+
+    // WHETHER `count` IS WITHIN `min..=max`
+    pub fn check(&self, count: u64) -> Result<()> {
+        require!(count <= self.max, CounterError::CounterAboveMax);
+        require!(count >= self.min, CounterError::CounterBelowMin);
+        Ok(())
+    }
+}
+
+// THE COUNT AFTER `op`, OR WHY IT CAN'T CHANGE THAT WAY
+// The arithmetic and the bounds of a change and nothing else - no accounts, clock or signer.
+// Handlers run the bounds through `CounterPolicy::check_bounds` instead, after the other checks
+pub fn apply_op(count: u64, op: CountOp, rules: CountRules) -> Result<u64> {
+    let new_count = rules.apply(count, op)?;
+    rules.check(new_count)?;
+    Ok(new_count)
+}
+
+// ONE STEP OF AN `apply_batch` CALL
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BatchOp {
+    Increment(u64), // Add this much (not zero)
+    Decrement(u64), // Subtract this much (not zero)
+    Set(u64),       // Overwrite the count (owner only)
+}
+
+// THE LIFECYCLE OF A COUNTER
+//
+//   Active <-> Frozen          (temporarily locked, e.g. during a dispute)
+//   Active  -> Archived        (read-only forever, can still be closed)
+//   Active/Frozen <-> PendingClose (closing has been requested, can be cancelled back to Active)
+//   Active <-> Migrating       (admin only, while the account is being upgraded)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum CounterState {
+    Active,
+    Frozen,
+    Archived,
+    PendingClose,
+    Migrating,
+}
+
+impl CounterState {
+    // The full list of allowed moves - anything not listed here is rejected
+    pub fn can_transition_to(self, next: CounterState) -> bool {
+        use CounterState::*;
+        matches!(
+            (self, next),
+            (Active, Frozen)
+                | (Frozen, Active)
+                | (Active, Archived)
+                | (Active, PendingClose)
+                | (Frozen, PendingClose)
+                | (PendingClose, Active)
+                | (Active, Migrating)
+                | (Migrating, Active)
+        )
+    }
+
+    // Only active counters can be incremented/decremented
+    pub fn allows_updates(self) -> bool {
+        self == CounterState::Active
+    }
+
+    // Frozen and migrating counters must not disappear
+    pub fn allows_close(self) -> bool {
+        matches!(self, CounterState::Active | CounterState::Archived | CounterState::PendingClose)
+    }
+}
+
+impl Counter {
+    // RE-CHECK EVERYTHING THAT SHOULD ALWAYS BE TRUE ABOUT A COUNTER
+    // Returns a bitmask of `invariant_failure` values (0 = healthy)
+    pub fn invariant_failures(&self, key: &Pubkey, data_len: usize, program_id: &Pubkey) -> u32 {
+        let mut failures = 0;
+
+        let seeds: &[&[u8]] = &[seeds::COUNTER, self.namespace_seed(), self.creator.as_ref(), self.label.as_bytes()];
+        match Pubkey::create_program_address(&[seeds, &[&[self.bump]]].concat(), program_id) {
+            Ok(address) if address == *key => {}
+            _ => failures |= invariant_failure::PDA_MISMATCH,
+        }
+        let (_, canonical_bump) = Pubkey::find_program_address(seeds, program_id);
+        if canonical_bump != self.bump {
+            failures |= invariant_failure::NON_CANONICAL_BUMP;
+        }
+        if data_len < 8 + Counter::INIT_SPACE {
+            failures |= invariant_failure::ACCOUNT_TOO_SMALL;
+        }
+        if self.authority == Pubkey::default() {
+            failures |= invariant_failure::MISSING_AUTHORITY;
+        }
+        if self.layout_version != COUNTER_LAYOUT_VERSION {
+            failures |= invariant_failure::OUTDATED_LAYOUT;
+        }
+
+        failures
+    }
+
+    // WHETHER THE COUNTER IS LOCKED (see `freeze`) - the lifecycle state is the only record of it
+    pub fn is_frozen(&self) -> bool {
+        self.state == CounterState::Frozen
+    }
+
+    // ACCOUNT SIZE NEEDED TO HOLD THIS DISPLAY METADATA (empty strings = the bare size)
+    pub fn space_for_metadata(label: &str, uri: &str) -> usize {
+        8 + Counter::INIT_SPACE + label.len() + uri.len()
+    }
+
+    // THE COUNT AFTER `op`, OR WHY IT CAN'T CHANGE THAT WAY (see `apply_op`)
+    pub fn apply(&self, op: CountOp) -> Result<u64> {
+        apply_op(self.count, op, self.rules())
+    }
+
+    // WHERE AN INCREMENT (`up`) OR DECREMENT BY `amount` LANDS, FOLLOWING `overflow_mode`
+    pub fn stepped(&self, amount: u64, up: bool) -> Result<u64> {
+        self.stepped_from(self.count, amount, up)
+    }
+
+    // THE SAME, STARTING FROM `count` INSTEAD OF THE STORED VALUE (e.g. after a period rollover)
+    pub fn stepped_from(&self, count: u64, amount: u64, up: bool) -> Result<u64> {
+        let op = if up { CountOp::Increment(amount) } else { CountOp::Decrement(amount) };
+        self.rules().apply(count, op)
+    }
+
+    // THE PARTNER NAMESPACE PART OF THE COUNTER'S ADDRESS SEEDS
+    // Empty without a namespace, which derives the same address as leaving the seed out, so
+    // every instruction can use the one seed list for both kinds of counter
+    pub fn namespace_seed(&self) -> &[u8] {
+        if self.namespace == [0; PARTNER_ID_LEN] {
+            &[]
+        } else {
+            &self.namespace
+        }
+    }
+
+    // THE BOUNDS AND OVERFLOW MODE CHANGES TO THE COUNT ARE WORKED OUT AGAINST
+    pub fn rules(&self) -> CountRules {
+        CountRules { min: self.min_count, max: self.max_count, overflow_mode: self.overflow_mode }
+    }
+
+    // THE MILESTONE AN INCREMENT FROM `previous_count` TO THE CURRENT COUNT REACHED, IF ANY
+    // A wrapping counter that went round only counts as reaching a milestone on its way up
+    pub fn milestone_reached(&self, previous_count: u64) -> Option<u64> {
+        if self.milestone_interval == 0 {
+            return None;
+        }
+        let milestone = self.count - self.count % self.milestone_interval;
+        (milestone > previous_count).then_some(milestone)
+    }
+
+    // NOTE THE CURRENT COUNT AGAINST THE ALERT THRESHOLD (see `set_alert_threshold`)
+    // Returns the alert's direction the first time the count is past the threshold; it has to
+    // come back before the same alert can fire again
+    pub fn check_alert(&mut self) -> Option<AlertDirection> {
+        let direction = self.alert_direction?;
+        let past = direction.is_past(self.count, self.alert_threshold);
+        let crossed = past && !self.last_alert_state;
+        self.last_alert_state = past;
+        crossed.then_some(direction)
+    }
+
+    // WHETHER `expires_at` HAS PASSED
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+
+    // WHETHER A WAGER THAT CAN STILL BE SETTLED IS RIDING ON THIS COUNTER
+    // Wagers past their deadline can only be refunded, so they stop counting then even if
+    // nobody has cranked `refund_wager` (which doesn't see the counters)
+    pub fn in_open_wager(&self, now: i64) -> bool {
+        self.open_wagers > 0 && now <= self.wager_deadline
+    }
+
+    // WHETHER `now` IS INSIDE THE COUNTING WINDOW (always, without one)
+    pub fn in_window(&self, now: i64) -> bool {
+        (self.window_start == 0 || now >= self.window_start) && (self.window_end == 0 || now <= self.window_end)
+    }
+
+    // COUNT AN INCREMENT TOWARDS THE DAILY STREAK
+    // The first increment of a day extends the streak if the previous one was yesterday and
+    // starts a new one after a gap; later increments that day change nothing. Returns whether
+    // the streak changed
+    pub fn extend_streak(&mut self, now: i64) -> bool {
+        let today = now.div_euclid(SECONDS_PER_DAY);
+        if self.current_streak > 0 && today == self.last_day {
+            return false;
+        }
+        let consecutive = self.current_streak > 0 && today == self.last_day + 1;
+        self.current_streak = if consecutive { self.current_streak.saturating_add(1) } else { 1 };
+        self.best_streak = self.best_streak.max(self.current_streak);
+        self.last_day = today;
+        true
+    }
+
+    // THE START OF THE CURRENT RESET PERIOD, IF THE STORED ONE HAS LAPSED (see `reset_interval`)
+    // Periods stay aligned to the first one, however many went by without a single change
+    pub fn lapsed_period(&self, now: i64) -> Option<i64> {
+        if self.reset_interval <= 0 || now < self.period_start.saturating_add(self.reset_interval) {
+            return None;
+        }
+        let periods = (now - self.period_start) / self.reset_interval;
+        Some(self.period_start + periods * self.reset_interval)
+    }
+
+    // THE DECAY OWED AT `now` (see `set_decay_rate`): the count after it and the new
+    // `last_decay_ts`. Only whole days count, the rest of the current one carries over. The
+    // count never decays below the counter's minimum (zero unless it has bounds)
+    pub fn pending_decay(&self, now: i64) -> Option<(u64, i64)> {
+        if self.decay_rate_per_day == 0 {
+            return None;
+        }
+        let days = now.saturating_sub(self.last_decay_ts) / SECONDS_PER_DAY;
+        if days <= 0 {
+            return None;
+        }
+        let decayed = self
+            .count
+            .saturating_sub(self.decay_rate_per_day.saturating_mul(days as u64))
+            .max(self.min_count.min(self.count));
+        Some((decayed, self.last_decay_ts + days * SECONDS_PER_DAY))
+    }
+
+    // START A NEW RESET PERIOD: KEEP THE FINAL COUNT, THEN GO BACK TO THE STARTING VALUE
+    pub fn roll_over_period(&mut self, period_start: i64) {
+        self.accrue_twa(period_start); // The old count lasted until the period ended
+        self.last_period_count = self.count;
+        self.count = self.min_count;
+        self.period_start = period_start;
+    }
+
+    // ADD THE CURRENT COUNT, HELD SINCE `last_update_ts`, TO THE TIME-WEIGHTED SUM
+    // Called just before the count changes. `at` may lie in the past (decay and period
+    // rollovers are dated to when they fell due); a time before the last update adds nothing
+    pub fn accrue_twa(&mut self, at: i64) {
+        if at <= self.last_update_ts {
+            return;
+        }
+        let held = (at - self.last_update_ts) as u128;
+        self.sum_count_x_seconds = self.sum_count_x_seconds.saturating_add(self.count as u128 * held);
+        self.last_update_ts = at;
+    }
+
+    // THE TIME-WEIGHTED AVERAGE AT `now`, COUNTING THE CURRENT COUNT UP TO THEN (nothing written)
+    // Before a full second has passed the average is just the count
+    pub fn time_weighted_average(&self, now: i64) -> TwaView {
+        let held = now.saturating_sub(self.last_update_ts).max(0) as u128;
+        let sum_count_x_seconds = self.sum_count_x_seconds.saturating_add(self.count as u128 * held);
+        let seconds = now.saturating_sub(self.twa_started_at).max(0);
+        let average = match seconds {
+            0 => self.count,
+            _ => u64::try_from(sum_count_x_seconds / seconds as u128).unwrap_or(u64::MAX),
+        };
+        TwaView { average, sum_count_x_seconds, seconds }
+    }
+
+    // HOW MANY MORE INCREMENTS `daily_limit` ALLOWS TODAY (u64::MAX without a limit)
+    pub fn daily_increments_left(&self, now: i64) -> u64 {
+        if self.daily_limit == 0 {
+            return u64::MAX;
+        }
+        let used = if self.day_stamp == now.div_euclid(SECONDS_PER_DAY) { self.today_count } else { 0 };
+        self.daily_limit.saturating_sub(used)
+    }
+
+    // COUNT `increments` TOWARDS TODAY'S LIMIT, STARTING AFRESH ON A NEW DAY
+    pub fn count_daily_increments(&mut self, now: i64, increments: u64) {
+        let today = now.div_euclid(SECONDS_PER_DAY);
+        if self.day_stamp != today {
+            self.day_stamp = today;
+            self.today_count = 0;
+        }
+        self.today_count = self.today_count.saturating_add(increments);
+    }
+
+    // NUMBER THE NEXT EVENT ABOUT THIS COUNTER
+    // Unlike `version`, this only moves for the events indexers follow, so a consumer that
+    // sees 7 after 5 knows it missed one, and one that sees 5 twice can drop the duplicate
+    pub fn next_sequence(&mut self) -> u64 {
+        self.sequence = self.sequence.saturating_add(1);
+        self.sequence
+    }
+
+    // WHEN THE OWNER WAS LAST SEEN (what `claim_authority` measures inactivity from)
+    // The latest change to the count, or veto of a recovery claim if that came later
+    pub fn last_active_at(&self) -> i64 {
+        self.last_updated.max(self.recovery_vetoed_at)
+    }
+
+    // WHETHER A RECOVERY CLAIM IS PENDING
+    // A claim the counter has seen activity since no longer stands: it was made because the
+    // counter had gone quiet, and it hasn't stayed that way
+    pub fn claim_pending(&self) -> bool {
+        self.recovery_claimed_at != 0 && self.last_active_at() < self.recovery_claimed_at
+    }
+
+    // MARK THE ACCOUNT AS CHANGED
+    // Called by everything that writes the counter except heartbeats, which only record when
+    // they were sent. Off-chain caches compare versions to spot missed or out-of-order updates
+    pub fn next_version(&mut self) {
+        self.version = self.version.saturating_add(1);
+    }
+
+    // COUNT ONE OPERATION TOWARDS TODAY'S ACTIVITY
+    pub fn record_activity(&mut self, now: i64) {
+        let today = now.div_euclid(SECONDS_PER_DAY);
+        // Clear the slots of any days that passed without activity (at most a full week)
+        if today > self.activity_day {
+            let skipped = (today - self.activity_day).min(ACTIVITY_WINDOW_DAYS as i64);
+            for back in 0..skipped {
+                self.daily_ops[Self::activity_slot(today - back)] = 0;
+            }
+            self.activity_day = today;
+        }
+        let slot = Self::activity_slot(today);
+        self.daily_ops[slot] = self.daily_ops[slot].saturating_add(1);
+        self.total_ops = self.total_ops.saturating_add(1);
+    }
+
+    // HOW MANY OPERATIONS HAPPENED IN THE LAST `window_days` DAYS (today included)
+    pub fn ops_in_window(&self, now: i64, window_days: u8) -> u32 {
+        let today = now.div_euclid(SECONDS_PER_DAY);
+        (0..window_days as i64)
+            .map(|back| today - back)
+            // Slots only hold data for the week ending on `activity_day`
+            .filter(|day| *day <= self.activity_day && self.activity_day - day < ACTIVITY_WINDOW_DAYS as i64)
+            .map(|day| self.daily_ops[Self::activity_slot(day)])
+            .fold(0u32, u32::saturating_add)
+    }
+
+    fn activity_slot(day: i64) -> usize {
+        day.rem_euclid(ACTIVITY_WINDOW_DAYS as i64) as usize
+    }
+}
+
+// THE SHORT ID ACCOUNT DATA STRUCTURE
+// Maps a human-friendly code to a counter address
+#[account]
+#[derive(InitSpace)]
+pub struct ShortId {
+    pub code: [u8; SHORT_ID_LEN], // The code itself, e.g. "ABC234"
+    pub counter: Pubkey,          // The counter this code points to
+    pub owner: Pubkey,            // Who claimed the code (and gets the deposit back)
+    pub deposit: u64,             // Anti-squatting deposit locked in this account
+    pub bump: u8,                 // A special number that helps find this account
+}
+
+// THE PROGRAM-WIDE CONFIG ACCOUNT
+// A single account (seeds = ["config"]) holding settings that apply to every counter
+#[account]
+#[derive(InitSpace)]
+pub struct ProgramConfig {
+    pub admin: Pubkey,               // Who can change these settings
+    pub disabled_instructions: u64,  // Bitmask of `instruction_flag` values that are switched off
+    pub bump: u8,                    // A special number that helps find this account
+    pub op_fee_lamports: u64,        // Fee per increment/decrement in lamports (0 = no fees)
+    pub fee_mint: Pubkey,            // Token that can be used instead of SOL (default key = SOL only)
+    pub price_authority: Pubkey,     // Oracle allowed to push the SOL -> token rate
+    pub tokens_per_sol: u64,         // Latest rate: fee mint base units worth 1 SOL
+    pub price_updated_at: i64,       // When the rate was last pushed
+    pub max_price_age_seconds: i64,  // How old the rate may be before token payments are refused
+    pub refund_payer_bps: u16,       // Share of reclaimed rent for the original payer
+    pub refund_authority_bps: u16,   // Share of reclaimed rent for the counter owner
+    pub refund_cranker_bps: u16,     // Share of reclaimed rent for whoever runs the cleanup
+    pub stale_after_seconds: i64,    // Idle time before anyone may reclaim a counter (0 = never)
+    pub paused: bool,                // Emergency brake: blocks everything but admin actions
+    pub max_counters_per_user: u32,  // Most live counters one wallet may have (0 = no cap, see `UserRegistry`)
+    pub disabled_instructions_hi: u64, // Flag bits 64 and up of the same mask
+    pub init_fee_lamports: u64,      // Fee per new counter in lamports (0 = free, see `set_fee_schedule`)
+    pub fee_recipient: Pubkey,       // Where SOL protocol fees are withdrawn to (default key = the admin)
+    pub close_timelock_seconds: i64, // How long a close waits after `request_close` (0 = no timelock)
+    pub close_grace_seconds: i64,    // How long a soft-closed counter can be reopened (0 = no soft closes)
+    pub migration_authority: Pubkey, // Who may `import` counters from another program (default key = nobody)
+    #[max_len(MAX_PARTNERS)]
+    pub partners: Vec<[u8; PARTNER_ID_LEN]>, // Namespaces `initialize_namespaced` may create counters in
+}
+
+impl ProgramConfig {
+    // Stop right away if the program is paused or the admin has switched this instruction off
+    pub fn require_enabled(&self, flag: u128) -> Result<()> {
+        require!(!self.paused, CounterError::ProgramPaused);
+        require!(self.disabled_mask() & flag == 0, CounterError::InstructionDisabled);
+        Ok(())
+    }
+
+    // The whole `instruction_flag` mask: `disabled_instructions` holds the low 64 bits
+    pub fn disabled_mask(&self) -> u128 {
+        (self.disabled_instructions_hi as u128) << 64 | self.disabled_instructions as u128
+    }
+
+    // Stores the whole mask back into its two words
+    pub fn set_disabled_mask(&mut self, mask: u128) {
+        self.disabled_instructions = mask as u64;
+        self.disabled_instructions_hi = (mask >> 64) as u64;
+    }
+
+    // CONVERT A LAMPORT FEE INTO FEE TOKENS USING THE ORACLE RATE
+    // Rounds up so paying in tokens is never cheaper than paying in SOL
+    pub fn token_fee_amount(&self, fee_lamports: u64, now: i64) -> Result<u64> {
+        require!(
+            self.tokens_per_sol > 0 && now.saturating_sub(self.price_updated_at) <= self.max_price_age_seconds,
+            CounterError::StalePrice
+        );
+        let amount = (fee_lamports as u128 * self.tokens_per_sol as u128)
+            .div_ceil(LAMPORTS_PER_SOL as u128);
+        u64::try_from(amount).map_err(|_| error!(CounterError::FeeOverflow))
+    }
+
+    // SPLIT RECLAIMED LAMPORTS INTO (payer, authority, cranker) SHARES
+    // The authority gets the rounding remainder so the three always add up to `total`
+    pub fn refund_split(&self, total: u64) -> (u64, u64, u64) {
+        let share = |bps: u16| (total as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        let payer = share(self.refund_payer_bps);
+        let cranker = share(self.refund_cranker_bps);
+        (payer, total - payer - cranker, cranker)
+    }
+}
+
+// THE GLOBAL STATS ACCOUNT DATA STRUCTURE
+// A single account (seeds = ["stats"]) with totals across every counter, so indexers don't
+// have to scan them all
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalStats {
+    pub total_counters: u64,   // Counters ever created
+    pub total_increments: u64, // Increment operations (of any amount) ever made
+    pub total_decrements: u64, // Decrement operations (of any amount) ever made
+    pub total_closed: u64,     // Counters closed by their owner or reclaimed as stale
+    pub bump: u8,              // A special number that helps find this account
+}
+
+impl GlobalStats {
+    // Tell indexers about the new totals
+    pub fn announce(&self) {
+        emit_event!(GlobalStatsUpdated {
+            total_counters: self.total_counters,
+            total_increments: self.total_increments,
+            total_decrements: self.total_decrements,
+            total_closed: self.total_closed,
+        });
+    }
+}
+
+// THE SEQUENCER ACCOUNT DATA STRUCTURE
+// One per wallet (seeds = ["sequencer", authority]); goes up by one on every operation that passes it in
+#[account]
+#[derive(InitSpace)]
+pub struct Sequencer {
+    pub authority: Pubkey, // The wallet this sequencer belongs to
+    pub seq: u64,          // Number of operations ordered so far (the next expected value)
+    pub bump: u8,          // A special number that helps find this account
+}
+
+// THE ACTIVITY RECEIPT ACCOUNT DATA STRUCTURE
+// Written by `prove_activity` (seeds = ["activity", counter, min_ops as u32 LE, window_days])
+// Verifiers check `proven_at` against their own period, e.g. "proven since Monday"
+#[account]
+#[derive(InitSpace)]
+pub struct ActivityReceipt {
+    pub counter: Pubkey,     // The counter that was checked
+    pub authority: Pubkey,   // Its owner when the proof was made
+    pub min_ops: u32,        // The criteria: at least this many operations...
+    pub window_days: u8,     // ...in this many days, ending on the day of the proof
+    pub ops_in_window: u32,  // What the counter actually had
+    pub total_ops: u64,      // The counter's all-time operation total at the time
+    pub proven_at: i64,      // When the proof was made (unix timestamp)
+    pub bump: u8,            // A special number that helps find this account
+}
+
+// THE COUNTER SNAPSHOT ACCOUNT DATA STRUCTURE
+// Written once by `snapshot` (seeds = ["snapshot", counter, label as u64 LE]) and never changed
+#[account]
+#[derive(InitSpace)]
+pub struct CounterSnapshot {
+    pub counter: Pubkey,     // The counter that was recorded
+    pub authority: Pubkey,   // Who took the snapshot (and gets the rent back on `close_snapshot`)
+    pub label: u64,          // The caller's key for this snapshot, e.g. a date or period number
+    pub count: u64,          // The counter's value at the time
+    pub slot: u64,           // When the snapshot was taken (slot)...
+    pub timestamp: i64,      // ...and (unix timestamp)
+    pub bump: u8,            // A special number that helps find this account
+}
+
+// THE OPERATOR DELEGATION ACCOUNT DATA STRUCTURE
+// Written by `approve_operator` (seeds = ["delegate", counter, operator]); lets `operator`
+// increment the counter until `allowance` runs out
+#[account]
+#[derive(InitSpace)]
+pub struct Delegation {
+    pub counter: Pubkey,     // The counter the operator may increment
+    pub operator: Pubkey,    // The wallet allowed to increment it
+    pub authority: Pubkey,   // The owner who approved it (the approval lapses if ownership moves)
+    pub allowance: u64,      // How much the operator may still add
+    pub bump: u8,            // A special number that helps find this account
+}
+
+// THE SESSION ACCOUNT DATA STRUCTURE
+// Written by `create_session` (seeds = ["session", counter, session_key]); lets `session_key`
+// increment and decrement the counter as its owner while `is_live`
+#[account]
+#[derive(InitSpace)]
+pub struct Session {
+    pub counter: Pubkey,     // The counter the session key may change
+    pub session_key: Pubkey, // The key the app signs with
+    pub authority: Pubkey,   // The owner who started it (the session lapses if ownership moves)
+    pub expires_at: i64,     // When the session stops working (unix timestamp)
+    pub ops_left: u32,       // How many more increments/decrements it may sign
+    pub bump: u8,            // A special number that helps find this account
+}
+
+impl Session {
+    // Whether the session can still sign an operation at `now`
+    pub fn is_live(&self, now: i64) -> bool {
+        now < self.expires_at && self.ops_left > 0
+    }
+}
+
+// THE COUNTER GROUP ACCOUNT DATA STRUCTURE
+// A running total over a set of counters (seeds = ["group", authority, name]), e.g. one per
+// device in a fleet. Counters join with `join_group`, bringing their count with them, and
+// every later change through `update_count` / `apply_batch` moves `total` by the same amount
+#[account]
+#[derive(InitSpace)]
+pub struct CounterGroup {
+    pub authority: Pubkey,   // Who created the group (and has to agree to every new member)
+    #[max_len(MAX_LABEL_LEN)]
+    pub name: String,        // Part of the address, like a counter's label
+    pub total: u64,          // The members' counts added up
+    pub members: u32,        // How many counters are in the group
+    pub capacity: u32,       // The most members it may have
+    pub bump: u8,            // A special number that helps find this account
+}
+
+// THE WAGER ACCOUNT DATA STRUCTURE
+// A bet between two owners on whose counter reaches `target` first (seeds = ["wager",
+// creator_counter, opponent_counter]). Both stakes sit in its vault (seeds = ["wager_vault",
+// wager]) until `settle_wager` pays the winner, or `refund_wager` hands them back after `deadline`
+#[account]
+#[derive(InitSpace)]
+pub struct Wager {
+    pub creator: Pubkey,          // Who started it (and paid its rent)
+    pub opponent: Pubkey,         // Who took the bet
+    pub creator_counter: Pubkey,
+    pub opponent_counter: Pubkey,
+    pub target: u64,              // The count to reach
+    pub stake_lamports: u64,      // What each side put in (the vault holds twice this)
+    pub deadline: i64,            // Unix timestamp after which it can only be refunded
+    pub bump: u8,                 // A special number that helps find this account
+    pub creator_counter_created_at: i64, // The counters' `created_at`, so one closed and recreated at
+    pub opponent_counter_created_at: i64, // the same address since can't settle the wager
+}
+
+// THE COUNTER TEMPLATE ACCOUNT DATA STRUCTURE
+// Named counter settings (seeds = ["template", authority, name]) that `initialize_from_template`
+// gives new counters. Fixed once created
+#[account]
+#[derive(InitSpace)]
+pub struct CounterTemplate {
+    pub authority: Pubkey,   // Who created the template
+    #[max_len(MAX_LABEL_LEN)]
+    pub name: String,        // Part of the address, like a counter's label
+    pub params: TemplateParams,
+    pub bump: u8,            // A special number that helps find this account
+}
+
+// THE SETTINGS A TEMPLATE HANDS ON
+// `initialize`'s arguments (None = its default) and the owner settings made afterwards
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct TemplateParams {
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+    pub is_public: bool,
+    pub cooldown_seconds: Option<i64>,
+    pub overflow_mode: Option<OverflowMode>,
+    pub nft_gate: Option<Pubkey>,
+    pub fee_lamports: u64,        // See `set_counter_fee`
+    pub gate_mint: Pubkey,        // See `set_token_gate` (default key = no gate)
+    pub min_balance: u64,
+    pub daily_limit: u64,         // See `set_daily_limit`
+    pub milestone_interval: u64,  // See `set_milestone_interval`
+    pub step: Option<u64>,        // See `set_step` (None = 1)
+}
+
+// THE USER DEFAULTS ACCOUNT DATA STRUCTURE
+// A wallet's own starting settings (seeds = ["defaults", authority]) that `initialize` fills in
+// for whatever it isn't given, when the account is passed in. Changed with `set_defaults`
+#[account]
+#[derive(InitSpace)]
+pub struct UserDefaults {
+    pub authority: Pubkey,   // The wallet whose new counters these apply to
+    pub params: DefaultParams,
+    pub bump: u8,            // A special number that helps find this account
+}
+
+// THE `initialize` ARGUMENTS A WALLET CAN SET DEFAULTS FOR (None = the program's own default)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Default, InitSpace)]
+pub struct DefaultParams {
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+    pub cooldown_seconds: Option<i64>,
+    pub overflow_mode: Option<OverflowMode>,
+    pub step: Option<u64>,
+}
+
+// THE LEADERBOARD ACCOUNT DATA STRUCTURE
+// A single account (seeds = ["leaderboard"]) holding the highest counters submitted with
+// `submit_score`, highest first. Unused places are at the end and have a default `counter`
+#[account]
+#[derive(InitSpace)]
+pub struct Leaderboard {
+    pub entries: [LeaderboardEntry; LEADERBOARD_SIZE], // Sorted by count, highest first
+    pub bump: u8,                                      // A special number that helps find this account
+}
+
+// ONE PLACE ON THE LEADERBOARD
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default, InitSpace)]
+pub struct LeaderboardEntry {
+    pub counter: Pubkey,     // The counter (default = unused place)
+    pub authority: Pubkey,   // Who owned it when it was submitted
+    pub count: u64,          // Its count when it was submitted
+}
+
+impl LeaderboardEntry {
+    pub fn is_empty(&self) -> bool {
+        self.counter == Pubkey::default()
+    }
+}
+
+impl Leaderboard {
+    // Puts `entry` on the board if it belongs there and re-sorts it. Returns the entry's rank
+    // (0 = top), or None if the board is full and it doesn't beat the lowest count
+    pub fn submit(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        let lowest = LEADERBOARD_SIZE - 1;
+        let place = self
+            .entries
+            .iter()
+            .position(|existing| existing.counter == entry.counter)
+            .or_else(|| self.entries.iter().position(LeaderboardEntry::is_empty))
+            .or_else(|| (entry.count > self.entries[lowest].count).then_some(lowest))?;
+        self.entries[place] = entry;
+
+        // Stable, so ties keep the order they reached their count in
+        self.entries.sort_by_key(|existing| std::cmp::Reverse((!existing.is_empty(), existing.count)));
+        self.entries.iter().position(|existing| existing.counter == entry.counter)
+    }
+}
+
+// THE CONTRIBUTOR ACCOUNT DATA STRUCTURE
+// Written by `add_contributor` (seeds = ["contributor", counter, member]); lets `member`
+// increment the counter and tallies what they added. Scan these accounts filtered by
+// `counter` to build a leaderboard
+#[account]
+#[derive(InitSpace)]
+pub struct Contributor {
+    pub counter: Pubkey,      // The shared counter
+    pub member: Pubkey,       // The wallet allowed to increment it
+    pub authority: Pubkey,    // The owner who added them (the registration lapses if ownership moves)
+    pub contributions: u64,   // Everything the member's increments have added
+    pub bump: u8,             // A special number that helps find this account
+}
+
+// THE ROLE ACCOUNT DATA STRUCTURE
+// Written by `grant_role` (seeds = ["role", counter, member]); lets `member` make the changes
+// to the count its `roles` bits allow (see `role`), e.g. an ops bot that only increments
+#[account]
+#[derive(InitSpace)]
+pub struct Role {
+    pub counter: Pubkey,      // The counter the roles are on
+    pub member: Pubkey,       // The wallet holding them
+    pub authority: Pubkey,    // The owner who granted them (they lapse if ownership moves)
+    pub roles: u8,            // `role` bits
+    pub bump: u8,             // A special number that helps find this account
+}
+
+// THE COMMITMENT ACCOUNT DATA STRUCTURE
+// Written by `commit` (seeds = ["commitment", counter, user]): the SHA-256 of a value `user`
+// will add to the counter, and a salt, so nobody can see the value until `reveal`
+#[account]
+#[derive(InitSpace)]
+pub struct Commitment {
+    pub counter: Pubkey,      // The counter the value is for
+    pub user: Pubkey,         // Who committed
+    pub hash: [u8; 32],       // sha256(value as 8 little-endian bytes || salt)
+    pub committed_at: i64,    // Unix timestamp of the commit
+    pub bump: u8,             // A special number that helps find this account
+}
+
+impl Commitment {
+    // WHETHER `value` AND `salt` ARE WHAT WAS COMMITTED TO
+    pub fn matches(&self, value: u64, salt: &[u8; 32]) -> bool {
+        anchor_lang::solana_program::hash::hashv(&[&value.to_le_bytes(), salt]).to_bytes() == self.hash
+    }
+}
+
+// THE MULTISIG AUTHORITY ACCOUNT DATA STRUCTURE
+// Written by `create_multisig` (seeds = ["multisig", counter]) and set as the counter's
+// authority, so the counter only changes once `threshold` of `owners` approved a proposal
+#[account]
+#[derive(InitSpace)]
+pub struct MultisigAuthority {
+    pub counter: Pubkey,      // The counter this multisig runs
+    #[max_len(MAX_MULTISIG_OWNERS)]
+    pub owners: Vec<Pubkey>,  // Who may propose and approve (fixed at creation, no duplicates)
+    pub threshold: u8,        // Approvals a proposal needs before it can be executed
+    pub proposal_count: u64,  // Proposals made so far - also the index of the next one
+    pub bump: u8,             // A special number that helps find this account
+}
+
+impl MultisigAuthority {
+    // Where `owner` sits in `owners` (its bit in `MultisigProposal.approvals`)
+    pub fn owner_slot(&self, owner: &Pubkey) -> Option<usize> {
+        self.owners.iter().position(|candidate| candidate == owner)
+    }
+}
+
+// A CHANGE WAITING FOR ITS MULTISIG'S APPROVAL
+// Seeds = ["proposal", multisig, index as little-endian u64]; deleted once executed
+#[account]
+#[derive(InitSpace)]
+pub struct MultisigProposal {
+    pub multisig: Pubkey,
+    pub index: u64,           // Its number among the multisig's proposals
+    pub proposer: Pubkey,     // Paid the rent, and gets it back on execution
+    pub op: MultisigOp,
+    pub approvals: u16,       // One bit per `MultisigAuthority.owners` entry that approved
+    pub bump: u8,
+}
+
+// WHAT A MULTISIG PROPOSAL DOES TO ITS COUNTER
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum MultisigOp {
+    Increment(u64),            // Add this much (not zero)
+    Decrement(u64),            // Subtract this much (not zero)
+    Set(u64),                  // Overwrite the count
+    Reset,                     // Back to the counter's starting value
+    TransferAuthority(Pubkey), // Hand the counter to this wallet, leaving the multisig behind
+}
+
+// THE USER REGISTRY ACCOUNT DATA STRUCTURE
+// One per wallet (seeds = ["registry", creator]), listing every live counter the wallet
+// created, so apps can enumerate them with a single account read instead of a
+// `getProgramAccounts` scan. Counters stay listed under their creator when ownership moves,
+// since the creator is part of their address
+#[account]
+pub struct UserRegistry {
+    pub user: Pubkey,                 // The creator whose counters these are
+    pub bump: u8,                     // A special number that helps find this account
+    pub counters: Vec<RegistryEntry>, // One entry per live counter, in no particular order
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RegistryEntry {
+    pub counter: Pubkey,
+    pub label: String,
+}
+
+impl UserRegistry {
+    // Size of a registry with no entries yet (discriminator + user + bump + vec length)
+    pub const EMPTY_SPACE: usize = 8 + 32 + 1 + 4;
+
+    // Size needed to hold the current entries
+    pub fn space(&self) -> usize {
+        Self::EMPTY_SPACE + self.counters.iter().map(|entry| 32 + 4 + entry.label.len()).sum::<usize>()
+    }
+}
+
+// THE COUNTER HISTORY ACCOUNT DATA STRUCTURE
+// Written by `initialize_history` (seeds = ["history", counter]). A zero-copy ring buffer of the
+// counter's last HISTORY_LEN changes: `entries[total % HISTORY_LEN]` is written next, so once it
+// is full the oldest entry is overwritten. Explorers read it straight from the account
+#[account(zero_copy)]
+pub struct CounterHistory {
+    pub counter: Pubkey,                    // The counter whose changes are logged
+    pub total: u64,                         // Changes logged ever (not just the ones still held)
+    pub bump: u8,                           // A special number that helps find this account
+    pub padding: [u8; 7],                   // Keeps `entries` 8-byte aligned
+    pub entries: [HistoryEntry; HISTORY_LEN],
+}
+
+// ONE LOGGED CHANGE
+#[zero_copy]
+pub struct HistoryEntry {
+    pub slot: u64,      // When the change happened
+    pub previous: u64,  // The count before...
+    pub new: u64,       // ...and after
+    pub operation: u8,  // A `CounterOperation` (0 = Increment, 1 = Decrement, 2 = Set, 3 = Reset)
+    pub padding: [u8; 7],
+}
+
+impl CounterHistory {
+    // WRITE A CHANGE INTO THE NEXT SLOT, OVERWRITING THE OLDEST ONE WHEN FULL
+    pub fn record(&mut self, slot: u64, operation: CounterOperation, previous: u64, new: u64) {
+        let index = (self.total % HISTORY_LEN as u64) as usize;
+        self.entries[index] = HistoryEntry { slot, previous, new, operation: operation as u8, padding: [0; 7] };
+        self.total = self.total.saturating_add(1);
+    }
+
+    // THE ENTRIES STILL HELD, OLDEST FIRST
+    pub fn entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        let held = self.total.min(HISTORY_LEN as u64) as usize;
+        let oldest = (self.total - held as u64) as usize;
+        (oldest..oldest + held).map(move |i| &self.entries[i % HISTORY_LEN])
+    }
+}
+
+// THE SIGNED COUNTER ACCOUNT DATA STRUCTURE
+// Written by `initialize_signed` (seeds = ["signed_counter", authority, label]). Deliberately
+// plain: owner-only changes by any amount, in either direction, with no bounds or fees
+#[account]
+#[derive(InitSpace)]
+pub struct SignedCounter {
+    pub authority: Pubkey,   // The owner (the only one who can change it)
+    #[max_len(MAX_LABEL_LEN)]
+    pub label: String,       // The counter's name, part of its address
+    pub count: i64,          // The current value - may be negative
+    pub bump: u8,            // A special number that helps find this account
+}
+
+impl SignedCounter {
+    // WHERE AN INCREMENT (`up`) OR DECREMENT BY `amount` LANDS - it still has to fit in an i64
+    pub fn stepped(&self, amount: u64, up: bool) -> Result<i64> {
+        Ok(if up {
+            self.count.checked_add_unsigned(amount).ok_or(CounterError::CounterOverflow)?
+        } else {
+            self.count.checked_sub_unsigned(amount).ok_or(CounterError::CounterUnderflow)?
+        })
+    }
+}
+
+// THE BIG COUNTER ACCOUNT DATA STRUCTURE
+// Written by `initialize_big` or `upgrade_to_big` (seeds = ["big_counter", authority, label]).
+// As plain as a signed counter. The u128 count is kept as two u64 words so every Borsh reader
+// can decode the account; use `count` / `set_count` rather than the words themselves
+#[account]
+#[derive(InitSpace)]
+pub struct BigCounter {
+    pub authority: Pubkey,   // The owner (the only one who can change it)
+    #[max_len(MAX_LABEL_LEN)]
+    pub label: String,       // The counter's name, part of its address
+    pub count_lo: u64,       // The low 64 bits of the count
+    pub count_hi: u64,       // The high 64 bits of the count
+    pub bump: u8,            // A special number that helps find this account
+}
+
+impl BigCounter {
+    // The count as one number
+    pub fn count(&self) -> u128 {
+        (self.count_hi as u128) << 64 | self.count_lo as u128
+    }
+
+    // Stores `count` back into its two words
+    pub fn set_count(&mut self, count: u128) {
+        self.count_lo = count as u64;
+        self.count_hi = (count >> 64) as u64;
+    }
+
+    // Where an increment (`up`) or decrement by `amount` lands
+    pub fn stepped(&self, amount: u64, up: bool) -> Result<u128> {
+        Ok(if up {
+            self.count().checked_add(amount as u128).ok_or(CounterError::CounterOverflow)?
+        } else {
+            self.count().checked_sub(amount as u128).ok_or(CounterError::CounterUnderflow)?
+        })
+    }
+}
+
+// THE ZERO-COPY COUNTER ACCOUNT DATA STRUCTURE
+// Written by `initialize_zc` or `convert_to_zc` (seeds = ["counter_zc", authority, label]).
+// As plain as a big counter, and zero-copy like a counter array: `increment_zc` and
+// `decrement_zc` change the count where it lies instead of (de)serializing the whole account,
+// for counters changed thousands of times a day
+#[account(zero_copy)]
+pub struct CounterZC {
+    pub authority: Pubkey,   // The owner (the only one who can change it)
+    pub count: u64,          // The current value
+    pub bump: u8,            // A special number that helps find this account
+    pub padding: [u8; 7],    // Keeps the size a multiple of 8
+}
+
+// THE COUNTER ARRAY ACCOUNT DATA STRUCTURE
+// Written by `initialize_array` (seeds = ["counter_array", authority, label]). Zero-copy, so
+// changing one slot never (de)serializes the other COUNTER_ARRAY_SLOTS - 1
+#[account(zero_copy)]
+pub struct CounterArray {
+    pub authority: Pubkey,                   // The owner (the only one who can change it)
+    pub slots: [u64; COUNTER_ARRAY_SLOTS],   // The counters themselves
+    pub flags: [u8; COUNTER_ARRAY_SLOTS],    // One byte of `slot_flag` bits per slot
+    pub bump: u8,                            // A special number that helps find this account
+    pub padding: [u8; 7],                    // Keeps the size a multiple of 8
+}
+
+// THE REWARD CONFIG ACCOUNT DATA STRUCTURE
+// A single account (seeds = ["rewards"]) that is also the mint authority of the reward token
+#[account]
+#[derive(InitSpace)]
+pub struct RewardConfig {
+    pub mint: Pubkey,              // The reward token (seeds = ["reward_mint"])
+    pub tokens_per_increment: u64, // Minted once per increment, whatever amount it adds
+    pub enabled: bool,             // Switched off by `disable_rewards`
+    pub bump: u8,                  // A special number that helps find this account
+}
@@ -0,0 +1,124 @@
+//! Property tests for the counter arithmetic: `apply_op` / `CountRules` and the `Counter`
+//! methods handlers build on. Plain `cargo test`, no validator or program build needed.
+
+use anchor_lang::prelude::*;
+use counter::{apply_op, CountOp, CountRules, Counter, CounterError, OverflowMode};
+use proptest::prelude::*;
+
+// A counter with every field zeroed/empty, as a starting point for the tests to fill in
+fn blank_counter() -> Counter {
+    let mut data = vec![0u8; 8 + Counter::INIT_SPACE];
+    data[..8].copy_from_slice(Counter::DISCRIMINATOR);
+    Counter::try_deserialize(&mut &data[..]).unwrap()
+}
+
+fn overflow_mode() -> impl Strategy<Value = OverflowMode> {
+    prop_oneof![Just(OverflowMode::Checked), Just(OverflowMode::Saturating), Just(OverflowMode::Wrapping)]
+}
+
+// Rules with `min <= max`, and a count inside them
+fn rules_and_count() -> impl Strategy<Value = (CountRules, u64)> {
+    (any::<u64>(), any::<u64>(), overflow_mode())
+        .prop_map(|(a, b, overflow_mode)| CountRules { min: a.min(b), max: a.max(b), overflow_mode })
+        .prop_flat_map(|rules| (Just(rules), rules.min..=rules.max))
+}
+
+fn count_op() -> impl Strategy<Value = CountOp> {
+    prop_oneof![
+        any::<u64>().prop_map(CountOp::Increment),
+        any::<u64>().prop_map(CountOp::Decrement),
+        any::<u64>().prop_map(CountOp::Set),
+        Just(CountOp::Reset),
+    ]
+}
+
+fn code(error: anchor_lang::error::Error) -> u32 {
+    match error {
+        anchor_lang::error::Error::AnchorError(error) => error.error_code_number,
+        other => panic!("expected a program error, got {other:?}"),
+    }
+}
+
+proptest! {
+    #[test]
+    fn count_stays_within_bounds((rules, count) in rules_and_count(), op in count_op()) {
+        if let Ok(new_count) = apply_op(count, op, rules) {
+            prop_assert!(new_count <= rules.max);
+            prop_assert!(new_count >= rules.min);
+        }
+    }
+
+    #[test]
+    fn any_sequence_stays_within_bounds((rules, count) in rules_and_count(), ops in prop::collection::vec(count_op(), 1..32)) {
+        let mut count = count;
+        for op in ops {
+            // A refused change leaves the count where it was
+            count = apply_op(count, op, rules).unwrap_or(count);
+            prop_assert!((rules.min..=rules.max).contains(&count));
+        }
+    }
+
+    #[test]
+    fn checked_never_wraps((rules, count) in rules_and_count(), amount in any::<u64>()) {
+        let rules = CountRules { overflow_mode: OverflowMode::Checked, ..rules };
+        match rules.apply(count, CountOp::Decrement(amount)) {
+            Ok(new_count) => prop_assert_eq!(new_count, count - amount),
+            Err(error) => {
+                prop_assert!(amount > count);
+                prop_assert_eq!(code(error), u32::from(CounterError::CounterUnderflow));
+            }
+        }
+        match rules.apply(count, CountOp::Increment(amount)) {
+            Ok(new_count) => prop_assert_eq!(new_count, count + amount),
+            Err(error) => {
+                prop_assert!(count.checked_add(amount).is_none());
+                prop_assert_eq!(code(error), u32::from(CounterError::CounterOverflow));
+            }
+        }
+    }
+
+    #[test]
+    fn saturating_and_wrapping_never_fail((rules, count) in rules_and_count(), amount in any::<u64>(), up in any::<bool>()) {
+        prop_assume!(rules.overflow_mode != OverflowMode::Checked);
+        let op = if up { CountOp::Increment(amount) } else { CountOp::Decrement(amount) };
+        prop_assert!(apply_op(count, op, rules).is_ok());
+    }
+
+    #[test]
+    fn wrapping_round_trips((rules, count) in rules_and_count(), amount in any::<u64>()) {
+        let rules = CountRules { overflow_mode: OverflowMode::Wrapping, ..rules };
+        let up = rules.apply(count, CountOp::Increment(amount)).unwrap();
+        prop_assert_eq!(rules.apply(up, CountOp::Decrement(amount)).unwrap(), count);
+    }
+
+    #[test]
+    fn out_of_bounds_corrections_are_refused((rules, count) in rules_and_count(), value in any::<u64>()) {
+        let result = apply_op(count, CountOp::Set(value), rules);
+        prop_assert_eq!(result.is_ok(), (rules.min..=rules.max).contains(&value));
+        prop_assert_eq!(apply_op(count, CountOp::Reset, rules).unwrap(), rules.min);
+    }
+
+    #[test]
+    fn counter_steps_like_its_rules((rules, count) in rules_and_count(), amount in any::<u64>(), up in any::<bool>()) {
+        let mut counter = blank_counter();
+        counter.min_count = rules.min;
+        counter.max_count = rules.max;
+        counter.overflow_mode = rules.overflow_mode;
+        counter.count = count;
+        let op = if up { CountOp::Increment(amount) } else { CountOp::Decrement(amount) };
+        prop_assert_eq!(counter.stepped(amount, up).ok(), rules.apply(count, op).ok());
+        prop_assert_eq!(counter.apply(op).ok(), apply_op(count, op, rules).ok());
+    }
+
+    #[test]
+    fn version_strictly_increases(version in 0..u64::MAX, changes in 1..64usize) {
+        let mut counter = blank_counter();
+        counter.version = version;
+        for _ in 0..changes {
+            let before = counter.version;
+            counter.next_version();
+            // Only a counter that already reached u64::MAX stays there
+            prop_assert!(counter.version > before || before == u64::MAX);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Log-driven indexer for the counter program.
+//!
+//! Reads program log lines on stdin (e.g. piped from `solana logs <program-id>`), decodes
+//! every counter event and prints one JSON object per event on stdout, including the
+//! indexer's current view of that counter's value.
+//!
+//! Usage: `solana logs 8hQm3nryK3s2x32nm38h5U7usk6QYRBFZbi2j3oU1kG1 | cargo run -p counter-indexer`
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+
+use counter_client::anchor_lang::prelude::Pubkey;
+use counter_client::events::{decode_log, CounterEvent};
+use serde_json::{json, Value};
+
+// The latest known value of every counter seen so far
+#[derive(Default)]
+struct Index {
+    counts: BTreeMap<Pubkey, u64>,
+}
+
+impl Index {
+    // Apply an event and describe it as JSON
+    fn apply(&mut self, event: &CounterEvent) -> Value {
+        match event {
+            CounterEvent::Initialized(e) => {
+                self.counts.insert(e.counter, e.count);
+                json!({
+                    "event": "initialized",
+                    "counter": e.counter.to_string(),
+                    "authority": e.user.to_string(),
+                    "sponsor": e.sponsor.to_string(),
+                    "label": e.label,
+                    "count": e.count,
+                    "sequence": e.sequence,
+                    "slot": e.slot,
+                })
+            }
+            CounterEvent::Updated(e) => {
+                self.counts.insert(e.counter, e.new_count);
+                json!({
+                    "event": "updated",
+                    "counter": e.counter.to_string(),
+                    "operation": e.operation.as_str(),
+                    "previous_count": e.previous_count,
+                    "count": e.new_count,
+                    "version": e.version,
+                    "sequence": e.sequence,
+                    "slot": e.slot,
+                })
+            }
+            CounterEvent::BatchApplied(e) => {
+                self.counts.insert(e.counter, e.end_count);
+                json!({
+                    "event": "batch_applied",
+                    "counter": e.counter.to_string(),
+                    "ops": e.ops,
+                    "previous_count": e.start_count,
+                    "count": e.end_count,
+                    "version": e.version,
+                    "sequence": e.sequence,
+                    "slot": e.slot,
+                })
+            }
+            CounterEvent::Closed(e) => {
+                self.counts.remove(&e.counter);
+                json!({
+                    "event": "closed",
+                    "counter": e.counter.to_string(),
+                    "final_count": e.final_count,
+                    "sequence": e.sequence,
+                    "slot": e.slot,
+                })
+            }
+            CounterEvent::StateChanged(e) => json!({
+                "event": "state_changed",
+                "counter": e.counter.to_string(),
+                "from": format!("{:?}", e.from),
+                "to": format!("{:?}", e.to),
+            }),
+            CounterEvent::Heartbeat(e) => {
+                self.counts.insert(e.counter, e.count);
+                json!({ "event": "heartbeat", "counter": e.counter.to_string(), "count": e.count, "slot": e.slot })
+            }
+            CounterEvent::ActivityProven(e) => json!({
+                "event": "activity_proven",
+                "counter": e.counter.to_string(),
+                "receipt": e.receipt.to_string(),
+                "ops_in_window": e.ops_in_window,
+                "window_days": e.window_days,
+            }),
+            CounterEvent::Milestone(e) => json!({
+                "event": "milestone",
+                "counter": e.counter.to_string(),
+                "milestone": e.milestone,
+                "slot": e.slot,
+            }),
+            CounterEvent::ErrorDetail(e) => json!({
+                "event": "error_detail",
+                "counter": e.counter.to_string(),
+                "error_code": e.error_code,
+                "operation": e.operation.as_str(),
+                "amount": e.amount,
+                "count": e.count,
+                "limit": e.limit,
+            }),
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let mut index = Index::default();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    for line in io::stdin().lock().lines() {
+        let Some(event) = decode_log(&line?) else { continue };
+        writeln!(out, "{}", index.apply(&event))?;
+    }
+
+    eprintln!("Indexed {} live counters", index.counts.len());
+    Ok(())
+}
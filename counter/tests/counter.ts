@@ -1,8 +1,10 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Counter } from "../target/types/counter";
+import { CounterCaller } from "../target/types/counter_caller";
 import { expect } from "chai";
 import { Keypair } from "@solana/web3.js";
+import { createHash } from "crypto";
 
 describe("counter", () => {
   // Configure the client to use the local cluster.
@@ -22,7 +24,36 @@ describe("counter", () => {
   let unauthorizedUser: Keypair;
   let unauthorizedCounterPda: anchor.web3.PublicKey;
 
+  // The program-wide config PDA and the program's upgrade data account
+  const [configPda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+  const [statsPda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("stats")],
+    program.programId
+  );
+  const [programDataPda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
   before(async () => {
+    // The config must exist before any counter can be created.
+    // The provider wallet deployed the program, so it is the upgrade authority.
+    if (!(await provider.connection.getAccountInfo(configPda))) {
+      await program.methods
+        .initializeConfig()
+        .accounts({
+          admin: user,
+          programData: programDataPda,
+        })
+        .rpc();
+    }
+    if (!(await provider.connection.getAccountInfo(statsPda))) {
+      await program.methods.initializeStats().accounts({ admin: user }).rpc();
+    }
+
     // Create an unauthorized user for testing
     unauthorizedUser = Keypair.generate();
 
@@ -42,10 +73,11 @@ describe("counter", () => {
 
   it("Initializes the counter", async () => {
     const tx = await program.methods
-      .initialize()
+      .initialize("", null, null, false, null, null, null, null, null, null, null, null, null)
       .accounts({
         counter: counterPda,
         user: user,
+        payer: user,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
       .rpc();
@@ -61,7 +93,7 @@ describe("counter", () => {
 
   it("Increments the counter", async () => {
     const tx = await program.methods
-      .increment()
+      .increment("", false, null)
       .accounts({
         counter: counterPda,
         user: user,
@@ -78,7 +110,7 @@ describe("counter", () => {
   it("Prevents unauthorized increment", async () => {
     try {
       await program.methods
-        .increment()
+        .increment("", false, null)
         .accounts({
           counter: counterPda,
           user: unauthorizedUser.publicKey,
@@ -94,7 +126,7 @@ describe("counter", () => {
 
   it("Decrements the counter", async () => {
     const tx = await program.methods
-      .decrement()
+      .decrement("", false, null)
       .accounts({
         counter: counterPda,
         user: user,
@@ -111,7 +143,7 @@ describe("counter", () => {
   it("Prevents unauthorized decrement", async () => {
     // First increment to 1 so we can test decrement
     await program.methods
-      .increment()
+      .increment("", false, null)
       .accounts({
         counter: counterPda,
         user: user,
@@ -120,7 +152,7 @@ describe("counter", () => {
 
     try {
       await program.methods
-        .decrement()
+        .decrement("", false, null)
         .accounts({
           counter: counterPda,
           user: unauthorizedUser.publicKey,
@@ -139,7 +171,7 @@ describe("counter", () => {
     let counterAccount = await program.account.counter.fetch(counterPda);
     while (counterAccount.count.toNumber() > 0) {
       await program.methods
-        .decrement()
+        .decrement("", false, null)
         .accounts({
           counter: counterPda,
           user: user,
@@ -151,7 +183,7 @@ describe("counter", () => {
     // Now try to decrement below 0 - should fail
     try {
       await program.methods
-        .decrement()
+        .decrement("", false, null)
         .accounts({
           counter: counterPda,
           user: user,
@@ -166,7 +198,7 @@ describe("counter", () => {
   it("Prevents unauthorized close", async () => {
     try {
       await program.methods
-        .close()
+        .close("", false, null)
         .accounts({
           counter: counterPda,
           user: unauthorizedUser.publicKey,
@@ -182,7 +214,7 @@ describe("counter", () => {
 
   it("Closes the counter", async () => {
     const tx = await program.methods
-      .close()
+      .close("", false, null)
       .accounts({
         counter: counterPda,
         user: user,
@@ -199,4 +231,4428 @@ describe("counter", () => {
       expect(error.message).to.include("Account does not exist");
     }
   });
+
+  // Create a fresh wallet with some SOL so each feature can be tested in isolation
+  const fundedUser = async (): Promise<Keypair> => {
+    const keypair = Keypair.generate();
+    const airdropTx = await provider.connection.requestAirdrop(
+      keypair.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropTx);
+    return keypair;
+  };
+
+  // `label` names one of the owner's counters; "" is the wallet's default counter
+  const counterPdaFor = (owner: anchor.web3.PublicKey, label = "") =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("counter"), owner.toBuffer(), Buffer.from(label)],
+      program.programId
+    )[0];
+
+  const initializeFor = async (
+    owner: Keypair,
+    label = "",
+    bounds: { min: anchor.BN | null; max: anchor.BN | null } = { min: null, max: null },
+    isPublic = false,
+    cooldownSeconds: anchor.BN | null = null,
+    overflowMode: any = null,
+    expiresAt: anchor.BN | null = null,
+    nftGate: anchor.web3.PublicKey | null = null,
+    step: anchor.BN | null = null,
+    windowStart: anchor.BN | null = null,
+    windowEnd: anchor.BN | null = null
+  ) => {
+    await program.methods
+      .initialize(label, bounds.min, bounds.max, isPublic, cooldownSeconds, overflowMode, expiresAt, nftGate, step, windowStart, windowEnd, null, null)
+      .accounts({
+        counter: counterPdaFor(owner.publicKey, label),
+        user: owner.publicKey,
+        payer: owner.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+  };
+
+  // Decode every Anchor event emitted by a confirmed transaction
+  const eventsFrom = async (signature: string) => {
+    await provider.connection.confirmTransaction(signature, "confirmed");
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+    return [...parser.parseLogs(tx?.meta?.logMessages ?? [])];
+  };
+
+  describe("short IDs", () => {
+    let owner: Keypair;
+    const code = Buffer.from("ABC234");
+    const [shortIdPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("short_id"), code],
+      program.programId
+    );
+
+    before(async () => {
+      owner = await fundedUser();
+      await initializeFor(owner);
+    });
+
+    it("Rejects codes outside the base32 alphabet", async () => {
+      const badCode = Buffer.from("abc!01");
+      const [badPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("short_id"), badCode],
+        program.programId
+      );
+      try {
+        await program.methods
+          .claimShortId([...badCode])
+          .accounts({
+            shortId: badPda,
+            counter: counterPdaFor(owner.publicKey),
+            user: owner.publicKey,
+          })
+          .signers([owner])
+          .rpc();
+        expect.fail("Should have rejected an invalid short ID");
+      } catch (error: any) {
+        expect(error.message).to.include("Short ID must be 6 characters");
+      }
+    });
+
+    it("Claims a short ID with a deposit", async () => {
+      await program.methods
+        .claimShortId([...code])
+        .accounts({
+          shortId: shortIdPda,
+          counter: counterPdaFor(owner.publicKey),
+          user: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+
+      const shortId = await program.account.shortId.fetch(shortIdPda);
+      expect(shortId.counter.toString()).to.equal(counterPdaFor(owner.publicKey).toString());
+      expect(shortId.owner.toString()).to.equal(owner.publicKey.toString());
+      expect(shortId.deposit.toNumber()).to.equal(10_000_000);
+    });
+
+    it("Prevents claiming a code twice", async () => {
+      const squatter = await fundedUser();
+      await initializeFor(squatter);
+      try {
+        await program.methods
+          .claimShortId([...code])
+          .accounts({
+            shortId: shortIdPda,
+            counter: counterPdaFor(squatter.publicKey),
+            user: squatter.publicKey,
+          })
+          .signers([squatter])
+          .rpc();
+        expect.fail("Should not be able to claim a taken code");
+      } catch (error: any) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Releases the short ID and refunds the deposit", async () => {
+      const before = await provider.connection.getBalance(owner.publicKey);
+      await program.methods
+        .releaseShortId()
+        .accounts({ shortId: shortIdPda, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      const after = await provider.connection.getBalance(owner.publicKey);
+      expect(after - before).to.be.greaterThan(10_000_000 - 10_000);
+      expect(await provider.connection.getAccountInfo(shortIdPda)).to.be.null;
+    });
+  });
+
+  describe("invariant checks", () => {
+    it("Reports a healthy counter as passing", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+
+      const sig = await program.methods
+        .verifyInvariants()
+        .accounts({ counter: counterPdaFor(owner.publicKey) })
+        .rpc();
+
+      const [event] = (await eventsFrom(sig)).filter((e) => e.name === "invariantsChecked");
+      expect(event.data.passed).to.equal(true);
+      expect(event.data.failures).to.equal(0);
+    });
+  });
+
+  describe("view_count", () => {
+    it("Returns the count, authority and version as return data", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "viewed");
+      const counter = counterPdaFor(owner.publicKey, "viewed");
+      await program.methods
+        .increment("viewed", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      // Anyone can read it - here the provider wallet pays
+      const sig = await program.methods.viewCount().accounts({ counter }).rpc({ commitment: "confirmed" });
+
+      // CounterView { count: u64, authority: Pubkey, version: u64 }
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const returnData = Buffer.from(tx!.meta!.returnData!.data[0], "base64");
+      expect(returnData.readBigUInt64LE(0)).to.equal(BigInt(1));
+      expect(new anchor.web3.PublicKey(returnData.subarray(8, 40)).equals(owner.publicKey)).to.be.true;
+      expect(returnData.readBigUInt64LE(40)).to.equal(BigInt(1));
+    });
+  });
+
+  describe("heartbeats", () => {
+    it("Emits the current value and rate-limits repeats", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+
+      // Anyone can send the heartbeat - here the provider wallet pays for it
+      const sig = await program.methods
+        .heartbeat()
+        .accounts({ counter: counterPdaFor(owner.publicKey) })
+        .rpc();
+      const [event] = (await eventsFrom(sig)).filter((e) => e.name === "counterHeartbeat");
+      expect(event.data.count.toNumber()).to.equal(0);
+      expect(event.data.authority.toString()).to.equal(owner.publicKey.toString());
+
+      try {
+        await program.methods
+          .heartbeat()
+          .accounts({ counter: counterPdaFor(owner.publicKey) })
+          .rpc();
+        expect.fail("Second heartbeat should be rate-limited");
+      } catch (error: any) {
+        expect(error.message).to.include("Heartbeat sent too soon");
+      }
+    });
+  });
+
+  describe("protocol fees", () => {
+    const [treasuryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury")],
+      program.programId
+    );
+    const setOpFee = (lamports: number) =>
+      program.methods
+        .setFeeConfig(new anchor.BN(lamports), anchor.web3.PublicKey.default, user, new anchor.BN(60))
+        .accounts({ config: configPda, admin: user })
+        .rpc();
+
+    const setFeeSchedule = (initLamports: number, opLamports: number, recipient = anchor.web3.PublicKey.default) =>
+      program.methods
+        .setFeeSchedule(new anchor.BN(initLamports), new anchor.BN(opLamports), recipient)
+        .accounts({ config: configPda, admin: user })
+        .rpc();
+
+    after(async () => {
+      await setOpFee(0);
+      await setFeeSchedule(0, 0);
+    });
+
+    it("Charges the SOL fee into the treasury on increment", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+      // Big enough that the treasury ends up rent-exempt
+      await setOpFee(1_000_000);
+
+      const before = await provider.connection.getBalance(treasuryPda);
+      await program.methods
+        .increment("", false, null)
+        .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      const after = await provider.connection.getBalance(treasuryPda);
+      expect(after - before).to.equal(1_000_000);
+    });
+
+    it("Charges the creation fee from the fee schedule into the treasury", async () => {
+      const owner = await fundedUser();
+      await setFeeSchedule(1_000_000, 0);
+
+      const before = await provider.connection.getBalance(treasuryPda);
+      await initializeFor(owner);
+      const after = await provider.connection.getBalance(treasuryPda);
+      expect(after - before).to.equal(1_000_000);
+
+      const config = await program.account.programConfig.fetch(configPda);
+      expect(config.initFeeLamports.toNumber()).to.equal(1_000_000);
+      expect(config.opFeeLamports.toNumber()).to.equal(0);
+
+      // Cloning makes a new counter too, so it pays the same fee
+      const beforeClone = await provider.connection.getBalance(treasuryPda);
+      await program.methods
+        .cloneCounter("fee-copy", false)
+        .accounts({ source: counterPdaFor(owner.publicKey), user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect((await provider.connection.getBalance(treasuryPda)) - beforeClone).to.equal(1_000_000);
+      await setFeeSchedule(0, 0);
+    });
+
+    it("Only lets the admin set the fee schedule", async () => {
+      const stranger = await fundedUser();
+      try {
+        await program.methods
+          .setFeeSchedule(new anchor.BN(1), new anchor.BN(1), stranger.publicKey)
+          .accounts({ config: configPda, admin: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Only the admin can set the fee schedule");
+      } catch (error: any) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Rejects token payments when no fee mint is configured", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+      await setOpFee(1_000_000);
+      try {
+        await program.methods
+          .increment("", false, null)
+          .accounts({
+            counter: counterPdaFor(owner.publicKey),
+            user: owner.publicKey,
+            feePayerTokenAccount: owner.publicKey,
+            treasuryTokenAccount: null,
+            tokenProgram: null,
+          })
+          .signers([owner])
+          .rpc();
+        expect.fail("Partial token fee accounts should be rejected");
+      } catch (error: any) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Only lets the price authority push prices", async () => {
+      const stranger = await fundedUser();
+      try {
+        await program.methods
+          .pushFeePrice(new anchor.BN(1_000))
+          .accounts({ config: configPda, priceAuthority: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Only the price authority can push prices");
+      } catch (error: any) {
+        expect(error).to.exist;
+      }
+    });
+  });
+
+  describe("lifecycle states", () => {
+    let owner: Keypair;
+
+    const transition = (target: object) =>
+      program.methods
+        .transitionState(target as any, false, null)
+        .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+    before(async () => {
+      owner = await fundedUser();
+      await initializeFor(owner);
+    });
+
+    it("Blocks updates while frozen and resumes when unfrozen", async () => {
+      await transition({ frozen: {} });
+      try {
+        await program.methods
+          .increment("", false, null)
+          .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("Frozen counters should not change");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterFrozen");
+      }
+
+      await transition({ active: {} });
+      const counterAccount = await program.account.counter.fetch(counterPdaFor(owner.publicKey));
+      expect(counterAccount.state).to.deep.equal({ active: {} });
+    });
+
+    it("Freezes and unfreezes with the dedicated instructions", async () => {
+      const accounts = { counter: counterPdaFor(owner.publicKey), user: owner.publicKey };
+      const frozen = (await eventsFrom(await program.methods.freeze().accounts(accounts).signers([owner]).rpc())).find(
+        (e) => e.name === "counterFrozen"
+      );
+      expect(frozen, "freeze should announce CounterFrozen").to.not.be.undefined;
+
+      try {
+        await program.methods.close("", false, null).accounts(accounts).signers([owner]).rpc();
+        expect.fail("Frozen counters can't be closed");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterFrozen");
+      }
+
+      const unfrozen = (await eventsFrom(await program.methods.unfreeze().accounts(accounts).signers([owner]).rpc())).find(
+        (e) => e.name === "counterUnfrozen"
+      );
+      expect(unfrozen, "unfreeze should announce CounterUnfrozen").to.not.be.undefined;
+      const counterAccount = await program.account.counter.fetch(counterPdaFor(owner.publicKey));
+      expect(counterAccount.state).to.deep.equal({ active: {} });
+    });
+
+    it("Rejects transitions that the lifecycle doesn't allow", async () => {
+      await transition({ archived: {} });
+      try {
+        await transition({ active: {} });
+        expect.fail("Archived counters can't be reactivated");
+      } catch (error: any) {
+        expect(error.message).to.include("Invalid counter state transition");
+      }
+    });
+
+    it("Doesn't let owners enter the migrating state", async () => {
+      const other = await fundedUser();
+      await initializeFor(other);
+      try {
+        await program.methods
+          .transitionState({ migrating: {} } as any, false, null)
+          .accounts({ counter: counterPdaFor(other.publicKey), user: other.publicKey })
+          .signers([other])
+          .rpc();
+        expect.fail("Only the admin can start a migration");
+      } catch (error: any) {
+        expect(error.message).to.include("Invalid counter state transition");
+      }
+    });
+  });
+
+  describe("dry runs", () => {
+    it("Runs every check but leaves the counter untouched", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+
+      const sig = await program.methods
+        .increment("", true, null)
+        .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+
+      const counterAccount = await program.account.counter.fetch(counterPdaFor(owner.publicKey));
+      expect(counterAccount.count.toNumber()).to.equal(0);
+
+      // WouldSucceed { counter: Pubkey, previous_count: u64, new_count: u64 }
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const returnData = Buffer.from(tx!.meta!.returnData!.data[0], "base64");
+      expect(returnData.readBigUInt64LE(32)).to.equal(BigInt(0));
+      expect(returnData.readBigUInt64LE(40)).to.equal(BigInt(1));
+    });
+
+    it("Still fails when the real call would fail", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+      try {
+        await program.methods
+          .decrement("", true, null)
+          .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("A dry run of an underflow should fail");
+      } catch (error: any) {
+        expect(error.message).to.include("Counter would underflow");
+      }
+    });
+  });
+
+  describe("stale counter reclaiming", () => {
+    const setCleanup = (payerBps: number, authorityBps: number, crankerBps: number, staleAfter: number) =>
+      program.methods
+        .setCleanupConfig(payerBps, authorityBps, crankerBps, new anchor.BN(staleAfter))
+        .accounts({ config: configPda, admin: user })
+        .rpc();
+
+    after(async () => {
+      await setCleanup(0, 10_000, 0, 0);
+    });
+
+    it("Rejects refund splits that don't add up to 100%", async () => {
+      try {
+        await setCleanup(5_000, 5_000, 1, 0);
+        expect.fail("Split over 10000 bps should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("Refund split must add up to 10000 basis points");
+      }
+    });
+
+    it("Refuses to reclaim a counter that is still in use", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+      await setCleanup(0, 10_000, 0, 3_600);
+      try {
+        await program.methods
+          .reclaimStale()
+          .accounts({
+            counter: counterPdaFor(owner.publicKey),
+            payer: owner.publicKey,
+            authority: owner.publicKey,
+            cranker: user,
+          })
+          .rpc();
+        expect.fail("Fresh counters are not stale");
+      } catch (error: any) {
+        expect(error.message).to.include("Counter is not stale");
+      }
+    });
+
+    it("Splits the rent exactly between payer, authority and cranker", async () => {
+      const owner = await fundedUser();
+      // Funded, because a brand-new wallet receiving less than the rent-exempt minimum would be rejected
+      const cranker = await fundedUser();
+      await initializeFor(owner);
+      await setCleanup(2_500, 5_000, 2_500, 1);
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+
+      const counterPda = counterPdaFor(owner.publicKey);
+      const total = await provider.connection.getBalance(counterPda);
+      const ownerBefore = await provider.connection.getBalance(owner.publicKey);
+      const crankerBefore = await provider.connection.getBalance(cranker.publicKey);
+
+      // The provider wallet pays the transaction fee, so the cranker balance moves by exactly its share
+      await program.methods
+        .reclaimStale()
+        .accounts({
+          counter: counterPda,
+          payer: owner.publicKey,
+          authority: owner.publicKey,
+          cranker: cranker.publicKey,
+        })
+        .signers([cranker])
+        .rpc();
+
+      const payerShare = Math.floor((total * 2_500) / 10_000);
+      const crankerShare = Math.floor((total * 2_500) / 10_000);
+      const authorityShare = total - payerShare - crankerShare;
+
+      // The payer and the authority are the same wallet for a normal initialize
+      const ownerAfter = await provider.connection.getBalance(owner.publicKey);
+      expect(ownerAfter - ownerBefore).to.equal(payerShare + authorityShare);
+      const crankerAfter = await provider.connection.getBalance(cranker.publicKey);
+      expect(crankerAfter - crankerBefore).to.equal(crankerShare);
+      expect(await provider.connection.getAccountInfo(counterPda)).to.be.null;
+    });
+  });
+
+  describe("counter expiry", () => {
+    it("Stops changes after expiry and lets anyone collect the counter", async () => {
+      const owner = await fundedUser();
+      const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 2);
+      await initializeFor(owner, "ttl", { min: null, max: null }, false, null, null, expiresAt);
+      const counter = counterPdaFor(owner.publicKey, "ttl");
+
+      // Too early to collect
+      try {
+        await program.methods
+          .collectExpired()
+          .accounts({ counter, authority: owner.publicKey, cranker: user })
+          .rpc();
+        expect.fail("The counter hasn't expired yet");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterNotExpired");
+      }
+
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+      try {
+        await program.methods
+          .increment("ttl", false, null)
+          .accounts({ counter, user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("Expired counters can't change");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterExpired");
+      }
+
+      await program.methods
+        .collectExpired()
+        .accounts({ counter, authority: owner.publicKey, cranker: user, registry: null })
+        .rpc();
+      expect(await provider.connection.getAccountInfo(counter)).to.be.null;
+
+      // Collected without the registry: the owner drops the leftover entry themselves
+      const [registry] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("registry"), owner.publicKey.toBuffer()], program.programId);
+      await program.methods
+        .pruneRegistry()
+        .accounts({ registry, user: owner.publicKey })
+        .remainingAccounts([{ pubkey: counter, isSigner: false, isWritable: false }])
+        .signers([owner])
+        .rpc();
+      const { counters } = await program.account.userRegistry.fetch(registry);
+      expect(counters.map((entry) => entry.counter.toBase58())).to.not.include(counter.toBase58());
+    });
+  });
+
+  describe("sequencers", () => {
+    let owner: Keypair;
+    let sequencerPda: anchor.web3.PublicKey;
+
+    const incrementWithSeq = (expectedSeq: number | null) =>
+      program.methods
+        .increment("", false, expectedSeq === null ? null : new anchor.BN(expectedSeq))
+        .accounts({
+          counter: counterPdaFor(owner.publicKey),
+          user: owner.publicKey,
+          sequencer: sequencerPda,
+        })
+        .signers([owner])
+        .rpc();
+
+    before(async () => {
+      owner = await fundedUser();
+      await initializeFor(owner);
+      [sequencerPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("sequencer"), owner.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initializeSequencer()
+        .accounts({ user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+    });
+
+    it("Advances the sequence on every operation that passes it", async () => {
+      await incrementWithSeq(0);
+      await incrementWithSeq(null); // No expectation, but the sequence still moves
+      const sequencer = await program.account.sequencer.fetch(sequencerPda);
+      expect(sequencer.seq.toNumber()).to.equal(2);
+    });
+
+    it("Rejects operations that expect a stale sequence number", async () => {
+      try {
+        await incrementWithSeq(1);
+        expect.fail("Out-of-order operation should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("Sequence number mismatch");
+      }
+    });
+
+    it("Requires the sequencer when an expected number is given", async () => {
+      try {
+        await program.methods
+          .increment("", false, new anchor.BN(2))
+          .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("expected_seq without a sequencer should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("Sequencer account required");
+      }
+    });
+  });
+
+  describe("instruction aliases", () => {
+    // Anchor discriminators are sha256("global:<name>")[..8]. These bytes are what deployed
+    // clients and CPI callers already send, so they must never change across upgrades.
+    const PINNED_DISCRIMINATORS: Record<string, number[]> = {
+      initialize: [175, 175, 109, 31, 13, 152, 155, 237],
+      increment: [11, 18, 104, 9, 104, 174, 59, 33],
+      decrement: [106, 227, 168, 59, 248, 27, 150, 101],
+      close: [98, 165, 201, 177, 108, 65, 206, 96],
+    };
+
+    it("Keeps the original discriminators for the old instruction names", () => {
+      for (const [name, discriminator] of Object.entries(PINNED_DISCRIMINATORS)) {
+        const ix = program.idl.instructions.find((i) => i.name === name);
+        expect(ix, `${name} is still exposed`).to.not.be.undefined;
+        expect(Array.from(ix!.discriminator)).to.deep.equal(discriminator);
+      }
+    });
+
+    it("Forwards increment and decrement to the same logic as apply_delta", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+      const accounts = { counter: counterPdaFor(owner.publicKey), user: owner.publicKey };
+
+      await program.methods.applyDelta("", new anchor.BN(5), false, null).accounts(accounts).signers([owner]).rpc();
+      await program.methods.increment("", false, null).accounts(accounts).signers([owner]).rpc();
+      await program.methods.decrement("", false, null).accounts(accounts).signers([owner]).rpc();
+      await program.methods.applyDelta("", new anchor.BN(-2), false, null).accounts(accounts).signers([owner]).rpc();
+
+      const counterAccount = await program.account.counter.fetch(counterPdaFor(owner.publicKey));
+      expect(counterAccount.count.toNumber()).to.equal(3);
+    });
+
+    it("Rejects a zero delta", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+      try {
+        await program.methods
+          .applyDelta("", new anchor.BN(0), false, null)
+          .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("A zero delta should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("Amount must be greater than zero");
+      }
+    });
+  });
+
+  describe("activity proofs", () => {
+    const receiptPdaFor = (counter: anchor.web3.PublicKey, minOps: number, windowDays: number) => {
+      const minOpsBytes = Buffer.alloc(4);
+      minOpsBytes.writeUInt32LE(minOps);
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("activity"), counter.toBuffer(), minOpsBytes, Buffer.from([windowDays])],
+        program.programId
+      )[0];
+    };
+
+    let owner: Keypair;
+
+    before(async () => {
+      owner = await fundedUser();
+      await initializeFor(owner);
+      const accounts = { counter: counterPdaFor(owner.publicKey), user: owner.publicKey };
+      for (let i = 0; i < 3; i++) {
+        await program.methods.increment("", false, null).accounts(accounts).signers([owner]).rpc();
+      }
+    });
+
+    it("Writes a receipt anyone can read when the criteria are met", async () => {
+      const counter = counterPdaFor(owner.publicKey);
+      await program.methods
+        .proveActivity(3, 7)
+        .accounts({ counter, prover: user })
+        .rpc();
+
+      const receipt = await program.account.activityReceipt.fetch(receiptPdaFor(counter, 3, 7));
+      expect(receipt.counter.toString()).to.equal(counter.toString());
+      expect(receipt.authority.toString()).to.equal(owner.publicKey.toString());
+      expect(receipt.opsInWindow).to.equal(3);
+      expect(receipt.totalOps.toNumber()).to.equal(3);
+    });
+
+    it("Refreshes an existing receipt when proven again", async () => {
+      const counter = counterPdaFor(owner.publicKey);
+      await program.methods
+        .increment("", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      await program.methods.proveActivity(3, 7).accounts({ counter, prover: user }).rpc();
+
+      const receipt = await program.account.activityReceipt.fetch(receiptPdaFor(counter, 3, 7));
+      expect(receipt.opsInWindow).to.equal(4);
+    });
+
+    it("Refuses to prove more activity than happened", async () => {
+      try {
+        await program.methods
+          .proveActivity(100, 7)
+          .accounts({ counter: counterPdaFor(owner.publicKey), prover: user })
+          .rpc();
+        expect.fail("Unmet criteria should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("Activity criteria not met");
+      }
+    });
+
+    it("Rejects windows longer than the counter remembers", async () => {
+      try {
+        await program.methods
+          .proveActivity(1, 8)
+          .accounts({ counter: counterPdaFor(owner.publicKey), prover: user })
+          .rpc();
+        expect.fail("An 8-day window should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("Activity window must be between 1 and 7 days");
+      }
+    });
+  });
+
+  describe("snapshots", () => {
+    const snapshotPdaFor = (counter: anchor.web3.PublicKey, label: number) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("snapshot"), counter.toBuffer(), new anchor.BN(label).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    it("Records the value at the time and keeps it", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "reported");
+      const counter = counterPdaFor(owner.publicKey, "reported");
+      await program.methods
+        .incrementBy("reported", new anchor.BN(3), false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .snapshot(new anchor.BN(20250131))
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      await program.methods
+        .increment("reported", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      const snapshot = await program.account.counterSnapshot.fetch(snapshotPdaFor(counter, 20250131));
+      expect(snapshot.count.toNumber()).to.equal(3);
+      expect(snapshot.counter.toBase58()).to.equal(counter.toBase58());
+      expect(snapshot.timestamp.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("Only lets the owner take snapshots, and returns the rent on close", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "periodic");
+      const counter = counterPdaFor(owner.publicKey, "periodic");
+      const stranger = await fundedUser();
+      try {
+        await program.methods
+          .snapshot(new anchor.BN(1))
+          .accounts({ counter, user: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Only the owner should take snapshots");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+
+      await program.methods
+        .snapshot(new anchor.BN(1))
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      const snapshot = snapshotPdaFor(counter, 1);
+      await program.methods
+        .closeSnapshot()
+        .accounts({ snapshot, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(snapshot)).to.equal(null);
+    });
+  });
+
+  describe("increment_by / decrement_by", () => {
+    let owner: Keypair;
+    let accounts: { counter: anchor.web3.PublicKey; user: anchor.web3.PublicKey };
+
+    before(async () => {
+      owner = await fundedUser();
+      await initializeFor(owner);
+      accounts = { counter: counterPdaFor(owner.publicKey), user: owner.publicKey };
+    });
+
+    it("Changes the counter by the given amount", async () => {
+      await program.methods.incrementBy("", new anchor.BN(10), false, null).accounts(accounts).signers([owner]).rpc();
+      await program.methods.decrementBy("", new anchor.BN(4), false, null).accounts(accounts).signers([owner]).rpc();
+
+      const counterAccount = await program.account.counter.fetch(accounts.counter);
+      expect(counterAccount.count.toNumber()).to.equal(6);
+    });
+
+    it("Reports the operation as an enum in CounterUpdated", async () => {
+      const sig = await program.methods
+        .decrementBy("", new anchor.BN(1), false, null)
+        .accounts(accounts)
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+
+      const [event] = (await eventsFrom(sig)).filter((e) => e.name === "counterUpdated");
+      expect(event.data.operation).to.deep.equal({ decrement: {} });
+      await program.methods.incrementBy("", new anchor.BN(1), false, null).accounts(accounts).signers([owner]).rpc();
+    });
+
+    it("Rejects a zero amount", async () => {
+      try {
+        await program.methods.incrementBy("", new anchor.BN(0), false, null).accounts(accounts).signers([owner]).rpc();
+        expect.fail("A zero amount should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("Amount must be greater than zero");
+      }
+    });
+
+    it("Refuses to go below zero", async () => {
+      try {
+        await program.methods.decrementBy("", new anchor.BN(7), false, null).accounts(accounts).signers([owner]).rpc();
+        expect.fail("Decrementing past zero should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("Counter would underflow");
+      }
+      const counterAccount = await program.account.counter.fetch(accounts.counter);
+      expect(counterAccount.count.toNumber()).to.equal(6);
+    });
+  });
+
+  describe("apply_batch", () => {
+    it("Applies every step in order and reports the start and end values", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "batched");
+      const counter = counterPdaFor(owner.publicKey, "batched");
+
+      const sig = await program.methods
+        .applyBatch(
+          "batched",
+          [
+            { increment: [new anchor.BN(5)] },
+            { decrement: [new anchor.BN(2)] },
+            { set: [new anchor.BN(10)] },
+            { increment: [new anchor.BN(1)] },
+          ],
+          false,
+          null
+        )
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(11);
+      const batch = (await eventsFrom(sig)).find((e) => e.name === "counterBatchApplied");
+      expect(batch.data.startCount.toNumber()).to.equal(0);
+      expect(batch.data.endCount.toNumber()).to.equal(11);
+      expect(batch.data.ops).to.equal(4);
+    });
+
+    it("Changes nothing when one step fails", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "atomic");
+      const counter = counterPdaFor(owner.publicKey, "atomic");
+      try {
+        await program.methods
+          .applyBatch("atomic", [{ increment: [new anchor.BN(3)] }, { decrement: [new anchor.BN(4)] }], false, null)
+          .accounts({ counter, user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("The batch should underflow");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterUnderflow");
+      }
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("increment_if", () => {
+    it("Increments only when the count matches", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "cas");
+      const counter = counterPdaFor(owner.publicKey, "cas");
+
+      await program.methods
+        .incrementIf("cas", new anchor.BN(0), false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(1);
+
+      // A second client still thinks the counter is at 0
+      try {
+        await program.methods
+          .incrementIf("cas", new anchor.BN(0), false, null)
+          .accounts({ counter, user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("The count has moved on");
+      } catch (error: any) {
+        expect(error.message).to.include("CountMismatch");
+      }
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(1);
+    });
+  });
+
+  describe("versions", () => {
+    it("Bumps the version on every change and reports it in CounterUpdated", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "versioned");
+      const counter = counterPdaFor(owner.publicKey, "versioned");
+      expect((await program.account.counter.fetch(counter)).version.toNumber()).to.equal(0);
+
+      const sig = await program.methods
+        .increment("versioned", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+      const [event] = (await eventsFrom(sig)).filter((e) => e.name === "counterUpdated");
+      expect(event.data.version.toNumber()).to.equal(1);
+
+      // Settings changes count too, not just the value
+      await program.methods
+        .setCounterFee(new anchor.BN(1000))
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect((await program.account.counter.fetch(counter)).version.toNumber()).to.equal(2);
+    });
+
+    it("Numbers a counter's events in order, ending with CounterClosed", async () => {
+      const owner = await fundedUser();
+      const counter = counterPdaFor(owner.publicKey, "numbered");
+      const sequences: number[] = [];
+      const record = async (sig: string, name: string) => {
+        const event = (await eventsFrom(sig)).find((e) => e.name === name);
+        expect(event.data.slot.toNumber()).to.be.greaterThan(0);
+        expect(event.data.timestamp.toNumber()).to.be.greaterThan(0);
+        sequences.push(event.data.sequence.toNumber());
+      };
+
+      const created = await program.methods
+        .initialize("numbered", null, null, false, null, null, null, null, null, null, null, null, null)
+        .accounts({ counter, user: owner.publicKey, payer: owner.publicKey, systemProgram: anchor.web3.SystemProgram.programId })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+      await record(created, "counterInitialized");
+      for (let i = 0; i < 2; i++) {
+        const sig = await program.methods
+          .increment("numbered", false, null)
+          .accounts({ counter, user: owner.publicKey })
+          .signers([owner])
+          .rpc({ commitment: "confirmed" });
+        await record(sig, "counterUpdated");
+      }
+      const sig = await program.methods
+        .close("numbered", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+      await record(sig, "counterClosed");
+
+      expect(sequences).to.deep.equal([0, 1, 2, 3]);
+    });
+  });
+
+  describe("close_to", () => {
+    it("Refunds the rent to a separate recipient", async () => {
+      const owner = await fundedUser();
+      const coldStorage = Keypair.generate().publicKey;
+      await initializeFor(owner, "custodial");
+      const counter = counterPdaFor(owner.publicKey, "custodial");
+      const rent = await provider.connection.getBalance(counter);
+
+      const sig = await program.methods
+        .closeTo("custodial", false, null)
+        .accounts({ counter, user: owner.publicKey, rentRecipient: coldStorage })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+
+      expect(await provider.connection.getBalance(coldStorage)).to.equal(rent);
+      const [event] = (await eventsFrom(sig)).filter((e) => e.name === "counterClosed");
+      expect(event.data.rentRecipient.toString()).to.equal(coldStorage.toString());
+    });
+
+    it("Requires a recipient", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "nowhere");
+      try {
+        await program.methods
+          .closeTo("nowhere", false, null)
+          .accounts({ counter: counterPdaFor(owner.publicKey, "nowhere"), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("close_to needs a rent recipient");
+      } catch (error: any) {
+        expect(error.message).to.include("RentRecipientRequired");
+      }
+    });
+  });
+
+  describe("merge", () => {
+    it("Adds the source into the destination and closes the source", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "typo");
+      await initializeFor(owner, "steps");
+      const source = counterPdaFor(owner.publicKey, "typo");
+      const destination = counterPdaFor(owner.publicKey, "steps");
+      for (const [label, counter, amount] of [["typo", source, 4], ["steps", destination, 6]] as const) {
+        await program.methods
+          .incrementBy(label, new anchor.BN(amount), false, null)
+          .accounts({ counter, user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+      }
+
+      await program.methods
+        .merge()
+        .accounts({ source, destination, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      expect((await program.account.counter.fetch(destination)).count.toNumber()).to.equal(10);
+      expect(await provider.connection.getAccountInfo(source)).to.equal(null);
+    });
+
+    it("Refuses counters owned by someone else and merging a counter into itself", async () => {
+      const owner = await fundedUser();
+      const stranger = await fundedUser();
+      await initializeFor(owner, "mine");
+      await initializeFor(stranger, "theirs");
+      const mine = counterPdaFor(owner.publicKey, "mine");
+      const theirs = counterPdaFor(stranger.publicKey, "theirs");
+
+      for (const [destination, expected] of [[theirs, "Unauthorized"], [mine, "SameCounter"]] as const) {
+        try {
+          await program.methods
+            .merge()
+            .accounts({ source: mine, destination, user: owner.publicKey })
+            .signers([owner])
+            .rpc();
+          expect.fail("Merge should be refused");
+        } catch (error: any) {
+          expect(error.message).to.include(expected);
+        }
+      }
+    });
+  });
+
+  describe("split", () => {
+    it("Moves part of a counter into a new one", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "pool");
+      const source = counterPdaFor(owner.publicKey, "pool");
+      await program.methods
+        .incrementBy("pool", new anchor.BN(10), false, null)
+        .accounts({ counter: source, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .split(new anchor.BN(3), "side")
+        .accounts({ source, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      expect((await program.account.counter.fetch(source)).count.toNumber()).to.equal(7);
+      const created = await program.account.counter.fetch(counterPdaFor(owner.publicKey, "side"));
+      expect(created.count.toNumber()).to.equal(3);
+      expect(created.authority.toBase58()).to.equal(owner.publicKey.toBase58());
+    });
+
+    it("Can't take more than the counter holds", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "small");
+      try {
+        await program.methods
+          .split(new anchor.BN(1), "nothing")
+          .accounts({ source: counterPdaFor(owner.publicKey, "small"), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("Split should underflow");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterUnderflow");
+      }
+    });
+  });
+
+  describe("initialize_if_needed", () => {
+    it("Creates the counter once and is a no-op afterwards", async () => {
+      const owner = await fundedUser();
+      const counter = counterPdaFor(owner.publicKey, "racy");
+      const create = () =>
+        program.methods
+          .initializeIfNeeded("racy", null, null, false, null, null, null, null, null, null, null, null, null)
+          .accounts({ counter, user: owner.publicKey })
+          .signers([owner])
+          .rpc({ commitment: "confirmed" });
+
+      await create();
+      await program.methods
+        .increment("racy", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      // The second call neither fails nor resets the counter, and announces nothing
+      const sig = await create();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(1);
+      expect(await eventsFrom(sig)).to.be.empty;
+    });
+  });
+
+  describe("named counters", () => {
+    it("Lets one wallet own several independent counters", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "steps");
+      await initializeFor(owner, "logins");
+
+      await program.methods
+        .incrementBy("steps", new anchor.BN(3), false, null)
+        .accounts({ counter: counterPdaFor(owner.publicKey, "steps"), user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      const steps = await program.account.counter.fetch(counterPdaFor(owner.publicKey, "steps"));
+      const logins = await program.account.counter.fetch(counterPdaFor(owner.publicKey, "logins"));
+      expect(steps.label).to.equal("steps");
+      expect(steps.count.toNumber()).to.equal(3);
+      expect(logins.count.toNumber()).to.equal(0);
+    });
+
+    it("Keeps the default counter at the original address", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+      const [originalPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("counter"), owner.publicKey.toBuffer()],
+        program.programId
+      );
+      const counterAccount = await program.account.counter.fetch(originalPda);
+      expect(counterAccount.label).to.equal("");
+    });
+
+    it("Rejects labels longer than a PDA seed", async () => {
+      const owner = await fundedUser();
+      try {
+        await initializeFor(owner, "x".repeat(33));
+        expect.fail("A 33-byte label should be rejected");
+      } catch (error: any) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Only finds a counter under the label it was created with", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "score");
+      try {
+        await program.methods
+          .increment("points", false, null)
+          .accounts({ counter: counterPdaFor(owner.publicKey, "score"), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("The wrong label should not match the counter");
+      } catch (error: any) {
+        expect(error.message).to.include("ConstraintSeeds");
+      }
+    });
+  });
+
+  describe("authority transfers", () => {
+    let owner: Keypair;
+    let newOwner: Keypair;
+    let counter: anchor.web3.PublicKey;
+
+    before(async () => {
+      owner = await fundedUser();
+      newOwner = await fundedUser();
+      await initializeFor(owner);
+      counter = counterPdaFor(owner.publicKey);
+    });
+
+    it("Does not hand over control until the new owner accepts", async () => {
+      await program.methods
+        .transferAuthority(newOwner.publicKey)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      const pending = await program.account.counter.fetch(counter);
+      expect(pending.authority.toString()).to.equal(owner.publicKey.toString());
+      expect(pending.pendingAuthority!.toString()).to.equal(newOwner.publicKey.toString());
+
+      // The current owner keeps full control in the meantime
+      await program.methods.increment("", false, null).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+    });
+
+    it("Only lets the proposed owner accept", async () => {
+      const stranger = await fundedUser();
+      try {
+        await program.methods
+          .acceptAuthority()
+          .accounts({ counter, newAuthority: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Only the pending authority can accept");
+      } catch (error: any) {
+        expect(error.message).to.include("Signer is not the pending authority");
+      }
+    });
+
+    it("Moves control to the new owner on acceptance", async () => {
+      const sig = await program.methods
+        .acceptAuthority()
+        .accounts({ counter, newAuthority: newOwner.publicKey })
+        .signers([newOwner])
+        .rpc({ commitment: "confirmed" });
+
+      const [event] = (await eventsFrom(sig)).filter((e) => e.name === "authorityTransferred");
+      expect(event.data.previousAuthority.toString()).to.equal(owner.publicKey.toString());
+
+      const accepted = await program.account.counter.fetch(counter);
+      expect(accepted.authority.toString()).to.equal(newOwner.publicKey.toString());
+      expect(accepted.pendingAuthority).to.be.null;
+
+      // Same address, new key in charge
+      await program.methods.increment("", false, null).accounts({ counter, user: newOwner.publicKey }).signers([newOwner]).rpc();
+      try {
+        await program.methods.increment("", false, null).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+        expect.fail("The previous owner should have lost control");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+  });
+
+  describe("set_count / reset", () => {
+    let owner: Keypair;
+    let accounts: { counter: anchor.web3.PublicKey; user: anchor.web3.PublicKey };
+
+    before(async () => {
+      owner = await fundedUser();
+      await initializeFor(owner);
+      accounts = { counter: counterPdaFor(owner.publicKey), user: owner.publicKey };
+    });
+
+    it("Overwrites the counter with an exact value", async () => {
+      const sig = await program.methods
+        .setCount("", new anchor.BN(42), false, null)
+        .accounts(accounts)
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+
+      const [event] = (await eventsFrom(sig)).filter((e) => e.name === "counterUpdated");
+      expect(event.data.operation).to.deep.equal({ set: {} });
+      expect(event.data.newCount.toNumber()).to.equal(42);
+    });
+
+    it("Resets the counter to zero", async () => {
+      const sig = await program.methods
+        .reset("", false, null)
+        .accounts(accounts)
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+
+      const [event] = (await eventsFrom(sig)).filter((e) => e.name === "counterUpdated");
+      expect(event.data.operation).to.deep.equal({ reset: {} });
+      expect(event.data.previousCount.toNumber()).to.equal(42);
+      const counterAccount = await program.account.counter.fetch(accounts.counter);
+      expect(counterAccount.count.toNumber()).to.equal(0);
+    });
+
+    it("Only lets the owner correct a counter", async () => {
+      const stranger = await fundedUser();
+      try {
+        await program.methods
+          .setCount("", new anchor.BN(1), false, null)
+          .accounts({ counter: accounts.counter, user: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+        expect.fail("A stranger should not be able to set the count");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+  });
+
+  describe("min/max bounds", () => {
+    let owner: Keypair;
+    let accounts: { counter: anchor.web3.PublicKey; user: anchor.web3.PublicKey };
+
+    before(async () => {
+      owner = await fundedUser();
+      await initializeFor(owner, "points", { min: new anchor.BN(5), max: new anchor.BN(10) });
+      accounts = { counter: counterPdaFor(owner.publicKey, "points"), user: owner.publicKey };
+    });
+
+    it("Starts at the minimum", async () => {
+      const counterAccount = await program.account.counter.fetch(accounts.counter);
+      expect(counterAccount.count.toNumber()).to.equal(5);
+      expect(counterAccount.maxCount.toNumber()).to.equal(10);
+    });
+
+    it("Caps increments at the maximum", async () => {
+      await program.methods.incrementBy("points", new anchor.BN(5), false, null).accounts(accounts).signers([owner]).rpc();
+      try {
+        await program.methods.increment("points", false, null).accounts(accounts).signers([owner]).rpc();
+        expect.fail("Going past the maximum should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("Counter would go above its maximum");
+      }
+    });
+
+    it("Stops decrements and set_count at the minimum", async () => {
+      try {
+        await program.methods.setCount("points", new anchor.BN(4), false, null).accounts(accounts).signers([owner]).rpc();
+        expect.fail("Going below the minimum should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("Counter would go below its minimum");
+      }
+    });
+
+    it("Resets to the minimum", async () => {
+      await program.methods.reset("points", false, null).accounts(accounts).signers([owner]).rpc();
+      const counterAccount = await program.account.counter.fetch(accounts.counter);
+      expect(counterAccount.count.toNumber()).to.equal(5);
+    });
+
+    it("Rejects a minimum above the maximum", async () => {
+      const other = await fundedUser();
+      try {
+        await initializeFor(other, "", { min: new anchor.BN(3), max: new anchor.BN(2) });
+        expect.fail("Inverted bounds should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("Counter minimum is larger than its maximum");
+      }
+    });
+  });
+
+  describe("overflow modes", () => {
+    const bounds = { min: new anchor.BN(0), max: new anchor.BN(9) };
+
+    it("Saturates at the maximum", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "capped", bounds, false, null, { saturating: {} });
+      const counter = counterPdaFor(owner.publicKey, "capped");
+
+      await program.methods
+        .incrementBy("capped", new anchor.BN(25), false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(9);
+    });
+
+    it("Wraps around within the range", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "rounds", bounds, false, null, { wrapping: {} });
+      const counter = counterPdaFor(owner.publicKey, "rounds");
+
+      await program.methods
+        .incrementBy("rounds", new anchor.BN(12), false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(2);
+
+      await program.methods
+        .decrementBy("rounds", new anchor.BN(3), false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(9);
+    });
+  });
+
+  describe("milestones", () => {
+    it("Announces multiples of the milestone interval", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "steps");
+      const counter = counterPdaFor(owner.publicKey, "steps");
+      await program.methods
+        .setMilestoneInterval(new anchor.BN(10))
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      const quiet = await program.methods
+        .incrementBy("steps", new anchor.BN(7), false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+      expect((await eventsFrom(quiet)).some((e) => e.name === "milestoneReached")).to.be.false;
+
+      // 7 -> 23 jumps past 10 and 20; the highest one is announced
+      const sig = await program.methods
+        .incrementBy("steps", new anchor.BN(16), false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+      const [event] = (await eventsFrom(sig)).filter((e) => e.name === "milestoneReached");
+      expect(event.data.milestone.toNumber()).to.equal(20);
+    });
+  });
+
+  describe("public counters", () => {
+    let owner: Keypair;
+    let clicker: Keypair;
+    let counter: anchor.web3.PublicKey;
+
+    before(async () => {
+      owner = await fundedUser();
+      clicker = await fundedUser();
+      await initializeFor(owner, "clicks", undefined, true);
+      counter = counterPdaFor(owner.publicKey, "clicks");
+    });
+
+    it("Lets anyone increment and records who clicked", async () => {
+      const sig = await program.methods
+        .increment("clicks", false, null)
+        .accounts({ counter, user: clicker.publicKey })
+        .signers([clicker])
+        .rpc({ commitment: "confirmed" });
+
+      const [event] = (await eventsFrom(sig)).filter((e) => e.name === "counterUpdated");
+      expect(event.data.user.toString()).to.equal(clicker.publicKey.toString());
+      const counterAccount = await program.account.counter.fetch(counter);
+      expect(counterAccount.count.toNumber()).to.equal(1);
+    });
+
+    it("Keeps decrementing owner-only", async () => {
+      try {
+        await program.methods
+          .decrement("clicks", false, null)
+          .accounts({ counter, user: clicker.publicKey })
+          .signers([clicker])
+          .rpc();
+        expect.fail("Only the owner may decrement a public counter");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("Keeps private counters owner-only", async () => {
+      const privateOwner = await fundedUser();
+      await initializeFor(privateOwner);
+      try {
+        await program.methods
+          .increment("", false, null)
+          .accounts({ counter: counterPdaFor(privateOwner.publicKey), user: clicker.publicKey })
+          .signers([clicker])
+          .rpc();
+        expect.fail("Only the owner may increment a private counter");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+  });
+
+  describe("operator delegation", () => {
+    let owner: Keypair;
+    let operator: Keypair;
+    let counter: anchor.web3.PublicKey;
+    let delegation: anchor.web3.PublicKey;
+
+    before(async () => {
+      owner = await fundedUser();
+      operator = await fundedUser();
+      await initializeFor(owner, "delegated");
+      counter = counterPdaFor(owner.publicKey, "delegated");
+      [delegation] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("delegate"), counter.toBuffer(), operator.publicKey.toBuffer()],
+        program.programId
+      );
+    });
+
+    const operatorIncrement = () =>
+      program.methods
+        .increment("delegated", false, null)
+        .accounts({ counter, user: operator.publicKey, delegation })
+        .signers([operator])
+        .rpc();
+
+    it("Rejects operators that were never approved", async () => {
+      try {
+        await program.methods
+          .increment("delegated", false, null)
+          .accounts({ counter, user: operator.publicKey })
+          .signers([operator])
+          .rpc();
+        expect.fail("An unapproved operator should not be able to increment");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("Lets an approved operator increment until the allowance runs out", async () => {
+      await program.methods
+        .approveOperator(operator.publicKey, new anchor.BN(2))
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      await operatorIncrement();
+      await operatorIncrement();
+      const account = await program.account.delegation.fetch(delegation);
+      expect(account.allowance.toNumber()).to.equal(0);
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(2);
+
+      try {
+        await operatorIncrement();
+        expect.fail("The allowance should be exhausted");
+      } catch (error: any) {
+        expect(error.message).to.include("AllowanceExhausted");
+      }
+    });
+
+    it("Never lets an operator decrement", async () => {
+      try {
+        await program.methods
+          .decrement("delegated", false, null)
+          .accounts({ counter, user: operator.publicKey, delegation })
+          .signers([operator])
+          .rpc();
+        expect.fail("Operators may only increment");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("Closes the delegation on revoke", async () => {
+      await program.methods
+        .revokeOperator()
+        .accounts({ counter, delegation, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(delegation)).to.be.null;
+    });
+  });
+
+  describe("shared counters", () => {
+    const contributorPdaFor = (counter: anchor.web3.PublicKey, member: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("contributor"), counter.toBuffer(), member.toBuffer()],
+        program.programId
+      )[0];
+
+    it("Lets registered contributors increment and tallies what they add", async () => {
+      const owner = await fundedUser();
+      const member = await fundedUser();
+      await initializeFor(owner, "team");
+      const counter = counterPdaFor(owner.publicKey, "team");
+      const contributor = contributorPdaFor(counter, member.publicKey);
+      await program.methods
+        .addContributor(member.publicKey)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      for (const amount of [2, 3]) {
+        await program.methods
+          .incrementBy("team", new anchor.BN(amount), false, null)
+          .accounts({ counter, user: member.publicKey, contributor })
+          .signers([member])
+          .rpc();
+      }
+
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(5);
+      expect((await program.account.contributor.fetch(contributor)).contributions.toNumber()).to.equal(5);
+    });
+
+    it("Stops a removed contributor from incrementing", async () => {
+      const owner = await fundedUser();
+      const member = await fundedUser();
+      await initializeFor(owner, "ex-team");
+      const counter = counterPdaFor(owner.publicKey, "ex-team");
+      await program.methods
+        .addContributor(member.publicKey)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      await program.methods
+        .removeContributor()
+        .accounts({ counter, contributor: contributorPdaFor(counter, member.publicKey), user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      try {
+        await program.methods
+          .increment("ex-team", false, null)
+          .accounts({ counter, user: member.publicKey })
+          .signers([member])
+          .rpc();
+        expect.fail("Removed contributors should not increment");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+  });
+
+  describe("streaks", () => {
+    it("Starts a streak on the first increment of the day and ignores the rest", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "habit");
+      const counter = counterPdaFor(owner.publicKey, "habit");
+
+      const first = await program.methods
+        .increment("habit", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      const streak = (await eventsFrom(first)).find((e) => e.name === "streakUpdated");
+      expect(streak.data.currentStreak).to.equal(1);
+
+      const second = await program.methods
+        .increment("habit", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect((await eventsFrom(second)).some((e) => e.name === "streakUpdated")).to.equal(false);
+
+      const account = await program.account.counter.fetch(counter);
+      expect(account.currentStreak).to.equal(1);
+      expect(account.bestStreak).to.equal(1);
+      expect(account.lastDay.toNumber()).to.equal(Math.floor(Date.now() / 1000 / 86_400));
+    });
+  });
+
+  describe("daily limits", () => {
+    it("Rejects increments past the daily limit", async () => {
+      const owner = await fundedUser();
+      const visitor = await fundedUser();
+      await initializeFor(owner, "capped", { min: null, max: null }, true);
+      const counter = counterPdaFor(owner.publicKey, "capped");
+      await program.methods
+        .setDailyLimit(new anchor.BN(2))
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      for (let i = 0; i < 2; i++) {
+        await program.methods
+          .increment("capped", false, null)
+          .accounts({ counter, user: visitor.publicKey })
+          .signers([visitor])
+          .rpc();
+      }
+      try {
+        await program.methods
+          .increment("capped", false, null)
+          .accounts({ counter, user: visitor.publicKey })
+          .signers([visitor])
+          .rpc();
+        expect.fail("The third increment should be refused");
+      } catch (error: any) {
+        expect(error.message).to.include("DailyLimitReached");
+      }
+      expect((await program.account.counter.fetch(counter)).todayCount.toNumber()).to.equal(2);
+    });
+  });
+
+  describe("cooldowns", () => {
+    it("Rejects an increment made before the cooldown has passed", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "slow", undefined, true, new anchor.BN(3600));
+      try {
+        await program.methods
+          .increment("slow", false, null)
+          .accounts({ counter: counterPdaFor(owner.publicKey, "slow"), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("The counter was only just created");
+      } catch (error: any) {
+        expect(error.message).to.include("CooldownActive");
+      }
+    });
+
+    it("Still allows decrements during the cooldown", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "slow", { min: null, max: null }, false, new anchor.BN(3600));
+      const counter = counterPdaFor(owner.publicKey, "slow");
+      await program.methods
+        .setCount("slow", new anchor.BN(5), false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      await program.methods
+        .decrement("slow", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(4);
+    });
+
+    it("Rejects a negative cooldown", async () => {
+      const owner = await fundedUser();
+      try {
+        await initializeFor(owner, "", undefined, false, new anchor.BN(-1));
+        expect.fail("A negative cooldown should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidCooldown");
+      }
+    });
+  });
+
+  describe("user registries", () => {
+    const registryPdaFor = (owner: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("registry"), owner.toBuffer()], program.programId)[0];
+
+    after(async () => {
+      await program.methods.setMaxCountersPerUser(0).accounts({ config: configPda, admin: user }).rpc();
+    });
+
+    it("Lists a wallet's counters as they are created and closed", async () => {
+      const owner = await fundedUser();
+      for (const label of ["first", "a-much-longer-second-label"]) {
+        await initializeFor(owner, label);
+      }
+      let registry = await program.account.userRegistry.fetch(registryPdaFor(owner.publicKey));
+      expect(registry.user.toString()).to.equal(owner.publicKey.toString());
+      expect(registry.counters.map((entry) => entry.label)).to.deep.equal(["first", "a-much-longer-second-label"]);
+      expect(registry.counters[0].counter.toString()).to.equal(counterPdaFor(owner.publicKey, "first").toString());
+
+      await program.methods
+        .close("first", false, null)
+        .accounts({
+          counter: counterPdaFor(owner.publicKey, "first"),
+          user: owner.publicKey,
+          registry: registryPdaFor(owner.publicKey),
+        })
+        .signers([owner])
+        .rpc();
+      registry = await program.account.userRegistry.fetch(registryPdaFor(owner.publicKey));
+      expect(registry.counters.map((entry) => entry.label)).to.deep.equal(["a-much-longer-second-label"]);
+    });
+
+    it("Enforces max_counters_per_user", async () => {
+      try {
+        await program.methods
+          .setMaxCountersPerUser(1)
+          .accounts({ config: configPda, admin: unauthorizedUser.publicKey })
+          .signers([unauthorizedUser])
+          .rpc();
+        expect.fail("Only the admin may set the cap");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+      await program.methods.setMaxCountersPerUser(1).accounts({ config: configPda, admin: user }).rpc();
+
+      const owner = await fundedUser();
+      await initializeFor(owner, "only");
+      try {
+        await initializeFor(owner, "one-too-many");
+        expect.fail("A second counter should exceed the cap");
+      } catch (error: any) {
+        expect(error.message).to.include("TooManyCounters");
+      }
+
+      // Closing a counter (with the registry passed in) frees its place under the cap
+      await program.methods
+        .close("only", false, null)
+        .accounts({
+          counter: counterPdaFor(owner.publicKey, "only"),
+          user: owner.publicKey,
+          registry: registryPdaFor(owner.publicKey),
+        })
+        .signers([owner])
+        .rpc();
+      await initializeFor(owner, "one-too-many");
+    });
+  });
+
+  describe("NFT-gated counters", () => {
+    it("Only takes increments from the owner and holders of the collection", async () => {
+      const owner = await fundedUser();
+      const stranger = await fundedUser();
+      const collection = Keypair.generate().publicKey;
+      await initializeFor(owner, "holders", undefined, true, null, null, null, collection);
+      const counter = counterPdaFor(owner.publicKey, "holders");
+      expect((await program.account.counter.fetch(counter)).nftGate.toString()).to.equal(collection.toString());
+
+      // Public, but without an NFT of the collection a stranger is turned away
+      try {
+        await program.methods
+          .increment("holders", false, null)
+          .accounts({ counter, user: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Incrementing without an NFT of the collection should fail");
+      } catch (error: any) {
+        expect(error.message).to.include("NotCollectionHolder");
+      }
+
+      await program.methods
+        .increment("holders", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(1);
+    });
+  });
+
+  describe("counter vaults", () => {
+    let owner: Keypair;
+    let visitor: Keypair;
+    let counter: anchor.web3.PublicKey;
+    let vault: anchor.web3.PublicKey;
+    const deposit = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 100);
+
+    before(async () => {
+      owner = await fundedUser();
+      visitor = await fundedUser();
+      await initializeFor(owner, "crowdfund", undefined, true);
+      counter = counterPdaFor(owner.publicKey, "crowdfund");
+      [vault] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), counter.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .setVaultDeposit(deposit)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+    });
+
+    it("Requires the vault once a deposit is set", async () => {
+      try {
+        await program.methods
+          .increment("crowdfund", false, null)
+          .accounts({ counter, user: visitor.publicKey })
+          .signers([visitor])
+          .rpc();
+        expect.fail("Incrementing without the vault should fail");
+      } catch (error: any) {
+        expect(error.message).to.include("VaultRequired");
+      }
+    });
+
+    it("Moves the deposit into the vault on every increment", async () => {
+      for (const clicker of [visitor, owner]) {
+        const sig = await program.methods
+          .increment("crowdfund", false, null)
+          .accounts({ counter, user: clicker.publicKey, vault })
+          .signers([clicker])
+          .rpc({ commitment: "confirmed" });
+        const [deposited] = (await eventsFrom(sig)).filter((e) => e.name === "vaultDeposited");
+        expect(deposited.data.payer.toString()).to.equal(clicker.publicKey.toString());
+      }
+      expect(await provider.connection.getBalance(vault)).to.equal(2 * deposit.toNumber());
+    });
+
+    it("Lets only the owner withdraw", async () => {
+      try {
+        await program.methods
+          .withdrawVault(deposit)
+          .accounts({ counter, user: visitor.publicKey })
+          .signers([visitor])
+          .rpc();
+        expect.fail("Only the owner may withdraw");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+
+      await program.methods
+        .withdrawVault(new anchor.BN(2 * deposit.toNumber()))
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect(await provider.connection.getBalance(vault)).to.equal(0);
+    });
+
+    it("Drains the counter and refunds its deposits in one call", async () => {
+      for (let i = 0; i < 2; i++) {
+        await program.methods
+          .increment("crowdfund", false, null)
+          .accounts({ counter, user: owner.publicKey, vault })
+          .signers([owner])
+          .rpc();
+      }
+      const count = (await program.account.counter.fetch(counter)).count.toNumber();
+
+      const sig = await program.methods.drain().accounts({ counter, user: owner.publicKey, vault }).signers([owner]).rpc();
+      const [drained] = (await eventsFrom(sig)).filter((e) => e.name === "counterDrained");
+      expect(drained.data.drained.toNumber()).to.equal(count);
+      expect(drained.data.vaultLamports.toNumber()).to.equal(2 * deposit.toNumber()); // Capped at what the vault held
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(0);
+      expect(await provider.connection.getBalance(vault)).to.equal(0);
+    });
+  });
+
+  describe("counter fees", () => {
+    let owner: Keypair;
+    let visitor: Keypair;
+    let counter: anchor.web3.PublicKey;
+    let counterTreasury: anchor.web3.PublicKey;
+    const fee = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 100);
+
+    before(async () => {
+      owner = await fundedUser();
+      visitor = await fundedUser();
+      await initializeFor(owner, "paid", undefined, true);
+      counter = counterPdaFor(owner.publicKey, "paid");
+      [counterTreasury] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("counter_treasury"), counter.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("Only lets the owner set the fee", async () => {
+      try {
+        await program.methods
+          .setCounterFee(fee)
+          .accounts({ counter, user: visitor.publicKey })
+          .signers([visitor])
+          .rpc();
+        expect.fail("Only the owner may set the fee");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+
+      await program.methods
+        .setCounterFee(fee)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect((await program.account.counter.fetch(counter)).feeLamports.eq(fee)).to.be.true;
+    });
+
+    it("Charges visitors into the counter's treasury", async () => {
+      const before = await provider.connection.getBalance(counterTreasury);
+      const sig = await program.methods
+        .increment("paid", false, null)
+        .accounts({ counter, user: visitor.publicKey })
+        .signers([visitor])
+        .rpc({ commitment: "confirmed" });
+
+      expect(await provider.connection.getBalance(counterTreasury)).to.equal(before + fee.toNumber());
+      const [paid] = (await eventsFrom(sig)).filter((e) => e.name === "counterFeePaid");
+      expect(paid.data.payer.toString()).to.equal(visitor.publicKey.toString());
+    });
+
+    it("Doesn't charge the owner", async () => {
+      const before = await provider.connection.getBalance(counterTreasury);
+      await program.methods
+        .increment("paid", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect(await provider.connection.getBalance(counterTreasury)).to.equal(before);
+    });
+
+    it("Lets only the owner withdraw", async () => {
+      try {
+        await program.methods
+          .withdrawFees(fee)
+          .accounts({ counter, user: visitor.publicKey })
+          .signers([visitor])
+          .rpc();
+        expect.fail("Only the owner may withdraw");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+
+      await program.methods
+        .withdrawFees(fee)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect(await provider.connection.getBalance(counterTreasury)).to.equal(0);
+    });
+  });
+
+  describe("token-gated counters", () => {
+    let owner: Keypair;
+    let visitor: Keypair;
+    let counter: anchor.web3.PublicKey;
+
+    before(async () => {
+      owner = await fundedUser();
+      visitor = await fundedUser();
+      await initializeFor(owner, "members", undefined, true);
+      counter = counterPdaFor(owner.publicKey, "members");
+      await program.methods
+        .setTokenGate(Keypair.generate().publicKey, new anchor.BN(1))
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+    });
+
+    it("Rejects callers without the gate token", async () => {
+      try {
+        await program.methods
+          .increment("members", false, null)
+          .accounts({ counter, user: visitor.publicKey })
+          .signers([visitor])
+          .rpc();
+        expect.fail("Non-holders should not be able to increment");
+      } catch (error: any) {
+        expect(error.message).to.include("InsufficientTokenBalance");
+      }
+    });
+
+    it("Never gates the owner", async () => {
+      await program.methods
+        .increment("members", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(1);
+    });
+
+    it("Opens up again once the gate is removed", async () => {
+      await program.methods
+        .setTokenGate(anchor.web3.PublicKey.default, new anchor.BN(0))
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      await program.methods
+        .increment("members", false, null)
+        .accounts({ counter, user: visitor.publicKey })
+        .signers([visitor])
+        .rpc();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(2);
+    });
+  });
+
+  describe("increment rewards", () => {
+    const [rewardConfigPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("rewards")],
+      program.programId
+    );
+    const [rewardMintPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_mint")],
+      program.programId
+    );
+
+    it("Creates a reward mint only the program can mint", async () => {
+      await program.methods.initializeRewards(new anchor.BN(1)).accounts({ admin: user }).rpc();
+
+      const rewards = await program.account.rewardConfig.fetch(rewardConfigPda);
+      expect(rewards.mint.toString()).to.equal(rewardMintPda.toString());
+      expect(rewards.enabled).to.be.true;
+      expect(rewards.tokensPerIncrement.toNumber()).to.equal(1);
+    });
+
+    it("Only lets the admin configure rewards", async () => {
+      const stranger = await fundedUser();
+      try {
+        await program.methods
+          .disableRewards()
+          .accounts({ admin: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Only the admin may disable rewards");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("Rejects an increment with only some of the reward accounts", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "rewarded");
+      try {
+        await program.methods
+          .increment("rewarded", false, null)
+          .accounts({
+            counter: counterPdaFor(owner.publicKey, "rewarded"),
+            user: owner.publicKey,
+            rewardConfig: rewardConfigPda,
+          })
+          .signers([owner])
+          .rpc();
+        expect.fail("Reward accounts must be passed together");
+      } catch (error: any) {
+        expect(error.message).to.include("IncompleteRewardAccounts");
+      }
+    });
+
+    it("Mints one reward per increment, however much it adds", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "big spender");
+      const counter = counterPdaFor(owner.publicKey, "big spender");
+
+      // A token account for the reward mint, owned by the caller (SPL Token `InitializeAccount3`)
+      const tokenProgram = new anchor.web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+      const rewardAccount = Keypair.generate();
+      const lamports = await provider.connection.getMinimumBalanceForRentExemption(165);
+      const tx = new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: user,
+          newAccountPubkey: rewardAccount.publicKey,
+          lamports,
+          space: 165,
+          programId: tokenProgram,
+        }),
+        new anchor.web3.TransactionInstruction({
+          programId: tokenProgram,
+          keys: [
+            { pubkey: rewardAccount.publicKey, isSigner: false, isWritable: true },
+            { pubkey: rewardMintPda, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([Buffer.from([18]), owner.publicKey.toBuffer()]),
+        })
+      );
+      await provider.sendAndConfirm(tx, [rewardAccount]);
+
+      await program.methods
+        .incrementBy("big spender", new anchor.BN(1000), false, null)
+        .accounts({
+          counter,
+          user: owner.publicKey,
+          rewardConfig: rewardConfigPda,
+          rewardMint: rewardMintPda,
+          rewardTokenAccount: rewardAccount.publicKey,
+          tokenProgram,
+        })
+        .signers([owner])
+        .rpc();
+
+      const balance = await provider.connection.getTokenAccountBalance(rewardAccount.publicKey);
+      expect(balance.value.amount).to.equal("1");
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(1000);
+    });
+
+    it("Switches rewards off", async () => {
+      await program.methods.disableRewards().accounts({ admin: user }).rpc();
+      expect((await program.account.rewardConfig.fetch(rewardConfigPda)).enabled).to.be.false;
+    });
+  });
+
+  describe("global stats", () => {
+    it("Keeps program-wide totals in step with every counter", async () => {
+      const before = await program.account.globalStats.fetch(statsPda);
+      const owner = await fundedUser();
+      await initializeFor(owner, "tally");
+      const counter = counterPdaFor(owner.publicKey, "tally");
+
+      for (const method of ["increment", "increment", "decrement"] as const) {
+        await program.methods[method]("tally", false, null)
+          .accounts({ counter, user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+      }
+      const sig = await program.methods
+        .close("tally", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+
+      const after = await program.account.globalStats.fetch(statsPda);
+      expect(after.totalCounters.sub(before.totalCounters).toNumber()).to.equal(1);
+      expect(after.totalIncrements.sub(before.totalIncrements).toNumber()).to.equal(2);
+      expect(after.totalDecrements.sub(before.totalDecrements).toNumber()).to.equal(1);
+      expect(after.totalClosed.sub(before.totalClosed).toNumber()).to.equal(1);
+
+      const [event] = (await eventsFrom(sig)).filter((e) => e.name === "globalStatsUpdated");
+      expect(event.data.totalClosed.eq(after.totalClosed)).to.be.true;
+    });
+
+    it("Only lets the admin create the stats account", async () => {
+      const stranger = await fundedUser();
+      try {
+        await program.methods
+          .initializeStats()
+          .accounts({ admin: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+        expect.fail("The stats account already exists and belongs to the admin");
+      } catch (error: any) {
+        expect(error).to.exist;
+      }
+    });
+  });
+
+  describe("counter history", () => {
+    const historyPdaFor = (counter: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("history"), counter.toBuffer()],
+        program.programId
+      )[0];
+
+    it("Logs every change in a ring buffer", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "audited");
+      const counter = counterPdaFor(owner.publicKey, "audited");
+      const history = historyPdaFor(counter);
+      await program.methods
+        .initializeHistory()
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .incrementBy("audited", new anchor.BN(5), false, null)
+        .accounts({ counter, user: owner.publicKey, history })
+        .signers([owner])
+        .rpc();
+      await program.methods
+        .decrement("audited", false, null)
+        .accounts({ counter, user: owner.publicKey, history })
+        .signers([owner])
+        .rpc();
+
+      const log = await program.account.counterHistory.fetch(history);
+      expect(log.total.toNumber()).to.equal(2);
+      expect(log.entries[0].previous.toNumber()).to.equal(0);
+      expect(log.entries[0].new.toNumber()).to.equal(5);
+      expect(log.entries[1].operation).to.equal(1); // Decrement
+      expect(log.entries[1].new.toNumber()).to.equal(4);
+    });
+
+    it("Requires the history account once it exists", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "strict");
+      const counter = counterPdaFor(owner.publicKey, "strict");
+      await program.methods
+        .initializeHistory()
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      try {
+        await program.methods
+          .increment("strict", false, null)
+          .accounts({ counter, user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("Changes must be logged");
+      } catch (error: any) {
+        expect(error.message).to.include("HistoryRequired");
+      }
+    });
+  });
+
+  describe("signed counters", () => {
+    it("Goes below zero", async () => {
+      const owner = await fundedUser();
+      const [counter] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("signed_counter"), owner.publicKey.toBuffer(), Buffer.from("balance")],
+        program.programId
+      );
+      await program.methods
+        .initializeSigned("balance")
+        .accounts({ user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .decrementSigned("balance", new anchor.BN(30))
+        .accounts({ user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      await program.methods
+        .incrementSigned("balance", new anchor.BN(5))
+        .accounts({ user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      expect((await program.account.signedCounter.fetch(counter)).count.toNumber()).to.equal(-25);
+    });
+  });
+
+  describe("big counters", () => {
+    const bigCounterPdaFor = (owner: anchor.web3.PublicKey, label: string) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("big_counter"), owner.toBuffer(), Buffer.from(label)],
+        program.programId
+      )[0];
+    const countOf = (account: { countLo: anchor.BN; countHi: anchor.BN }) =>
+      account.countHi.shln(64).add(account.countLo).toString();
+
+    it("Counts past u64::MAX", async () => {
+      const owner = await fundedUser();
+      const u64Max = new anchor.BN("18446744073709551615");
+      await program.methods
+        .initializeBig("events")
+        .accounts({ user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      for (let i = 0; i < 2; i++) {
+        await program.methods
+          .incrementBig("events", u64Max)
+          .accounts({ user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+      }
+
+      const account = await program.account.bigCounter.fetch(bigCounterPdaFor(owner.publicKey, "events"));
+      expect(countOf(account)).to.equal(u64Max.muln(2).toString());
+    });
+
+    it("Upgrades a counter into a big counter", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "upgraded");
+      const counterPda = counterPdaFor(owner.publicKey, "upgraded");
+      await program.methods
+        .incrementBy("upgraded", new anchor.BN(42), false, null)
+        .accounts({ counter: counterPda, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      await program.methods
+        .upgradeToBig()
+        .accounts({ counter: counterPda, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(counterPda)).to.be.null;
+      const account = await program.account.bigCounter.fetch(bigCounterPdaFor(owner.publicKey, "upgraded"));
+      expect(countOf(account)).to.equal("42");
+    });
+  });
+
+  describe("zero-copy counters", () => {
+    const zcPdaFor = (owner: anchor.web3.PublicKey, label: string) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("counter_zc"), owner.toBuffer(), Buffer.from(label)],
+        program.programId
+      )[0];
+
+    it("Increments and decrements in place", async () => {
+      const owner = await fundedUser();
+      await program.methods.initializeZc("hot").accounts({ user: owner.publicKey }).signers([owner]).rpc();
+      for (const method of ["incrementZc", "incrementZc", "decrementZc"] as const) {
+        await program.methods[method]("hot").accounts({ user: owner.publicKey }).signers([owner]).rpc();
+      }
+      const account = await program.account.counterZc.fetch(zcPdaFor(owner.publicKey, "hot"));
+      expect(account.count.toNumber()).to.equal(1);
+      expect(account.authority.toBase58()).to.equal(owner.publicKey.toBase58());
+    });
+
+    it("Converts a counter into a zero-copy counter", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "converted");
+      const counterPda = counterPdaFor(owner.publicKey, "converted");
+      await program.methods
+        .incrementBy("converted", new anchor.BN(7), false, null)
+        .accounts({ counter: counterPda, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      await program.methods.convertToZc().accounts({ counter: counterPda, user: owner.publicKey }).signers([owner]).rpc();
+
+      expect(await provider.connection.getAccountInfo(counterPda)).to.be.null;
+      const account = await program.account.counterZc.fetch(zcPdaFor(owner.publicKey, "converted"));
+      expect(account.count.toNumber()).to.equal(7);
+    });
+  });
+
+  describe("counter arrays", () => {
+    let owner: Keypair;
+    let array: anchor.web3.PublicKey;
+
+    before(async () => {
+      owner = await fundedUser();
+      [array] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("counter_array"), owner.publicKey.toBuffer(), Buffer.from("inventory")],
+        program.programId
+      );
+      await program.methods
+        .initializeArray("inventory")
+        .accounts({ user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+    });
+
+    it("Increments and decrements individual slots", async () => {
+      for (const method of ["incrementSlot", "incrementSlot", "decrementSlot"] as const) {
+        await program.methods[method]("inventory", 1023)
+          .accounts({ user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+      }
+
+      const account = await program.account.counterArray.fetch(array);
+      expect(account.slots[1023].toNumber()).to.equal(1);
+      expect(account.slots[0].toNumber()).to.equal(0);
+    });
+
+    it("Rejects slots past the end of the array", async () => {
+      try {
+        await program.methods
+          .incrementSlot("inventory", 1024)
+          .accounts({ user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("There are only 1024 slots");
+      } catch (error: any) {
+        expect(error.message).to.include("SlotOutOfBounds");
+      }
+    });
+
+    it("Refuses to change a locked slot", async () => {
+      await program.methods
+        .setSlotFlags("inventory", 7, 1)
+        .accounts({ user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      try {
+        await program.methods
+          .incrementSlot("inventory", 7)
+          .accounts({ user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("Locked slots can't change");
+      } catch (error: any) {
+        expect(error.message).to.include("SlotLocked");
+      }
+    });
+  });
+
+  describe("instruction kill switches", () => {
+    let owner: Keypair;
+    const INCREMENT_FLAG = 1 << 1;
+
+    before(async () => {
+      owner = await fundedUser();
+      await initializeFor(owner);
+    });
+
+    after(async () => {
+      // Leave everything enabled for the remaining tests
+      await program.methods
+        .setDisabledInstructions(new anchor.BN(0))
+        .accounts({ config: configPda, admin: user })
+        .rpc();
+    });
+
+    it("Only lets the admin change the mask", async () => {
+      try {
+        await program.methods
+          .setDisabledInstructions(new anchor.BN(INCREMENT_FLAG))
+          .accounts({ config: configPda, admin: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("Non-admin should not change instruction flags");
+      } catch (error: any) {
+        expect(error).to.exist;
+      }
+    });
+
+    it("Blocks a disabled instruction until it is re-enabled", async () => {
+      await program.methods
+        .setDisabledInstructions(new anchor.BN(INCREMENT_FLAG))
+        .accounts({ config: configPda, admin: user })
+        .rpc();
+
+      try {
+        await program.methods
+          .increment("", false, null)
+          .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("Increment should be disabled");
+      } catch (error: any) {
+        expect(error.message).to.include("This instruction is currently disabled");
+      }
+
+      await program.methods
+        .setDisabledInstructions(new anchor.BN(0))
+        .accounts({ config: configPda, admin: user })
+        .rpc();
+
+      await program.methods
+        .increment("", false, null)
+        .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      const counterAccount = await program.account.counter.fetch(counterPdaFor(owner.publicKey));
+      expect(counterAccount.count.toNumber()).to.equal(1);
+    });
+
+    it("Switches single instructions without touching the rest of the mask", async () => {
+      const CLOSE_FLAG = 1 << 3;
+      await program.methods
+        .setDisabledInstructions(new anchor.BN(INCREMENT_FLAG))
+        .accounts({ config: configPda, admin: user })
+        .rpc();
+      await program.methods
+        .setInstructionFlags(new anchor.BN(INCREMENT_FLAG), new anchor.BN(CLOSE_FLAG))
+        .accounts({ config: configPda, admin: user })
+        .rpc();
+
+      const config = await program.account.programConfig.fetch(configPda);
+      expect(config.disabledInstructions.toNumber()).to.equal(CLOSE_FLAG);
+
+      try {
+        await program.methods
+          .close("", false, null)
+          .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("Close should be disabled");
+      } catch (error: any) {
+        expect(error.message).to.include("This instruction is currently disabled");
+      }
+      await program.methods
+        .increment("", false, null)
+        .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      try {
+        await program.methods
+          .setInstructionFlags(new anchor.BN(CLOSE_FLAG), new anchor.BN(CLOSE_FLAG))
+          .accounts({ config: configPda, admin: user })
+          .rpc();
+        expect.fail("A flag can't be enabled and disabled at once");
+      } catch (error: any) {
+        expect(error.message).to.include("ConflictingInstructionFlags");
+      }
+    });
+  });
+
+  describe("global pause", () => {
+    let owner: Keypair;
+
+    before(async () => {
+      owner = await fundedUser();
+      await initializeFor(owner);
+    });
+
+    after(async () => {
+      // Leave the program running for anything that comes after
+      await program.methods.setPaused(false).accounts({ config: configPda, admin: user }).rpc();
+    });
+
+    it("Only lets the admin pause", async () => {
+      try {
+        await program.methods
+          .setPaused(true)
+          .accounts({ config: configPda, admin: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("Non-admin should not pause the program");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("Blocks every mutation while paused", async () => {
+      await program.methods.setPaused(true).accounts({ config: configPda, admin: user }).rpc();
+
+      for (const method of [
+        program.methods.increment("", false, null),
+        program.methods.close("", false, null),
+      ]) {
+        try {
+          await method
+            .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+            .signers([owner])
+            .rpc();
+          expect.fail("Mutations should fail while paused");
+        } catch (error: any) {
+          expect(error.message).to.include("ProgramPaused");
+        }
+      }
+
+      try {
+        await initializeFor(await fundedUser());
+        expect.fail("New counters should not be created while paused");
+      } catch (error: any) {
+        expect(error.message).to.include("ProgramPaused");
+      }
+    });
+
+    it("Lets everything through again once unpaused", async () => {
+      await program.methods.setPaused(false).accounts({ config: configPda, admin: user }).rpc();
+      await program.methods
+        .increment("", false, null)
+        .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect((await program.account.counter.fetch(counterPdaFor(owner.publicKey))).count.toNumber()).to.equal(1);
+    });
+  });
+
+  describe("CPI from another program", () => {
+    const caller = anchor.workspace.counterCaller as Program<CounterCaller>;
+
+    it("Increments a counter through counter_caller and reads it back with view_count", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "called");
+      const counter = counterPdaFor(owner.publicKey, "called");
+      const [treasury] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("treasury")], program.programId);
+      const [counterTreasury] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("counter_treasury"), counter.toBuffer()],
+        program.programId
+      );
+
+      // The owner signs the outer transaction; the signature carries through to the counter program
+      const sig = await caller.methods
+        .bump("called")
+        .accounts({
+          counter,
+          user: owner.publicKey,
+          config: configPda,
+          stats: statsPda,
+          treasury,
+          counterTreasury,
+        })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(1);
+      const [event] = (await eventsFrom(sig)).filter((e) => e.name === "counterUpdated");
+      expect(event.data.newCount.toNumber()).to.equal(1);
+
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      expect(tx!.meta!.logMessages!.join("\n")).to.include(`Counter ${counter.toBase58()} is now at 1`);
+    });
+
+    it("Still enforces the counter's own rules", async () => {
+      const owner = await fundedUser();
+      const stranger = await fundedUser();
+      await initializeFor(owner, "guarded");
+      const counter = counterPdaFor(owner.publicKey, "guarded");
+      const [treasury] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("treasury")], program.programId);
+      const [counterTreasury] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("counter_treasury"), counter.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await caller.methods
+          .bump("guarded")
+          .accounts({
+            counter,
+            user: stranger.publicKey,
+            config: configPda,
+            stats: statsPda,
+            treasury,
+            counterTreasury,
+          })
+          .signers([stranger])
+          .rpc();
+        expect.fail("A stranger shouldn't be able to increment a private counter through CPI");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+  });
+
+  describe("layout versioning", () => {
+    it("Stamps new counters with the current layout", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "layout");
+      const counter = await program.account.counter.fetch(counterPdaFor(owner.publicKey, "layout"));
+      expect(counter.layoutVersion).to.equal(22);
+    });
+
+    it("Refuses to migrate a counter that is already current", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "current");
+      try {
+        await program.methods
+          .migrate(owner.publicKey, "current")
+          .accounts({ counter: counterPdaFor(owner.publicKey, "current"), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("A current counter has nothing to migrate");
+      } catch (error: any) {
+        expect(error.message).to.include("AlreadyMigrated");
+      }
+    });
+  });
+
+  describe("display metadata", () => {
+    it("Grows the counter to fit its metadata and shrinks it back", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "shown");
+      const counterPda = counterPdaFor(owner.publicKey, "shown");
+      const bareSize = (await provider.connection.getAccountInfo(counterPda))!.data.length;
+
+      const label = "Daily push-ups";
+      const uri = "https://example.com/push-ups.json";
+      await program.methods
+        .setMetadata(label, uri)
+        .accounts({ counter: counterPda, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      let counter = await program.account.counter.fetch(counterPda);
+      expect(counter.metadataLabel).to.equal(label);
+      expect(counter.metadataUri).to.equal(uri);
+      expect((await provider.connection.getAccountInfo(counterPda))!.data.length).to.equal(
+        bareSize + label.length + uri.length
+      );
+
+      await program.methods.clearMetadata().accounts({ counter: counterPda, user: owner.publicKey }).signers([owner]).rpc();
+      counter = await program.account.counter.fetch(counterPda);
+      expect(counter.metadataLabel).to.equal("");
+      expect((await provider.connection.getAccountInfo(counterPda))!.data.length).to.equal(bareSize);
+    });
+
+    it("Rejects metadata that is too long", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "wordy");
+      try {
+        await program.methods
+          .setMetadata("x".repeat(65), "")
+          .accounts({ counter: counterPdaFor(owner.publicKey, "wordy"), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("A 65-byte label should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("MetadataTooLong");
+      }
+    });
+  });
+
+  describe("scheduled resets", () => {
+    it("Starts over once the reset period has passed", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "weekly");
+      const counterPda = counterPdaFor(owner.publicKey, "weekly");
+      const increment = () =>
+        program.methods.increment("weekly", false, null).accounts({ counter: counterPda, user: owner.publicKey }).signers([owner]).rpc();
+
+      await program.methods.setResetInterval(new anchor.BN(2)).accounts({ counter: counterPda, user: owner.publicKey }).signers([owner]).rpc();
+      await increment();
+      await increment();
+
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+      const events = await eventsFrom(await increment());
+      const rollover = events.find((event) => event.name === "periodRolledOver");
+      expect(rollover, "the late increment should roll the period over").to.not.be.undefined;
+      expect(rollover!.data.lastPeriodCount.toNumber()).to.equal(2);
+
+      const counter = await program.account.counter.fetch(counterPda);
+      expect(counter.count.toNumber()).to.equal(1); // Started over, then incremented
+      expect(counter.lastPeriodCount.toNumber()).to.equal(2);
+    });
+  });
+
+  describe("multisig counters", () => {
+    it("Decrements only after 2 of 3 owners approve", async () => {
+      const creator = await fundedUser();
+      const [alice, bob, carol] = [await fundedUser(), await fundedUser(), await fundedUser()];
+      await initializeFor(creator, "treasury");
+      const counterPda = counterPdaFor(creator.publicKey, "treasury");
+      await program.methods
+        .incrementBy("treasury", new anchor.BN(10), false, null)
+        .accounts({ counter: counterPda, user: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+      const [multisigPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("multisig"), counterPda.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .createMultisig([alice.publicKey, bob.publicKey, carol.publicKey], 2)
+        .accounts({ counter: counterPda, user: creator.publicKey })
+        .signers([creator])
+        .rpc();
+      expect((await program.account.counter.fetch(counterPda)).authority.toString()).to.equal(multisigPda.toString());
+
+      const index = new anchor.BN(0);
+      const [proposalPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), multisigPda.toBuffer(), index.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      await program.methods
+        .proposeOp({ decrement: { 0: new anchor.BN(3) } } as any)
+        .accounts({ multisig: multisigPda, user: alice.publicKey })
+        .signers([alice])
+        .rpc();
+
+      const execute = () =>
+        program.methods
+          .executeOp()
+          .accounts({ counter: counterPda, proposal: proposalPda, proposer: alice.publicKey, user: carol.publicKey })
+          .signers([carol])
+          .rpc();
+      try {
+        await execute();
+        expect.fail("One approval shouldn't be enough");
+      } catch (error: any) {
+        expect(error.message).to.include("NotEnoughApprovals");
+      }
+
+      await program.methods.approveOp().accounts({ multisig: multisigPda, proposal: proposalPda, user: bob.publicKey }).signers([bob]).rpc();
+      await execute();
+      expect((await program.account.counter.fetch(counterPda)).count.toNumber()).to.equal(7);
+      expect(await provider.connection.getAccountInfo(proposalPda)).to.be.null; // Closed on execution
+    });
+  });
+
+  describe("session keys", () => {
+    it("Lets a session key increment until its budget runs out", async () => {
+      const owner = await fundedUser();
+      const sessionKey = await fundedUser(); // Pays its own transaction fees
+      await initializeFor(owner, "taps");
+      const counterPda = counterPdaFor(owner.publicKey, "taps");
+      const [sessionPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("session"), counterPda.toBuffer(), sessionKey.publicKey.toBuffer()],
+        program.programId
+      );
+
+      const expiresAt = Math.floor(Date.now() / 1000) + 3600;
+      await program.methods
+        .createSession(sessionKey.publicKey, new anchor.BN(expiresAt), 2)
+        .accounts({ counter: counterPda, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      const tap = () =>
+        program.methods
+          .increment("taps", false, null)
+          .accounts({ counter: counterPda, user: sessionKey.publicKey, session: sessionPda })
+          .signers([sessionKey])
+          .rpc();
+      await tap();
+      await tap();
+      expect((await program.account.counter.fetch(counterPda)).count.toNumber()).to.equal(2);
+
+      try {
+        await tap();
+        expect.fail("The session only allowed two operations");
+      } catch (error: any) {
+        expect(error.message).to.include("SessionExhausted");
+      }
+
+      await program.methods
+        .revokeSession()
+        .accounts({ counter: counterPda, session: sessionPda, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(sessionPda)).to.be.null;
+    });
+
+    it("Spends one operation per batch step, and refuses batches bigger than what's left", async () => {
+      const owner = await fundedUser();
+      const sessionKey = await fundedUser();
+      await initializeFor(owner, "batched");
+      const counterPda = counterPdaFor(owner.publicKey, "batched");
+      const [sessionPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("session"), counterPda.toBuffer(), sessionKey.publicKey.toBuffer()],
+        program.programId
+      );
+      const expiresAt = Math.floor(Date.now() / 1000) + 3600;
+      await program.methods
+        .createSession(sessionKey.publicKey, new anchor.BN(expiresAt), 3)
+        .accounts({ counter: counterPda, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      const batch = (steps: number) =>
+        program.methods
+          .applyBatch("batched", Array.from({ length: steps }, () => ({ increment: [new anchor.BN(1)] })), false, null)
+          .accounts({ counter: counterPda, user: sessionKey.publicKey, session: sessionPda })
+          .signers([sessionKey])
+          .rpc();
+      await batch(2);
+      expect((await program.account.session.fetch(sessionPda)).opsLeft).to.equal(1);
+
+      try {
+        await batch(2);
+        expect.fail("Only one operation was left");
+      } catch (error: any) {
+        expect(error.message).to.include("SessionExhausted");
+      }
+      expect((await program.account.counter.fetch(counterPda)).count.toNumber()).to.equal(2);
+    });
+  });
+
+  describe("leaderboard", () => {
+    it("Ranks submitted counters highest first", async () => {
+      const low = await fundedUser();
+      const high = await fundedUser();
+      await initializeFor(low, "score");
+      await initializeFor(high, "score");
+      const lowPda = counterPdaFor(low.publicKey, "score");
+      const highPda = counterPdaFor(high.publicKey, "score");
+      const [leaderboardPda] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("leaderboard")], program.programId);
+
+      for (let i = 0; i < 2; i++) {
+        await program.methods.increment("score", false, null).accounts({ counter: highPda, user: high.publicKey }).signers([high]).rpc();
+      }
+      await program.methods.increment("score", false, null).accounts({ counter: lowPda, user: low.publicKey }).signers([low]).rpc();
+
+      await program.methods.submitScore().accounts({ counter: lowPda, user: low.publicKey }).signers([low]).rpc();
+      const sig = await program.methods.submitScore().accounts({ counter: highPda, user: high.publicKey }).signers([high]).rpc();
+
+      const updated = (await eventsFrom(sig)).find((e) => e.name === "leaderboardUpdated");
+      expect(updated.data.rank).to.equal(0);
+
+      const { entries } = await program.account.leaderboard.fetch(leaderboardPda);
+      expect(entries[0].counter.toBase58()).to.equal(highPda.toBase58());
+      expect(entries[0].count.toNumber()).to.equal(2);
+      expect(entries[1].counter.toBase58()).to.equal(lowPda.toBase58());
+    });
+  });
+
+  describe("cloning counters", () => {
+    it("Copies the settings, and the value only when asked", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "template", { min: new anchor.BN(5), max: new anchor.BN(50) }, true, new anchor.BN(0));
+      const source = counterPdaFor(owner.publicKey, "template");
+      await program.methods.increment("template", false, null).accounts({ counter: source, user: owner.publicKey }).signers([owner]).rpc();
+
+      await program.methods.cloneCounter("fresh", false).accounts({ source, user: owner.publicKey }).signers([owner]).rpc();
+      await program.methods.cloneCounter("copy", true).accounts({ source, user: owner.publicKey }).signers([owner]).rpc();
+
+      const fresh = await program.account.counter.fetch(counterPdaFor(owner.publicKey, "fresh"));
+      expect(fresh.count.toNumber()).to.equal(5);
+      expect(fresh.minCount.toNumber()).to.equal(5);
+      expect(fresh.maxCount.toNumber()).to.equal(50);
+      expect(fresh.isPublic).to.be.true;
+      expect((await program.account.counter.fetch(counterPdaFor(owner.publicKey, "copy"))).count.toNumber()).to.equal(6);
+    });
+  });
+
+  describe("count transfers", () => {
+    it("Moves count to another owner's counter", async () => {
+      const sender = await fundedUser();
+      const recipient = await fundedUser();
+      await initializeFor(sender, "points");
+      await initializeFor(recipient, "points");
+      const source = counterPdaFor(sender.publicKey, "points");
+      const destination = counterPdaFor(recipient.publicKey, "points");
+      for (let i = 0; i < 3; i++) {
+        await program.methods.increment("points", false, null).accounts({ counter: source, user: sender.publicKey }).signers([sender]).rpc();
+      }
+
+      const sig = await program.methods
+        .transferCount(new anchor.BN(2))
+        .accounts({ source, destination, user: sender.publicKey })
+        .signers([sender])
+        .rpc();
+      const [transferred] = (await eventsFrom(sig)).filter((e) => e.name === "countTransferred");
+      expect(transferred.data.recipient.toBase58()).to.equal(recipient.publicKey.toBase58());
+      expect((await program.account.counter.fetch(source)).count.toNumber()).to.equal(1);
+      expect((await program.account.counter.fetch(destination)).count.toNumber()).to.equal(2);
+
+      try {
+        await program.methods
+          .transferCount(new anchor.BN(5))
+          .accounts({ source, destination, user: sender.publicKey })
+          .signers([sender])
+          .rpc();
+        expect.fail("The source only holds 1");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterUnderflow");
+      }
+    });
+  });
+
+  describe("decay", () => {
+    it("Only decays once a full day has passed", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "reputation");
+      const counter = counterPdaFor(owner.publicKey, "reputation");
+      await program.methods.increment("reputation", false, null).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+
+      await program.methods.setDecayRate(new anchor.BN(1)).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      const decaying = await program.account.counter.fetch(counter);
+      expect(decaying.decayRatePerDay.toNumber()).to.equal(1);
+      expect(decaying.lastDecayTs.toNumber()).to.be.greaterThan(0);
+
+      try {
+        await program.methods.applyDecay().accounts({ counter }).rpc();
+        expect.fail("Nothing should be owed within the first day");
+      } catch (error: any) {
+        expect(error.message).to.include("NoDecayDue");
+      }
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(1);
+    });
+  });
+
+  describe("referrals", () => {
+    it("Credits the referrer's counter on increments", async () => {
+      const referrer = await fundedUser();
+      const visitor = await fundedUser();
+      await initializeFor(referrer, "ref");
+      await initializeFor(visitor, "mine");
+      const referrerCounter = counterPdaFor(referrer.publicKey, "ref");
+      const counter = counterPdaFor(visitor.publicKey, "mine");
+
+      const sig = await program.methods
+        .incrementWithReferrer("mine", referrer.publicKey, false, null)
+        .accounts({ counter, user: visitor.publicKey, referrerCounter })
+        .signers([visitor])
+        .rpc();
+      const [recorded] = (await eventsFrom(sig)).filter((e) => e.name === "referralRecorded");
+      expect(recorded.data.referrals.toNumber()).to.equal(1);
+      expect((await program.account.counter.fetch(referrerCounter)).referrals.toNumber()).to.equal(1);
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(1);
+
+      try {
+        await program.methods
+          .incrementWithReferrer("mine", visitor.publicKey, false, null)
+          .accounts({ counter, user: visitor.publicKey, referrerCounter })
+          .signers([visitor])
+          .rpc();
+        expect.fail("The referrer has to own the referrer counter");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidReferrer");
+      }
+    });
+  });
+
+  describe("memos", () => {
+    it("Records the reason in CounterUpdated", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "audited");
+      const counter = counterPdaFor(owner.publicKey, "audited");
+
+      const sig = await program.methods
+        .incrementWithMemo("audited", "manual correction #42", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      const [updated] = (await eventsFrom(sig)).filter((e) => e.name === "counterUpdated");
+      expect(updated.data.memo).to.equal("manual correction #42");
+
+      try {
+        await program.methods
+          .decrementWithMemo("audited", "x".repeat(65), false, null)
+          .accounts({ counter, user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("Memos are capped at 64 bytes");
+      } catch (error: any) {
+        expect(error.message).to.include("MemoTooLong");
+      }
+    });
+  });
+
+  describe("two-phase close", () => {
+    const setCloseTimelock = (seconds: number) =>
+      program.methods.setCloseTimelock(new anchor.BN(seconds)).accounts({ config: configPda, admin: user }).rpc();
+    const closeCounter = (owner: Keypair) =>
+      program.methods
+        .close("", false, null)
+        .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+    const manage = (owner: Keypair) => ({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey });
+
+    before(async () => {
+      await setCloseTimelock(2);
+    });
+
+    after(async () => {
+      await setCloseTimelock(0);
+    });
+
+    it("Only closes after the timelock since request_close", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+      try {
+        await closeCounter(owner);
+        expect.fail("Close without a request should fail");
+      } catch (error: any) {
+        expect(error.message).to.include("CloseNotRequested");
+      }
+
+      const signature = await program.methods.requestClose().accounts(manage(owner)).signers([owner]).rpc();
+      const requested = (await eventsFrom(signature)).find((e) => e.name === "closeRequested");
+      expect(requested.data.closableAt.toNumber()).to.equal(requested.data.requestedAt.toNumber() + 2);
+      try {
+        await closeCounter(owner);
+        expect.fail("Close inside the timelock should fail");
+      } catch (error: any) {
+        expect(error.message).to.include("CloseTimelockActive");
+      }
+
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+      await closeCounter(owner);
+      expect(await provider.connection.getAccountInfo(counterPdaFor(owner.publicKey))).to.be.null;
+    });
+
+    it("Lets the owner cancel a pending close", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+      await program.methods.requestClose().accounts(manage(owner)).signers([owner]).rpc();
+      const signature = await program.methods.cancelClose().accounts(manage(owner)).signers([owner]).rpc();
+      expect((await eventsFrom(signature)).some((e) => e.name === "closeCancelled")).to.be.true;
+
+      const counter = await program.account.counter.fetch(counterPdaFor(owner.publicKey));
+      expect(counter.closeRequestedAt.toNumber()).to.equal(0);
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+      try {
+        await closeCounter(owner);
+        expect.fail("A cancelled close should need a new request");
+      } catch (error: any) {
+        expect(error.message).to.include("CloseNotRequested");
+      }
+    });
+  });
+
+  describe("counter templates", () => {
+    const templatePdaFor = (authority: anchor.web3.PublicKey, name: string) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("template"), authority.toBuffer(), Buffer.from(name)],
+        program.programId
+      )[0];
+    const params = {
+      min: new anchor.BN(5),
+      max: new anchor.BN(50),
+      isPublic: true,
+      cooldownSeconds: null,
+      overflowMode: { saturating: {} },
+      nftGate: null,
+      feeLamports: new anchor.BN(1_000),
+      gateMint: anchor.web3.PublicKey.default,
+      minBalance: new anchor.BN(0),
+      dailyLimit: new anchor.BN(20),
+      milestoneInterval: new anchor.BN(10),
+      step: null,
+    };
+
+    it("Creates counters with a template's settings", async () => {
+      const org = await fundedUser();
+      const member = await fundedUser();
+      await program.methods
+        .createTemplate("standard", params)
+        .accounts({ user: org.publicKey })
+        .signers([org])
+        .rpc();
+      const template = templatePdaFor(org.publicKey, "standard");
+
+      const signature = await program.methods
+        .initializeFromTemplate("from-template")
+        .accounts({ template, user: member.publicKey })
+        .signers([member])
+        .rpc();
+      const created = (await eventsFrom(signature)).find((e) => e.name === "counterCreatedFromTemplate");
+      expect(created.data.template.toBase58()).to.equal(template.toBase58());
+
+      const counter = await program.account.counter.fetch(counterPdaFor(member.publicKey, "from-template"));
+      expect(counter.count.toNumber()).to.equal(5);
+      expect(counter.maxCount.toNumber()).to.equal(50);
+      expect(counter.isPublic).to.be.true;
+      expect(counter.overflowMode).to.deep.equal({ saturating: {} });
+      expect(counter.feeLamports.toNumber()).to.equal(1_000);
+      expect(counter.dailyLimit.toNumber()).to.equal(20);
+      expect(counter.milestoneInterval.toNumber()).to.equal(10);
+    });
+
+    it("Rejects templates with inverted bounds", async () => {
+      const org = await fundedUser();
+      try {
+        await program.methods
+          .createTemplate("inverted", { ...params, min: new anchor.BN(60) })
+          .accounts({ user: org.publicKey })
+          .signers([org])
+          .rpc();
+        expect.fail("min above max should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidBounds");
+      }
+    });
+  });
+
+  describe("step size", () => {
+    it("Moves the counter by its step", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "tally", undefined, false, null, null, null, null, new anchor.BN(5));
+      const counterPda = counterPdaFor(owner.publicKey, "tally");
+      const accounts = { counter: counterPda, user: owner.publicKey };
+
+      await program.methods.increment("tally", false, null).accounts(accounts).signers([owner]).rpc();
+      await program.methods.increment("tally", false, null).accounts(accounts).signers([owner]).rpc();
+      await program.methods.decrement("tally", false, null).accounts(accounts).signers([owner]).rpc();
+      expect((await program.account.counter.fetch(counterPda)).count.toNumber()).to.equal(5);
+
+      await program.methods.setStep(new anchor.BN(2)).accounts(accounts).signers([owner]).rpc();
+      await program.methods.increment("tally", false, null).accounts(accounts).signers([owner]).rpc();
+      expect((await program.account.counter.fetch(counterPda)).count.toNumber()).to.equal(7);
+    });
+
+    it("Rejects a zero step", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+      try {
+        await program.methods
+          .setStep(new anchor.BN(0))
+          .accounts({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("A zero step should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidStep");
+      }
+    });
+  });
+
+  describe("nonces", () => {
+    it("Rejects a replayed nonce", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+      const accounts = { counter: counterPdaFor(owner.publicKey), user: owner.publicKey };
+      await program.methods.incrementWithNonce("", new anchor.BN(7), false, null).accounts(accounts).signers([owner]).rpc();
+
+      for (const nonce of [7, 3]) {
+        try {
+          await program.methods
+            .incrementWithNonce("", new anchor.BN(nonce), false, null)
+            .accounts(accounts)
+            .signers([owner])
+            .rpc();
+          expect.fail("A used nonce should be rejected");
+        } catch (error: any) {
+          expect(error.message).to.include("NonceAlreadyUsed");
+        }
+      }
+
+      await program.methods.incrementWithNonce("", new anchor.BN(8), false, null).accounts(accounts).signers([owner]).rpc();
+      const counter = await program.account.counter.fetch(accounts.counter);
+      expect(counter.count.toNumber()).to.equal(2);
+      expect(counter.lastNonce.toNumber()).to.equal(8);
+    });
+  });
+
+  describe("keeper upkeep", () => {
+    it("Archives an expired counter and tips the keeper from the vault", async () => {
+      const owner = await fundedUser();
+      const keeper = await fundedUser();
+      const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 2);
+      await initializeFor(owner, "kept", { min: null, max: null }, false, null, null, expiresAt);
+      const counter = counterPdaFor(owner.publicKey, "kept");
+      const [vault] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), counter.toBuffer()],
+        program.programId
+      );
+      const deposit = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 100);
+      const tip = 5_000;
+      await program.methods.setVaultDeposit(deposit).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      await program.methods.setKeeperTip(new anchor.BN(tip)).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      await program.methods
+        .increment("kept", false, null)
+        .accounts({ counter, user: owner.publicKey, vault })
+        .signers([owner])
+        .rpc();
+
+      try {
+        await program.methods.touch().accounts({ counter, keeper: keeper.publicKey }).signers([keeper]).rpc();
+        expect.fail("Nothing is due before the counter expires");
+      } catch (error: any) {
+        expect(error.message).to.include("NothingToTouch");
+      }
+
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+      const vaultBefore = await provider.connection.getBalance(vault);
+      const sig = await program.methods
+        .touch()
+        .accounts({ counter, keeper: keeper.publicKey })
+        .signers([keeper])
+        .rpc({ commitment: "confirmed" });
+      const [touched] = (await eventsFrom(sig)).filter((e) => e.name === "counterTouched");
+      expect(touched.data.archived).to.equal(true);
+      expect(touched.data.tipLamports.toNumber()).to.equal(tip);
+      expect(vaultBefore - (await provider.connection.getBalance(vault))).to.equal(tip);
+      expect((await program.account.counter.fetch(counter)).state).to.deep.equal({ archived: {} });
+
+      // Archiving only happens once
+      try {
+        await program.methods.touch().accounts({ counter, keeper: keeper.publicKey }).signers([keeper]).rpc();
+        expect.fail("An archived counter has nothing left to do");
+      } catch (error: any) {
+        expect(error.message).to.include("NothingToTouch");
+      }
+    });
+  });
+
+  describe("threshold alerts", () => {
+    it("Alerts once per crossing", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "queue");
+      const counter = counterPdaFor(owner.publicKey, "queue");
+      await program.methods
+        .setAlertThreshold(new anchor.BN(2), { above: {} })
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      const alertsFrom = async (method: "increment" | "decrement") => {
+        const builder =
+          method === "increment"
+            ? program.methods.increment("queue", false, null)
+            : program.methods.decrement("queue", false, null);
+        const sig = await builder
+          .accounts({ counter, user: owner.publicKey })
+          .signers([owner])
+          .rpc({ commitment: "confirmed" });
+        return (await eventsFrom(sig)).filter((e) => e.name === "thresholdCrossed");
+      };
+
+      expect(await alertsFrom("increment")).to.have.length(0); // 1
+      const [crossed] = await alertsFrom("increment"); // 2
+      expect(crossed.data.count.toNumber()).to.equal(2);
+      expect(crossed.data.direction).to.deep.equal({ above: {} });
+      expect(await alertsFrom("increment")).to.have.length(0); // 3, still past it
+      expect(await alertsFrom("decrement")).to.have.length(0); // 2
+      expect(await alertsFrom("decrement")).to.have.length(0); // 1, re-armed
+      expect(await alertsFrom("increment")).to.have.length(1); // 2 again
+
+      await program.methods.clearAlertThreshold().accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      expect((await program.account.counter.fetch(counter)).alertDirection).to.be.null;
+    });
+  });
+
+  describe("hooks", () => {
+    const caller = anchor.workspace.counterCaller as Program<CounterCaller>;
+
+    it("Calls the hook program after every change", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "hooked");
+      const counter = counterPdaFor(owner.publicKey, "hooked");
+
+      try {
+        await program.methods.setHook(program.programId).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+        expect.fail("A counter can't hook into its own program");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidHookProgram");
+      }
+      await program.methods.setHook(caller.programId).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+
+      try {
+        await program.methods.increment("hooked", false, null).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+        expect.fail("The hook program has to be passed in");
+      } catch (error: any) {
+        expect(error.message).to.include("HookProgramRequired");
+      }
+
+      const sig = await program.methods
+        .increment("hooked", false, null)
+        .accounts({ counter, user: owner.publicKey, hookProgram: caller.programId })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      expect(tx!.meta!.logMessages!.join("\n")).to.include(`Hook: counter ${counter.toBase58()} went from 0 to 1`);
+
+      await program.methods
+        .setHook(anchor.web3.PublicKey.default)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      await program.methods.increment("hooked", false, null).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(2);
+    });
+  });
+
+  describe("counter groups", () => {
+    const groupPdaFor = (authority: anchor.web3.PublicKey, name: string) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("group"), authority.toBuffer(), Buffer.from(name)],
+        program.programId
+      )[0];
+
+    it("Keeps a running total of its members' counts", async () => {
+      const fleet = await fundedUser();
+      const device = await fundedUser();
+      await program.methods.createGroup("fleet", 1).accounts({ user: fleet.publicKey }).signers([fleet]).rpc();
+      const group = groupPdaFor(fleet.publicKey, "fleet");
+
+      await initializeFor(device, "device");
+      const counter = counterPdaFor(device.publicKey, "device");
+      await program.methods.increment("device", false, null).accounts({ counter, user: device.publicKey }).signers([device]).rpc();
+      await program.methods
+        .joinGroup()
+        .accounts({ counter, user: device.publicKey, group, authority: fleet.publicKey })
+        .signers([device, fleet])
+        .rpc();
+      expect((await program.account.counterGroup.fetch(group)).total.toNumber()).to.equal(1);
+
+      await initializeFor(fleet, "spare");
+      try {
+        await program.methods
+          .joinGroup()
+          .accounts({ counter: counterPdaFor(fleet.publicKey, "spare"), user: fleet.publicKey, group, authority: fleet.publicKey })
+          .signers([fleet])
+          .rpc();
+        expect.fail("The group only has room for one counter");
+      } catch (error: any) {
+        expect(error.message).to.include("GroupFull");
+      }
+
+      try {
+        await program.methods.increment("device", false, null).accounts({ counter, user: device.publicKey }).signers([device]).rpc();
+        expect.fail("The group has to be passed in");
+      } catch (error: any) {
+        expect(error.message).to.include("GroupRequired");
+      }
+
+      const sig = await program.methods
+        .incrementBy("device", new anchor.BN(5), false, null)
+        .accounts({ counter, user: device.publicKey, group })
+        .signers([device])
+        .rpc({ commitment: "confirmed" });
+      const [updated] = (await eventsFrom(sig)).filter((event) => event.name === "groupTotalUpdated");
+      expect(updated.data.previousTotal.toNumber()).to.equal(1);
+      expect(updated.data.total.toNumber()).to.equal(6);
+      await program.methods.decrement("device", false, null).accounts({ counter, user: device.publicKey, group }).signers([device]).rpc();
+      expect((await program.account.counterGroup.fetch(group)).total.toNumber()).to.equal(5);
+
+      await program.methods.leaveGroup().accounts({ counter, user: device.publicKey, group }).signers([device]).rpc();
+      const left = await program.account.counterGroup.fetch(group);
+      expect(left.total.toNumber()).to.equal(0);
+      expect(left.members).to.equal(0);
+      await program.methods.increment("device", false, null).accounts({ counter, user: device.publicKey }).signers([device]).rpc();
+    });
+
+    it("Refuses to move a member's count outside the paths that roll the group up", async () => {
+      const owner = await fundedUser();
+      await program.methods.createGroup("pooled", 2).accounts({ user: owner.publicKey }).signers([owner]).rpc();
+      const group = groupPdaFor(owner.publicKey, "pooled");
+      const members = ["alpha", "beta"].map((label) => counterPdaFor(owner.publicKey, label));
+      for (const [label, counter] of [["alpha", members[0]], ["beta", members[1]]] as const) {
+        await initializeFor(owner, label);
+        await program.methods.incrementBy(label, new anchor.BN(3), false, null).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+        await program.methods.joinGroup().accounts({ counter, user: owner.publicKey, group, authority: owner.publicKey }).signers([owner]).rpc();
+      }
+
+      try {
+        await program.methods.addFrom().accounts({ source: members[1], destination: members[0], user: owner.publicKey }).signers([owner]).rpc();
+        expect.fail("add_from doesn't roll the group up");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterInGroup");
+      }
+      try {
+        await program.methods.setDecayRate(new anchor.BN(1)).accounts({ counter: members[0], user: owner.publicKey }).signers([owner]).rpc();
+        expect.fail("Decay doesn't roll the group up");
+      } catch (error: any) {
+        expect(error.message).to.include("DecayInGroup");
+      }
+      try {
+        await program.methods.setResetInterval(new anchor.BN(3600)).accounts({ counter: members[0], user: owner.publicKey }).signers([owner]).rpc();
+        expect.fail("A rollover by touch doesn't roll the group up");
+      } catch (error: any) {
+        expect(error.message).to.include("ResetInGroup");
+      }
+
+      const counts = await Promise.all(members.map(async (counter) => (await program.account.counter.fetch(counter)).count.toNumber()));
+      expect((await program.account.counterGroup.fetch(group)).total.toNumber()).to.equal(counts[0] + counts[1]);
+    });
+  });
+
+  describe("validate_op", () => {
+    // OpValidation { counter: Pubkey, op: u8, allowed: bool, error_code: u32, previous_count: u64,
+    //                new_count: u64, fee_lamports: u64, counter_fee_lamports: u64, token_fee: u64 }
+    const validationFrom = async (sig: string) => {
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const data = Buffer.from(tx!.meta!.returnData!.data[0], "base64");
+      return {
+        allowed: data.readUInt8(33) === 1,
+        errorCode: data.readUInt32LE(34),
+        previousCount: Number(data.readBigUInt64LE(38)),
+        newCount: Number(data.readBigUInt64LE(46)),
+      };
+    };
+
+    it("Reports whether an operation would go through without making it", async () => {
+      const owner = await fundedUser();
+      const stranger = await fundedUser();
+      await initializeFor(owner, "preflight", { min: null, max: new anchor.BN(10) });
+      const counter = counterPdaFor(owner.publicKey, "preflight");
+
+      let sig = await program.methods
+        .validateOp({ increment: {} }, new anchor.BN(4))
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+      expect(await validationFrom(sig)).to.deep.equal({ allowed: true, errorCode: 0, previousCount: 0, newCount: 4 });
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(0);
+
+      // Past the maximum: refused, but the preflight itself succeeds
+      sig = await program.methods
+        .validateOp({ increment: {} }, new anchor.BN(11))
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+      const outOfBounds = await validationFrom(sig);
+      expect(outOfBounds.allowed).to.be.false;
+      expect(outOfBounds.errorCode).to.equal(program.idl.errors.find((e) => e.name === "CounterAboveMax")!.code);
+
+      sig = await program.methods
+        .validateOp({ decrement: {} }, new anchor.BN(1))
+        .accounts({ counter, user: stranger.publicKey })
+        .signers([stranger])
+        .rpc({ commitment: "confirmed" });
+      const unauthorized = await validationFrom(sig);
+      expect(unauthorized.allowed).to.be.false;
+      expect(unauthorized.errorCode).to.equal(program.idl.errors.find((e) => e.name === "Unauthorized")!.code);
+    });
+
+    it("Refuses what the change itself would, like a missing change log", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "logged-preflight");
+      const counter = counterPdaFor(owner.publicKey, "logged-preflight");
+      await program.methods.initializeHistory().accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      const [history] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("history"), counter.toBuffer()], program.programId);
+
+      const preflight = async (withHistory: boolean) =>
+        validationFrom(
+          await program.methods
+            .validateOp({ increment: {} }, new anchor.BN(1))
+            .accounts({ counter, user: owner.publicKey, history: withHistory ? history : null })
+            .signers([owner])
+            .rpc({ commitment: "confirmed" })
+        );
+      const withoutLog = await preflight(false);
+      expect(withoutLog.allowed).to.be.false;
+      expect(withoutLog.errorCode).to.equal(program.idl.errors.find((e) => e.name === "HistoryRequired")!.code);
+      expect((await preflight(true)).allowed).to.be.true;
+    });
+  });
+
+  describe("close_all", () => {
+    const childPda = (...seeds: Buffer[]) => anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+    it("Closes a counter together with the accounts that belong to it", async () => {
+      const owner = await fundedUser();
+      const operator = anchor.web3.Keypair.generate();
+      await initializeFor(owner, "sweep");
+      const counter = counterPdaFor(owner.publicKey, "sweep");
+      const accounts = { counter, user: owner.publicKey };
+      await program.methods.initializeHistory().accounts(accounts).signers([owner]).rpc();
+      await program.methods.approveOperator(operator.publicKey, new anchor.BN(1)).accounts(accounts).signers([owner]).rpc();
+      await program.methods.snapshot(new anchor.BN(7)).accounts(accounts).signers([owner]).rpc();
+      const children = [
+        childPda(Buffer.from("history"), counter.toBuffer()),
+        childPda(Buffer.from("delegate"), counter.toBuffer(), operator.publicKey.toBuffer()),
+        childPda(Buffer.from("snapshot"), counter.toBuffer(), new anchor.BN(7).toArrayLike(Buffer, "le", 8)),
+      ].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+
+      // Another counter's accounts can't be swept along
+      const other = await fundedUser();
+      await initializeFor(other, "other");
+      const otherCounter = counterPdaFor(other.publicKey, "other");
+      await program.methods.snapshot(new anchor.BN(7)).accounts({ counter: otherCounter, user: other.publicKey }).signers([other]).rpc();
+      const foreign = childPda(Buffer.from("snapshot"), otherCounter.toBuffer(), new anchor.BN(7).toArrayLike(Buffer, "le", 8));
+      try {
+        await program.methods
+          .closeAll("sweep", null)
+          .accounts(accounts)
+          .remainingAccounts([...children, { pubkey: foreign, isSigner: false, isWritable: true }])
+          .signers([owner])
+          .rpc();
+        expect.fail("Only this counter's accounts can be closed with it");
+      } catch (error: any) {
+        expect(error.message).to.include("NotACounterChild");
+      }
+
+      const sig = await program.methods
+        .closeAll("sweep", null)
+        .accounts(accounts)
+        .remainingAccounts(children)
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+      const [closed] = (await eventsFrom(sig)).filter((e) => e.name === "counterChildrenClosed");
+      expect(closed.data.accounts).to.equal(3);
+      for (const account of [counter, ...children.map((child) => child.pubkey)]) {
+        expect(await provider.connection.getAccountInfo(account)).to.be.null;
+      }
+    });
+  });
+
+  describe("top_up", () => {
+    it("Lets an account's authority add lamports to it", async () => {
+      const owner = await fundedUser();
+      const stranger = await fundedUser();
+      await initializeFor(owner, "topped");
+      const counter = counterPdaFor(owner.publicKey, "topped");
+      const before = await provider.connection.getBalance(counter);
+
+      const sig = await program.methods
+        .topUp(new anchor.BN(5000))
+        .accounts({ account: counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+      const [topped] = (await eventsFrom(sig)).filter((e) => e.name === "accountToppedUp");
+      expect(topped.data.balance.toNumber()).to.equal(before + 5000);
+      expect(await provider.connection.getBalance(counter)).to.equal(before + 5000);
+
+      try {
+        await program.methods
+          .topUp(new anchor.BN(5000))
+          .accounts({ account: counter, user: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+        expect.fail("Only the counter's owner can top it up");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+  });
+
+  describe("event schema versions", () => {
+    it("Starts every event with its schema version", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "versioned");
+      const counter = counterPdaFor(owner.publicKey, "versioned");
+      const sig = await program.methods
+        .increment("versioned", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+
+      const events = await eventsFrom(sig);
+      expect(events.length).to.be.greaterThan(0);
+      for (const event of events) {
+        expect(event.data.schemaVersion).to.equal(1);
+      }
+    });
+  });
+
+  describe("partner namespaces", () => {
+    const partnerId = Array.from(Buffer.from("acme0001"));
+    const namespacedPdaFor = (owner: anchor.web3.PublicKey, label: string) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("counter"), Buffer.from(partnerId), owner.toBuffer(), Buffer.from(label)],
+        program.programId
+      )[0];
+    const initializeNamespaced = (owner: anchor.web3.Keypair, label: string) =>
+      program.methods
+        .initializeNamespaced(partnerId, label, null, null, false, null, null, null, null, null, null, null, null, null)
+        .accounts({ user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+    it("Creates counters under an approved partner's seeds", async () => {
+      const owner = await fundedUser();
+      try {
+        await initializeNamespaced(owner, "score");
+        expect.fail("The partner isn't approved yet");
+      } catch (error: any) {
+        expect(error.message).to.include("UnknownPartner");
+      }
+
+      await program.methods.approvePartner(partnerId).accounts({ config: configPda, admin: user }).rpc();
+      await initializeNamespaced(owner, "score");
+      // The same label stays free outside the namespace
+      await initializeFor(owner, "score");
+
+      const counter = namespacedPdaFor(owner.publicKey, "score");
+      expect((await program.account.counter.fetch(counter)).namespace).to.deep.equal(partnerId);
+      await program.methods.increment("score", false, null).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(1);
+      expect((await program.account.counter.fetch(counterPdaFor(owner.publicKey, "score"))).count.toNumber()).to.equal(0);
+
+      await program.methods.revokePartner(partnerId).accounts({ config: configPda, admin: user }).rpc();
+      // Revoking stops new counters, but existing ones keep working
+      await program.methods.increment("score", false, null).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      try {
+        await initializeNamespaced(owner, "other");
+        expect.fail("The partner was revoked");
+      } catch (error: any) {
+        expect(error.message).to.include("UnknownPartner");
+      }
+    });
+  });
+
+  describe("add_from", () => {
+    it("Adds another owner's counter into a running total", async () => {
+      const owner = await fundedUser();
+      const other = await fundedUser();
+      await initializeFor(owner, "grand-total");
+      await initializeFor(other, "daily");
+      const total = counterPdaFor(owner.publicKey, "grand-total");
+      const daily = counterPdaFor(other.publicKey, "daily");
+      await program.methods.incrementBy("daily", new anchor.BN(3), false, null).accounts({ counter: daily, user: other.publicKey }).signers([other]).rpc();
+
+      for (let i = 0; i < 2; i++) {
+        await program.methods.addFrom().accounts({ source: daily, destination: total, user: owner.publicKey }).signers([owner]).rpc();
+      }
+      expect((await program.account.counter.fetch(total)).count.toNumber()).to.equal(6);
+      expect((await program.account.counter.fetch(daily)).count.toNumber()).to.equal(3);
+
+      const sig = await program.methods
+        .addFrom()
+        .accounts({ source: daily, destination: total, user: owner.publicKey })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+      const [added] = (await eventsFrom(sig)).filter((event) => event.name === "counterAddedFrom");
+      expect(added.data.source.toBase58()).to.equal(daily.toBase58());
+      expect(added.data.destination.toBase58()).to.equal(total.toBase58());
+      expect(added.data.amount.toNumber()).to.equal(3);
+      expect(added.data.destinationCount.toNumber()).to.equal(9);
+
+      try {
+        await program.methods.addFrom().accounts({ source: total, destination: daily, user: owner.publicKey }).signers([owner]).rpc();
+        expect.fail("Only the destination's owner may add to it");
+      } catch (error: any) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+  });
+
+  describe("wagers", () => {
+    const stake = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 100);
+
+    it("Pays both stakes to whoever reaches the target first", async () => {
+      const alice = await fundedUser();
+      const bob = await fundedUser();
+      await initializeFor(alice, "race");
+      await initializeFor(bob, "race");
+      const aliceCounter = counterPdaFor(alice.publicKey, "race");
+      const bobCounter = counterPdaFor(bob.publicKey, "race");
+      const [wager] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("wager"), aliceCounter.toBuffer(), bobCounter.toBuffer()],
+        program.programId
+      );
+      const deadline = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
+      await program.methods
+        .createWager(bob.publicKey, new anchor.BN(2), stake, deadline)
+        .accounts({ creatorCounter: aliceCounter, opponentCounter: bobCounter, user: alice.publicKey, opponentUser: bob.publicKey })
+        .signers([alice, bob])
+        .rpc();
+
+      const settle = () =>
+        program.methods
+          .settleWager()
+          .accounts({ wager, creatorCounter: aliceCounter, opponentCounter: bobCounter, creator: alice.publicKey, opponent: bob.publicKey })
+          .rpc({ commitment: "confirmed" });
+      try {
+        await settle();
+        expect.fail("Nobody has reached the target yet");
+      } catch (error: any) {
+        expect(error.message).to.include("WagerTargetNotReached");
+      }
+      try {
+        await program.methods.refundWager().accounts({ wager, creator: alice.publicKey, opponent: bob.publicKey }).rpc();
+        expect.fail("The deadline hasn't passed");
+      } catch (error: any) {
+        expect(error.message).to.include("WagerNotExpired");
+      }
+
+      // While the wager is open the counters only move a step at a time
+      const bobAccounts = { counter: bobCounter, user: bob.publicKey };
+      for (const jump of [
+        program.methods.incrementBy("race", new anchor.BN(2), false, null),
+        program.methods.setCount("race", new anchor.BN(2), false, null),
+      ]) {
+        try {
+          await jump.accounts(bobAccounts).signers([bob]).rpc();
+          expect.fail("A jump to the target shouldn't decide the wager");
+        } catch (error: any) {
+          expect(error.message).to.include("CounterInWager");
+        }
+      }
+
+      try {
+        await program.methods.close("race", false, null).accounts(bobAccounts).signers([bob]).rpc();
+        expect.fail("Closing and recreating the counter at the target would win the wager");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterInWager");
+      }
+
+      for (let i = 0; i < 2; i++) {
+        await program.methods.increment("race", false, null).accounts(bobAccounts).signers([bob]).rpc();
+      }
+      const before = await provider.connection.getBalance(bob.publicKey);
+      const [settled] = (await eventsFrom(await settle())).filter((event) => event.name === "wagerSettled");
+      expect(settled.data.winner.toBase58()).to.equal(bob.publicKey.toBase58());
+      expect(settled.data.payout.toNumber()).to.equal(stake.toNumber() * 2);
+      expect(await provider.connection.getBalance(bob.publicKey)).to.equal(before + stake.toNumber() * 2);
+      expect(await provider.connection.getAccountInfo(wager)).to.be.null;
+
+      // Settled: both counters are free again
+      expect((await program.account.counter.fetch(bobCounter)).openWagers).to.equal(0);
+      await program.methods.setCount("race", new anchor.BN(10), false, null).accounts(bobAccounts).signers([bob]).rpc();
+    });
+  });
+
+  describe("roles", () => {
+    const INCREMENTER = 1 << 0;
+    const DECREMENTER = 1 << 1;
+    const rolePdaFor = (counter: anchor.web3.PublicKey, member: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("role"), counter.toBuffer(), member.toBuffer()],
+        program.programId
+      )[0];
+
+    it("Lets each member make only the changes their roles allow", async () => {
+      const owner = await fundedUser();
+      const bot = await fundedUser();
+      const finance = await fundedUser();
+      await initializeFor(owner, "ledger");
+      const counter = counterPdaFor(owner.publicKey, "ledger");
+      for (const [member, roles] of [[bot, INCREMENTER], [finance, DECREMENTER]] as const) {
+        await program.methods.grantRole(member.publicKey, roles).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      }
+      const change = (member: anchor.web3.Keypair, delta: number) =>
+        program.methods
+          .applyDelta("ledger", new anchor.BN(delta), false, null)
+          .accounts({ counter, user: member.publicKey, role: rolePdaFor(counter, member.publicKey) })
+          .signers([member])
+          .rpc();
+
+      await change(bot, 5);
+      await change(finance, -2);
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(3);
+      for (const [member, delta] of [[bot, -1], [finance, 1]] as const) {
+        try {
+          await change(member, delta);
+          expect.fail("The role doesn't cover this change");
+        } catch (error: any) {
+          expect(error.message).to.include("Unauthorized");
+        }
+      }
+
+      await program.methods.revokeRole(INCREMENTER).accounts({ counter, role: rolePdaFor(counter, bot.publicKey), user: owner.publicKey }).signers([owner]).rpc();
+      expect(await provider.connection.getAccountInfo(rolePdaFor(counter, bot.publicKey))).to.be.null;
+    });
+
+    it("Exempts members from the token fee in batches, as in single changes", async () => {
+      const owner = await fundedUser();
+      const bot = await fundedUser();
+      const stranger = await fundedUser();
+      await initializeFor(owner, "tolled", undefined, true);
+      const counter = counterPdaFor(owner.publicKey, "tolled");
+      const [rewardMint] = anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("reward_mint")], program.programId);
+      await program.methods.setTokenFee(new anchor.BN(1)).accounts({ counter, feeMint: rewardMint, user: owner.publicKey }).signers([owner]).rpc();
+      await program.methods.grantRole(bot.publicKey, INCREMENTER).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+
+      const steps = [{ increment: [new anchor.BN(1)] }, { increment: [new anchor.BN(2)] }];
+      await program.methods
+        .applyBatch("tolled", steps, false, null)
+        .accounts({ counter, user: bot.publicKey, role: rolePdaFor(counter, bot.publicKey) })
+        .signers([bot])
+        .rpc();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(3);
+
+      try {
+        await program.methods.applyBatch("tolled", steps, false, null).accounts({ counter, user: stranger.publicKey }).signers([stranger]).rpc();
+        expect.fail("Everyone else pays the token fee");
+      } catch (error: any) {
+        expect(error.message).to.include("TokenFeeAccountsRequired");
+      }
+    });
+  });
+
+  describe("counting windows", () => {
+    const initializeWindowed = (owner: Keypair, label: string, start: number, end: number) =>
+      initializeFor(owner, label, undefined, false, null, null, null, null, null, new anchor.BN(start), new anchor.BN(end));
+
+    it("Only counts inside the window, and can't be finalized before it ends", async () => {
+      const owner = await fundedUser();
+      const now = Math.floor(Date.now() / 1000);
+      try {
+        await initializeWindowed(owner, "backwards", now + 600, now + 300);
+        expect.fail("The window ends before it starts");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidWindow");
+      }
+
+      await initializeWindowed(owner, "later", now + 600, now + 1200);
+      const later = counterPdaFor(owner.publicKey, "later");
+      try {
+        await program.methods.increment("later", false, null).accounts({ counter: later, user: owner.publicKey }).signers([owner]).rpc();
+        expect.fail("The window hasn't opened yet");
+      } catch (error: any) {
+        expect(error.message).to.include("OutsideWindow");
+      }
+
+      await initializeWindowed(owner, "open", now - 600, now + 1200);
+      const open = counterPdaFor(owner.publicKey, "open");
+      await program.methods.increment("open", false, null).accounts({ counter: open, user: owner.publicKey }).signers([owner]).rpc();
+      const counter = await program.account.counter.fetch(open);
+      expect(counter.count.toNumber()).to.equal(1);
+      expect(counter.windowEnd.toNumber()).to.equal(now + 1200);
+      try {
+        await program.methods.finalize().accounts({ counter: open, user: user }).rpc();
+        expect.fail("The window is still open");
+      } catch (error: any) {
+        expect(error.message).to.include("WindowNotEnded");
+      }
+    });
+  });
+
+  describe("commit-reveal", () => {
+    const sealed = (value: number, salt: Buffer) => {
+      const bytes = Buffer.alloc(8);
+      bytes.writeBigUInt64LE(BigInt(value));
+      return Array.from(createHash("sha256").update(Buffer.concat([bytes, salt])).digest());
+    };
+
+    it("Takes sealed values before the window and checks them on reveal", async () => {
+      const owner = await fundedUser();
+      const voter = await fundedUser();
+      const now = Math.floor(Date.now() / 1000);
+      await initializeFor(owner, "poll", undefined, true, null, null, null, null, null, new anchor.BN(now + 600), new anchor.BN(now + 1200));
+      await initializeFor(owner, "open-poll", undefined, true);
+      const counter = counterPdaFor(owner.publicKey, "poll");
+      const salt = Buffer.alloc(32, 7);
+
+      try {
+        await program.methods
+          .commit(sealed(1, salt))
+          .accounts({ counter: counterPdaFor(owner.publicKey, "open-poll"), user: voter.publicKey })
+          .signers([voter])
+          .rpc();
+        expect.fail("A counter without a window has no commit phase");
+      } catch (error: any) {
+        expect(error.message).to.include("CommitPhaseOver");
+      }
+
+      await program.methods.commit(sealed(1, salt)).accounts({ counter, user: voter.publicKey }).signers([voter]).rpc();
+      const reveal = (value: number) =>
+        program.methods.reveal(new anchor.BN(value), Array.from(salt)).accounts({ counter, user: voter.publicKey }).signers([voter]).rpc();
+      try {
+        await reveal(2);
+        expect.fail("2 isn't the committed value");
+      } catch (error: any) {
+        expect(error.message).to.include("CommitmentMismatch");
+      }
+      try {
+        await reveal(1);
+        expect.fail("The window hasn't opened yet");
+      } catch (error: any) {
+        expect(error.message).to.include("OutsideWindow");
+      }
+    });
+  });
+
+  describe("weighted increments", () => {
+    it("Needs a weight mint and the caller's token account for it", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "stake-vote", undefined, true);
+      const counter = counterPdaFor(owner.publicKey, "stake-vote");
+      const incrementWeighted = () =>
+        program.methods.incrementWeighted("stake-vote", false, null).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      try {
+        await incrementWeighted();
+        expect.fail("No weight mint has been set");
+      } catch (error: any) {
+        expect(error.message).to.include("WeightNotConfigured");
+      }
+
+      const mint = anchor.web3.Keypair.generate().publicKey;
+      const setWeightConfig = (divisor: number) =>
+        program.methods.setWeightConfig(mint, new anchor.BN(divisor)).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      try {
+        await setWeightConfig(0);
+        expect.fail("A divisor of zero is refused");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidWeightDivisor");
+      }
+      await setWeightConfig(1_000);
+      const stored = await program.account.counter.fetch(counter);
+      expect(stored.weightMint.toBase58()).to.equal(mint.toBase58());
+      expect(stored.weightDivisor.toNumber()).to.equal(1_000);
+      try {
+        await incrementWeighted();
+        expect.fail("The caller's token account is missing");
+      } catch (error: any) {
+        expect(error.message).to.include("WeightAccountRequired");
+      }
+    });
+  });
+
+  describe("oracle feeds", () => {
+    it("Lets the feed authority push fresher values only", async () => {
+      const owner = await fundedUser();
+      const reporter = await fundedUser();
+      await initializeFor(owner, "tvl");
+      const counter = counterPdaFor(owner.publicKey, "tvl");
+      const push = (signer: Keypair, value: number, observedAt: number) =>
+        program.methods
+          .pushValue(new anchor.BN(value), new anchor.BN(observedAt))
+          .accounts({ counter, feedAuthority: signer.publicKey })
+          .signers([signer])
+          .rpc({ commitment: "confirmed" });
+      const now = Math.floor(Date.now() / 1000);
+
+      try {
+        await push(reporter, 10, now - 60);
+        expect.fail("No feed authority has been set");
+      } catch (error: any) {
+        expect(error.message).to.include("NotFeedAuthority");
+      }
+
+      await program.methods.setFeedAuthority(reporter.publicKey).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      const sig = await push(reporter, 1_234, now - 60);
+      const stored = await program.account.counter.fetch(counter);
+      expect(stored.count.toNumber()).to.equal(1_234);
+      expect(stored.lastObservedAt.toNumber()).to.equal(now - 60);
+      const [event] = (await eventsFrom(sig)).filter((e) => e.name === "feedValuePushed");
+      expect(event.data.observedAt.toNumber()).to.equal(now - 60);
+
+      try {
+        await push(reporter, 1_000, now - 120);
+        expect.fail("An older observation is refused");
+      } catch (error: any) {
+        expect(error.message).to.include("StaleObservation");
+      }
+      try {
+        await push(owner, 5, now - 30);
+        expect.fail("Only the feed authority pushes");
+      } catch (error: any) {
+        expect(error.message).to.include("NotFeedAuthority");
+      }
+    });
+  });
+
+  describe("time-weighted averages", () => {
+    it("Tracks the count over time and returns the average as return data", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "twa");
+      const counter = counterPdaFor(owner.publicKey, "twa");
+      const created = await program.account.counter.fetch(counter);
+      expect(created.twaStartedAt.toNumber()).to.be.greaterThan(0);
+      expect(created.lastUpdateTs.toNumber()).to.equal(created.twaStartedAt.toNumber());
+      expect(created.sumCountXSeconds.toNumber()).to.equal(0);
+
+      await program.methods
+        .increment("twa", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      const updated = await program.account.counter.fetch(counter);
+      expect(updated.lastUpdateTs.toNumber()).to.be.at.least(created.lastUpdateTs.toNumber());
+      expect(updated.sumCountXSeconds.toNumber()).to.equal(0); // It held zero until the increment
+
+      // Anyone can read it - here the provider wallet pays
+      const sig = await program.methods.observeTwa().accounts({ counter }).rpc({ commitment: "confirmed" });
+
+      // TwaView { average: u64, sum_count_x_seconds: u128, seconds: i64 }
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const returnData = Buffer.from(tx!.meta!.returnData!.data[0], "base64");
+      const average = returnData.readBigUInt64LE(0);
+      const sum = returnData.readBigUInt64LE(8) + (returnData.readBigUInt64LE(16) << BigInt(64));
+      const seconds = returnData.readBigInt64LE(24);
+      expect(average <= BigInt(1)).to.be.true; // Never above the highest count it has held
+      expect(seconds >= BigInt(0)).to.be.true;
+      if (seconds > BigInt(0)) {
+        expect(average).to.equal(sum / seconds);
+      }
+    });
+  });
+
+  describe("soft closes", () => {
+    const setCloseGrace = (seconds: number) =>
+      program.methods.setCloseGrace(new anchor.BN(seconds)).accounts({ config: configPda, admin: user }).rpc();
+    const manage = (owner: Keypair) => ({ counter: counterPdaFor(owner.publicKey), user: owner.publicKey });
+
+    before(async () => {
+      await setCloseGrace(2);
+    });
+
+    after(async () => {
+      await setCloseGrace(0);
+    });
+
+    it("Refuses changes while closed and lets the owner reopen within the grace period", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+      const counter = counterPdaFor(owner.publicKey);
+      await program.methods.increment("", false, null).accounts(manage(owner)).signers([owner]).rpc();
+
+      const signature = await program.methods.softClose().accounts(manage(owner)).signers([owner]).rpc();
+      const closed = (await eventsFrom(signature)).find((e) => e.name === "counterSoftClosed");
+      expect(closed.data.reclaimableAt.toNumber()).to.equal(closed.data.closedAt.toNumber() + 2);
+      try {
+        await program.methods.increment("", false, null).accounts(manage(owner)).signers([owner]).rpc();
+        expect.fail("A soft-closed counter shouldn't take changes");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterSoftClosed");
+      }
+      try {
+        await program.methods.reclaimClosed().accounts({ counter, authority: owner.publicKey, cranker: user }).rpc();
+        expect.fail("The grace period isn't over yet");
+      } catch (error: any) {
+        expect(error.message).to.include("CloseGraceActive");
+      }
+
+      await program.methods.reopen().accounts(manage(owner)).signers([owner]).rpc();
+      await program.methods.increment("", false, null).accounts(manage(owner)).signers([owner]).rpc();
+      const account = await program.account.counter.fetch(counter);
+      expect(account.closedAt.toNumber()).to.equal(0);
+      expect(account.count.toNumber()).to.equal(2);
+    });
+
+    it("Lets anyone reclaim the counter once the grace period is over", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner);
+      const counter = counterPdaFor(owner.publicKey);
+      await program.methods.softClose().accounts(manage(owner)).signers([owner]).rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+      try {
+        await program.methods.reopen().accounts(manage(owner)).signers([owner]).rpc();
+        expect.fail("The grace period is over");
+      } catch (error: any) {
+        expect(error.message).to.include("CloseGraceLapsed");
+      }
+      await program.methods.reclaimClosed().accounts({ counter, authority: owner.publicKey, cranker: user }).rpc();
+      expect(await provider.connection.getAccountInfo(counter)).to.be.null;
+    });
+  });
+
+  describe("user defaults", () => {
+    const defaultsPdaFor = (authority: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync([Buffer.from("defaults"), authority.toBuffer()], program.programId)[0];
+
+    it("Prefills the arguments initialize isn't given from the creator's defaults", async () => {
+      const owner = await fundedUser();
+      const defaults = defaultsPdaFor(owner.publicKey);
+      await program.methods
+        .setDefaults({
+          min: new anchor.BN(5),
+          max: new anchor.BN(50),
+          cooldownSeconds: null,
+          overflowMode: { saturating: {} },
+          step: new anchor.BN(3),
+        })
+        .accounts({ user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+      // An explicit argument wins over the default
+      await program.methods
+        .initialize("defaulted", null, new anchor.BN(40), false, null, null, null, null, null, null, null, null, null)
+        .accounts({ counter: counterPdaFor(owner.publicKey, "defaulted"), user: owner.publicKey, payer: owner.publicKey, defaults })
+        .signers([owner])
+        .rpc();
+      const counter = await program.account.counter.fetch(counterPdaFor(owner.publicKey, "defaulted"));
+      expect(counter.count.toNumber()).to.equal(5);
+      expect(counter.minCount.toNumber()).to.equal(5);
+      expect(counter.maxCount.toNumber()).to.equal(40);
+      expect(counter.step.toNumber()).to.equal(3);
+      expect(counter.overflowMode).to.deep.equal({ saturating: {} });
+
+      // Without the defaults account passed in, nothing is prefilled
+      await initializeFor(owner, "plain");
+      const plain = await program.account.counter.fetch(counterPdaFor(owner.publicKey, "plain"));
+      expect(plain.step.toNumber()).to.equal(1);
+    });
+
+    it("Rejects defaults that no counter could be created with", async () => {
+      const owner = await fundedUser();
+      try {
+        await program.methods
+          .setDefaults({ min: null, max: null, cooldownSeconds: null, overflowMode: null, step: new anchor.BN(0) })
+          .accounts({ user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("A step of zero should be rejected");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidStep");
+      }
+    });
+  });
+
+  describe("export and import", () => {
+    const setMigrationAuthority = (authority: anchor.web3.PublicKey) =>
+      program.methods.setMigrationAuthority(authority).accounts({ config: configPda, admin: user }).rpc();
+
+    after(async () => {
+      await setMigrationAuthority(anchor.web3.PublicKey.default);
+    });
+
+    it("Exports a snapshot through return data and stops the counter changing", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "outgoing");
+      const counter = counterPdaFor(owner.publicKey, "outgoing");
+      const manage = { counter, user: owner.publicKey };
+      await program.methods.increment("outgoing", false, null).accounts(manage).signers([owner]).rpc();
+
+      const sig = await program.methods.export().accounts(manage).signers([owner]).rpc({ commitment: "confirmed" });
+      const exported = (await eventsFrom(sig)).find((e) => e.name === "counterExported");
+      expect(exported.data.count.toNumber()).to.equal(1);
+
+      // ExportedCounter { program, counter, creator, namespace: [u8; 8], label: String, authority, count, .. }
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const returnData = Buffer.from(tx!.meta!.returnData!.data[0], "base64");
+      expect(new anchor.web3.PublicKey(returnData.subarray(0, 32)).equals(program.programId)).to.be.true;
+      expect(new anchor.web3.PublicKey(returnData.subarray(32, 64)).equals(counter)).to.be.true;
+      const labelLen = returnData.readUInt32LE(104);
+      expect(returnData.subarray(108, 108 + labelLen).toString()).to.equal("outgoing");
+      expect(returnData.readBigUInt64LE(108 + labelLen + 32)).to.equal(BigInt(1));
+
+      expect((await program.account.counter.fetch(counter)).state).to.deep.equal({ migrating: {} });
+      try {
+        await program.methods.increment("outgoing", false, null).accounts(manage).signers([owner]).rpc();
+        expect.fail("An exported counter shouldn't take changes");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterNotActive");
+      }
+    });
+
+    it("Only lets the migration authority import a snapshot", async () => {
+      const owner = await fundedUser();
+      const snapshot = {
+        program: Keypair.generate().publicKey,
+        counter: Keypair.generate().publicKey,
+        creator: owner.publicKey,
+        namespace: Array(8).fill(0),
+        label: "incoming",
+        authority: owner.publicKey,
+        count: new anchor.BN(42),
+        minCount: new anchor.BN(0),
+        maxCount: new anchor.BN(100),
+        step: new anchor.BN(2),
+        isPublic: false,
+        cooldownSeconds: new anchor.BN(0),
+        overflowMode: { checked: {} },
+        version: new anchor.BN(7),
+        totalOps: new anchor.BN(21),
+        exportedAt: new anchor.BN(0),
+      };
+      const counter = counterPdaFor(owner.publicKey, "incoming");
+      try {
+        await program.methods.import(snapshot).accounts({ counter, migrationAuthority: user }).rpc();
+        expect.fail("Nobody may import before the admin names a migration authority");
+      } catch (error: any) {
+        expect(error.message).to.include("NotMigrationAuthority");
+      }
+
+      await setMigrationAuthority(user);
+      const sig = await program.methods.import(snapshot).accounts({ counter, migrationAuthority: user }).rpc();
+      const imported = (await eventsFrom(sig)).find((e) => e.name === "counterImported");
+      expect(imported.data.sourceProgram.equals(snapshot.program)).to.be.true;
+
+      const account = await program.account.counter.fetch(counter);
+      expect(account.authority.equals(owner.publicKey)).to.be.true;
+      expect(account.count.toNumber()).to.equal(42);
+      expect(account.step.toNumber()).to.equal(2);
+      expect(account.version.toNumber()).to.equal(7);
+      expect(account.totalOps.toNumber()).to.equal(21);
+
+      // The owner carries on with it as usual
+      await program.methods
+        .increment("incoming", false, null)
+        .accounts({ counter, user: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(44);
+    });
+  });
+
+  describe("error details", () => {
+    it("Logs the numbers behind a limit error before failing", async () => {
+      const owner = await fundedUser();
+      await initializeFor(owner, "ceiling", { min: null, max: new anchor.BN(10) });
+      const accounts = { counter: counterPdaFor(owner.publicKey, "ceiling"), user: owner.publicKey };
+      await program.methods.incrementBy("ceiling", new anchor.BN(8), false, null).accounts(accounts).signers([owner]).rpc();
+
+      let logs: string[] = [];
+      try {
+        await program.methods.incrementBy("ceiling", new anchor.BN(5), false, null).accounts(accounts).signers([owner]).rpc();
+        expect.fail("Going past the maximum should fail");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterAboveMax");
+        logs = error.logs ?? [];
+      }
+
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const detail = [...parser.parseLogs(logs)].find((e) => e.name === "counterErrorDetail");
+      expect(detail.data.counter.equals(accounts.counter)).to.be.true;
+      expect(detail.data.errorCode).to.equal(program.idl.errors.find((e) => e.name === "CounterAboveMax")!.code);
+      expect(detail.data.operation).to.deep.equal({ increment: {} });
+      expect(detail.data.amount.toNumber()).to.equal(5);
+      expect(detail.data.count.toNumber()).to.equal(8);
+      expect(detail.data.limit.toNumber()).to.equal(10);
+    });
+  });
+
+  describe("sponsored creation", () => {
+    it("Lets a sponsor pay the rent while the user owns the counter", async () => {
+      const sponsor = await fundedUser();
+      const owner = Keypair.generate(); // Holds no SOL at all
+      const counter = counterPdaFor(owner.publicKey, "sponsored");
+      const sig = await program.methods
+        .initialize("sponsored", null, null, false, null, null, null, null, null, null, null, null, null)
+        .accounts({ counter, user: owner.publicKey, payer: sponsor.publicKey })
+        .signers([owner, sponsor])
+        .rpc({ commitment: "confirmed" });
+
+      const account = await program.account.counter.fetch(counter);
+      expect(account.authority.equals(owner.publicKey)).to.be.true;
+      expect(account.payer.equals(sponsor.publicKey)).to.be.true;
+      expect(await provider.connection.getBalance(owner.publicKey)).to.equal(0);
+
+      const initialized = (await eventsFrom(sig)).find((e) => e.name === "counterInitialized");
+      expect(initialized.data.user.equals(owner.publicKey)).to.be.true;
+      expect(initialized.data.sponsor.equals(sponsor.publicKey)).to.be.true;
+
+      // The owner can use it right away
+      await program.methods.increment("sponsored", false, null).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      expect((await program.account.counter.fetch(counter)).count.toNumber()).to.equal(1);
+    });
+  });
+
+  describe("authority recovery", () => {
+    it("Lets the recovery authority claim a quiet counter, and the owner veto it", async () => {
+      const owner = await fundedUser();
+      const recovery = await fundedUser();
+      const counter = counterPdaFor(owner.publicKey, "recoverable");
+      await program.methods
+        .initialize("recoverable", null, null, false, null, null, null, null, null, null, null, recovery.publicKey, new anchor.BN(1))
+        .accounts({ counter, user: owner.publicKey, payer: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      const claim = () =>
+        program.methods.claimAuthority().accounts({ counter, recoveryAuthority: recovery.publicKey }).signers([recovery]).rpc({ commitment: "confirmed" });
+
+      // Nobody else may claim it
+      const stranger = await fundedUser();
+      try {
+        await program.methods.claimAuthority().accounts({ counter, recoveryAuthority: stranger.publicKey }).signers([stranger]).rpc();
+        expect.fail("Only the recovery authority may claim");
+      } catch (error: any) {
+        expect(error.message).to.include("NotRecoveryAuthority");
+      }
+
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+      const sig = await claim();
+      const claimed = (await eventsFrom(sig)).find((e) => e.name === "authorityClaimed");
+      expect(claimed.data.recoveryAuthority.equals(recovery.publicKey)).to.be.true;
+      expect(claimed.data.completesAt.toNumber() - claimed.data.claimedAt.toNumber()).to.equal(7 * 86_400);
+
+      // The challenge window is still open
+      try {
+        await program.methods
+          .completeAuthorityClaim()
+          .accounts({ counter, recoveryAuthority: recovery.publicKey })
+          .signers([recovery])
+          .rpc();
+        expect.fail("The claim can't complete during the challenge window");
+      } catch (error: any) {
+        expect(error.message).to.include("ClaimChallengeActive");
+      }
+
+      await program.methods.vetoClaim().accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      const account = await program.account.counter.fetch(counter);
+      expect(account.authority.equals(owner.publicKey)).to.be.true;
+      expect(account.recoveryClaimedAt.toNumber()).to.equal(0);
+    });
+
+    it("Voids a claim once the counter changes again", async () => {
+      const owner = await fundedUser();
+      const recovery = await fundedUser();
+      const counter = counterPdaFor(owner.publicKey, "revived");
+      await program.methods
+        .initialize("revived", null, null, false, null, null, null, null, null, null, null, recovery.publicKey, new anchor.BN(1))
+        .accounts({ counter, user: owner.publicKey, payer: owner.publicKey })
+        .signers([owner])
+        .rpc();
+      const claim = () => program.methods.claimAuthority().accounts({ counter, recoveryAuthority: recovery.publicKey }).signers([recovery]).rpc();
+
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+      await claim();
+      await program.methods.increment("revived", false, null).accounts({ counter, user: owner.publicKey }).signers([owner]).rpc();
+      try {
+        await program.methods
+          .completeAuthorityClaim()
+          .accounts({ counter, recoveryAuthority: recovery.publicKey })
+          .signers([recovery])
+          .rpc();
+        expect.fail("The counter changed after the claim");
+      } catch (error: any) {
+        expect(error.message).to.include("CounterStillActive");
+      }
+
+      // The void claim doesn't block a new one once the counter goes quiet again
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+      await claim();
+    });
+
+    it("Needs both a recovery authority and a positive inactivity threshold", async () => {
+      const owner = await fundedUser();
+      try {
+        await program.methods
+          .initialize("half", null, null, false, null, null, null, null, null, null, null, Keypair.generate().publicKey, null)
+          .accounts({ counter: counterPdaFor(owner.publicKey, "half"), user: owner.publicKey, payer: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        expect.fail("A recovery authority without a threshold should be refused");
+      } catch (error: any) {
+        expect(error.message).to.include("InvalidRecovery");
+      }
+    });
+  });
 });
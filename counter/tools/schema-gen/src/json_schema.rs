@@ -0,0 +1,120 @@
+//! JSON Schema output.
+//!
+//! Integers wider than 32 bits are encoded as decimal strings (JavaScript numbers lose
+//! precision above 2^53), public keys as base58 strings and raw bytes as base64 strings.
+
+use anchor_lang::idl::types::{IdlArrayLen, IdlDefinedFields, IdlType, IdlTypeDef, IdlTypeDefTy};
+use serde_json::{json, Map, Value};
+
+use crate::{short_name, Schema};
+
+pub fn render(schema: &Schema) -> Value {
+    let defs: Map<String, Value> = schema
+        .types
+        .values()
+        .map(|def| (short_name(&def.name).to_string(), type_def(def)))
+        .collect();
+
+    let refs = |names: &[String]| -> Vec<Value> { names.iter().map(|name| reference(name)).collect() };
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": "counter.schema.json",
+        "title": "Counter program accounts and events",
+        "$defs": defs,
+        "properties": {
+            "accounts": { "oneOf": refs(&schema.accounts) },
+            "events": { "oneOf": refs(&schema.events) },
+        },
+    })
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{name}") })
+}
+
+fn type_def(def: &IdlTypeDef) -> Value {
+    match &def.ty {
+        IdlTypeDefTy::Struct { fields } => fields_schema(fields.as_ref()),
+        IdlTypeDefTy::Enum { variants } => {
+            // Fieldless enums are plain strings, otherwise each variant is `{ "Variant": {...} }`
+            if variants.iter().all(|v| v.fields.is_none()) {
+                let names: Vec<&str> = variants.iter().map(|v| v.name.as_str()).collect();
+                json!({ "type": "string", "enum": names })
+            } else {
+                let one_of: Vec<Value> = variants
+                    .iter()
+                    .map(|v| {
+                        json!({
+                            "type": "object",
+                            "properties": { v.name.clone(): fields_schema(v.fields.as_ref()) },
+                            "required": [v.name],
+                            "additionalProperties": false,
+                        })
+                    })
+                    .collect();
+                json!({ "oneOf": one_of })
+            }
+        }
+        IdlTypeDefTy::Type { alias } => ty(alias),
+    }
+}
+
+fn fields_schema(fields: Option<&IdlDefinedFields>) -> Value {
+    match fields {
+        None => json!({ "type": "object", "additionalProperties": false }),
+        Some(IdlDefinedFields::Named(fields)) => {
+            let properties: Map<String, Value> = fields.iter().map(|f| (f.name.clone(), ty(&f.ty))).collect();
+            let required: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+            json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+                "additionalProperties": false,
+            })
+        }
+        Some(IdlDefinedFields::Tuple(types)) => {
+            let items: Vec<Value> = types.iter().map(ty).collect();
+            json!({ "type": "array", "prefixItems": items, "items": false })
+        }
+    }
+}
+
+fn integer(min: i64, max: i64) -> Value {
+    json!({ "type": "integer", "minimum": min, "maximum": max })
+}
+
+fn big_integer(signed: bool) -> Value {
+    let pattern = if signed { "^-?[0-9]+$" } else { "^[0-9]+$" };
+    json!({ "type": "string", "pattern": pattern })
+}
+
+fn ty(ty: &IdlType) -> Value {
+    match ty {
+        IdlType::Bool => json!({ "type": "boolean" }),
+        IdlType::U8 => integer(0, u8::MAX.into()),
+        IdlType::I8 => integer(i8::MIN.into(), i8::MAX.into()),
+        IdlType::U16 => integer(0, u16::MAX.into()),
+        IdlType::I16 => integer(i16::MIN.into(), i16::MAX.into()),
+        IdlType::U32 => integer(0, u32::MAX.into()),
+        IdlType::I32 => integer(i32::MIN.into(), i32::MAX.into()),
+        IdlType::F32 | IdlType::F64 => json!({ "type": "number" }),
+        IdlType::U64 | IdlType::U128 | IdlType::U256 => big_integer(false),
+        IdlType::I64 | IdlType::I128 | IdlType::I256 => big_integer(true),
+        IdlType::Bytes => json!({ "type": "string", "contentEncoding": "base64" }),
+        IdlType::String => json!({ "type": "string" }),
+        IdlType::Pubkey => json!({ "type": "string", "pattern": "^[1-9A-HJ-NP-Za-km-z]{32,44}$" }),
+        IdlType::Option(inner) => json!({ "anyOf": [self::ty(inner), { "type": "null" }] }),
+        IdlType::Vec(inner) => json!({ "type": "array", "items": self::ty(inner) }),
+        IdlType::Array(inner, len) => match len {
+            IdlArrayLen::Value(len) => {
+                json!({ "type": "array", "items": self::ty(inner), "minItems": len, "maxItems": len })
+            }
+            IdlArrayLen::Generic(_) => json!({ "type": "array", "items": self::ty(inner) }),
+        },
+        IdlType::Defined { name, .. } => reference(short_name(name)),
+        // Generic parameters are resolved by Anchor before they reach us; accept anything
+        IdlType::Generic(_) => json!({}),
+        _ => json!({}),
+    }
+}
@@ -0,0 +1,196 @@
+//! Build-time generator for non-Rust consumers of the counter program.
+//!
+//! Reads the type definitions that Anchor's `idl-build` feature derives from the
+//! program's own Rust structs and writes them out as:
+//!
+//! - `counter.schema.json` — a JSON Schema (draft 2020-12) with one `$defs` entry per type
+//! - `counter.proto`       — proto3 messages/enums for the same types
+//!
+//! Usage: `cargo run -p counter-schema-gen -- [output-dir]` (defaults to `schemas/`)
+
+mod json_schema;
+mod protobuf;
+
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use anchor_lang::idl::types::IdlTypeDef;
+use anchor_lang::IdlBuild;
+
+// Anchor names types by their full Rust path ("counter::Counter"); consumers only need "Counter"
+pub fn short_name(name: &str) -> &str {
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+// Add a type (and every type it refers to) to the collection
+fn collect<T: IdlBuild>(types: &mut BTreeMap<String, IdlTypeDef>, names: &mut Vec<String>) {
+    if let Some(def) = T::create_type() {
+        names.push(short_name(&def.name).to_string());
+    }
+    let mut found = BTreeMap::new();
+    T::insert_types(&mut found);
+    found.extend(T::create_type().map(|def| (def.name.clone(), def)));
+    for (name, def) in found {
+        types.insert(short_name(&name).to_string(), def);
+    }
+}
+
+// Every on-chain account and event type, grouped so generated files can tell them apart.
+// Remember to list new `#[account]` / `#[event]` types here.
+pub struct Schema {
+    pub types: BTreeMap<String, IdlTypeDef>,
+    pub accounts: Vec<String>,
+    pub events: Vec<String>,
+}
+
+fn schema() -> Schema {
+    let mut types = BTreeMap::new();
+    let mut accounts = Vec::new();
+    let mut events = Vec::new();
+
+    // ACCOUNTS
+    collect::<counter::Counter>(&mut types, &mut accounts);
+    collect::<counter::ShortId>(&mut types, &mut accounts);
+    collect::<counter::ProgramConfig>(&mut types, &mut accounts);
+    collect::<counter::Sequencer>(&mut types, &mut accounts);
+    collect::<counter::ActivityReceipt>(&mut types, &mut accounts);
+    collect::<counter::Delegation>(&mut types, &mut accounts);
+    collect::<counter::Contributor>(&mut types, &mut accounts);
+    collect::<counter::RewardConfig>(&mut types, &mut accounts);
+    collect::<counter::GlobalStats>(&mut types, &mut accounts);
+    collect::<counter::CounterHistory>(&mut types, &mut accounts);
+    collect::<counter::CounterArray>(&mut types, &mut accounts);
+    collect::<counter::SignedCounter>(&mut types, &mut accounts);
+    collect::<counter::BigCounter>(&mut types, &mut accounts);
+    collect::<counter::CounterZC>(&mut types, &mut accounts);
+    collect::<counter::CounterSnapshot>(&mut types, &mut accounts);
+    collect::<counter::UserRegistry>(&mut types, &mut accounts);
+    collect::<counter::Session>(&mut types, &mut accounts);
+    collect::<counter::Leaderboard>(&mut types, &mut accounts);
+    collect::<counter::CounterTemplate>(&mut types, &mut accounts);
+    collect::<counter::UserDefaults>(&mut types, &mut accounts);
+    collect::<counter::CounterGroup>(&mut types, &mut accounts);
+    collect::<counter::Wager>(&mut types, &mut accounts);
+    collect::<counter::Role>(&mut types, &mut accounts);
+    collect::<counter::Commitment>(&mut types, &mut accounts);
+    collect::<counter::MultisigAuthority>(&mut types, &mut accounts);
+    collect::<counter::MultisigProposal>(&mut types, &mut accounts);
+
+    // EVENTS
+    collect::<counter::CounterInitialized>(&mut types, &mut events);
+    collect::<counter::CounterUpdated>(&mut types, &mut events);
+    collect::<counter::CounterErrorDetail>(&mut types, &mut events);
+    collect::<counter::CounterBatchApplied>(&mut types, &mut events);
+    collect::<counter::CounterClosed>(&mut types, &mut events);
+    collect::<counter::CountersMerged>(&mut types, &mut events);
+    collect::<counter::CountTransferred>(&mut types, &mut events);
+    collect::<counter::CounterSplit>(&mut types, &mut events);
+    collect::<counter::CounterCloned>(&mut types, &mut events);
+    collect::<counter::ArraySlotUpdated>(&mut types, &mut events);
+    collect::<counter::SignedCounterUpdated>(&mut types, &mut events);
+    collect::<counter::BigCounterUpdated>(&mut types, &mut events);
+    collect::<counter::BigCounterUpgraded>(&mut types, &mut events);
+    collect::<counter::CounterZCUpdated>(&mut types, &mut events);
+    collect::<counter::CounterConvertedToZC>(&mut types, &mut events);
+    collect::<counter::MilestoneReached>(&mut types, &mut events);
+    collect::<counter::StreakUpdated>(&mut types, &mut events);
+    collect::<counter::GlobalStatsUpdated>(&mut types, &mut events);
+    collect::<counter::ShortIdClaimed>(&mut types, &mut events);
+    collect::<counter::ShortIdReleased>(&mut types, &mut events);
+    collect::<counter::InstructionFlagsUpdated>(&mut types, &mut events);
+    collect::<counter::ProgramPauseChanged>(&mut types, &mut events);
+    collect::<counter::CounterHeartbeat>(&mut types, &mut events);
+    collect::<counter::InvariantsChecked>(&mut types, &mut events);
+    collect::<counter::FeePaid>(&mut types, &mut events);
+    collect::<counter::FeeConfigUpdated>(&mut types, &mut events);
+    collect::<counter::FeeScheduleUpdated>(&mut types, &mut events);
+    collect::<counter::CloseRequested>(&mut types, &mut events);
+    collect::<counter::CloseCancelled>(&mut types, &mut events);
+    collect::<counter::CounterSoftClosed>(&mut types, &mut events);
+    collect::<counter::CounterReopened>(&mut types, &mut events);
+    collect::<counter::CounterExported>(&mut types, &mut events);
+    collect::<counter::CounterImported>(&mut types, &mut events);
+    collect::<counter::AuthorityClaimed>(&mut types, &mut events);
+    collect::<counter::AuthorityClaimVetoed>(&mut types, &mut events);
+    collect::<counter::TemplateCreated>(&mut types, &mut events);
+    collect::<counter::CounterCreatedFromTemplate>(&mut types, &mut events);
+    collect::<counter::DefaultsUpdated>(&mut types, &mut events);
+    collect::<counter::CounterFeePaid>(&mut types, &mut events);
+    collect::<counter::CounterFeeUpdated>(&mut types, &mut events);
+    collect::<counter::VaultDeposited>(&mut types, &mut events);
+    collect::<counter::PeriodRolledOver>(&mut types, &mut events);
+    collect::<counter::DecayApplied>(&mut types, &mut events);
+    collect::<counter::CounterTouched>(&mut types, &mut events);
+    collect::<counter::ThresholdCrossed>(&mut types, &mut events);
+    collect::<counter::HookUpdated>(&mut types, &mut events);
+    collect::<counter::GroupCreated>(&mut types, &mut events);
+    collect::<counter::CounterChildrenClosed>(&mut types, &mut events);
+    collect::<counter::AccountToppedUp>(&mut types, &mut events);
+    collect::<counter::PartnerUpdated>(&mut types, &mut events);
+    collect::<counter::CounterAddedFrom>(&mut types, &mut events);
+    collect::<counter::WagerCreated>(&mut types, &mut events);
+    collect::<counter::WagerSettled>(&mut types, &mut events);
+    collect::<counter::RoleUpdated>(&mut types, &mut events);
+    collect::<counter::CounterFinalized>(&mut types, &mut events);
+    collect::<counter::ValueCommitted>(&mut types, &mut events);
+    collect::<counter::ValueRevealed>(&mut types, &mut events);
+    collect::<counter::GroupTotalUpdated>(&mut types, &mut events);
+    collect::<counter::CounterMetadataUpdated>(&mut types, &mut events);
+    collect::<counter::VaultDepositUpdated>(&mut types, &mut events);
+    collect::<counter::VaultWithdrawn>(&mut types, &mut events);
+    collect::<counter::CounterDrained>(&mut types, &mut events);
+    collect::<counter::CounterMigrated>(&mut types, &mut events);
+    collect::<counter::TokenGateUpdated>(&mut types, &mut events);
+    collect::<counter::WeightConfigUpdated>(&mut types, &mut events);
+    collect::<counter::FeedAuthorityUpdated>(&mut types, &mut events);
+    collect::<counter::FeedValuePushed>(&mut types, &mut events);
+    collect::<counter::RewardMinted>(&mut types, &mut events);
+    collect::<counter::RewardsConfigured>(&mut types, &mut events);
+    collect::<counter::FeePricePushed>(&mut types, &mut events);
+    collect::<counter::CounterStateChanged>(&mut types, &mut events);
+    collect::<counter::CounterFrozen>(&mut types, &mut events);
+    collect::<counter::CounterUnfrozen>(&mut types, &mut events);
+    collect::<counter::RentRefundSplit>(&mut types, &mut events);
+    collect::<counter::ActivityProven>(&mut types, &mut events);
+    collect::<counter::SnapshotTaken>(&mut types, &mut events);
+    collect::<counter::AuthorityTransferInitiated>(&mut types, &mut events);
+    collect::<counter::AuthorityTransferred>(&mut types, &mut events);
+    collect::<counter::OperatorApproved>(&mut types, &mut events);
+    collect::<counter::OperatorRevoked>(&mut types, &mut events);
+    collect::<counter::ContributorAdded>(&mut types, &mut events);
+    collect::<counter::ContributorRemoved>(&mut types, &mut events);
+    collect::<counter::SessionCreated>(&mut types, &mut events);
+    collect::<counter::SessionRevoked>(&mut types, &mut events);
+    collect::<counter::LeaderboardUpdated>(&mut types, &mut events);
+    collect::<counter::ReferralRecorded>(&mut types, &mut events);
+    collect::<counter::CounterTokenFeePaid>(&mut types, &mut events);
+    collect::<counter::TokenFeeUpdated>(&mut types, &mut events);
+    collect::<counter::MultisigCreated>(&mut types, &mut events);
+    collect::<counter::OpProposed>(&mut types, &mut events);
+    collect::<counter::OpApproved>(&mut types, &mut events);
+    collect::<counter::OpExecuted>(&mut types, &mut events);
+
+    Schema { types, accounts, events }
+}
+
+fn main() -> std::io::Result<()> {
+    let out_dir = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("schemas"));
+    fs::create_dir_all(&out_dir)?;
+
+    let schema = schema();
+
+    let json = serde_json::to_string_pretty(&json_schema::render(&schema)).expect("schema is valid JSON");
+    fs::write(out_dir.join("counter.schema.json"), json + "\n")?;
+    fs::write(out_dir.join("counter.proto"), protobuf::render(&schema))?;
+
+    println!(
+        "Wrote {} types ({} accounts, {} events) to {}",
+        schema.types.len(),
+        schema.accounts.len(),
+        schema.events.len(),
+        out_dir.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,128 @@
+//! proto3 output.
+//!
+//! Public keys are base58 strings, 128/256-bit integers are decimal strings and
+//! fixed-size byte arrays are `bytes`. Fieldless enums become proto enums; enums with
+//! data become a message with a `oneof` over one nested message per variant.
+
+use std::fmt::Write;
+
+use anchor_lang::idl::types::{IdlDefinedFields, IdlField, IdlType, IdlTypeDefTy};
+
+use crate::{short_name, Schema};
+
+pub fn render(schema: &Schema) -> String {
+    let mut out = String::new();
+    out.push_str("// Generated by counter-schema-gen from the counter program's Rust types. Do not edit.\n");
+    out.push_str("syntax = \"proto3\";\n\npackage counter;\n");
+
+    for (name, def) in &schema.types {
+        out.push('\n');
+        let kind = if schema.accounts.contains(name) {
+            Some("Account")
+        } else if schema.events.contains(name) {
+            Some("Event")
+        } else {
+            None
+        };
+        if let Some(kind) = kind {
+            let _ = writeln!(out, "// {kind}");
+        }
+
+        match &def.ty {
+            IdlTypeDefTy::Struct { fields } => message(&mut out, name, fields.as_ref(), ""),
+            IdlTypeDefTy::Enum { variants } if variants.iter().all(|v| v.fields.is_none()) => {
+                let prefix = screaming_snake(name);
+                let _ = writeln!(out, "enum {name} {{");
+                for (i, variant) in variants.iter().enumerate() {
+                    let _ = writeln!(out, "  {prefix}_{} = {i};", screaming_snake(&variant.name));
+                }
+                out.push_str("}\n");
+            }
+            IdlTypeDefTy::Enum { variants } => {
+                let _ = writeln!(out, "message {name} {{");
+                for variant in variants {
+                    message(&mut out, &variant.name, variant.fields.as_ref(), "  ");
+                }
+                out.push_str("  oneof variant {\n");
+                for (i, variant) in variants.iter().enumerate() {
+                    let _ = writeln!(out, "    {} {} = {};", variant.name, snake(&variant.name), i + 1);
+                }
+                out.push_str("  }\n}\n");
+            }
+            IdlTypeDefTy::Type { alias } => {
+                let _ = writeln!(out, "message {name} {{\n  {} value = 1;\n}}", field_type(alias));
+            }
+        }
+    }
+
+    out
+}
+
+fn message(out: &mut String, name: &str, fields: Option<&IdlDefinedFields>, indent: &str) {
+    let _ = writeln!(out, "{indent}message {name} {{");
+    let fields: Vec<IdlField> = match fields {
+        None => Vec::new(),
+        Some(IdlDefinedFields::Named(fields)) => fields.clone(),
+        // Tuple fields get positional names
+        Some(IdlDefinedFields::Tuple(types)) => types
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| IdlField { name: format!("field_{i}"), docs: Vec::new(), ty: ty.clone() })
+            .collect(),
+    };
+    for (i, field) in fields.iter().enumerate() {
+        let _ = writeln!(out, "{indent}  {} {} = {};", field_type(&field.ty), field.name, i + 1);
+    }
+    let _ = writeln!(out, "{indent}}}");
+}
+
+fn field_type(ty: &IdlType) -> String {
+    match ty {
+        // proto3 can't nest `optional`/`repeated`, so only scalars keep their wrapper
+        IdlType::Option(inner) if !matches!(**inner, IdlType::Vec(_) | IdlType::Array(..)) => {
+            format!("optional {}", scalar(inner))
+        }
+        IdlType::Option(inner) => field_type(inner),
+        IdlType::Vec(inner) if matches!(**inner, IdlType::U8) => "bytes".into(),
+        IdlType::Array(inner, _) if matches!(**inner, IdlType::U8) => "bytes".into(),
+        IdlType::Vec(inner) | IdlType::Array(inner, _) => format!("repeated {}", scalar(inner)),
+        other => scalar(other),
+    }
+}
+
+fn scalar(ty: &IdlType) -> String {
+    match ty {
+        IdlType::Bool => "bool".into(),
+        IdlType::U8 | IdlType::U16 | IdlType::U32 => "uint32".into(),
+        IdlType::I8 | IdlType::I16 | IdlType::I32 => "int32".into(),
+        IdlType::U64 => "uint64".into(),
+        IdlType::I64 => "int64".into(),
+        IdlType::F32 => "float".into(),
+        IdlType::F64 => "double".into(),
+        IdlType::Bytes => "bytes".into(),
+        IdlType::Defined { name, .. } => short_name(name).to_string(),
+        // Pubkeys, strings and integers too wide for protobuf
+        _ => "string".into(),
+    }
+}
+
+// "CounterUpdated" -> "counter_updated"
+fn snake(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// "CounterUpdated" -> "COUNTER_UPDATED"
+fn screaming_snake(name: &str) -> String {
+    snake(name).to_ascii_uppercase()
+}